@@ -0,0 +1,482 @@
+# User interface strings of Revset Explorer. See src/i18n.rs for how to add a
+# language.
+
+## Command line
+
+repository-not-found = Cannot find the specified repository
+not-a-repository = {$path} is not a jj repository, as it has no .jj folder
+drop-repository = Drop a repository folder to open it
+recent-save-failed = Cannot save the recent repositories: {$error}
+using-repository = Using repository in {$path}
+wrote-file = Wrote {$path}
+font-not-readable = Cannot read the font {$path}
+window-title = Revset Explorer - {$repository}
+window-title-operation = Revset Explorer - {$repository} at operation {$operation}
+sample-exists = Sample repository directory "{$path}" already exists. Please remove it first.
+sample-failed = Failed to create sample repository
+sample-created =
+    Sample repository created in "{$path}". Run the following command to explore it:
+    revset-explorer -R {$path}
+
+## Menus
+
+menu-file = File
+menu-open-tab = Open repository in new tab…
+menu-open-recent = Open recent
+menu-no-recent = No recent repositories
+menu-reload = Reload repository
+menu-watch = Watch repository
+menu-export-as = Export as {$format}…
+menu-export-settings = Export settings…
+menu-import-settings = Import settings…
+menu-save-result-set = Save result set…
+menu-save-result-set-hover = Save the commits of the view, with the operation, to compare with later
+menu-load-result-set = Load result set…
+menu-load-result-set-hover = Compare the view with saved commits, ringing the commits that joined it in green and those that left it in red
+menu-export-perf-report = Export performance report…
+menu-export-perf-report-hover = Save the timings of the latest revset evaluations and graph layouts as JSON, for attaching to a bug report
+menu-edit = Edit
+menu-copy-graph = Copy graph as image
+menu-replace-symbol = Replace symbol in revsets…
+menu-view = View
+menu-node-limit = Node limit
+menu-node-limit-hover = Maximum number of commits in the graph. More commits take longer to lay out.
+menu-zoom-in = Zoom in
+menu-zoom-out = Zoom out
+menu-zoom-reset = Reset zoom
+menu-viewpoints = Viewpoints
+viewpoint-save = Save viewpoint
+viewpoints-full = All {$count} viewpoints are taken. Remove one or save under the name of one.
+menu-larger-labels = Larger graph labels
+menu-smaller-labels = Smaller graph labels
+menu-reset-labels = Reset graph labels
+menu-palette = Palette
+palette-standard = Standard
+palette-color-blind = Color-blind safe
+palette-high-contrast = High contrast
+menu-node-size = Node size
+node-size-uniform = Uniform
+node-size-files = By files changed
+node-size-lines = By lines changed
+menu-author-label = Author on nodes
+author-label-none = None
+author-label-initials = Initials
+author-label-email = Email name
+menu-guides = Guides
+guide-generation-bands = Generation bands
+guide-date-lines = Date lines
+guide-immutable-boundary = Immutable boundary
+guide-mutable = mutable
+guide-immutable = immutable
+menu-edges = Edges
+edge-routing-straight = Straight
+edge-routing-curved = Curved
+menu-arrowheads = Arrowheads:
+arrowheads-none = None
+arrowheads-small = Small
+arrowheads-normal = Normal
+arrowheads-large = Large
+menu-arrow-direction = Arrows point to:
+arrow-direction-parents = Parents
+arrow-direction-children = Children
+watch-paused = Paused
+watch-paused-hover = Only reload from the File menu, e.g. to choose when a huge repository is evaluated again
+watch-poll = Every
+watch-seconds = { " " }s
+watch-instant = Instantly
+watch-instant-hover = Reload as soon as jj writes a new operation. The operations are watched in the background, without redrawing the window meanwhile.
+watch-notify = Notify when the Select result changes
+watch-notify-hover = After reloading, list the commits that joined and left the Select result
+menu-signature-badges = Signature badges
+menu-distances = Generation distances
+menu-distances-hover = Label the nodes with their distance from @, or from the commit chosen with "Measure distances from here". A commit at distance n from x is in ancestors(x, n + 1) or descendants(x, n + 1).
+menu-label-overlaps = Avoid label overlaps
+menu-label-overlaps-hover = Move labels up or down, or hide them, where they would cover other labels or nodes. Hover or select a node to always see its label.
+menu-highlight-path = Highlight lineage on hover
+menu-highlight-path-hover = Dim everything but the ancestors and descendants of the hovered node in the view, to trace its lineage
+menu-date-histogram = Date histogram
+menu-date-histogram-hover = Show how the committer dates of the view are spread below the status bar. Drag over it to highlight the commits in that period.
+menu-number-results = Number Select results
+menu-number-results-hover = Label the nodes with their position in the Select results, in the order the revset gives them, to check revsets like latest(mine(), 5)
+menu-commit-ids = Commit IDs
+menu-commit-ids-hover = Show the commit IDs below the nodes, with the shortest unique prefix underlined like in jj log
+menu-clusters = Cluster by bookmark
+menu-clusters-hover = Draw hulls around the commits that belong to each bookmark, i.e. that have it as their nearest descendant bookmark in the view, like the history of a Git branch
+menu-change-groups = Group divergent commits
+menu-change-groups-hover = Draw a box around the commits in the view that share a change ID, i.e. divergent changes
+menu-change-nodes = Nodes for changes
+menu-change-nodes-hover = Draw the commits that share a change ID, like divergent commits, as one node. Right-click the node to expand it into its commits.
+menu-simulate = Simulate color vision
+vision-normal = Normal
+vision-protanopia = Protanopia (no red)
+vision-deuteranopia = Deuteranopia (no green)
+vision-tritanopia = Tritanopia (no blue)
+vision-achromatopsia = Achromatopsia (no color)
+menu-shortcuts = Keyboard shortcuts
+menu-help = Help
+menu-about = About Revset Explorer
+about-title = About
+about-version = Version
+about-jj-lib = jj-lib
+about-backend = Commit backend
+about-op-store = Operation store
+about-index = Index
+about-operation = Operation
+about-check-updates = Check for updates
+about-check-updates-hover = Ask GitHub for the latest release. Nothing is sent until clicking.
+about-checking = Checking for updates…
+about-update-available = Version {$version} is available
+about-newer-jj = It is built with jj-lib {$version}, which may read repositories written by a newer jj
+about-up-to-date = This is the latest version
+about-check-failed = Could not check for updates: {$error}
+about-no-release = No release found
+format-html = HTML
+format-snapshot = snapshot
+format-layout = layout
+format-svg = SVG image
+format-png = PNG image
+format-dot = Graphviz DOT
+
+## Keyboard shortcuts
+
+shortcuts-title = Keyboard shortcuts
+shortcuts-revsets = Revset editing
+shortcuts-graph = Graph navigation
+shortcuts-panels = Panels and dialogs
+shortcut-previous-revset = Previous revset in the history
+shortcut-next-revset = Next revset in the history
+shortcut-complete-revset = Insert the first completion
+shortcut-next-node = Move to the next or previous node
+shortcut-nearby-node = Move to the nearest node in that direction
+shortcut-toggle-node = Select or deselect the node
+shortcut-select-node = Select only the node
+shortcut-node-menu = Open the context menu of the node
+shortcut-shortcuts = Show keyboard shortcuts
+shortcut-viewpoint = Jump to the saved viewpoint
+shortcut-close = Close the dialog or menu
+
+## Revset inputs and toolbar
+
+revset-select = Select
+revset-view = View
+rebase-source = Rebase source
+revset-compare = Compared
+revset-hint = Enter a revset here, like "@". Navigate to previous entries using up/down keys.
+revset-pending = Not evaluated
+revset-pending-hover = Evaluated when you pause typing, or at once with Enter. Until then, the graph shows the previous revset.
+completion-function = function
+completion-alias = alias
+completion-bookmark = bookmark
+completion-tag = tag
+completion-operator = operator
+lint-empty-range = {$text} is always empty, as a range leaves out the ancestors of its start
+lint-name-for-email = {$text}() only matches emails, and this has no @
+lint-missing-glob = * and ? in {$text} are matched literally without glob:
+lint-fix = Fix
+lint-fix-hover = Change it to {$fix}
+index-loading = Loading the index of the repository…
+index-loading-hint = This takes a while in very large repositories. The graph is shown when it is done.
+view-evaluating = Evaluating the View revset…
+select-evaluating = Evaluating the Select revset…
+view-evaluation-failed = The evaluation of the View revset failed.
+select-evaluation-failed = The evaluation of the Select revset failed.
+load-more = Load {$count} more
+load-more-hover = The graph shows the first {$limit} commits of the view. Add the next commits of the view to the graph.
+node-limit = Node limit of {$limit} reached. The graph is incomplete.
+truncated-working-copy = Working copy not shown due to the node limit.
+truncated-trunk = trunk() not shown due to the node limit.
+truncated-select = { $count ->
+        [one] One Select match not shown due to the node limit.
+       *[other] {$count} Select matches not shown due to the node limit.
+    }
+color-by-author = Color by author
+path-filter = Changed path:
+path-filter-hint = src/foo/
+path-filter-apply = Add to Select
+path-filter-last-touched = Last touched
+path-filter-last-touched-hover = Select the latest commits in the view that changed the path, like a quick jj file annotate
+author-filter = Authors:
+author-chip = {$name} ({$count})
+author-filter-apply = Add authors to Select
+author-filter-apply-hover = Only select commits by the chosen authors, with author(exact:…)
+date-filter = Committer dates:
+date-filter-from = from
+date-filter-to = to
+date-filter-reset = Reset
+date-filter-apply = Add dates to Select
+date-filter-apply-hover = Only select commits in the date range, with committer_date(after:…) and committer_date(before:…)
+date-histogram = Committer date histogram
+date-histogram-hover = {$from} – {$to}: { $count ->
+        [one] 1 commit
+       *[other] {$count} commits
+    }
+diff-search = Search in diffs:
+diff-search-hint = text
+diff-search-progress = Searching: {$checked} of {$total} commits, { $matches ->
+        [one] 1 match
+       *[other] {$matches} matches
+    }
+diff-search-done = { $matches ->
+        [0] No matching commits
+        [one] 1 matching commit
+       *[other] {$matches} matching commits
+    }
+diff-search-cancel = Cancel
+diff-search-clear = Clear
+busy-evaluation = Searching diffs
+busy-diffs = Computing diffs
+busy-prefetch = Prefetching commits
+undo-preview = Preview jj undo
+undo-preview-hover = Mark the commits that would appear (green ring) or disappear (red ring) if the latest operation was undone
+undo-preview-summary = Undoing "{$operation}": {$appearing} appear, {$disappearing} disappear
+undo-preview-nothing = (nothing to undo)
+result-set-summary = Since the saved result: {$joined} joined, {$left} left
+result-set-summary-hover = Saved from {$revset} at operation {$operation}
+result-set-clear = Clear
+result-change-title = The Select result changed
+result-change-joined = { $count ->
+        [one] 1 commit joined
+       *[other] {$count} commits joined
+    }
+result-change-left = { $count ->
+        [one] 1 commit left
+       *[other] {$count} commits left
+    }
+rebase-preview = Preview jj rebase
+rebase-preview-hover = Show faded copies of the Rebase source commits and their descendants where `jj rebase -s` would put them on the last selected commit
+rebase-preview-summary = {$count} commits would move onto {$destination}
+rebase-preview-destination = Select the destination commit
+compare = Compare
+compare-hover = Color the commits by whether they are in the Select revset, the Compared revset or both
+compare-only-select = Only Select: {$count}
+compare-only-compared = Only Compared: {$count}
+compare-both = Both: {$count}
+empty-view-title = The View is empty
+empty-view-revset = No commits are in {$revset}
+empty-view-present = present() hides bookmarks and tags that don't exist. Check their names.
+empty-view-depth = The depth of ancestors() and descendants() may be too small to reach the commits.
+empty-view-intersection = The two sides of & or ~ may have no commits in common.
+empty-view-show = Show {$revset}
+empty-view-show-hover = The nearest wider revset with commits
+scope-non-empty = Non-empty
+scope-mutable = Mutable
+scope-mine = Mine
+scope-hover = Restrict the View to {$revset}
+boundary-focus = Boundary focus
+boundary-focus-hover = Show the commits around the immutable heads, with the boundary between mutable and immutable commits highlighted: {$revset}
+outline-heads = Heads
+outline-heads-hover = Outline heads(<view>), the commits in the view without children in it
+outline-roots = Roots
+outline-roots-hover = Outline roots(<view>), the commits in the view without parents in it
+outline-failed = Failed to find the heads and roots: {$error}
+color-rule-failed = Color rule "{$revset}" failed: {$error}
+select-since-green = Select since last green
+select-since-green-hover = Select the descendants of the latest commits that passed CI
+
+## Node context menu
+
+open-in-browser = Open in browser
+no-forge = No forge detected. Set forge.commit_url in the config file or use --commit-url.
+show-diff-in = Show diff in {$tool}
+show-diff = Show diff (jj diff)
+copy-revset-symbol = Copy revset symbol
+copy-revset-symbol-hover = Copy the shortest change ID prefix, or commit_id() with a commit ID prefix when the change is divergent
+pin-commit = Pin to the view
+unpin-commit = Unpin from the view
+pin-commit-hover = Pinned commits are always shown in this repository, whatever the View revset
+expand-change = Expand into {$count} commits
+collapse-change = Collapse into one node
+measure-distances = Measure distances from here
+preview-bookmark-move = Preview moving {$bookmark} here
+new-commit = New commit on top (jj new)
+edit-commit = Edit (jj edit)
+abandon-commit = Abandon (jj abandon)
+rebase = Rebase {$revision} onto {$destination} (jj rebase)
+rebase-selected = Rebase selected onto selected (jj rebase)
+rebase-hover = Select the commit to rebase and then the destination commit
+
+## Dialogs
+
+bookmark-move-title = Move {$bookmark} to {$target}
+bookmark-move-reachable = { $count ->
+        [one] 1 commit becomes reachable
+       *[other] {$count} commits become reachable
+    }
+bookmark-move-unreachable = { $count ->
+        [one] 1 commit stops being reachable
+       *[other] {$count} commits stop being reachable
+    }
+bookmark-move-backwards = Moves the bookmark backwards or sideways (--allow-backwards)
+bookmark-move-failed = Could not preview the bookmark move: {$error}
+move-bookmark = Move (jj bookmark move)
+confirm-command = Run the following command?
+run = Run
+working-copy-stale = ⚠ Working copy may be stale
+working-copy-stale-hover = Files changed after jj last snapshotted the working copy, so the working-copy commit may not include them yet. Any jj command snapshots it.
+snapshot = Snapshot
+snapshot-hover = Snapshot the working copy by running jj status, and reload
+cancel = Cancel
+open-tab-title = Open repository in new tab
+open-tab-path = Repository:
+open-tab = Open
+tab-close = Close {$name}
+export-title = Export as {$format}
+export-file = File:
+export-legend = Legend
+export-legend-hover = Show the revsets and what the node colors mean next to the graph
+export-select-only = Only the Select matches
+export-select-only-hover = Leave out the commits that are not in Select, except those between the matches, like connected(), and close up the rows
+export = Export
+settings-export-title = Export settings
+settings-import-title = Import settings
+settings-import-note = Replaces the look, navigation and color rules in {$path}.
+import = Import
+result-set-save-title = Save result set
+result-set-load-title = Load result set
+save = Save
+load = Load
+replace-symbol-title = Replace symbol in revsets
+replace-symbol-from = Find:
+replace-symbol-to = Replace with:
+replace-symbol-recording = Session recording:
+replace-symbol-recording-hint = Optional, e.g. session.jsonl
+replace-symbol-count = { $count ->
+        [one] 1 revset of the session and the color rules to change:
+       *[other] {$count} revsets of the session and the color rules to change:
+    }
+replace-symbol = Replace
+export-html-title = View: {$view} | Select: {$select}
+legend-view = View
+legend-select = Select
+legend-compare = Compared
+legend-working-copy = Working copy
+legend-immutable = Immutable
+legend-mutable = Mutable
+legend-in-select = {$kind}, in Select
+legend-rule = Color rule: {$revset}
+
+## Details panel
+
+change-id = Change ID
+commit-id = Commit ID
+signature = Signature
+signature-verifying = Verifying…
+signature-unsigned = Not signed
+signature-good = Good
+signature-unknown = Not verified
+signature-invalid = Invalid
+signature-by = {$status}, by {$signer}
+signature-revsets = Signed commits: signed(), unsigned: ~signed()
+no-description = (no description set)
+annotation = Annotation
+annotation-good = good
+annotation-bad = bad
+annotation-neutral = neutral
+issues = Issues
+select-matching = Select all commits with it: {$revset}
+conflicts = { $count ->
+        [one] 1 conflicted file
+       *[other] {$count} conflicted files
+    }
+conflicts-choose = Choose a file to preview its conflict markers.
+conflicts-not-file = The conflict has no file contents, e.g. it is between a file and a directory.
+conflicts-failed = Cannot read the conflicts: {$error}
+diff-loading = Loading changes…
+diff-failed = Cannot compute the changes: {$error}
+diff-no-changes = No changes
+diff-summary = { $files ->
+        [one] 1 file changed
+       *[other] {$files} files changed
+    }, { $insertions ->
+        [one] 1 insertion
+       *[other] {$insertions} insertions
+    }, { $deletions ->
+        [one] 1 deletion
+       *[other] {$deletions} deletions
+    }
+diff-binary = binary
+diff-too-large = The diff has {$lines} lines, which is too many to show here.
+
+## Status bar
+
+done = Done
+choose-nodes = Choose the selected nodes and press Done or close the window.
+choose-revset = Choose the select revset and press Done or close the window.
+copied-graph = Copied the graph to the clipboard
+copied = Copied {$text} to the clipboard
+exported-graph = Exported the graph to {$path}
+export-failed = Export failed: {$error}
+exported-settings = Exported the settings to {$path}
+imported-settings = Imported the settings from {$path}. The language, zoom and fonts change after a restart.
+import-failed = Import failed: {$error}
+saved-result-set = Saved { $count ->
+        [one] 1 commit
+       *[other] {$count} commits
+    } to {$path}
+result-set-failed = Result set: {$error}
+perf-report-title = Export performance report
+replaced-symbol = Replaced {$from} with {$to} in { $count ->
+        [one] 1 revset
+       *[other] {$count} revsets
+    }
+replace-symbol-failed = Replacing failed: {$error}
+reload-failed = Failed to reload the repository: {$error}
+reload = Reload
+reload-hover = Reload the repository, to show what jj commands have changed since it was loaded ({$shortcut}). The zoom and position of the graph are kept.
+watch-failed = The operations of the repository could not be read for watching: {$error}
+watch-hook-failed = The command "{$command}" failed: {$error}
+clusters-failed = Failed to cluster the commits by bookmark: {$error}
+change-groups-failed = Failed to group the divergent commits: {$error}
+change-group = {$change_id}: {$count} divergent commits
+distances-failed = Failed to compute the generation distances: {$error}
+session-failed = Session recording or replay failed: {$error}
+script-failed = The script failed: {$error}
+replay-start = Replay of {$count} steps
+replay-step = Step {$step} of {$count}, at {$seconds} s
+replay-previous = Previous step
+replay-next = Next step
+annotations-failed = Failed to load the annotations: {$error}
+pins-failed = Pinned commits: {$error}
+annotations-unresolved = { $count ->
+        [one] 1 annotated ID matches no commit: {$ids}
+       *[other] {$count} annotated IDs match no commit: {$ids}
+    }
+ci-no-remote = CI status is enabled, but no GitHub or GitLab "origin" remote was found
+ci-fetch-failed = Failed to fetch CI status: {$error}
+jj-failed = Failed to run jj: {$error}
+
+tab-commits = Commits
+tab-results = Results
+results-count = {$count} commits in Select, in evaluation order
+results-count-ordering = {$count} commits in Select, in evaluation order. The result depends on the order in {$functions}.
+results-commit = Commit
+results-description = Description
+results-author = Author
+results-date = Date
+tab-operations = Operations
+operation-id = Operation:
+operation-description = Description:
+operation-user = User:
+operation-time = Time:
+operation-count = {$count} operations, the current one in green. Select one to see its details.
+operation-log-failed = Failed to load the operation log: {$error}
+operation-show = Show the repository at this operation
+operation-show-hover = See how the revsets evaluated at this operation, e.g. before a rebase or an abandon. Reload the repository to get back to the latest operation.
+operation-shown = The repository is shown at this operation
+operation-load-failed = Failed to load the operation: {$error}
+past-operation = Showing the repository at operation {$operation}: {$description}
+past-operation-back = Back to the latest operation
+
+edge-hover = {$child} → parent {$parent}
+
+## Screen readers
+
+commit-graph = Commit graph
+operation-graph = Operation graph
+node-name = Commit {$label}: {$description}
+node-name-no-description = Commit {$label}, no description
+node-working-copy = {$change_id}, working copy
+
+## Terminal user interface
+
+tui-help = Tab: switch field  ↑/↓: scroll  Esc: quit
@@ -0,0 +1,482 @@
+# Swedish translation of Revset Explorer. jj commands and revsets are kept
+# as is.
+
+## Command line
+
+repository-not-found = Kan inte hitta det angivna förrådet
+not-a-repository = {$path} är inte ett jj-förråd, eftersom det saknar en .jj-mapp
+drop-repository = Släpp en förrådsmapp för att öppna den
+recent-save-failed = Kan inte spara de senaste förråden: {$error}
+using-repository = Använder förrådet i {$path}
+wrote-file = Skrev {$path}
+font-not-readable = Kan inte läsa typsnittet {$path}
+window-title = Revset Explorer - {$repository}
+window-title-operation = Revset Explorer - {$repository} vid operation {$operation}
+sample-exists = Katalogen "{$path}" för exempelförrådet finns redan. Ta bort den först.
+sample-failed = Kunde inte skapa exempelförrådet
+sample-created =
+    Exempelförrådet skapades i "{$path}". Kör följande kommando för att utforska det:
+    revset-explorer -R {$path}
+
+## Menus
+
+menu-file = Arkiv
+menu-open-tab = Öppna förråd i ny flik…
+menu-open-recent = Öppna senaste
+menu-no-recent = Inga senaste förråd
+menu-reload = Läs in förrådet igen
+menu-watch = Bevaka förrådet
+menu-export-as = Exportera som {$format}…
+menu-export-settings = Exportera inställningar…
+menu-import-settings = Importera inställningar…
+menu-save-result-set = Spara resultatmängd…
+menu-save-result-set-hover = Spara vyns commits, med operationen, för att jämföra med senare
+menu-load-result-set = Läs in resultatmängd…
+menu-load-result-set-hover = Jämför vyn med sparade commits och ringa in de commits som tillkommit i grönt och de som försvunnit i rött
+menu-export-perf-report = Exportera prestandarapport…
+menu-export-perf-report-hover = Spara tiderna för de senaste utvärderingarna av revsets och layouterna av grafen som JSON, för att bifoga till en felrapport
+menu-edit = Redigera
+menu-copy-graph = Kopiera grafen som bild
+menu-replace-symbol = Ersätt symbol i revsets…
+menu-view = Visa
+menu-node-limit = Nodgräns
+menu-node-limit-hover = Högsta antalet commits i grafen. Fler commits tar längre tid att placera ut.
+menu-zoom-in = Zooma in
+menu-zoom-out = Zooma ut
+menu-zoom-reset = Återställ zoom
+menu-viewpoints = Vypunkter
+viewpoint-save = Spara vypunkt
+viewpoints-full = Alla {$count} vypunkter är upptagna. Ta bort en eller spara under namnet på en.
+menu-larger-labels = Större etiketter i grafen
+menu-smaller-labels = Mindre etiketter i grafen
+menu-reset-labels = Återställ etiketter i grafen
+menu-palette = Palett
+palette-standard = Standard
+palette-color-blind = Anpassad för färgblindhet
+palette-high-contrast = Hög kontrast
+menu-node-size = Nodstorlek
+node-size-uniform = Enhetlig
+node-size-files = Efter ändrade filer
+node-size-lines = Efter ändrade rader
+menu-author-label = Författare på noder
+author-label-none = Ingen
+author-label-initials = Initialer
+author-label-email = E-postnamn
+menu-guides = Stödlinjer
+guide-generation-bands = Generationsband
+guide-date-lines = Datumlinjer
+guide-immutable-boundary = Gräns för oföränderliga
+guide-mutable = föränderliga
+guide-immutable = oföränderliga
+menu-edges = Kanter
+edge-routing-straight = Raka
+edge-routing-curved = Böjda
+menu-arrowheads = Pilspetsar:
+arrowheads-none = Inga
+arrowheads-small = Små
+arrowheads-normal = Normala
+arrowheads-large = Stora
+menu-arrow-direction = Pilarna pekar mot:
+arrow-direction-parents = Föräldrar
+arrow-direction-children = Barn
+watch-paused = Pausad
+watch-paused-hover = Läs bara in igen från Arkiv-menyn, t.ex. för att välja när ett stort förråd utvärderas igen
+watch-poll = Var
+watch-seconds = { " " }s
+watch-instant = Direkt
+watch-instant-hover = Läs in igen så fort jj skriver en ny operation. Operationerna bevakas i bakgrunden, utan att fönstret ritas om under tiden.
+watch-notify = Meddela när Markera-resultatet ändras
+watch-notify-hover = Lista de commits som tillkom i och försvann från Markera-resultatet efter inläsningen
+menu-signature-badges = Märken för signaturer
+menu-distances = Generationsavstånd
+menu-distances-hover = Märk noderna med deras avstånd från @, eller från den commit som valts med "Mät avstånd härifrån". En commit på avståndet n från x finns i ancestors(x, n + 1) eller descendants(x, n + 1).
+menu-label-overlaps = Undvik överlappande etiketter
+menu-label-overlaps-hover = Flytta etiketter uppåt eller nedåt, eller dölj dem, där de skulle täcka andra etiketter eller noder. Hovra över eller markera en nod för att alltid se dess etikett.
+menu-highlight-path = Framhäv härstamning vid hovring
+menu-highlight-path-hover = Dämpa allt utom förfäderna och ättlingarna till noden under pekaren i vyn, för att följa dess härstamning
+menu-date-histogram = Datumhistogram
+menu-date-histogram-hover = Visa hur vyns commit-datum är spridda under statusfältet. Dra över det för att framhäva commits från den perioden.
+menu-number-results = Numrera markeringens resultat
+menu-number-results-hover = Märk noderna med deras plats i markeringens resultat, i den ordning revseten ger dem, för att kontrollera revsets som latest(mine(), 5)
+menu-commit-ids = Commit-ID
+menu-commit-ids-hover = Visa commit-ID under noderna, med det kortaste unika prefixet understruket som i jj log
+menu-clusters = Gruppera efter bokmärke
+menu-clusters-hover = Rita områden runt de commits som hör till varje bokmärke, dvs. har det som närmaste efterkommande bokmärke i vyn, som historiken för en Git-gren
+menu-change-groups = Gruppera divergenta commits
+menu-change-groups-hover = Rita en ruta runt de commits i vyn som har samma ändrings-ID, dvs. divergenta ändringar
+menu-change-nodes = Noder för ändringar
+menu-change-nodes-hover = Rita commits som delar ändrings-ID, som divergenta commits, som en nod. Högerklicka på noden för att expandera den till sina commits.
+menu-simulate = Simulera färgseende
+vision-normal = Normalt
+vision-protanopia = Protanopi (ej rött)
+vision-deuteranopia = Deuteranopi (ej grönt)
+vision-tritanopia = Tritanopi (ej blått)
+vision-achromatopsia = Akromatopsi (ingen färg)
+menu-shortcuts = Kortkommandon
+menu-help = Hjälp
+menu-about = Om Revset Explorer
+about-title = Om
+about-version = Version
+about-jj-lib = jj-lib
+about-backend = Commit-lagring
+about-op-store = Operationslagring
+about-index = Index
+about-operation = Operation
+about-check-updates = Sök efter uppdateringar
+about-check-updates-hover = Fråga GitHub efter den senaste utgåvan. Inget skickas förrän du klickar.
+about-checking = Söker efter uppdateringar…
+about-update-available = Version {$version} finns
+about-newer-jj = Den är byggd med jj-lib {$version}, som kan läsa förråd skrivna av en nyare jj
+about-up-to-date = Det här är den senaste versionen
+about-check-failed = Kunde inte söka efter uppdateringar: {$error}
+about-no-release = Ingen utgåva hittades
+format-html = HTML
+format-snapshot = ögonblicksbild
+format-layout = layout
+format-svg = SVG-bild
+format-png = PNG-bild
+format-dot = Graphviz DOT
+
+## Keyboard shortcuts
+
+shortcuts-title = Kortkommandon
+shortcuts-revsets = Redigering av revset
+shortcuts-graph = Navigering i grafen
+shortcuts-panels = Paneler och dialoger
+shortcut-previous-revset = Föregående revset i historiken
+shortcut-next-revset = Nästa revset i historiken
+shortcut-complete-revset = Infoga det första förslaget
+shortcut-next-node = Gå till nästa eller föregående nod
+shortcut-nearby-node = Gå till närmaste nod i den riktningen
+shortcut-toggle-node = Markera eller avmarkera noden
+shortcut-select-node = Markera bara noden
+shortcut-node-menu = Öppna nodens snabbmeny
+shortcut-shortcuts = Visa kortkommandon
+shortcut-viewpoint = Hoppa till den sparade vypunkten
+shortcut-close = Stäng dialogen eller menyn
+
+## Revset inputs and toolbar
+
+revset-select = Markera
+revset-view = Visa
+rebase-source = Källa för rebase
+revset-compare = Jämförd
+revset-hint = Skriv en revset här, t.ex. "@". Bläddra bland tidigare värden med upp/ned-tangenterna.
+revset-pending = Inte utvärderad
+revset-pending-hover = Utvärderas när du slutar skriva, eller direkt med Enter. Tills dess visar grafen den förra revseten.
+completion-function = funktion
+completion-alias = alias
+completion-bookmark = bokmärke
+completion-tag = tagg
+completion-operator = operator
+lint-empty-range = {$text} är alltid tom, eftersom ett intervall utesluter sin starts förfäder
+lint-name-for-email = {$text}() matchar bara e-postadresser, och här finns inget @
+lint-missing-glob = * och ? i {$text} matchas bokstavligt utan glob:
+lint-fix = Rätta
+lint-fix-hover = Ändra till {$fix}
+index-loading = Läser in förrådets index…
+index-loading-hint = Det tar en stund i mycket stora förråd. Grafen visas när det är klart.
+view-evaluating = Utvärderar vyns revset…
+select-evaluating = Utvärderar Select-revseten…
+view-evaluation-failed = Utvärderingen av vyns revset misslyckades.
+select-evaluation-failed = Utvärderingen av Select-revseten misslyckades.
+load-more = Läs in {$count} till
+load-more-hover = Grafen visar vyns första {$limit} commits. Lägg till vyns nästa commits i grafen.
+node-limit = Gränsen på {$limit} noder nåddes. Grafen är ofullständig.
+truncated-working-copy = Arbetskopian visas inte på grund av nodgränsen.
+truncated-trunk = trunk() visas inte på grund av nodgränsen.
+truncated-select = { $count ->
+        [one] En Markera-träff visas inte på grund av nodgränsen.
+       *[other] {$count} Markera-träffar visas inte på grund av nodgränsen.
+    }
+color-by-author = Färga efter författare
+path-filter = Ändrad sökväg:
+path-filter-hint = src/foo/
+path-filter-apply = Lägg till i Markera
+path-filter-last-touched = Senast ändrad
+path-filter-last-touched-hover = Markera de senaste commits i vyn som ändrade sökvägen, som ett snabbt jj file annotate
+author-filter = Författare:
+author-chip = {$name} ({$count})
+author-filter-apply = Lägg till författare i Markera
+author-filter-apply-hover = Markera bara commits av de valda författarna, med author(exact:…)
+date-filter = Commit-datum:
+date-filter-from = från
+date-filter-to = till
+date-filter-reset = Återställ
+date-filter-apply = Lägg till datum i Markera
+date-filter-apply-hover = Markera bara commits inom datumintervallet, med committer_date(after:…) och committer_date(before:…)
+date-histogram = Histogram över commit-datum
+date-histogram-hover = {$from} – {$to}: { $count ->
+        [one] 1 commit
+       *[other] {$count} commits
+    }
+diff-search = Sök i diffar:
+diff-search-hint = text
+diff-search-progress = Söker: {$checked} av {$total} commits, { $matches ->
+        [one] 1 träff
+       *[other] {$matches} träffar
+    }
+diff-search-done = { $matches ->
+        [0] Inga matchande commits
+        [one] 1 matchande commit
+       *[other] {$matches} matchande commits
+    }
+diff-search-cancel = Avbryt
+diff-search-clear = Rensa
+busy-evaluation = Söker i diffar
+busy-diffs = Beräknar diffar
+busy-prefetch = Hämtar commits i förväg
+undo-preview = Förhandsgranska jj undo
+undo-preview-hover = Markera de commits som skulle dyka upp (grön ring) eller försvinna (röd ring) om den senaste operationen ångrades
+undo-preview-summary = Ångra "{$operation}": {$appearing} dyker upp, {$disappearing} försvinner
+undo-preview-nothing = (inget att ångra)
+result-set-summary = Sedan det sparade resultatet: {$joined} tillkomna, {$left} försvunna
+result-set-summary-hover = Sparat från {$revset} vid operation {$operation}
+result-set-clear = Rensa
+result-change-title = Markera-resultatet har ändrats
+result-change-joined = { $count ->
+        [one] 1 commit tillkom
+       *[other] {$count} commits tillkom
+    }
+result-change-left = { $count ->
+        [one] 1 commit försvann
+       *[other] {$count} commits försvann
+    }
+rebase-preview = Förhandsgranska jj rebase
+rebase-preview-hover = Visa bleka kopior av källans commits och deras ättlingar där `jj rebase -s` skulle placera dem på den senast markerade commiten
+rebase-preview-summary = {$count} commits skulle flyttas till {$destination}
+rebase-preview-destination = Markera målet
+compare = Jämför
+compare-hover = Färga commits efter om de finns i markeringens revset, den jämförda revseten eller båda
+compare-only-select = Bara Markera: {$count}
+compare-only-compared = Bara Jämförd: {$count}
+compare-both = Båda: {$count}
+empty-view-title = Vyn är tom
+empty-view-revset = Inga commits finns i {$revset}
+empty-view-present = present() döljer bokmärken och taggar som inte finns. Kontrollera deras namn.
+empty-view-depth = Djupet för ancestors() och descendants() kan vara för litet för att nå dessa commits.
+empty-view-intersection = De två sidorna av & eller ~ kanske inte har några commits gemensamt.
+empty-view-show = Visa {$revset}
+empty-view-show-hover = Den närmaste bredare revset som har commits
+scope-non-empty = Ej tomma
+scope-mutable = Föränderliga
+scope-mine = Mina
+scope-hover = Begränsa vyn till {$revset}
+boundary-focus = Gränsfokus
+boundary-focus-hover = Visa commits runt de oföränderliga huvudena, med gränsen mellan föränderliga och oföränderliga commits framhävd: {$revset}
+outline-heads = Huvuden
+outline-heads-hover = Rama in heads(<view>), de commits i vyn som saknar barn i den
+outline-roots = Rötter
+outline-roots-hover = Rama in roots(<view>), de commits i vyn som saknar föräldrar i den
+outline-failed = Kunde inte hitta huvuden och rötter: {$error}
+color-rule-failed = Färgregeln "{$revset}" misslyckades: {$error}
+select-since-green = Markera sedan senaste gröna
+select-since-green-hover = Markera ättlingarna till de senaste commits som klarade CI
+
+## Node context menu
+
+open-in-browser = Öppna i webbläsaren
+no-forge = Ingen forge hittades. Ange forge.commit_url i konfigurationsfilen eller använd --commit-url.
+show-diff-in = Visa diff i {$tool}
+show-diff = Visa diff (jj diff)
+copy-revset-symbol = Kopiera revset-symbol
+copy-revset-symbol-hover = Kopiera det kortaste prefixet av ändrings-ID:t, eller commit_id() med ett prefix av commit-ID:t när ändringen är divergent
+pin-commit = Fäst i vyn
+unpin-commit = Lossa från vyn
+pin-commit-hover = Fästa commits visas alltid i det här förrådet, oavsett vyns revset
+expand-change = Expandera till {$count} commits
+collapse-change = Slå ihop till en nod
+measure-distances = Mät avstånd härifrån
+preview-bookmark-move = Förhandsgranska att flytta {$bookmark} hit
+new-commit = Ny commit ovanpå (jj new)
+edit-commit = Redigera (jj edit)
+abandon-commit = Överge (jj abandon)
+rebase = Flytta {$revision} till {$destination} (jj rebase)
+rebase-selected = Flytta markerad till markerad (jj rebase)
+rebase-hover = Markera den commit som ska flyttas och sedan målet
+
+## Dialogs
+
+bookmark-move-title = Flytta {$bookmark} till {$target}
+bookmark-move-reachable = { $count ->
+        [one] 1 commit blir nåbar
+       *[other] {$count} commits blir nåbara
+    }
+bookmark-move-unreachable = { $count ->
+        [one] 1 commit slutar vara nåbar
+       *[other] {$count} commits slutar vara nåbara
+    }
+bookmark-move-backwards = Flyttar bokmärket bakåt eller åt sidan (--allow-backwards)
+bookmark-move-failed = Kunde inte förhandsgranska bokmärkesflytten: {$error}
+move-bookmark = Flytta (jj bookmark move)
+confirm-command = Köra följande kommando?
+run = Kör
+working-copy-stale = ⚠ Arbetskopian kan vara inaktuell
+working-copy-stale-hover = Filer har ändrats sedan jj senast tog en ögonblicksbild av arbetskopian, så arbetskopians commit kanske inte innehåller dem än. Alla jj-kommandon tar en ögonblicksbild.
+snapshot = Ta ögonblicksbild
+snapshot-hover = Ta en ögonblicksbild av arbetskopian genom att köra jj status, och läs in igen
+cancel = Avbryt
+open-tab-title = Öppna förråd i ny flik
+open-tab-path = Förråd:
+open-tab = Öppna
+tab-close = Stäng {$name}
+export-title = Exportera som {$format}
+export-file = Fil:
+export-legend = Teckenförklaring
+export-legend-hover = Visa revseten och vad nodfärgerna betyder bredvid grafen
+export-select-only = Bara Markera-träffarna
+export-select-only-hover = Utelämna de commits som inte är i Markera, utom de mellan träffarna, som connected(), och flytta ihop raderna
+export = Exportera
+settings-export-title = Exportera inställningar
+settings-import-title = Importera inställningar
+settings-import-note = Ersätter utseendet, navigeringen och färgreglerna i {$path}.
+import = Importera
+result-set-save-title = Spara resultatmängd
+result-set-load-title = Läs in resultatmängd
+save = Spara
+load = Läs in
+replace-symbol-title = Ersätt symbol i revsets
+replace-symbol-from = Sök:
+replace-symbol-to = Ersätt med:
+replace-symbol-recording = Sessionsinspelning:
+replace-symbol-recording-hint = Valfri, t.ex. session.jsonl
+replace-symbol-count = { $count ->
+        [one] 1 revset i sessionen och färgreglerna ändras:
+       *[other] {$count} revsets i sessionen och färgreglerna ändras:
+    }
+replace-symbol = Ersätt
+export-html-title = Visa: {$view} | Markera: {$select}
+legend-view = Visa
+legend-select = Markera
+legend-compare = Jämförd
+legend-working-copy = Arbetskopia
+legend-immutable = Oföränderlig
+legend-mutable = Föränderlig
+legend-in-select = {$kind}, i Markera
+legend-rule = Färgregel: {$revset}
+
+## Details panel
+
+change-id = Ändrings-ID
+commit-id = Commit-ID
+signature = Signatur
+signature-verifying = Verifierar…
+signature-unsigned = Inte signerad
+signature-good = Giltig
+signature-unknown = Inte verifierad
+signature-invalid = Ogiltig
+signature-by = {$status}, av {$signer}
+signature-revsets = Signerade commits: signed(), osignerade: ~signed()
+no-description = (ingen beskrivning angiven)
+annotation = Annotering
+annotation-good = bra
+annotation-bad = dålig
+annotation-neutral = neutral
+issues = Ärenden
+select-matching = Markera alla commits med den: {$revset}
+conflicts = { $count ->
+        [one] 1 fil med konflikt
+       *[other] {$count} filer med konflikter
+    }
+conflicts-choose = Välj en fil för att förhandsgranska dess konfliktmarkeringar.
+conflicts-not-file = Konflikten har inget filinnehåll, t.ex. för att den är mellan en fil och en katalog.
+conflicts-failed = Kan inte läsa konflikterna: {$error}
+diff-loading = Läser in ändringar…
+diff-failed = Kan inte beräkna ändringarna: {$error}
+diff-no-changes = Inga ändringar
+diff-summary = { $files ->
+        [one] 1 fil ändrad
+       *[other] {$files} filer ändrade
+    }, { $insertions ->
+        [one] 1 tillagd rad
+       *[other] {$insertions} tillagda rader
+    }, { $deletions ->
+        [one] 1 borttagen rad
+       *[other] {$deletions} borttagna rader
+    }
+diff-binary = binär
+diff-too-large = Diffen har {$lines} rader, vilket är för många för att visas här.
+
+## Status bar
+
+done = Klar
+choose-nodes = Välj noder och tryck på Klar eller stäng fönstret.
+choose-revset = Välj markeringens revset och tryck på Klar eller stäng fönstret.
+copied-graph = Kopierade grafen till urklipp
+copied = Kopierade {$text} till urklipp
+exported-graph = Exporterade grafen till {$path}
+export-failed = Exporten misslyckades: {$error}
+exported-settings = Exporterade inställningarna till {$path}
+imported-settings = Importerade inställningarna från {$path}. Språket, zoomen och typsnitten ändras efter en omstart.
+import-failed = Importen misslyckades: {$error}
+saved-result-set = Sparade { $count ->
+        [one] 1 commit
+       *[other] {$count} commits
+    } till {$path}
+result-set-failed = Resultatmängd: {$error}
+perf-report-title = Exportera prestandarapport
+replaced-symbol = Ersatte {$from} med {$to} i { $count ->
+        [one] 1 revset
+       *[other] {$count} revsets
+    }
+replace-symbol-failed = Ersättningen misslyckades: {$error}
+reload-failed = Kunde inte läsa in förrådet igen: {$error}
+reload = Läs in igen
+reload-hover = Läs in förrådet igen, för att visa vad jj-kommandon har ändrat sedan det lästes in ({$shortcut}). Grafens zoom och position behålls.
+watch-failed = Förrådets operationer kunde inte läsas för bevakningen: {$error}
+watch-hook-failed = Kommandot "{$command}" misslyckades: {$error}
+clusters-failed = Kunde inte gruppera commits efter bokmärke: {$error}
+change-groups-failed = Kunde inte gruppera de divergenta commits: {$error}
+change-group = {$change_id}: {$count} divergenta commits
+distances-failed = Kunde inte beräkna generationsavstånden: {$error}
+session-failed = Inspelning eller uppspelning av sessionen misslyckades: {$error}
+script-failed = Skriptet misslyckades: {$error}
+replay-start = Uppspelning av {$count} steg
+replay-step = Steg {$step} av {$count}, vid {$seconds} s
+replay-previous = Föregående steg
+replay-next = Nästa steg
+annotations-failed = Kunde inte läsa in annoteringarna: {$error}
+pins-failed = Fästa commits: {$error}
+annotations-unresolved = { $count ->
+        [one] 1 annoterat ID matchar ingen commit: {$ids}
+       *[other] {$count} annoterade ID:n matchar ingen commit: {$ids}
+    }
+ci-no-remote = CI-status är aktiverad, men ingen "origin"-fjärr på GitHub eller GitLab hittades
+ci-fetch-failed = Kunde inte hämta CI-status: {$error}
+jj-failed = Kunde inte köra jj: {$error}
+
+tab-commits = Commits
+tab-results = Resultat
+results-count = {$count} commits i markeringen, i evalueringsordning
+results-count-ordering = {$count} commits i markeringen, i evalueringsordning. Resultatet beror på ordningen i {$functions}.
+results-commit = Commit
+results-description = Beskrivning
+results-author = Författare
+results-date = Datum
+tab-operations = Operationer
+operation-id = Operation:
+operation-description = Beskrivning:
+operation-user = Användare:
+operation-time = Tid:
+operation-count = {$count} operationer, den nuvarande i grönt. Markera en för att se detaljerna.
+operation-log-failed = Kunde inte läsa in operationsloggen: {$error}
+operation-show = Visa förrådet vid den här operationen
+operation-show-hover = Se hur revsets utvärderades vid den här operationen, t.ex. före en rebase eller abandon. Läs in förrådet igen för att komma tillbaka till den senaste operationen.
+operation-shown = Förrådet visas vid den här operationen
+operation-load-failed = Kunde inte läsa in operationen: {$error}
+past-operation = Visar förrådet vid operation {$operation}: {$description}
+past-operation-back = Tillbaka till den senaste operationen
+
+edge-hover = {$child} → förälder {$parent}
+
+## Screen readers
+
+commit-graph = Commitgraf
+operation-graph = Operationsgraf
+node-name = Commit {$label}: {$description}
+node-name-no-description = Commit {$label}, ingen beskrivning
+node-working-copy = {$change_id}, arbetskopia
+
+## Terminal user interface
+
+tui-help = Tab: byt fält  ↑/↓: rulla  Esc: avsluta
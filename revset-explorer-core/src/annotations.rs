@@ -0,0 +1,152 @@
+//! Annotations of commits from a local file, like bisect results or benchmark
+//! regressions, for overlaying on the graph. The file maps commit or change IDs,
+//! or their unique prefixes, to a status and a label:
+//!
+//! ```toml
+//! [a1b2c3d4]
+//! status = "bad"
+//! label = "first bad commit"
+//!
+//! [kxmqpoyz]
+//! status = "good"
+//! ```
+//!
+//! The same structure can be written as JSON, in a file ending with `.json`.
+
+use anyhow::Context;
+use jj_lib::backend::CommitId;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use crate::jjgraph::JjGraph;
+use crate::resolve::string_literal;
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnnotationStatus {
+    /// E.g. tests passed or the benchmark improved
+    Good,
+    /// E.g. tests failed or the benchmark regressed
+    Bad,
+    #[default]
+    Neutral,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Annotation {
+    pub status: AnnotationStatus,
+    pub label: Option<String>,
+}
+
+/// Annotations by commit or change ID, as written in the file
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Annotations {
+    by_id: BTreeMap<String, Annotation>,
+}
+
+/// Annotations of the commits that the IDs resolved to
+#[derive(Debug, Default)]
+pub struct ResolvedAnnotations {
+    pub by_commit: HashMap<CommitId, Annotation>,
+    /// IDs that do not match any commit in the repository, or are ambiguous
+    pub unresolved: Vec<String>,
+}
+
+impl Annotations {
+    /// Loads TOML, or JSON if the file name ends with `.json`
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let by_id = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(anyhow::Error::from)
+        } else {
+            toml::from_str(&content).map_err(anyhow::Error::from)
+        };
+        let by_id = by_id.with_context(|| format!("Failed to load {}", path.display()))?;
+        Ok(Self { by_id })
+    }
+
+    /// Looks up the IDs in the repository. Divergent change IDs annotate all
+    /// their commits.
+    pub fn resolve(&self, jj_graph: &JjGraph) -> ResolvedAnnotations {
+        let mut resolved = ResolvedAnnotations::default();
+        for (id, annotation) in &self.by_id {
+            let commit_ids: Vec<_> = match jj_graph.get_revset(&string_literal(id)) {
+                Ok(revset) => revset.iter().filter_map(Result::ok).collect(),
+                Err(_) => vec![],
+            };
+            if commit_ids.is_empty() {
+                resolved.unresolved.push(id.clone());
+            }
+            for commit_id in commit_ids {
+                resolved.by_commit.insert(commit_id, annotation.clone());
+            }
+        }
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+    use jj_lib::object_id::ObjectId;
+    use jj_lib::repo::Repo;
+
+    #[test]
+    fn loads_toml_and_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let toml_path = dir.path().join("bisect.toml");
+        std::fs::write(
+            &toml_path,
+            "[abc]\nstatus = \"bad\"\nlabel = \"first bad\"\n[def]\n",
+        )
+        .unwrap();
+        let json_path = dir.path().join("bisect.json");
+        std::fs::write(
+            &json_path,
+            r#"{"abc": {"status": "bad", "label": "first bad"}, "def": {}}"#,
+        )
+        .unwrap();
+        let annotations = Annotations::load(&toml_path).unwrap();
+        assert_eq!(
+            annotations.by_id["abc"],
+            Annotation {
+                status: AnnotationStatus::Bad,
+                label: Some("first bad".to_owned())
+            }
+        );
+        assert_eq!(annotations.by_id["def"], Annotation::default());
+        assert_eq!(Annotations::load(&json_path).unwrap(), annotations);
+
+        std::fs::write(&toml_path, "[abc]\nstatus = \"flaky\"\n").unwrap();
+        let error = Annotations::load(&toml_path).unwrap_err();
+        assert!(format!("{error:#}").contains("flaky"), "{error:#}");
+    }
+
+    #[test]
+    fn resolves_commit_and_change_ids() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let change_id = jj_graph.get_repo().store().get_commit(&b).unwrap();
+        let change_id = change_id.change_id().reverse_hex();
+        let bad = Annotation {
+            status: AnnotationStatus::Bad,
+            label: None,
+        };
+        let annotations = Annotations {
+            by_id: BTreeMap::from([
+                (a.hex()[..12].to_owned(), Annotation::default()),
+                (change_id, bad.clone()),
+                ("no-such-id".to_owned(), Annotation::default()),
+            ]),
+        };
+        let resolved = annotations.resolve(&jj_graph);
+        assert_eq!(resolved.by_commit[&a], Annotation::default());
+        assert_eq!(resolved.by_commit[&b], bad);
+        assert_eq!(resolved.unresolved, ["no-such-id"]);
+    }
+}
@@ -0,0 +1,94 @@
+//! What `jj bookmark move` would do, as the commits that would become or stop
+//! being reachable from the bookmark
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use jj_lib::ref_name::RefName;
+
+use crate::jjgraph::JjGraph;
+use crate::resolve::ResolveError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookmarkMove {
+    pub name: String,
+    /// The current targets of the bookmark, more than one if it is conflicted
+    pub from: Vec<CommitId>,
+    pub to: CommitId,
+    /// `::to ~ ::from`, children first
+    pub reachable: Vec<CommitId>,
+    /// `::from ~ ::to`, children first. Non-empty when moving backwards or
+    /// sideways, which jj only does with `--allow-backwards`.
+    pub unreachable: Vec<CommitId>,
+}
+
+impl BookmarkMove {
+    pub fn is_backwards(&self) -> bool {
+        !self.unreachable.is_empty()
+    }
+}
+
+/// Previews moving the local bookmark `name` to `to`. At most `limit` commits
+/// are listed in each direction.
+pub fn preview_bookmark_move(
+    jj_graph: &JjGraph,
+    name: &str,
+    to: &CommitId,
+    limit: usize,
+) -> Result<BookmarkMove, ResolveError> {
+    let repo = jj_graph.get_repo();
+    let from: Vec<_> = (repo.view().get_local_bookmark(RefName::new(name)))
+        .added_ids()
+        .cloned()
+        .collect();
+    if from.is_empty() {
+        return Err(ResolveError::JjError(format!("No bookmark {name}")));
+    }
+    let from_revset = (from.iter())
+        .map(|id| id.hex())
+        .collect::<Vec<_>>()
+        .join(" | ");
+    let to_hex = to.hex();
+    let commits = |revset: &str| -> Result<Vec<CommitId>, ResolveError> {
+        let revset = jj_graph
+            .get_revset(revset)
+            .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
+        let commit_ids = revset.iter().take(limit).collect::<Result<_, _>>()?;
+        Ok(commit_ids)
+    };
+    Ok(BookmarkMove {
+        name: name.to_owned(),
+        reachable: commits(&format!("::{to_hex} ~ ::({from_revset})"))?,
+        unreachable: commits(&format!("::({from_revset}) ~ ::{to_hex}"))?,
+        from,
+        to: to.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn previews_forward_and_sideways_moves() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let side = repo.commit("side", &[&a]);
+        repo.set_bookmark("main", &b);
+        let jj_graph = repo.jj_graph();
+
+        let forward = preview_bookmark_move(&jj_graph, "main", &c, 100).unwrap();
+        assert_eq!(forward.from, vec![b.clone()]);
+        assert_eq!(forward.reachable, vec![c.clone()]);
+        assert!(!forward.is_backwards());
+
+        let sideways = preview_bookmark_move(&jj_graph, "main", &side, 100).unwrap();
+        assert_eq!(sideways.reachable, vec![side]);
+        assert_eq!(sideways.unreachable, vec![b]);
+        assert!(sideways.is_backwards());
+
+        assert!(preview_bookmark_move(&jj_graph, "missing", &c, 100).is_err());
+    }
+}
@@ -0,0 +1,87 @@
+//! Clusters of commits by the bookmark whose history they belong to, for
+//! explaining the branch structure like Git branches. A commit belongs to its
+//! nearest descendant bookmark, i.e. the one fewest child steps away.
+
+use jj_lib::backend::CommitId;
+use jj_lib::graph::GraphEdgeType;
+use std::collections::HashMap;
+
+use crate::jjgraph::JjGraph;
+use crate::resolve::ResolveError;
+
+/// The bookmark of each commit in the view that has a bookmarked descendant in
+/// the view. Ties go to the bookmark that sorts first.
+pub fn bookmark_clusters(
+    jj_graph: &JjGraph,
+    view: &str,
+) -> Result<HashMap<CommitId, String>, ResolveError> {
+    let repo = jj_graph.get_repo();
+    let mut bookmarks = HashMap::<CommitId, String>::new();
+    // Sorted by name, so the first name wins
+    for (name, target) in repo.view().local_bookmarks() {
+        for commit_id in target.added_ids() {
+            bookmarks
+                .entry(commit_id.clone())
+                .or_insert_with(|| name.as_str().to_owned());
+        }
+    }
+
+    let view = view.trim();
+    let revset = jj_graph
+        .get_revset(&format!("({view}) & ::(bookmarks() & ({view}))"))
+        .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
+    // Steps to the bookmark, and its name
+    let mut nearest = HashMap::<CommitId, (usize, String)>::new();
+    // Children come before their parents
+    for node in revset.iter_graph() {
+        let (commit_id, edges) = node?;
+        if let Some(name) = bookmarks.get(&commit_id) {
+            nearest.insert(commit_id.clone(), (0, name.clone()));
+        }
+        let Some((distance, name)) = nearest.get(&commit_id).cloned() else {
+            continue;
+        };
+        for edge in edges {
+            if edge.edge_type == GraphEdgeType::Missing {
+                continue;
+            }
+            let candidate = (distance + 1, name.clone());
+            let parent = nearest.entry(edge.target).or_insert(candidate.clone());
+            *parent = parent.clone().min(candidate);
+        }
+    }
+    Ok(nearest
+        .into_iter()
+        .map(|(commit_id, (_, name))| (commit_id, name))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn clusters_by_nearest_descendant_bookmark() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let feature = repo.commit("feature", &[&a]);
+        let unbookmarked = repo.commit("unbookmarked", &[&c]);
+        repo.set_bookmark("main", &c);
+        repo.set_bookmark("topic", &feature);
+        let jj_graph = repo.jj_graph();
+        let clusters = bookmark_clusters(&jj_graph, &format!("{a}::")).unwrap();
+        assert_eq!(clusters[&c], "main");
+        assert_eq!(clusters[&b], "main");
+        assert_eq!(clusters[&feature], "topic");
+        // Two steps from main, one from topic
+        assert_eq!(clusters[&a], "topic");
+        assert!(!clusters.contains_key(&unbookmarked));
+
+        // Bookmarks outside the view do not count
+        let clusters = bookmark_clusters(&jj_graph, &format!("{a}::{b}")).unwrap();
+        assert!(clusters.is_empty());
+    }
+}
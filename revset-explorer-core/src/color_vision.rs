@@ -0,0 +1,125 @@
+//! Simulation of color vision deficiencies, for checking that the colors of a
+//! graph can be told apart by everyone
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deficiency {
+    /// No red cones
+    Protanopia,
+    /// No green cones
+    Deuteranopia,
+    /// No blue cones
+    Tritanopia,
+    /// No color vision at all
+    Achromatopsia,
+}
+
+impl Deficiency {
+    pub const ALL: [Self; 4] = [
+        Self::Protanopia,
+        Self::Deuteranopia,
+        Self::Tritanopia,
+        Self::Achromatopsia,
+    ];
+}
+
+/// How an sRGB color appears with the deficiency.
+///
+/// Dichromacies use the full severity matrices of Machado, Oliveira and
+/// Fernandes (2009), applied in linear RGB.
+pub fn simulate(rgb: [u8; 3], deficiency: Deficiency) -> [u8; 3] {
+    let matrix = match deficiency {
+        Deficiency::Protanopia => [
+            [0.152286, 1.052583, -0.204868],
+            [0.114503, 0.786281, 0.099216],
+            [-0.003882, -0.048116, 1.051998],
+        ],
+        Deficiency::Deuteranopia => [
+            [0.367322, 0.860646, -0.227968],
+            [0.280085, 0.672501, 0.047413],
+            [-0.011820, 0.042940, 0.968881],
+        ],
+        Deficiency::Tritanopia => [
+            [1.255528, -0.076749, -0.178779],
+            [-0.078411, 0.930809, 0.147602],
+            [0.004733, 0.691367, 0.303900],
+        ],
+        // Relative luminance
+        Deficiency::Achromatopsia => [[0.2126, 0.7152, 0.0722]; 3],
+    };
+    let linear = rgb.map(to_linear);
+    matrix.map(|row| {
+        let value: f32 = row.iter().zip(linear).map(|(m, c)| m * c).sum();
+        from_linear(value)
+    })
+}
+
+fn to_linear(component: u8) -> f32 {
+    let c = f32::from(component) / 255.;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(value: f32) -> u8 {
+    let c = value.clamp(0., 1.);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1. / 2.4) - 0.055
+    };
+    (c * 255.).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mark::{FilterMatch, Marking, NodeType, Palette};
+
+    #[test]
+    fn simulates_deficiencies() {
+        // Gray is unaffected
+        for deficiency in Deficiency::ALL {
+            assert_eq!(simulate([0x80; 3], deficiency), [0x80; 3]);
+        }
+        // Red and green are confused
+        let red = simulate([0xff, 0, 0], Deficiency::Deuteranopia);
+        let green = simulate([0, 0x80, 0], Deficiency::Deuteranopia);
+        assert!(red.iter().zip(green).all(|(r, g)| r.abs_diff(g) < 0x30));
+        assert_eq!(simulate([0xff, 0, 0], Deficiency::Achromatopsia), [0x7f; 3]);
+    }
+
+    /// All colors of the color-blind palette stay apart with dichromacy
+    #[test]
+    fn color_blind_palette_is_distinguishable() {
+        let colors: Vec<_> = [
+            NodeType::WorkingCopy,
+            NodeType::Immutable,
+            NodeType::Regular,
+        ]
+        .into_iter()
+        .flat_map(|node_type| {
+            [FilterMatch::Match, FilterMatch::NoMatch].map(|filter_match| {
+                Palette::ColorBlind.color(Marking {
+                    node_type,
+                    filter_match,
+                })
+            })
+        })
+        .collect();
+        for deficiency in &Deficiency::ALL[..3] {
+            let simulated: Vec<_> = colors.iter().map(|c| simulate(*c, *deficiency)).collect();
+            for (i, a) in simulated.iter().enumerate() {
+                for b in &simulated[i + 1..] {
+                    let distance: u32 = a
+                        .iter()
+                        .zip(b)
+                        .map(|(a, b)| u32::from(a.abs_diff(*b)))
+                        .sum();
+                    assert!(distance > 0x60, "{deficiency:?}: {a:?} and {b:?}");
+                }
+            }
+        }
+    }
+}
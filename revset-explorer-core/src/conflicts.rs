@@ -0,0 +1,98 @@
+//! Conflicts in the trees of commits, materialized with conflict markers like
+//! jj writes them to the working copy
+
+use jj_lib::backend::{BackendResult, CommitId};
+use jj_lib::config::StackedConfig;
+use jj_lib::conflicts::{
+    ConflictMarkerStyle, ConflictMaterializeOptions, MaterializedTreeValue,
+    materialize_merge_result_to_bytes, materialize_tree_value,
+};
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::settings::UserSettings;
+use jj_lib::tree_merge::MergeOptions;
+
+use crate::resolve::ResolveError;
+
+fn jj_error(e: impl std::fmt::Display) -> ResolveError {
+    ResolveError::JjError(e.to_string())
+}
+
+/// Paths with conflicts in the tree of the commit, separated by `/` and
+/// sorted
+pub fn conflicted_paths(
+    repo: &dyn Repo,
+    commit_id: &CommitId,
+) -> Result<Vec<String>, ResolveError> {
+    let commit = repo.store().get_commit(commit_id).map_err(jj_error)?;
+    Ok((commit.tree().conflicts())
+        .map(|(path, _value)| path.as_internal_file_string().to_owned())
+        .collect())
+}
+
+/// The contents of the conflicted file at the path, with conflict markers in
+/// jj's default style. None if the path has no conflict between files, e.g.
+/// if it is resolved or a conflict between a file and a directory.
+pub fn conflict_markers(
+    repo: &dyn Repo,
+    commit_id: &CommitId,
+    path: &str,
+) -> Result<Option<String>, ResolveError> {
+    let path = RepoPathBuf::from_internal_string(path).map_err(jj_error)?;
+    futures::executor::block_on(conflict_markers_async(repo, commit_id, &path)).map_err(jj_error)
+}
+
+async fn conflict_markers_async(
+    repo: &dyn Repo,
+    commit_id: &CommitId,
+    path: &RepoPathBuf,
+) -> BackendResult<Option<String>> {
+    let store = repo.store();
+    let commit = store.get_commit_async(commit_id).await?;
+    let value = commit.tree().path_value_async(path).await?;
+    let MaterializedTreeValue::FileConflict(file) =
+        materialize_tree_value(store, path, value).await?
+    else {
+        return Ok(None);
+    };
+    let settings = UserSettings::from_config(StackedConfig::with_defaults())
+        .expect("the default config is valid");
+    let options = ConflictMaterializeOptions {
+        marker_style: ConflictMarkerStyle::Diff,
+        marker_len: None,
+        merge: MergeOptions::from_settings(&settings).expect("the default config is valid"),
+    };
+    let contents = materialize_merge_result_to_bytes(&file.contents, &options);
+    Ok(Some(String::from_utf8_lossy(&contents).into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn materializes_conflicts() {
+        let mut repo = TestRepo::init();
+        let base = repo.commit_with_files("base", &[], &[("a.txt", "1\n"), ("b.txt", "1\n")]);
+        let left = repo.commit_with_files("left", &[&base], &[("a.txt", "2\n")]);
+        let right = repo.commit_with_files("right", &[&base], &[("a.txt", "3\n")]);
+        let merge = repo.merge("merge", &left, &base, &right);
+        let jj_graph = repo.jj_graph();
+        let repo = jj_graph.get_repo();
+        assert_eq!(conflicted_paths(repo.as_ref(), &merge).unwrap(), ["a.txt"]);
+        assert_eq!(
+            conflicted_paths(repo.as_ref(), &left).unwrap(),
+            [] as [&str; 0]
+        );
+        let markers = conflict_markers(repo.as_ref(), &merge, "a.txt")
+            .unwrap()
+            .unwrap();
+        assert!(markers.starts_with("<<<<<<<"), "{markers}");
+        assert!(
+            markers.contains("+2\n") && markers.contains("3\n"),
+            "{markers}"
+        );
+        assert_eq!(conflict_markers(repo.as_ref(), &merge, "b.txt"), Ok(None));
+    }
+}
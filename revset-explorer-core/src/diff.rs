@@ -0,0 +1,307 @@
+//! Changes made by commits, compared to their first parent
+
+use futures::StreamExt;
+use jj_lib::backend::{BackendResult, CommitId, TreeValue};
+use jj_lib::conflicts::{MaterializedTreeValue, materialize_tree_value};
+use jj_lib::diff::{ContentDiff, DiffHunkKind, find_line_ranges};
+use jj_lib::matchers::EverythingMatcher;
+use jj_lib::merge::Merge;
+use jj_lib::repo::Repo;
+use jj_lib::repo_path::RepoPath;
+use jj_lib::store::Store;
+
+use crate::resolve::ResolveError;
+
+/// Number of changed lines of a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileStat {
+    /// Path in the repository, separated by `/`
+    pub path: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Binary files, conflicts and other files without lines are not counted
+    pub binary: bool,
+}
+
+/// Changes of a file, as in a unified diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    pub stat: FileStat,
+    /// Empty for binary files
+    pub hunks: Vec<Hunk>,
+}
+
+/// Changed lines and the unchanged lines around them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// Line number in the parent of the first line, counting from 1
+    pub old_start: usize,
+    /// Line number in the commit of the first line, counting from 1
+    pub new_start: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+impl Hunk {
+    /// Number of lines in the parent
+    pub fn old_len(&self) -> usize {
+        (self.lines.iter())
+            .filter(|line| line.kind != LineKind::Added)
+            .count()
+    }
+
+    /// Number of lines in the commit
+    pub fn new_len(&self) -> usize {
+        (self.lines.iter())
+            .filter(|line| line.kind != LineKind::Removed)
+            .count()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffLine {
+    pub kind: LineKind,
+    /// Without the line ending. Invalid UTF-8 is replaced.
+    pub text: String,
+}
+
+/// Unchanged lines shown around changes, like `diff -u`
+const CONTEXT_LINES: usize = 3;
+
+/// Lines changed by the commit, per file, compared to its first parent. The
+/// files are sorted by path.
+pub fn diff_stat(repo: &dyn Repo, commit_id: &CommitId) -> Result<Vec<FileStat>, ResolveError> {
+    Ok(file_diffs(repo, commit_id)?
+        .into_iter()
+        .map(|diff| diff.stat)
+        .collect())
+}
+
+/// Changes made by the commit, per file, compared to its first parent. The
+/// files are sorted by path.
+pub fn file_diffs(repo: &dyn Repo, commit_id: &CommitId) -> Result<Vec<FileDiff>, ResolveError> {
+    futures::executor::block_on(file_diffs_async(repo, commit_id))
+        .map_err(|e| ResolveError::JjError(e.to_string()))
+}
+
+async fn file_diffs_async(repo: &dyn Repo, commit_id: &CommitId) -> BackendResult<Vec<FileDiff>> {
+    let store = repo.store();
+    let commit = store.get_commit_async(commit_id).await?;
+    let parent_tree = match commit.parent_ids().first() {
+        Some(parent_id) => store.get_commit_async(parent_id).await?.tree(),
+        // The root commit
+        None => store.empty_merged_tree(),
+    };
+    let mut stream = parent_tree.diff_stream(&commit.tree(), &EverythingMatcher);
+    let mut diffs = vec![];
+    while let Some(entry) = stream.next().await {
+        let [before, after] = entry.values?.into_array();
+        let before = file_contents(store, &entry.path, before).await?;
+        let after = file_contents(store, &entry.path, after).await?;
+        diffs.push(file_diff(
+            entry.path.as_internal_file_string(),
+            before,
+            after,
+        ));
+    }
+    Ok(diffs)
+}
+
+/// The lines of a file or symlink, empty if absent. `None` if it has no lines.
+async fn file_contents(
+    store: &Store,
+    path: &RepoPath,
+    value: Merge<Option<TreeValue>>,
+) -> BackendResult<Option<Vec<u8>>> {
+    let contents = match materialize_tree_value(store, path, value).await? {
+        MaterializedTreeValue::Absent => vec![],
+        MaterializedTreeValue::File(mut file) => file.read_all(path).await?,
+        MaterializedTreeValue::Symlink { target, .. } => target.into_bytes(),
+        _ => return Ok(None),
+    };
+    Ok(Some(contents))
+}
+
+fn file_diff(path: &str, before: Option<Vec<u8>>, after: Option<Vec<u8>>) -> FileDiff {
+    let mut diff = FileDiff {
+        stat: FileStat {
+            path: path.to_owned(),
+            insertions: 0,
+            deletions: 0,
+            binary: true,
+        },
+        hunks: vec![],
+    };
+    let (Some(before), Some(after)) = (before, after) else {
+        return diff;
+    };
+    // Like Git, look for NUL bytes
+    if [&before, &after].iter().any(|c| c.contains(&0)) {
+        return diff;
+    }
+    diff.stat.binary = false;
+    let mut lines = vec![];
+    for hunk in ContentDiff::by_line([&before, &after]).hunks() {
+        let line = |kind, text: &[u8]| DiffLine {
+            kind,
+            text: String::from_utf8_lossy(text)
+                .trim_end_matches(['\n', '\r'])
+                .to_owned(),
+        };
+        let split = |contents| find_line_ranges(contents).into_iter().map(|r| &contents[r]);
+        match hunk.kind {
+            DiffHunkKind::Matching => {
+                lines.extend(split(hunk.contents[0]).map(|text| line(LineKind::Context, text)));
+            }
+            DiffHunkKind::Different => {
+                let removed = split(hunk.contents[0]).map(|text| line(LineKind::Removed, text));
+                let added = split(hunk.contents[1]).map(|text| line(LineKind::Added, text));
+                lines.extend(removed.chain(added));
+            }
+        }
+    }
+    diff.stat.deletions = (lines.iter())
+        .filter(|line| line.kind == LineKind::Removed)
+        .count();
+    diff.stat.insertions = (lines.iter())
+        .filter(|line| line.kind == LineKind::Added)
+        .count();
+    diff.hunks = hunks(lines, CONTEXT_LINES);
+    diff
+}
+
+/// Groups the changed lines of a file, with `context` unchanged lines around
+/// them. Changes closer than twice that share a hunk.
+fn hunks(lines: Vec<DiffLine>, context: usize) -> Vec<Hunk> {
+    // Ranges of lines to show
+    let mut ranges: Vec<std::ops::Range<usize>> = vec![];
+    for (i, line) in lines.iter().enumerate() {
+        if line.kind == LineKind::Context {
+            continue;
+        }
+        let range = i.saturating_sub(context)..(i + context + 1).min(lines.len());
+        match ranges.last_mut() {
+            Some(last) if range.start <= last.end => last.end = range.end,
+            _ => ranges.push(range),
+        }
+    }
+    let mut hunks = Vec::with_capacity(ranges.len());
+    let (mut old_line, mut new_line) = (1, 1);
+    let mut lines = lines.into_iter().enumerate().peekable();
+    for range in ranges {
+        let mut hunk = Hunk {
+            old_start: 0,
+            new_start: 0,
+            lines: vec![],
+        };
+        while let Some((i, line)) = lines.next_if(|(i, _)| *i < range.end) {
+            if i == range.start {
+                (hunk.old_start, hunk.new_start) = (old_line, new_line);
+            }
+            if line.kind != LineKind::Added {
+                old_line += 1;
+            }
+            if line.kind != LineKind::Removed {
+                new_line += 1;
+            }
+            if i >= range.start {
+                hunk.lines.push(line);
+            }
+        }
+        hunks.push(hunk);
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn counts_changed_lines() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("src/a.txt", "1\n2\n3\n"), ("b.bin", "\0")]);
+        let b = repo.commit_with_files("b", &[&a], &[("src/a.txt", "1\nx\n3\n4\n")]);
+        let jj_graph = repo.jj_graph();
+        let repo = jj_graph.get_repo();
+        let stat = |path: &str, insertions, deletions, binary| FileStat {
+            path: path.to_owned(),
+            insertions,
+            deletions,
+            binary,
+        };
+        assert_eq!(
+            diff_stat(repo.as_ref(), &a).unwrap(),
+            [stat("b.bin", 0, 0, true), stat("src/a.txt", 3, 0, false)]
+        );
+        assert_eq!(
+            diff_stat(repo.as_ref(), &b).unwrap(),
+            [stat("src/a.txt", 2, 1, false)]
+        );
+        let root = repo.store().root_commit_id().clone();
+        assert_eq!(diff_stat(repo.as_ref(), &root).unwrap(), []);
+    }
+
+    #[test]
+    fn groups_changes_into_hunks() {
+        let before: String = (1..=20).map(|i| format!("{i}\n")).collect();
+        let after = before
+            .replace("\n2\n", "\ntwo\n")
+            .replace("\n5\n", "\n5\nfive\n")
+            .replace("\n16\n", "\n");
+        let diff = file_diff("a", Some(before.into()), Some(after.into()));
+        let line = |kind, text: &str| DiffLine {
+            kind,
+            text: text.to_owned(),
+        };
+        let context = |text| line(LineKind::Context, text);
+        let hunk = |old_start, new_start, lines| Hunk {
+            old_start,
+            new_start,
+            lines,
+        };
+        assert_eq!(
+            diff.hunks,
+            [
+                hunk(
+                    1,
+                    1,
+                    vec![
+                        context("1"),
+                        line(LineKind::Removed, "2"),
+                        line(LineKind::Added, "two"),
+                        context("3"),
+                        context("4"),
+                        context("5"),
+                        line(LineKind::Added, "five"),
+                        context("6"),
+                        context("7"),
+                        context("8"),
+                    ]
+                ),
+                hunk(
+                    13,
+                    14,
+                    vec![
+                        context("13"),
+                        context("14"),
+                        context("15"),
+                        line(LineKind::Removed, "16"),
+                        context("17"),
+                        context("18"),
+                        context("19"),
+                    ]
+                ),
+            ]
+        );
+        assert_eq!((diff.hunks[1].old_len(), diff.hunks[1].new_len()), (7, 6));
+        assert_eq!((diff.stat.insertions, diff.stat.deletions), (2, 2));
+    }
+}
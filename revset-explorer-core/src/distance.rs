@@ -0,0 +1,100 @@
+//! Generation distances from a reference commit, i.e. the fewest parent steps
+//! between them. A commit at distance `n` from `x` is in `ancestors(x, n + 1)`
+//! or `descendants(x, n + 1)`.
+
+use jj_lib::backend::CommitId;
+use jj_lib::graph::GraphEdgeType;
+use jj_lib::object_id::ObjectId;
+use std::collections::HashMap;
+
+use crate::jjgraph::JjGraph;
+use crate::resolve::ResolveError;
+
+/// Distances from the reference to the commits in the view that are its
+/// ancestors, as negative numbers, or its descendants, as positive numbers.
+/// The reference itself has distance 0. Other commits are left out.
+pub fn generation_distances(
+    jj_graph: &JjGraph,
+    reference: &CommitId,
+    view: &str,
+) -> Result<HashMap<CommitId, i64>, ResolveError> {
+    let reference_hex = reference.hex();
+    let view = view.trim();
+    let revset = |revset: &str| {
+        jj_graph
+            .get_revset(revset)
+            .map_err(|e| ResolveError::RevsetParseError(e.to_string()))
+    };
+    let mut distances = HashMap::from([(reference.clone(), 0)]);
+
+    // Children come before their parents, starting from the reference
+    let ancestors = revset(&format!("::{reference_hex} & ({view})::"))?;
+    for node in ancestors.iter_graph() {
+        let (commit_id, edges) = node?;
+        let Some(&distance) = distances.get(&commit_id) else {
+            continue;
+        };
+        for edge in edges {
+            if edge.edge_type == GraphEdgeType::Direct {
+                let parent = distances.entry(edge.target).or_insert(distance - 1);
+                *parent = (*parent).max(distance - 1);
+            }
+        }
+    }
+
+    let descendants = revset(&format!("{reference_hex}:: & ::({view})"))?;
+    let descendants: Vec<_> = descendants.iter_graph().collect::<Result<_, _>>()?;
+    // Parents first, ending at the heads of the view
+    for (commit_id, edges) in descendants.into_iter().rev() {
+        let distance = (edges.iter())
+            .filter(|edge| edge.edge_type == GraphEdgeType::Direct)
+            .filter_map(|edge| distances.get(&edge.target))
+            .filter(|&&distance| distance >= 0)
+            .map(|distance| distance + 1)
+            .min();
+        if let Some(distance) = distance {
+            distances.entry(commit_id).or_insert(distance);
+        }
+    }
+    Ok(distances)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn measures_shortest_paths() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        // Both one and two steps from a
+        let d = repo.commit("d", &[&a, &c]);
+        let e = repo.commit("e", &[&d]);
+        let side = repo.commit("side", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let view = format!("{a}:: ~ {}", e.hex());
+        let distances = generation_distances(&jj_graph, &c, &view).unwrap();
+        assert_eq!(
+            distances,
+            HashMap::from([
+                (a.clone(), -2),
+                (b.clone(), -1),
+                (c.clone(), 0),
+                (d.clone(), 1)
+            ])
+        );
+        let distances = generation_distances(&jj_graph, &d, &view).unwrap();
+        assert_eq!(distances[&a], -1);
+        assert_eq!(distances[&b], -2);
+        assert!(!distances.contains_key(&side));
+        // Matches jj's depth
+        let revset = jj_graph
+            .get_revset(&format!("ancestors({}, 2)", d.hex()))
+            .unwrap();
+        let ids: Vec<_> = revset.iter().map(Result::unwrap).collect();
+        assert!(ids.contains(&a) && !ids.contains(&b));
+    }
+}
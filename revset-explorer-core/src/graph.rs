@@ -0,0 +1,561 @@
+//! Construction of commit graphs from revsets
+
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::graph::GraphEdgeType;
+use jj_lib::repo::Repo;
+use jj_lib::revset::Revset;
+use petgraph::stable_graph::{NodeIndex, StableGraph};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::jjgraph::JjGraph;
+use crate::resolve::ResolveError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CommitNode {
+    pub commit_id: CommitId,
+    /// Text from the label formatter
+    pub label: String,
+}
+
+/// Commits and the edges to their parents. Edges go from child to parent.
+/// Indirect edges go to an ancestor when the commits in between are not part
+/// of the revset.
+pub type CommitGraph = StableGraph<CommitNode, GraphEdgeType>;
+
+/// What the label formatter gets to know about a commit
+pub struct LabelContext<'a> {
+    pub commit: &'a Commit,
+    /// Shortest unique prefix of the change ID
+    pub change_id_prefix: &'a str,
+    pub is_working_copy: bool,
+}
+
+/// Which edges to include in the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EdgePolicy {
+    /// Direct and indirect edges
+    #[default]
+    All,
+    /// Only edges between parents and children
+    DirectOnly,
+}
+
+pub struct BuiltGraph {
+    pub graph: CommitGraph,
+    pub metadata: GraphMetadata,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GraphMetadata {
+    /// The node limit, if the revset had more commits than that. The graph is
+    /// then incomplete.
+    pub limit_hit: Option<usize>,
+    pub working_copy: Option<NodeIndex>,
+    /// Where to continue reading the revset, when it was cut off
+    pub continuation: Option<Continuation>,
+}
+
+/// Where [`GraphBuilder::extend`] continues reading the revset of a graph
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Continuation {
+    /// Index of the first commit of the revset left out. The prioritized
+    /// commits after it are already in the graph.
+    position: usize,
+    /// Edges from the graph to commits that are not in it yet
+    pending_edges: Vec<(CommitId, CommitId, GraphEdgeType)>,
+}
+
+/// Builds [`CommitGraph`]s from revsets
+///
+/// ```no_run
+/// # use revset_explorer_core::{EdgePolicy, GraphBuilder, JjGraph};
+/// # let jj_graph = JjGraph::new(".".as_ref())?;
+/// let built = GraphBuilder::new(&jj_graph)
+///     .max_nodes(500)
+///     .edge_policy(EdgePolicy::DirectOnly)
+///     .label_formatter(|ctx| ctx.commit.description().lines().next().unwrap_or("").to_owned())
+///     .build(jj_graph.get_revset("::@")?)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct GraphBuilder<'g> {
+    jj_graph: &'g JjGraph,
+    max_nodes: usize,
+    label_formatter: Box<dyn Fn(&LabelContext) -> String + 'g>,
+    edge_policy: EdgePolicy,
+    prefetched: Option<&'g HashMap<CommitId, Commit>>,
+    prioritized: Vec<CommitId>,
+    cancelled: Option<&'g AtomicBool>,
+}
+
+impl<'g> GraphBuilder<'g> {
+    pub const DEFAULT_MAX_NODES: usize = 100;
+    /// How many times the node limit the prioritized commits are looked for
+    /// past it
+    pub const PRIORITIZED_SEARCH_FACTOR: usize = 10;
+
+    pub fn new(jj_graph: &'g JjGraph) -> Self {
+        Self {
+            jj_graph,
+            max_nodes: Self::DEFAULT_MAX_NODES,
+            label_formatter: Box::new(default_label),
+            edge_policy: EdgePolicy::default(),
+            prefetched: None,
+            prioritized: vec![],
+            cancelled: None,
+        }
+    }
+
+    /// Maximum number of commits to include in the graph
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self
+    }
+
+    /// Sets the function creating the node labels. The default is
+    /// [`default_label`].
+    pub fn label_formatter(mut self, formatter: impl Fn(&LabelContext) -> String + 'g) -> Self {
+        self.label_formatter = Box::new(formatter);
+        self
+    }
+
+    pub fn edge_policy(mut self, edge_policy: EdgePolicy) -> Self {
+        self.edge_policy = edge_policy;
+        self
+    }
+
+    /// Commits of the revset to include even when the node limit is hit,
+    /// instead of the last other commits. When there are more than the limit,
+    /// the first ones are included. The revset is only read past the limit
+    /// when some of them are not among its first commits, and then at most
+    /// [`Self::PRIORITIZED_SEARCH_FACTOR`] times the limit further.
+    pub fn prioritize(mut self, commit_ids: impl IntoIterator<Item = CommitId>) -> Self {
+        self.prioritized = commit_ids.into_iter().collect();
+        self
+    }
+
+    /// Commits read ahead of time, used instead of reading them from the
+    /// store
+    pub fn prefetched(mut self, commits: &'g HashMap<CommitId, Commit>) -> Self {
+        self.prefetched = Some(commits);
+        self
+    }
+
+    /// Stops reading the revset when the flag is set, by another thread. The
+    /// graph is then incomplete, and meant to be thrown away.
+    pub fn cancelled(mut self, cancelled: &'g AtomicBool) -> Self {
+        self.cancelled = Some(cancelled);
+        self
+    }
+
+    /// Creates a graph of the commits in the revset
+    pub fn build<'a>(&self, revset: Box<dyn Revset + 'a>) -> Result<BuiltGraph, ResolveError> {
+        let mut built = BuiltGraph {
+            graph: CommitGraph::default(),
+            metadata: GraphMetadata::default(),
+        };
+        self.read(revset, &mut built)?;
+        Ok(built)
+    }
+
+    /// Adds the next commits of the revset, up to the node limit, to a graph
+    /// that was cut off by the limit, where it stopped reading. `revset` must
+    /// be the one the graph was built from. Returns the added nodes.
+    pub fn extend<'a>(
+        &self,
+        revset: Box<dyn Revset + 'a>,
+        built: &mut BuiltGraph,
+    ) -> Result<Vec<NodeIndex>, ResolveError> {
+        if built.metadata.continuation.is_none() {
+            return Ok(vec![]);
+        }
+        self.read(revset, built)
+    }
+
+    /// Reads the commits of the revset into the graph, from where it was cut
+    /// off, if it was
+    fn read<'a>(
+        &self,
+        revset: Box<dyn Revset + 'a>,
+        built: &mut BuiltGraph,
+    ) -> Result<Vec<NodeIndex>, ResolveError> {
+        let Continuation {
+            position: start,
+            mut pending_edges,
+        } = built.metadata.continuation.take().unwrap_or_default();
+        let mut node_map: HashMap<CommitId, NodeIndex> = (built.graph.node_indices())
+            .map(|node_idx| (built.graph[node_idx].commit_id.clone(), node_idx))
+            .collect();
+
+        // The commits not in the graph yet, with their positions in the
+        // revset. One more than the limit, to know if there are more.
+        let mut revs = (revset.iter_graph().enumerate().skip(start))
+            .filter(|(_, rev)| !(rev.as_ref()).is_ok_and(|(id, _)| node_map.contains_key(id)));
+        let mut read = vec![];
+        for (position, rev) in revs.by_ref() {
+            if self.is_cancelled() {
+                break;
+            }
+            read.push((position, rev?));
+            if read.len() > self.max_nodes {
+                break;
+            }
+        }
+        let mut prioritized = HashSet::new();
+        for commit_id in &self.prioritized {
+            if prioritized.len() == self.max_nodes {
+                break;
+            }
+            if !node_map.contains_key(commit_id) {
+                prioritized.insert(commit_id);
+            }
+        }
+        let beyond_limit = read.split_off(read.len().min(self.max_nodes));
+        // Where to continue when more are loaded
+        let mut next = beyond_limit.first().map(|(position, _)| *position);
+        let mut missing = prioritized.clone();
+        for (_, (commit_id, _)) in &read {
+            missing.remove(commit_id);
+        }
+        // Only read further when the prioritized commits are not among the
+        // first ones
+        let mut found = vec![];
+        if !missing.is_empty() {
+            let further =
+                (beyond_limit.into_iter().map(|(_, rev)| Ok(rev))).chain(revs.map(|(_, rev)| rev));
+            let search = self.max_nodes * Self::PRIORITIZED_SEARCH_FACTOR;
+            for rev in further.take(search) {
+                if missing.is_empty() || self.is_cancelled() {
+                    break;
+                }
+                let (commit_id, commit_edges) = rev?;
+                if missing.remove(&commit_id) {
+                    found.push((commit_id, commit_edges));
+                }
+            }
+        }
+        // The last other commits make room for the prioritized ones
+        let read_prioritized = (read.iter())
+            .filter(|(_, (commit_id, _))| prioritized.contains(commit_id))
+            .count();
+        let mut others_left = self.max_nodes - read_prioritized - found.len();
+        read.retain(|(position, (commit_id, _))| {
+            if prioritized.contains(commit_id) {
+                return true;
+            }
+            if others_left == 0 {
+                next = Some(next.map_or(*position, |next| next.min(*position)));
+                return false;
+            }
+            others_left -= 1;
+            true
+        });
+        let read = (read.into_iter().map(|(_, rev)| rev)).chain(found);
+
+        let working_copy_commit_id = self.jj_graph.working_copy_commit_id();
+        let mut added = vec![];
+        for (commit_id, commit_edges) in read {
+            let is_working_copy = Some(&commit_id) == working_copy_commit_id.as_ref();
+            let node_idx = built
+                .graph
+                .add_node(self.node(&commit_id, is_working_copy)?);
+            node_map.insert(commit_id.clone(), node_idx);
+            added.push(node_idx);
+            if is_working_copy {
+                built.metadata.working_copy = Some(node_idx);
+            }
+
+            for commit_edge in commit_edges {
+                let included = match commit_edge.edge_type {
+                    GraphEdgeType::Direct => true,
+                    GraphEdgeType::Indirect => self.edge_policy == EdgePolicy::All,
+                    // The target is not in the revset
+                    GraphEdgeType::Missing => false,
+                };
+                if included {
+                    pending_edges.push((
+                        commit_id.clone(),
+                        commit_edge.target,
+                        commit_edge.edge_type,
+                    ));
+                }
+            }
+        }
+        // Edges to commits that are not in the graph yet wait for them to be
+        // loaded
+        pending_edges.retain(|(start, end, edge_type)| {
+            let (Some(start), Some(end)) = (node_map.get(start), node_map.get(end)) else {
+                return true;
+            };
+            built.graph.add_edge(*start, *end, *edge_type);
+            false
+        });
+
+        built.metadata.limit_hit = next.map(|_| built.graph.node_count());
+        built.metadata.continuation = next.map(|position| Continuation {
+            position,
+            pending_edges,
+        });
+        Ok(added)
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.is_some_and(|c| c.load(Ordering::Relaxed))
+    }
+
+    /// The node of the commit, with its label
+    fn node(
+        &self,
+        commit_id: &CommitId,
+        is_working_copy: bool,
+    ) -> Result<CommitNode, ResolveError> {
+        let repo = self.jj_graph.get_repo();
+        let prefetched = self.prefetched.and_then(|commits| commits.get(commit_id));
+        let commit = match prefetched {
+            Some(commit) => commit.clone(),
+            None => (repo.store().get_commit(commit_id))
+                .map_err(|e| ResolveError::JjError(e.to_string()))?,
+        };
+        let change_id = commit.change_id();
+        let change_id_len = repo
+            .shortest_unique_change_id_prefix_len(change_id)
+            .map_err(|e| ResolveError::JjError(e.to_string()))?;
+        let change_id_prefix = &change_id.to_string()[..change_id_len];
+        let label = (self.label_formatter)(&LabelContext {
+            commit: &commit,
+            change_id_prefix,
+            is_working_copy,
+        });
+        Ok(CommitNode {
+            commit_id: commit_id.clone(),
+            label,
+        })
+    }
+}
+
+/// The shortest unique change ID prefix, prefixed by "@ " for the
+/// working-copy commit
+pub fn default_label(ctx: &LabelContext) -> String {
+    if ctx.is_working_copy {
+        format!("@ {}", ctx.change_id_prefix)
+    } else {
+        ctx.change_id_prefix.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+    use jj_lib::object_id::ObjectId;
+
+    fn revset_of(ids: &[&CommitId]) -> String {
+        ids.iter()
+            .map(|id| id.hex())
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    #[test]
+    fn builds_chain() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        repo.edit(&b);
+        let jj_graph = repo.jj_graph();
+        let built = GraphBuilder::new(&jj_graph)
+            .build(jj_graph.get_revset(&revset_of(&[&a, &b])).unwrap())
+            .unwrap();
+        let graph = &built.graph;
+        assert_eq!(graph.node_count(), 2);
+        assert_eq!(graph.edge_count(), 1);
+        let edge = graph.edge_indices().next().unwrap();
+        let (child, parent) = graph.edge_endpoints(edge).unwrap();
+        assert_eq!(graph[child].commit_id, b);
+        assert_eq!(graph[parent].commit_id, a);
+        assert_eq!(built.metadata.working_copy, Some(child));
+        assert!(graph[child].label.starts_with("@ "));
+        assert!(!graph[parent].label.starts_with("@ "));
+        assert_eq!(built.metadata.limit_hit, None);
+    }
+
+    #[test]
+    fn node_limit() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let jj_graph = repo.jj_graph();
+        let built = GraphBuilder::new(&jj_graph)
+            .max_nodes(2)
+            .build(jj_graph.get_revset(&revset_of(&[&a, &b, &c])).unwrap())
+            .unwrap();
+        assert_eq!(built.graph.node_count(), 2);
+        assert_eq!(built.metadata.limit_hit, Some(2));
+    }
+
+    #[test]
+    fn edge_policy() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let jj_graph = repo.jj_graph();
+        let revset = revset_of(&[&a, &c]);
+        let all = GraphBuilder::new(&jj_graph)
+            .build(jj_graph.get_revset(&revset).unwrap())
+            .unwrap();
+        assert_eq!(
+            all.graph.edge_weights().collect::<Vec<_>>(),
+            [&GraphEdgeType::Indirect]
+        );
+        let direct = GraphBuilder::new(&jj_graph)
+            .edge_policy(EdgePolicy::DirectOnly)
+            .build(jj_graph.get_revset(&revset).unwrap())
+            .unwrap();
+        assert_eq!(direct.graph.edge_count(), 0);
+    }
+
+    #[test]
+    fn label_formatter() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("first line\nsecond line", &[]);
+        let jj_graph = repo.jj_graph();
+        let built = GraphBuilder::new(&jj_graph)
+            .label_formatter(|ctx| ctx.commit.description().lines().next().unwrap().to_owned())
+            .build(jj_graph.get_revset(&a.hex()).unwrap())
+            .unwrap();
+        assert_eq!(
+            built.graph.node_weights().next().unwrap().label,
+            "first line"
+        );
+    }
+
+    #[test]
+    fn prioritized() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let jj_graph = repo.jj_graph();
+        let revset = revset_of(&[&a, &b, &c]);
+        let built = GraphBuilder::new(&jj_graph)
+            .max_nodes(2)
+            .prioritize([a.clone()])
+            .build(jj_graph.get_revset(&revset).unwrap())
+            .unwrap();
+        let mut commit_ids: Vec<_> = (built.graph.node_weights())
+            .map(|node| node.commit_id.clone())
+            .collect();
+        commit_ids.sort();
+        let mut expected = vec![a.clone(), c];
+        expected.sort();
+        assert_eq!(commit_ids, expected);
+        assert_eq!(built.metadata.limit_hit, Some(2));
+
+        // The first prioritized commits when they are more than the limit
+        let built = GraphBuilder::new(&jj_graph)
+            .max_nodes(1)
+            .prioritize([a.clone(), b])
+            .build(jj_graph.get_revset(&revset).unwrap())
+            .unwrap();
+        let commit_ids: Vec<_> = (built.graph.node_weights())
+            .map(|node| node.commit_id.clone())
+            .collect();
+        assert_eq!(commit_ids, [a]);
+    }
+
+    #[test]
+    fn prioritized_search_is_limited() {
+        let mut repo = TestRepo::init();
+        let mut chain = vec![repo.commit("0", &[])];
+        for i in 1..=GraphBuilder::PRIORITIZED_SEARCH_FACTOR + 1 {
+            let parent = chain.last().unwrap().clone();
+            chain.push(repo.commit(&i.to_string(), &[&parent]));
+        }
+        let jj_graph = repo.jj_graph();
+        let revset = revset_of(&chain.iter().collect::<Vec<_>>());
+        let nodes = |prioritized: &CommitId| {
+            let built = GraphBuilder::new(&jj_graph)
+                .max_nodes(1)
+                .prioritize([prioritized.clone()])
+                .build(jj_graph.get_revset(&revset).unwrap())
+                .unwrap();
+            assert_eq!(built.metadata.limit_hit, Some(1));
+            (built.graph.node_weights())
+                .map(|node| node.commit_id.clone())
+                .collect::<Vec<_>>()
+        };
+        // Newest first
+        assert_eq!(nodes(&chain[1]), [chain[1].clone()]);
+        // Further than the search, so the newest is shown instead
+        assert_eq!(nodes(&chain[0]), [chain.last().unwrap().clone()]);
+    }
+
+    #[test]
+    fn extends() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let d = repo.commit("d", &[&c]);
+        let e = repo.commit("e", &[&d]);
+        let jj_graph = repo.jj_graph();
+        let revset = || {
+            jj_graph
+                .get_revset(&revset_of(&[&a, &b, &c, &d, &e]))
+                .unwrap()
+        };
+        let builder = GraphBuilder::new(&jj_graph).max_nodes(2);
+        let mut built = builder.prioritize([a.clone()]).build(revset()).unwrap();
+        assert_eq!(built.graph.node_count(), 2);
+        assert_eq!(built.metadata.limit_hit, Some(2));
+
+        // Continues after e, skipping the prioritized a
+        let builder = GraphBuilder::new(&jj_graph).max_nodes(2);
+        let added = builder.extend(revset(), &mut built).unwrap();
+        let added: Vec<_> = (added.iter())
+            .map(|idx| built.graph[*idx].commit_id.clone())
+            .collect();
+        assert_eq!(added, [d.clone(), c.clone()]);
+        assert_eq!(built.metadata.limit_hit, Some(4));
+        let added = builder.extend(revset(), &mut built).unwrap();
+        assert_eq!(built.graph[added[0]].commit_id, b);
+        assert_eq!(built.metadata.limit_hit, None);
+        // The chain, with the edges to the commits loaded later
+        assert_eq!(built.graph.node_count(), 5);
+        assert_eq!(built.graph.edge_count(), 4);
+        assert!(builder.extend(revset(), &mut built).unwrap().is_empty());
+    }
+
+    #[test]
+    fn prefetched() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let jj_graph = repo.jj_graph();
+        // Under the wrong ID, to see that the store is not read
+        let store = jj_graph.get_repo().store().clone();
+        let commits = HashMap::from([(a.clone(), store.get_commit(&b).unwrap())]);
+        let built = GraphBuilder::new(&jj_graph)
+            .prefetched(&commits)
+            .label_formatter(|ctx| ctx.commit.description().to_owned())
+            .build(jj_graph.get_revset(&a.hex()).unwrap())
+            .unwrap();
+        assert_eq!(built.graph.node_weights().next().unwrap().label, "b");
+    }
+
+    #[test]
+    fn cancelled() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let jj_graph = repo.jj_graph();
+        let cancelled = AtomicBool::new(true);
+        let built = GraphBuilder::new(&jj_graph)
+            .cancelled(&cancelled)
+            .build(jj_graph.get_revset(&a.hex()).unwrap())
+            .unwrap();
+        assert_eq!(built.graph.node_count(), 0);
+    }
+}
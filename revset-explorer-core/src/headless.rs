@@ -0,0 +1,133 @@
+//! The graph as Revset Explorer would show it, without a window. Meant for
+//! regression tests of graph building and marking, and for scripts.
+
+use jj_lib::backend::CommitId;
+
+use crate::graph::GraphBuilder;
+use crate::jjgraph::JjGraph;
+use crate::mark::{Marking, mark_commits};
+use crate::resolve::{ResolveError, resolve_revset};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedGraph {
+    /// In the order the revset yields them, i.e. children before parents
+    pub nodes: Vec<RenderedNode>,
+    /// Indexes into `nodes`, from child to parent
+    pub edges: Vec<(usize, usize)>,
+    /// The node limit, if it was hit
+    pub limit_hit: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderedNode {
+    pub commit_id: CommitId,
+    pub label: String,
+    pub marking: Marking,
+    /// RGB
+    pub color: [u8; 3],
+}
+
+/// Builds and marks the graph of the `view` revset, with the commits in the
+/// `select` revset highlighted
+pub fn render(
+    jj_graph: &JjGraph,
+    view: &str,
+    select: &str,
+    max_nodes: usize,
+) -> Result<RenderedGraph, ResolveError> {
+    let (view_revset, ..) = resolve_revset(jj_graph, view)?;
+    let built = GraphBuilder::new(jj_graph)
+        .max_nodes(max_nodes)
+        .build(view_revset)?;
+    let graph = &built.graph;
+    let (select_revset, ..) = resolve_revset(jj_graph, select)?;
+    let commit_ids = graph.node_weights().map(|node| &node.commit_id);
+    let markings = mark_commits(jj_graph, commit_ids, Some(select_revset))?;
+
+    let node_positions: Vec<_> = graph.node_indices().collect();
+    let nodes = graph
+        .node_weights()
+        .zip(markings)
+        .map(|(node, marking)| RenderedNode {
+            commit_id: node.commit_id.clone(),
+            label: node.label.clone(),
+            marking,
+            color: marking.color(),
+        })
+        .collect();
+    let position = |node_idx| node_positions.iter().position(|&i| i == node_idx).unwrap();
+    let edges = graph
+        .edge_indices()
+        .map(|edge_idx| {
+            let (start, end) = graph.edge_endpoints(edge_idx).unwrap();
+            (position(start), position(end))
+        })
+        .collect();
+    Ok(RenderedGraph {
+        nodes,
+        edges,
+        limit_hit: built.metadata.limit_hit,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mark::{FilterMatch, NodeType};
+    use crate::test_repo::TestRepo;
+    use jj_lib::object_id::ObjectId;
+
+    #[test]
+    fn marks_working_copy_and_selection() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        repo.edit(&c);
+        let jj_graph = repo.jj_graph();
+        let rendered = render(&jj_graph, &format!("{}::", a.hex()), &b.hex(), 100).unwrap();
+
+        let ids: Vec<_> = rendered.nodes.iter().map(|n| &n.commit_id).collect();
+        assert_eq!(ids, [&c, &b, &a]);
+        assert_eq!(rendered.edges, [(0, 1), (1, 2)]);
+        let markings: Vec<_> = rendered.nodes.iter().map(|n| n.marking).collect();
+        assert_eq!(
+            markings,
+            [
+                Marking {
+                    node_type: NodeType::WorkingCopy,
+                    filter_match: FilterMatch::NoMatch
+                },
+                Marking {
+                    node_type: NodeType::Regular,
+                    filter_match: FilterMatch::Match
+                },
+                Marking {
+                    node_type: NodeType::Regular,
+                    filter_match: FilterMatch::NoMatch
+                },
+            ]
+        );
+        assert_eq!(rendered.nodes[1].color, [0xff, 0xfc, 0x00]);
+    }
+
+    #[test]
+    fn root_is_immutable() {
+        let repo = TestRepo::init();
+        let jj_graph = repo.jj_graph();
+        let rendered = render(&jj_graph, "root()", "", 100).unwrap();
+        assert_eq!(rendered.nodes.len(), 1);
+        assert_eq!(rendered.nodes[0].marking.node_type, NodeType::Immutable);
+        assert_eq!(rendered.nodes[0].marking.filter_match, FilterMatch::NoMatch);
+    }
+
+    #[test]
+    fn invalid_revset() {
+        let repo = TestRepo::init();
+        let jj_graph = repo.jj_graph();
+        assert!(matches!(
+            render(&jj_graph, "::(", "", 100),
+            Err(ResolveError::RevsetParseError(_))
+        ));
+    }
+}
@@ -0,0 +1,359 @@
+//! Access to a jj repository for evaluating revsets
+
+use etcetera::BaseStrategy;
+use jj_lib::backend::CommitId;
+use jj_lib::config::StackedConfig;
+use jj_lib::git::REMOTE_NAME_FOR_LOCAL_GIT_REPO;
+use jj_lib::op_store::OperationId;
+use jj_lib::ref_name::WorkspaceName;
+use jj_lib::repo::{ReadonlyRepo, Repo, RepoLoader, StoreFactories};
+use jj_lib::repo_path::RepoPathUiConverter;
+use jj_lib::revset::{self, Revset, RevsetDiagnostics, RevsetWorkspaceContext};
+use jj_lib::revset::{RevsetAliasesMap, RevsetExtensions, RevsetParseContext, SymbolResolver};
+use jj_lib::settings::UserSettings;
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// A loaded jj repository together with the revset aliases and extensions
+/// needed to evaluate revsets in it. Aliases are read from the built-in jj
+/// defaults and the user's jj config.
+#[derive(Clone)]
+pub struct JjGraph {
+    path_converter: RepoPathUiConverter,
+    aliases_map: RevsetAliasesMap,
+    parse_settings: ParseSettings,
+    repo: Arc<ReadonlyRepo>,
+    revset_exts: Arc<RevsetExtensions>,
+}
+
+/// Settings from the jj config that change what revsets mean, used like the
+/// jj CLI does
+#[derive(Debug, Clone, Default, PartialEq)]
+struct ParseSettings {
+    /// `user.email`, matched by `mine()`
+    user_email: String,
+    /// `ui.revsets-use-glob-by-default`, making string patterns without a
+    /// kind globs instead of substrings
+    use_glob_by_default: bool,
+}
+
+#[derive(Error, Debug)]
+pub enum RevsetError {
+    #[error("Failed to parse revset: {0}")]
+    ParseError(String),
+}
+
+impl JjGraph {
+    /// Loads the repository at `repo_path`, the workspace root containing `.jj`
+    pub fn new(repo_path: &Path) -> anyhow::Result<Self> {
+        Self::with_extensions(repo_path, RevsetExtensions::new())
+    }
+
+    /// Like [`JjGraph::new`], with custom revset functions and symbol
+    /// resolvers registered in `revset_exts`
+    pub fn with_extensions(
+        repo_path: &Path,
+        revset_exts: RevsetExtensions,
+    ) -> anyhow::Result<Self> {
+        Self::with_store_factories(repo_path, revset_exts, &StoreFactories::default())
+    }
+
+    /// Like [`JjGraph::with_extensions`], loading the repository using
+    /// `store_factories` instead of the jj defaults. Needed for repositories
+    /// with custom commit backends and other stores. Extend
+    /// [`StoreFactories::default`] to keep support for the built-in ones.
+    pub fn with_store_factories(
+        repo_path: &Path,
+        revset_exts: RevsetExtensions,
+        store_factories: &StoreFactories,
+    ) -> anyhow::Result<Self> {
+        let path_converter = RepoPathUiConverter::Fs {
+            cwd: PathBuf::from_str(".").unwrap(),
+            base: PathBuf::from_str(".").unwrap(),
+        };
+        let settings = UserSettings::from_config(StackedConfig::with_defaults())?;
+        let repo = RepoLoader::init_from_file_system(
+            &settings,
+            &repo_path.join(".jj/repo"),
+            store_factories,
+        )?
+        .load_at_head()?;
+
+        let mut aliases_map = RevsetAliasesMap::new();
+        let mut parse_settings = ParseSettings::default();
+        let user_config_path = {
+            let mut path = etcetera::choose_base_strategy().unwrap().config_dir();
+            path.push("jj/config.toml");
+            path
+        };
+        let jj_revsets = include_str!("revsets.toml");
+        load_config(jj_revsets, &mut aliases_map, &mut parse_settings)?;
+        match std::fs::read_to_string(&user_config_path) {
+            Ok(user_config) => load_config(&user_config, &mut aliases_map, &mut parse_settings)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+
+        Ok(Self {
+            path_converter,
+            aliases_map,
+            parse_settings,
+            repo,
+            revset_exts: Arc::new(revset_exts),
+        })
+    }
+
+    /// Parses and evaluates the revset
+    pub fn get_revset<'r>(&'r self, revset_str: &str) -> Result<Box<dyn Revset + 'r>, RevsetError> {
+        let now = chrono::Local::now();
+
+        let resolver = SymbolResolver::new(self.repo.as_ref(), self.revset_exts.symbol_resolvers());
+        let workspace = RevsetWorkspaceContext {
+            path_converter: &self.path_converter,
+            workspace_name: WorkspaceName::DEFAULT,
+        };
+        // Like in jj, `<bookmark>@git` is left out of `remote_bookmarks()` in
+        // git-backed repositories
+        let git_backed = jj_lib::git::get_git_backend(self.repo.store()).is_ok();
+        let context = RevsetParseContext {
+            aliases_map: &self.aliases_map,
+            local_variables: HashMap::new(),
+            user_email: &self.parse_settings.user_email,
+            date_pattern_context: now.into(),
+            default_ignored_remote: git_backed.then_some(REMOTE_NAME_FOR_LOCAL_GIT_REPO),
+            use_glob_by_default: self.parse_settings.use_glob_by_default,
+            extensions: &self.revset_exts,
+            workspace: Some(workspace),
+        };
+
+        let mut diagnostics = RevsetDiagnostics::new();
+        let (expr, _modifier) = revset::parse_with_modifier(&mut diagnostics, revset_str, &context)
+            .map_err(|e| RevsetError::ParseError(e.to_string()))?;
+        let resolved = expr
+            .resolve_user_expression(self.repo.as_ref(), &resolver)
+            .map_err(|e| RevsetError::ParseError(e.to_string()))?;
+        let revset = resolved
+            .evaluate(self.repo.as_ref())
+            .map_err(|e| RevsetError::ParseError(e.to_string()))?;
+
+        Ok(revset)
+    }
+
+    /// Whether string patterns without a kind are globs, as set by
+    /// `ui.revsets-use-glob-by-default` in the jj config
+    pub fn uses_glob_by_default(&self) -> bool {
+        self.parse_settings.use_glob_by_default
+    }
+
+    /// The revset aliases from the built-in jj defaults and the user's jj
+    /// config
+    pub fn aliases_map(&self) -> &RevsetAliasesMap {
+        &self.aliases_map
+    }
+
+    pub fn get_repo(&self) -> Arc<ReadonlyRepo> {
+        self.repo.clone()
+    }
+
+    /// The working-copy commit of the default workspace
+    pub fn working_copy_commit_id(&self) -> Option<CommitId> {
+        self.repo
+            .view()
+            .get_wc_commit_id(WorkspaceName::DEFAULT)
+            .cloned()
+    }
+
+    /// Paths of the files in the working-copy commit, and of the directories
+    /// containing them with a trailing `/`, sorted
+    pub fn tracked_paths(&self) -> anyhow::Result<Vec<String>> {
+        let Some(commit_id) = self.working_copy_commit_id() else {
+            return Ok(vec![]);
+        };
+        let commit = self.repo.store().get_commit(&commit_id)?;
+        let mut paths = BTreeSet::new();
+        for (path, _value) in commit.tree().entries() {
+            let path = path.as_internal_file_string();
+            for (i, _) in path.match_indices('/') {
+                paths.insert(format!("{}/", &path[..i]));
+            }
+            paths.insert(path.to_owned());
+        }
+        Ok(paths.into_iter().collect())
+    }
+
+    /// The repository as it was before the latest operation, i.e. what `jj undo`
+    /// would restore. Returns None at the root operation.
+    pub fn at_parent_operation(&self) -> anyhow::Result<Option<JjGraph>> {
+        let Some(parent_op) = self.repo.operation().parents().next() else {
+            return Ok(None);
+        };
+        let repo = self.repo.loader().load_at(&parent_op?)?;
+        Ok(Some(Self {
+            repo,
+            ..self.clone()
+        }))
+    }
+
+    /// The repository as it was at the operation, for seeing how revsets
+    /// evaluated before e.g. a rebase or an abandon
+    pub fn at_operation(&self, op_id: &OperationId) -> anyhow::Result<JjGraph> {
+        let loader = self.repo.loader();
+        let repo = loader.load_at(&loader.load_operation(op_id)?)?;
+        Ok(Self {
+            repo,
+            ..self.clone()
+        })
+    }
+
+    /// Whether operations have been made since the repository was loaded,
+    /// like by jj commands. Only reads the operation heads, so it is cheap
+    /// enough to poll.
+    pub fn is_outdated(&self) -> anyhow::Result<bool> {
+        let op_heads_store = self.repo.loader().op_heads_store();
+        let op_heads = futures::executor::block_on(op_heads_store.get_op_heads())?;
+        Ok(op_heads != [self.repo.op_id().clone()])
+    }
+
+    /// Description of the operation the repository was loaded at
+    pub fn operation_description(&self) -> String {
+        self.repo.operation().metadata().description.clone()
+    }
+
+    /// URL of the given git remote, if the repository is backed by git
+    pub fn git_remote_url(&self, remote_name: &str) -> Option<String> {
+        let git_repo = jj_lib::git::get_git_repo(self.repo.store()).ok()?;
+        let url = git_repo
+            .config_snapshot()
+            .string(format!("remote.{remote_name}.url").as_str())?
+            .to_string();
+        Some(url)
+    }
+}
+
+/// Reads the revset aliases and the parse settings of a jj config file,
+/// overriding those read before
+fn load_config(
+    config_str: &str,
+    aliases_map: &mut RevsetAliasesMap,
+    parse_settings: &mut ParseSettings,
+) -> anyhow::Result<()> {
+    let config = config_str.parse::<toml::Table>()?;
+    if let Some(aliases) = config.get("revset-aliases") {
+        for (alias, expr) in aliases.as_table().unwrap().iter() {
+            aliases_map.insert(alias, expr.as_str().unwrap()).unwrap();
+        }
+    }
+    let get = |table: &str, key: &str| config.get(table).and_then(|table| table.get(key));
+    if let Some(email) = get("user", "email").and_then(|email| email.as_str()) {
+        parse_settings.user_email = email.to_owned();
+    }
+    if let Some(glob) = get("ui", "revsets-use-glob-by-default").and_then(|glob| glob.as_bool()) {
+        parse_settings.use_glob_by_default = glob;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+    use jj_lib::object_id::ObjectId;
+
+    #[test]
+    fn store_factories() {
+        let repo = TestRepo::init();
+        let error = JjGraph::with_store_factories(
+            repo.path(),
+            RevsetExtensions::new(),
+            &StoreFactories::empty(),
+        )
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("Unsupported"), "{error}");
+
+        let mut store_factories = StoreFactories::empty();
+        store_factories.merge(StoreFactories::default());
+        let jj_graph =
+            JjGraph::with_store_factories(repo.path(), RevsetExtensions::new(), &store_factories)
+                .unwrap();
+        assert!(jj_graph.working_copy_commit_id().is_some());
+    }
+
+    #[test]
+    fn loads_earlier_operations() {
+        let mut repo = TestRepo::init();
+        let jj_graph = repo.jj_graph();
+        let op_id = jj_graph.get_repo().op_id().clone();
+        let a = repo.commit("a", &[]);
+        let jj_graph = repo.jj_graph();
+        assert!(
+            jj_graph
+                .get_revset(&a.hex())
+                .unwrap()
+                .iter()
+                .next()
+                .is_some()
+        );
+        let earlier = jj_graph.at_operation(&op_id).unwrap();
+        assert_eq!(earlier.get_repo().op_id(), &op_id);
+        assert!(earlier.is_outdated().unwrap());
+        // Made after the operation
+        assert!(earlier.get_revset(&a.hex()).is_err());
+    }
+
+    #[test]
+    fn lists_tracked_paths() {
+        let mut repo = TestRepo::init();
+        let files = [("src/ui/main.rs", ""), ("src/lib.rs", ""), ("README", "")];
+        let a = repo.commit_with_files("a", &[], &files);
+        repo.edit(&a);
+        assert_eq!(
+            repo.jj_graph().tracked_paths().unwrap(),
+            ["README", "src/", "src/lib.rs", "src/ui/", "src/ui/main.rs"]
+        );
+    }
+
+    #[test]
+    fn reads_parse_settings_from_config() {
+        let mut aliases_map = RevsetAliasesMap::new();
+        let mut parse_settings = ParseSettings::default();
+        let config = r#"
+            ui.revsets-use-glob-by-default = true
+            [user]
+            email = "jane@example.com"
+            [revset-aliases]
+            "wip()" = "description(wip)"
+        "#;
+        load_config(config, &mut aliases_map, &mut parse_settings).unwrap();
+        assert_eq!(
+            parse_settings,
+            ParseSettings {
+                user_email: "jane@example.com".to_owned(),
+                use_glob_by_default: true,
+            }
+        );
+        assert!(aliases_map.get_function("wip", 0).is_some());
+        // Later files override only what they set
+        load_config(
+            "[ui]\nrevsets-use-glob-by-default = false",
+            &mut aliases_map,
+            &mut parse_settings,
+        )
+        .unwrap();
+        assert!(!parse_settings.use_glob_by_default);
+        assert_eq!(parse_settings.user_email, "jane@example.com");
+    }
+
+    #[test]
+    fn outdated_by_new_operations() {
+        let mut repo = TestRepo::init();
+        let jj_graph = repo.jj_graph();
+        assert!(!jj_graph.is_outdated().unwrap());
+        repo.commit("a", &[]);
+        assert!(jj_graph.is_outdated().unwrap());
+        assert!(!repo.jj_graph().is_outdated().unwrap());
+    }
+}
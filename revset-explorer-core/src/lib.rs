@@ -0,0 +1,75 @@
+//! Revset evaluation and commit graph construction for jj repositories, as
+//! used by Revset Explorer. The crate has no GUI dependencies and builds
+//! plain [petgraph] graphs, so it can be used by other front ends.
+//!
+//! ```no_run
+//! use revset_explorer_core::{GraphBuilder, JjGraph, mark_commits, resolve_revset};
+//!
+//! let jj_graph = JjGraph::new("path/to/repo".as_ref())?;
+//! let (revset, _calc_time, _node_count) = resolve_revset(&jj_graph, "::@")?;
+//! let graph = GraphBuilder::new(&jj_graph).build(revset)?.graph;
+//!
+//! let (filter, ..) = resolve_revset(&jj_graph, "mine()")?;
+//! let commit_ids = graph.node_weights().map(|node| &node.commit_id);
+//! let markings = mark_commits(&jj_graph, commit_ids, Some(filter))?;
+//! for (node, marking) in graph.node_weights().zip(markings) {
+//!     println!("{} {:?}", node.label, marking);
+//! }
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+//!
+//! Everything except [`color_vision`], [`mark`] and [`snapshot`] needs the default `repo`
+//! feature.
+
+#[cfg(feature = "repo")]
+pub mod annotations;
+#[cfg(feature = "repo")]
+pub mod bookmark_move;
+#[cfg(feature = "repo")]
+pub mod clusters;
+pub mod color_vision;
+#[cfg(feature = "repo")]
+pub mod conflicts;
+#[cfg(feature = "repo")]
+pub mod diff;
+#[cfg(feature = "repo")]
+pub mod distance;
+#[cfg(feature = "repo")]
+pub mod graph;
+#[cfg(feature = "repo")]
+pub mod headless;
+#[cfg(feature = "repo")]
+pub mod jjgraph;
+#[cfg(feature = "repo")]
+pub mod lint;
+pub mod mark;
+#[cfg(feature = "repo")]
+pub mod oplog;
+#[cfg(feature = "repo")]
+pub mod rebase_preview;
+#[cfg(feature = "repo")]
+pub mod resolve;
+#[cfg(feature = "repo")]
+pub mod signatures;
+pub mod snapshot;
+#[cfg(feature = "repo")]
+pub mod symbols;
+#[cfg(all(feature = "repo", any(test, feature = "test-util")))]
+pub mod test_repo;
+#[cfg(feature = "repo")]
+pub mod trailers;
+#[cfg(feature = "repo")]
+pub mod working_copy;
+
+#[cfg(feature = "repo")]
+pub use graph::{
+    BuiltGraph, CommitGraph, CommitNode, EdgePolicy, GraphBuilder, GraphMetadata, LabelContext,
+};
+#[cfg(feature = "repo")]
+pub use jjgraph::JjGraph;
+#[cfg(feature = "repo")]
+pub use mark::mark_commits;
+pub use mark::{FilterMatch, Marking, Membership, NodeType, Palette};
+#[cfg(feature = "repo")]
+pub use resolve::{NodeCount, ResolveError, resolve_revset, resolve_revset_measure};
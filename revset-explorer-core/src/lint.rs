@@ -0,0 +1,176 @@
+//! Checks for revsets that are valid but likely not what was meant, like
+//! `x..x`, which is always empty. Each finding has the part of the revset it
+//! is about and, when the intent is clear enough, a replacement for it.
+
+use jj_lib::revset::{BinaryOp, ExpressionKind, ExpressionNode, parse_program};
+use std::ops::Range;
+
+use crate::resolve::string_literal;
+
+/// Functions taking string patterns, which match substrings by default
+const PATTERN_FUNCTIONS: &[&str] = &[
+    "author",
+    "author_email",
+    "author_name",
+    "bookmarks",
+    "committer",
+    "committer_email",
+    "committer_name",
+    "description",
+    "diff_contains",
+    "remote_bookmarks",
+    "subject",
+    "tags",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintKind {
+    /// `x..x`, the ancestors of `x` that are not ancestors of `x`
+    EmptyRange,
+    /// A name given to `author_email()` or `committer_email()`, which only
+    /// match the email
+    NameForEmail,
+    /// `*` or `?` in a pattern or a path without `glob:`, which are matched
+    /// literally
+    MissingGlob,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lint {
+    pub kind: LintKind,
+    /// Byte range of the revset that the lint is about
+    pub range: Range<usize>,
+    /// Text to replace the range with, to fix it
+    pub fix: Option<String>,
+}
+
+impl Lint {
+    /// The revset with the fix applied, if there is one
+    pub fn fixed(&self, revset: &str) -> Option<String> {
+        let fix = self.fix.as_ref()?;
+        let mut fixed = revset.to_owned();
+        fixed.replace_range(self.range.clone(), fix);
+        Some(fixed)
+    }
+}
+
+/// Likely mistakes in the revset, in the order they appear. Revsets that
+/// cannot be parsed have none, as jj reports the error. With
+/// `glob_by_default`, like `ui.revsets-use-glob-by-default` in the jj config,
+/// wildcards in patterns are intended.
+pub fn lint(revset: &str, glob_by_default: bool) -> Vec<Lint> {
+    let mut lints = vec![];
+    if let Ok(node) = parse_program(revset) {
+        lint_node(&node, &mut lints);
+    }
+    if glob_by_default {
+        lints.retain(|lint| lint.kind != LintKind::MissingGlob);
+    }
+    lints.sort_by_key(|lint| lint.range.start);
+    lints
+}
+
+fn lint_node(node: &ExpressionNode, lints: &mut Vec<Lint>) {
+    match &node.kind {
+        ExpressionKind::Binary(op, lhs, rhs) => {
+            if *op == BinaryOp::Range && lhs.span.as_str().trim() == rhs.span.as_str().trim() {
+                lints.push(Lint {
+                    kind: LintKind::EmptyRange,
+                    range: node.span.start()..node.span.end(),
+                    fix: Some(format!("::{}", lhs.span.as_str().trim())),
+                });
+            }
+            lint_node(lhs, lints);
+            lint_node(rhs, lints);
+        }
+        ExpressionKind::Unary(_, arg) => lint_node(arg, lints),
+        ExpressionKind::UnionAll(nodes) => nodes.iter().for_each(|node| lint_node(node, lints)),
+        ExpressionKind::Modifier(modifier) => lint_node(&modifier.body, lints),
+        ExpressionKind::AliasExpanded(_, node) => lint_node(node, lints),
+        ExpressionKind::FunctionCall(function) => {
+            let takes_patterns = PATTERN_FUNCTIONS.contains(&function.name);
+            let first_arg = function.args.first();
+            if let Some(ExpressionKind::String(value)) = first_arg.map(|arg| &arg.kind) {
+                let name = function.name;
+                if (name == "author_email" || name == "committer_email") && !value.contains('@') {
+                    lints.push(Lint {
+                        kind: LintKind::NameForEmail,
+                        range: function.name_span.start()..function.name_span.end(),
+                        fix: Some(name.trim_end_matches("_email").to_owned()),
+                    });
+                }
+            }
+            for arg in &function.args {
+                if let ExpressionKind::String(value) = &arg.kind
+                    && (takes_patterns || function.name == "files")
+                    && value.contains(['*', '?'])
+                {
+                    lints.push(Lint {
+                        kind: LintKind::MissingGlob,
+                        range: arg.span.start()..arg.span.end(),
+                        fix: Some(format!("glob:{}", string_literal(value))),
+                    });
+                }
+                lint_node(arg, lints);
+            }
+            for arg in &function.keyword_args {
+                lint_node(&arg.value, lints);
+            }
+        }
+        ExpressionKind::Identifier(_)
+        | ExpressionKind::String(_)
+        | ExpressionKind::StringPattern { .. }
+        | ExpressionKind::RemoteSymbol(_)
+        | ExpressionKind::AtWorkspace(_)
+        | ExpressionKind::AtCurrentWorkspace
+        | ExpressionKind::DagRangeAll
+        | ExpressionKind::RangeAll => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixed(revset: &str) -> Vec<(LintKind, Option<String>)> {
+        (lint(revset, false).into_iter())
+            .map(|lint| {
+                let fixed = lint.fixed(revset);
+                (lint.kind, fixed)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn finds_likely_mistakes() {
+        assert_eq!(
+            fixed("main..main | x"),
+            [(LintKind::EmptyRange, Some("::main | x".to_owned()))]
+        );
+        assert_eq!(
+            fixed("author_email(\"Jane\")"),
+            [(LintKind::NameForEmail, Some("author(\"Jane\")".to_owned()))]
+        );
+        assert_eq!(
+            fixed("mutable() & description(\"WIP*\")"),
+            [(
+                LintKind::MissingGlob,
+                Some("mutable() & description(glob:\"WIP*\")".to_owned())
+            )]
+        );
+        assert_eq!(
+            fixed("files('*.rs') & committer_email(x)"),
+            [(
+                LintKind::MissingGlob,
+                Some("files(glob:\"*.rs\") & committer_email(x)".to_owned())
+            )]
+        );
+        // Intended
+        assert!(lint("main..@ | author_email(\"jane@example.com\")", false).is_empty());
+        assert!(lint("description(glob:\"WIP*\") | description(\"WIP\")", false).is_empty());
+        assert!(lint("main..", false).is_empty());
+        assert!(lint("description(\"WIP*\")", true).is_empty());
+        // Left to jj
+        assert!(lint("main..main &", false).is_empty());
+    }
+}
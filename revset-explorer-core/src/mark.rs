@@ -0,0 +1,206 @@
+//! Classification of commits, used for coloring the graph
+
+#[cfg(feature = "repo")]
+use jj_lib::backend::CommitId;
+#[cfg(feature = "repo")]
+use jj_lib::revset::Revset;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "repo")]
+use crate::jjgraph::JjGraph;
+#[cfg(feature = "repo")]
+use crate::resolve::ResolveError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    WorkingCopy,
+    Immutable,
+    Regular,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FilterMatch {
+    Match,
+    NoMatch,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Marking {
+    pub node_type: NodeType,
+    /// Whether the commit is in the filter revset
+    pub filter_match: FilterMatch,
+}
+
+/// Which of two compared revsets a commit is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Membership {
+    OnlyA,
+    OnlyB,
+    Both,
+}
+
+impl Membership {
+    pub const ALL: [Self; 3] = [Self::OnlyA, Self::OnlyB, Self::Both];
+
+    /// None for commits in neither revset
+    pub fn new(in_a: bool, in_b: bool) -> Option<Self> {
+        match (in_a, in_b) {
+            (true, false) => Some(Membership::OnlyA),
+            (false, true) => Some(Membership::OnlyB),
+            (true, true) => Some(Membership::Both),
+            (false, false) => None,
+        }
+    }
+}
+
+impl Marking {
+    /// The node color used by Revset Explorer, as RGB. Commits matching the
+    /// filter are bright.
+    pub fn color(self) -> [u8; 3] {
+        Palette::Standard.color(self)
+    }
+}
+
+/// Node colors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Distinguishable with protanopia, deuteranopia and tritanopia. Based on
+    /// the Okabe-Ito palette.
+    ColorBlind,
+    /// Saturated colors for projectors and low vision. Commits not matching
+    /// the filter get deeper hues instead of grayish shades.
+    HighContrast,
+}
+
+impl Palette {
+    pub const ALL: [Self; 3] = [Self::Standard, Self::ColorBlind, Self::HighContrast];
+
+    /// The color of the marking, as RGB
+    pub fn color(self, marking: Marking) -> [u8; 3] {
+        use FilterMatch::{Match, NoMatch};
+        match (self, marking.node_type, marking.filter_match) {
+            (Palette::Standard, NodeType::WorkingCopy, Match) => [0x26, 0xff, 0x00],
+            (Palette::Standard, NodeType::WorkingCopy, NoMatch) => [0x29, 0x59, 0x23],
+            (Palette::Standard, NodeType::Immutable, Match) => [0x21, 0xcd, 0xff],
+            (Palette::Standard, NodeType::Immutable, NoMatch) => [0x2e, 0x50, 0x59],
+            (Palette::Standard, NodeType::Regular, Match) => [0xff, 0xfc, 0x00],
+            (Palette::Standard, NodeType::Regular, NoMatch) => [0x63, 0x62, 0x22],
+            (Palette::ColorBlind, NodeType::WorkingCopy, Match) => [0xe6, 0x9f, 0x00],
+            (Palette::ColorBlind, NodeType::WorkingCopy, NoMatch) => [0x5c, 0x3f, 0x00],
+            (Palette::ColorBlind, NodeType::Immutable, Match) => [0x00, 0x72, 0xb2],
+            (Palette::ColorBlind, NodeType::Immutable, NoMatch) => [0x00, 0x2d, 0x47],
+            (Palette::ColorBlind, NodeType::Regular, Match) => [0xff, 0xff, 0xff],
+            (Palette::ColorBlind, NodeType::Regular, NoMatch) => [0x66, 0x66, 0x66],
+            (Palette::HighContrast, NodeType::WorkingCopy, Match) => [0x00, 0xff, 0x00],
+            (Palette::HighContrast, NodeType::WorkingCopy, NoMatch) => [0x00, 0x99, 0x00],
+            (Palette::HighContrast, NodeType::Immutable, Match) => [0x00, 0xe5, 0xff],
+            (Palette::HighContrast, NodeType::Immutable, NoMatch) => [0x00, 0x5c, 0xff],
+            (Palette::HighContrast, NodeType::Regular, Match) => [0xff, 0xff, 0x00],
+            (Palette::HighContrast, NodeType::Regular, NoMatch) => [0xff, 0x80, 0x00],
+        }
+    }
+
+    /// The color of commits in the compared revsets, as RGB. Commits in both
+    /// get the color of mutable commits matching the filter.
+    pub fn membership_color(self, membership: Membership) -> [u8; 3] {
+        match (self, membership) {
+            (Palette::Standard, Membership::OnlyA) => [0xff, 0x41, 0x36],
+            (Palette::Standard, Membership::OnlyB) => [0x00, 0x74, 0xd9],
+            (Palette::Standard, Membership::Both) => [0xff, 0xfc, 0x00],
+            (Palette::ColorBlind, Membership::OnlyA) => [0xd5, 0x5e, 0x00],
+            (Palette::ColorBlind, Membership::OnlyB) => [0x56, 0xb4, 0xe9],
+            (Palette::ColorBlind, Membership::Both) => [0xff, 0xff, 0xff],
+            (Palette::HighContrast, Membership::OnlyA) => [0xff, 0x00, 0x00],
+            (Palette::HighContrast, Membership::OnlyB) => [0xff, 0x00, 0xff],
+            (Palette::HighContrast, Membership::Both) => [0xff, 0xff, 0x00],
+        }
+    }
+}
+
+/// Classifies the commits. Without a filter revset, no commit matches the
+/// filter.
+///
+/// Returns one marking per commit, in the same order as `commit_ids`.
+#[cfg(feature = "repo")]
+pub fn mark_commits<'a, 'c>(
+    jj_graph: &JjGraph,
+    commit_ids: impl IntoIterator<Item = &'c CommitId>,
+    filter_revset: Option<Box<dyn Revset + 'a>>,
+) -> Result<Vec<Marking>, ResolveError> {
+    // TODO: Global var
+    let immutable_revset = jj_graph
+        .get_revset("immutable()")
+        .map_err(|e| ResolveError::JjError(e.to_string()))?;
+    let is_immutable = immutable_revset.containing_fn();
+
+    let in_filter: Box<dyn Fn(&CommitId) -> Result<_, _>> =
+        if let Some(filter_revset) = filter_revset {
+            filter_revset.containing_fn()
+        } else {
+            Box::new(|_| Ok(false))
+        };
+
+    let working_copy_commit_id = jj_graph.working_copy_commit_id();
+    commit_ids
+        .into_iter()
+        .map(|commit_id| {
+            let node_type = if working_copy_commit_id.as_ref() == Some(commit_id) {
+                NodeType::WorkingCopy
+            } else if is_immutable(commit_id)? {
+                NodeType::Immutable
+            } else {
+                NodeType::Regular
+            };
+            let filter_match = if in_filter(commit_id)? {
+                FilterMatch::Match
+            } else {
+                FilterMatch::NoMatch
+            };
+            Ok(Marking {
+                node_type,
+                filter_match,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn high_contrast_colors_are_saturated() {
+        for node_type in [
+            NodeType::WorkingCopy,
+            NodeType::Immutable,
+            NodeType::Regular,
+        ] {
+            for filter_match in [FilterMatch::Match, FilterMatch::NoMatch] {
+                let color = Palette::HighContrast.color(Marking {
+                    node_type,
+                    filter_match,
+                });
+                assert_eq!(color.iter().min(), Some(&0), "{color:?}");
+                assert!(color.iter().max() >= Some(&0x99), "{color:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn memberships_have_distinct_colors() {
+        assert_eq!(Membership::new(true, false), Some(Membership::OnlyA));
+        assert_eq!(Membership::new(false, false), None);
+        for palette in Palette::ALL {
+            let colors: Vec<_> = (Membership::ALL.iter())
+                .map(|membership| palette.membership_color(*membership))
+                .collect();
+            for (i, color) in colors.iter().enumerate() {
+                assert!(!colors[i + 1..].contains(color), "{palette:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,81 @@
+//! Graph of the operation log, for seeing how the repository got to its
+//! current state, like `jj op log`
+
+use jj_lib::backend::Timestamp;
+use jj_lib::op_store::OperationId;
+use petgraph::stable_graph::StableGraph;
+use std::collections::HashMap;
+
+use crate::jjgraph::JjGraph;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OpNode {
+    pub id: OperationId,
+    pub description: String,
+    /// When the operation finished
+    pub time: Timestamp,
+    /// "user@host"
+    pub user: String,
+    /// Whether the operation only snapshotted the working copy
+    pub is_snapshot: bool,
+}
+
+/// Operations and the edges to their parent operations. Edges go from child to
+/// parent, like in [`crate::CommitGraph`].
+pub type OpGraph = StableGraph<OpNode, ()>;
+
+/// The latest `max_ops` operations, from the operation the repository was
+/// loaded at. Children come before their parents. Edges to older operations
+/// are left out.
+pub fn op_log_graph(jj_graph: &JjGraph, max_ops: usize) -> anyhow::Result<OpGraph> {
+    let repo = jj_graph.get_repo();
+    let mut graph = OpGraph::default();
+    let mut node_map = HashMap::new();
+    let mut edges = vec![];
+    for op in jj_lib::op_walk::walk_ancestors(std::slice::from_ref(repo.operation())).take(max_ops)
+    {
+        let op = op?;
+        let metadata = op.metadata();
+        let node_idx = graph.add_node(OpNode {
+            id: op.id().clone(),
+            description: metadata.description.clone(),
+            time: metadata.time.end,
+            user: format!("{}@{}", metadata.username, metadata.hostname),
+            is_snapshot: metadata.is_snapshot,
+        });
+        node_map.insert(op.id().clone(), node_idx);
+        edges.extend((op.parent_ids().iter()).map(|parent_id| (node_idx, parent_id.clone())));
+    }
+    for (child, parent_id) in edges {
+        if let Some(parent) = node_map.get(&parent_id) {
+            graph.add_edge(child, *parent, ());
+        }
+    }
+    Ok(graph)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn walks_operations_to_the_root() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        repo.set_bookmark("main", &a);
+        let graph = op_log_graph(&repo.jj_graph(), 100).unwrap();
+        let descriptions: Vec<_> = (graph.node_weights())
+            .map(|op| op.description.as_str())
+            .collect();
+        assert_eq!(descriptions.first(), Some(&"test bookmark"));
+        assert_eq!(descriptions.len(), 4);
+        // A chain, from the latest operation to the root one
+        assert_eq!(graph.edge_count(), 3);
+        let latest = graph.node_indices().next().unwrap();
+        assert_eq!(graph.neighbors(latest).next(), graph.node_indices().nth(1));
+
+        let graph = op_log_graph(&repo.jj_graph(), 2).unwrap();
+        assert_eq!((graph.node_count(), graph.edge_count()), (2, 1));
+    }
+}
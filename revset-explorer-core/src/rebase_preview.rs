@@ -0,0 +1,114 @@
+//! Where commits would end up after `jj rebase -s <source> -d <destination>`,
+//! computed from the commit graph without rewriting anything
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use std::collections::{HashMap, HashSet};
+
+use crate::jjgraph::JjGraph;
+use crate::resolve::ResolveError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebasedCommit {
+    pub commit_id: CommitId,
+    /// The parents after the rebase. The source commits get the destination,
+    /// their descendants keep their parents.
+    pub parents: Vec<CommitId>,
+    /// Parent steps from the rebased commit to the destination, 1 for the
+    /// source commits
+    pub depth: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RebasePreview {
+    pub destination: CommitId,
+    /// The source commits and their descendants, parents first
+    pub commits: Vec<RebasedCommit>,
+}
+
+/// Previews rebasing the commits of the `source` revset and their descendants,
+/// at most `limit` commits, onto `destination`
+pub fn preview_rebase(
+    jj_graph: &JjGraph,
+    source: &str,
+    destination: &CommitId,
+    limit: usize,
+) -> Result<RebasePreview, ResolveError> {
+    let source = source.trim();
+    let revset = |revset: &str| {
+        jj_graph
+            .get_revset(revset)
+            .map_err(|e| ResolveError::RevsetParseError(e.to_string()))
+    };
+    let sources: HashSet<CommitId> = revset(source)?.iter().collect::<Result<_, _>>()?;
+    // Children first
+    let moved: Vec<CommitId> = (revset(&format!("({source})::"))?.iter())
+        .take(limit)
+        .collect::<Result<_, _>>()?;
+    if moved.contains(destination) {
+        return Err(ResolveError::RevsetParseError(format!(
+            "Cannot rebase onto {}, which would be rebased itself",
+            &destination.hex()[..12]
+        )));
+    }
+
+    let store = jj_graph.get_repo().store().clone();
+    let mut depths = HashMap::new();
+    let mut commits = vec![];
+    for commit_id in moved.into_iter().rev() {
+        let parents = if sources.contains(&commit_id) {
+            vec![destination.clone()]
+        } else {
+            store
+                .get_commit(&commit_id)
+                .map_err(|e| ResolveError::JjError(e.to_string()))?
+                .parent_ids()
+                .to_vec()
+        };
+        let depth = 1
+            + (parents.iter())
+                .filter_map(|parent| depths.get(parent))
+                .max()
+                .unwrap_or(&0);
+        depths.insert(commit_id.clone(), depth);
+        commits.push(RebasedCommit {
+            commit_id,
+            parents,
+            depth,
+        });
+    }
+    Ok(RebasePreview {
+        destination: destination.clone(),
+        commits,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn moves_source_and_descendants_onto_destination() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let other = repo.commit("other", &[&a]);
+        let merge = repo.commit("merge", &[&c, &other]);
+        let destination = repo.commit("destination", &[&a]);
+        let jj_graph = repo.jj_graph();
+
+        let preview = preview_rebase(&jj_graph, &c.hex(), &destination, 100).unwrap();
+        let rebased: Vec<_> = (preview.commits.iter())
+            .map(|commit| (&commit.commit_id, commit.depth))
+            .collect();
+        assert_eq!(rebased, vec![(&c, 1), (&merge, 2)]);
+        assert_eq!(preview.commits[0].parents, vec![destination.clone()]);
+        // Parents outside the rebased commits stay
+        assert_eq!(preview.commits[1].parents, vec![c.clone(), other]);
+
+        assert!(preview_rebase(&jj_graph, &b.hex(), &c, 100).is_err());
+    }
+}
@@ -0,0 +1,181 @@
+//! Revset evaluation with timing and size estimates
+
+use jj_lib::revset::Revset;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::jjgraph::{JjGraph, RevsetError};
+
+/// Estimated number of commits in a revset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeCount {
+    Exact(usize),
+    AtLeast(usize),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ResolveError {
+    /// The revset is invalid. Typically a user error.
+    RevsetParseError(String),
+    /// The repository could not be read
+    JjError(String),
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ResolveError::RevsetParseError(msg) | ResolveError::JjError(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl From<jj_lib::revset::RevsetEvaluationError> for ResolveError {
+    fn from(e: jj_lib::revset::RevsetEvaluationError) -> Self {
+        ResolveError::JjError(e.to_string())
+    }
+}
+
+/// Quotes the text as a string in a revset, e.g. for `description("…")`
+pub fn string_literal(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Revset functions whose result depends on the order of commits or
+/// arguments
+const ORDERING_FUNCTIONS: &[&str] = &["latest", "first_parent", "first_ancestors", "coalesce"];
+
+/// The ordering-sensitive functions called in the revset, like `latest()`, in
+/// the order they first appear. Aliases are not expanded.
+pub fn ordering_functions(revset_str: &str) -> Vec<&'static str> {
+    let mut functions = vec![];
+    let mut in_string = false;
+    let mut chars = revset_str.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if in_string {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if c == '"' {
+            in_string = true;
+            continue;
+        }
+        if !(c.is_alphanumeric() || c == '_') {
+            continue;
+        }
+        let mut end = start + c.len_utf8();
+        while let Some(&(i, c)) = chars.peek() {
+            if !(c.is_alphanumeric() || c == '_') {
+                break;
+            }
+            end = i + c.len_utf8();
+            chars.next();
+        }
+        let is_call = revset_str[end..].trim_start().starts_with('(');
+        if let Some(function) = ORDERING_FUNCTIONS
+            .iter()
+            .find(|f| **f == &revset_str[start..end])
+            && is_call
+            && !functions.contains(function)
+        {
+            functions.push(*function);
+        }
+    }
+    functions
+}
+
+/// Evaluates the revset. An empty string gives no commits.
+///
+/// Returns the revset, the time it took to evaluate and the estimated number
+/// of commits in it.
+pub fn resolve_revset<'g>(
+    jj_graph: &'g JjGraph,
+    revset_str: &str,
+) -> Result<(Box<dyn Revset + 'g>, Duration, NodeCount), ResolveError> {
+    resolve_revset_helper(jj_graph, revset_str, None)
+}
+
+/// Like [`resolve_revset`], but evaluates the revset multiple times to measure
+/// the average time. Stops measuring when `cancelled` is set.
+pub fn resolve_revset_measure<'g>(
+    jj_graph: &'g JjGraph,
+    revset_str: &str,
+    cancelled: &AtomicBool,
+) -> Result<(Box<dyn Revset + 'g>, Duration, NodeCount), ResolveError> {
+    resolve_revset_helper(jj_graph, revset_str, Some(cancelled))
+}
+
+/// Measures when given the cancellation flag
+fn resolve_revset_helper<'g>(
+    jj_graph: &'g JjGraph,
+    revset_str: &str,
+    measure: Option<&AtomicBool>,
+) -> Result<(Box<dyn Revset + 'g>, Duration, NodeCount), ResolveError> {
+    let revset_str = if revset_str.trim().is_empty() {
+        // Not "empty()", which matches commits without changes
+        "none()"
+    } else {
+        revset_str
+    };
+    let (filter_revset, first_calc_time) = get_revset_timed(jj_graph, revset_str)
+        .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
+
+    let runs = if measure.is_some() { 100 } else { 1 };
+    let mut total_time = first_calc_time;
+    let mut run_count = 1;
+    for _ in 0..(runs - 1) {
+        if total_time > Duration::from_millis(100)
+            || measure.is_some_and(|cancelled| cancelled.load(Ordering::Relaxed))
+        {
+            // Don't let the user wait too long
+            break;
+        }
+        let (_, calc_time) = get_revset_timed(jj_graph, revset_str)
+            .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
+        run_count += 1;
+        total_time += calc_time;
+    }
+    let avg_time = total_time.checked_div(run_count).unwrap();
+
+    let node_count = filter_revset.count_estimate()?;
+    let node_count = if Some(node_count.0) == node_count.1 {
+        NodeCount::Exact(node_count.0)
+    } else {
+        NodeCount::AtLeast(node_count.0)
+    };
+
+    Ok((filter_revset, avg_time, node_count))
+}
+
+fn get_revset_timed<'a>(
+    jj_graph: &'a JjGraph,
+    value: &str,
+) -> Result<(Box<dyn Revset + 'a>, Duration), RevsetError> {
+    let start = Instant::now();
+    let revset = jj_graph.get_revset(value)?;
+    let end = Instant::now();
+    Ok((revset, end - start))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_ordering_functions() {
+        assert_eq!(
+            ordering_functions("latest(mine(), 5) | coalesce(x, latest(y))"),
+            vec!["latest", "coalesce"]
+        );
+        // Not calls
+        assert!(ordering_functions(r#"description("latest(") | latest"#).is_empty());
+        assert!(ordering_functions("::@").is_empty());
+    }
+}
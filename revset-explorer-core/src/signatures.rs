@@ -0,0 +1,88 @@
+//! Cryptographic signatures of commits, verified with the signing backends
+//! that jj supports, like GPG and SSH
+
+use jj_lib::commit::Commit;
+use jj_lib::signing::SigStatus;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Unsigned,
+    /// The signature matches the commit and the key is trusted
+    Good,
+    /// Signed, but the signature could not be checked, e.g. due to an unknown
+    /// key or a missing signing program
+    Unknown,
+    /// The signature does not match the commit, or is malformed
+    Invalid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub status: SignatureStatus,
+    /// Who signed, like the user ID of a GPG key, or else the key ID
+    pub signer: Option<String>,
+}
+
+/// Verifies the signature of the commit. This is slow for signed commits, as
+/// the signing program is run, but the result is cached by the store.
+pub fn verify(commit: &Commit) -> Signature {
+    if !commit.is_signed() {
+        return Signature {
+            status: SignatureStatus::Unsigned,
+            signer: None,
+        };
+    }
+    match commit.verification() {
+        Ok(Some(verification)) => Signature {
+            status: match verification.status {
+                SigStatus::Good => SignatureStatus::Good,
+                SigStatus::Unknown => SignatureStatus::Unknown,
+                SigStatus::Bad => SignatureStatus::Invalid,
+            },
+            signer: verification.display.or(verification.key),
+        },
+        Ok(None) => Signature {
+            status: SignatureStatus::Unsigned,
+            signer: None,
+        },
+        Err(jj_lib::signing::SignError::InvalidSignatureFormat) => Signature {
+            status: SignatureStatus::Invalid,
+            signer: None,
+        },
+        // The signing program failed, which says nothing about the signature
+        Err(jj_lib::signing::SignError::Backend(_)) => Signature {
+            status: SignatureStatus::Unknown,
+            signer: None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+    use jj_lib::repo::Repo;
+
+    #[test]
+    fn verifies_signatures() {
+        let mut repo = TestRepo::init();
+        let unsigned = repo.commit("unsigned", &[]);
+        // No signing backend can read it
+        let signed = repo.commit_signed("signed", &[&unsigned], b"not a signature");
+        let jj_graph = repo.jj_graph();
+        let store = jj_graph.get_repo().store().clone();
+        let verify_id = |id| verify(&store.get_commit(id).unwrap());
+        assert_eq!(
+            verify_id(&unsigned),
+            Signature {
+                status: SignatureStatus::Unsigned,
+                signer: None
+            }
+        );
+        assert_eq!(verify_id(&signed).status, SignatureStatus::Unknown);
+        // Policy revsets can use jj's signed()
+        let revset = jj_graph.get_revset("signed()").unwrap();
+        let ids: Vec<_> = revset.iter().map(Result::unwrap).collect();
+        assert_eq!(ids, [signed]);
+    }
+}
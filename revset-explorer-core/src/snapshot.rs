@@ -0,0 +1,392 @@
+//! A graph exported from a repository, for viewing where the repository is
+//! not available, e.g. in the web viewer. Revsets can't be evaluated without
+//! the repository, so the selections are evaluated when exporting.
+//!
+//! The JSON form is a stable interface for other tools. It is versioned by
+//! [`SCHEMA_VERSION`], which is bumped when a field is removed, renamed or
+//! changes meaning. Fields may be added without a version bump, so consumers
+//! should ignore fields they don't know. Added fields get `#[serde(default)]`,
+//! so that older snapshots of the same version still load.
+
+use serde::{Deserialize, Serialize};
+
+use crate::mark::{FilterMatch, Marking, NodeType};
+
+/// Version of the JSON schema, stored in [`Snapshot::version`]
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// [`SCHEMA_VERSION`] of the exporter
+    pub version: u32,
+    /// Name of the repository, for display
+    pub repository: String,
+    /// The revset the graph was built from
+    pub view: String,
+    /// In the order the revset yields them, i.e. children before parents
+    pub nodes: Vec<SnapshotNode>,
+    pub edges: Vec<SnapshotEdge>,
+    /// The node limit, if it was hit
+    pub limit_hit: Option<usize>,
+    pub selections: Vec<Selection>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotNode {
+    /// Full hex
+    pub commit_id: String,
+    /// Full reverse hex, as shown by jj
+    pub change_id: String,
+    pub label: String,
+    pub description: String,
+    pub author: String,
+    pub node_type: NodeType,
+    /// Bookmarks and tags pointing to the commit
+    pub refs: Vec<SnapshotRef>,
+}
+
+/// Indexes into [`Snapshot::nodes`], from child to parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotEdge {
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EdgeKind {
+    /// `to` is a parent of `from`
+    Direct,
+    /// `to` is an ancestor of `from`, with the commits in between not in the
+    /// view
+    Indirect,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotRef {
+    /// As written in revsets, e.g. "main" or "main@origin"
+    pub name: String,
+    pub kind: RefKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefKind {
+    Bookmark,
+    RemoteBookmark,
+    Tag,
+}
+
+/// A "select" revset evaluated against the nodes of the snapshot. The viewer
+/// shows one at a time, as a layer of highlighted nodes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Selection {
+    pub revset: String,
+    /// Indexes into [`Snapshot::nodes`] of the matching commits
+    pub nodes: Vec<usize>,
+}
+
+impl Snapshot {
+    /// Fails for snapshots of other schema versions, and for edges and
+    /// selections of nodes that are not in the snapshot
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        #[derive(Deserialize)]
+        struct Versioned {
+            version: u32,
+        }
+        let Versioned { version } = serde_json::from_str(json)?;
+        if version != SCHEMA_VERSION {
+            return Err(serde::de::Error::custom(format!(
+                "Unsupported snapshot version {version}, expected {SCHEMA_VERSION}"
+            )));
+        }
+        let snapshot: Self = serde_json::from_str(json)?;
+        let nodes = snapshot.nodes.len();
+        let edge_indexes = (snapshot.edges.iter()).flat_map(|edge| [edge.from, edge.to]);
+        let selection_indexes = (snapshot.selections.iter()).flat_map(|s| s.nodes.iter().copied());
+        if let Some(index) = edge_indexes.chain(selection_indexes).find(|i| *i >= nodes) {
+            return Err(serde::de::Error::custom(format!(
+                "Node index {index} out of range, there are {nodes} nodes"
+            )));
+        }
+        Ok(snapshot)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// One marking per node. Without a selection, no node matches.
+    pub fn markings(&self, selection: Option<&Selection>) -> Vec<Marking> {
+        let mut markings: Vec<_> = self
+            .nodes
+            .iter()
+            .map(|node| Marking {
+                node_type: node.node_type,
+                filter_match: FilterMatch::NoMatch,
+            })
+            .collect();
+        for &node in selection.iter().flat_map(|selection| &selection.nodes) {
+            if let Some(marking) = markings.get_mut(node) {
+                marking.filter_match = FilterMatch::Match;
+            }
+        }
+        markings
+    }
+
+    /// Exports the graph of the `view` revset, with the `selections` revsets
+    /// evaluated
+    #[cfg(feature = "repo")]
+    pub fn create(
+        jj_graph: &crate::JjGraph,
+        repository: String,
+        view: &str,
+        selections: &[String],
+        max_nodes: usize,
+    ) -> Result<Self, crate::ResolveError> {
+        use crate::{GraphBuilder, ResolveError, mark_commits, resolve_revset};
+        use jj_lib::graph::GraphEdgeType;
+        use jj_lib::object_id::ObjectId;
+        use jj_lib::repo::Repo;
+
+        let (view_revset, ..) = resolve_revset(jj_graph, view)?;
+        let built = GraphBuilder::new(jj_graph)
+            .max_nodes(max_nodes)
+            .build(view_revset)?;
+        let graph = &built.graph;
+        let commit_ids: Vec<_> = graph.node_weights().map(|node| &node.commit_id).collect();
+        let node_types = mark_commits(jj_graph, commit_ids.iter().copied(), None)?;
+
+        let repo = jj_graph.get_repo();
+        let store = repo.store().clone();
+        let mut refs = refs_by_commit(repo.view());
+        let nodes = graph
+            .node_weights()
+            .zip(node_types)
+            .map(|(node, marking)| {
+                let commit = store
+                    .get_commit(&node.commit_id)
+                    .map_err(|e| ResolveError::JjError(e.to_string()))?;
+                let author = commit.author();
+                Ok(SnapshotNode {
+                    commit_id: node.commit_id.hex(),
+                    change_id: commit.change_id().to_string(),
+                    label: node.label.clone(),
+                    description: commit.description().to_owned(),
+                    author: format!("{} <{}>", author.name, author.email),
+                    node_type: marking.node_type,
+                    refs: refs.remove(&node.commit_id).unwrap_or_default(),
+                })
+            })
+            .collect::<Result<_, ResolveError>>()?;
+
+        let node_positions: Vec<_> = graph.node_indices().collect();
+        let position = |node_idx| node_positions.iter().position(|&i| i == node_idx).unwrap();
+        let edges = graph
+            .edge_indices()
+            .map(|edge_idx| {
+                let (start, end) = graph.edge_endpoints(edge_idx).unwrap();
+                SnapshotEdge {
+                    from: position(start),
+                    to: position(end),
+                    kind: match graph[edge_idx] {
+                        GraphEdgeType::Direct => EdgeKind::Direct,
+                        _ => EdgeKind::Indirect,
+                    },
+                }
+            })
+            .collect();
+
+        let selections = selections
+            .iter()
+            .map(|revset| {
+                let (select_revset, ..) = resolve_revset(jj_graph, revset)?;
+                let markings =
+                    mark_commits(jj_graph, commit_ids.iter().copied(), Some(select_revset))?;
+                let nodes = markings
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, marking)| marking.filter_match == FilterMatch::Match)
+                    .map(|(i, _)| i)
+                    .collect();
+                Ok(Selection {
+                    revset: revset.clone(),
+                    nodes,
+                })
+            })
+            .collect::<Result<_, ResolveError>>()?;
+
+        Ok(Self {
+            version: SCHEMA_VERSION,
+            repository,
+            view: view.to_owned(),
+            nodes,
+            edges,
+            limit_hit: built.metadata.limit_hit,
+            selections,
+        })
+    }
+}
+
+#[cfg(feature = "repo")]
+fn refs_by_commit(
+    view: &jj_lib::view::View,
+) -> std::collections::HashMap<jj_lib::backend::CommitId, Vec<SnapshotRef>> {
+    let mut refs = std::collections::HashMap::<_, Vec<_>>::new();
+    let mut add = |target: &jj_lib::op_store::RefTarget, name: String, kind| {
+        for commit_id in target.added_ids() {
+            refs.entry(commit_id.clone())
+                .or_default()
+                .push(SnapshotRef {
+                    name: name.clone(),
+                    kind,
+                });
+        }
+    };
+    for (name, target) in view.local_bookmarks() {
+        add(target, name.as_str().to_owned(), RefKind::Bookmark);
+    }
+    for (symbol, remote_ref) in view.all_remote_bookmarks() {
+        // Mirrors the local bookmarks in colocated repositories
+        if symbol.remote == jj_lib::git::REMOTE_NAME_FOR_LOCAL_GIT_REPO {
+            continue;
+        }
+        add(
+            &remote_ref.target,
+            symbol.to_string(),
+            RefKind::RemoteBookmark,
+        );
+    }
+    for (name, target) in view.local_tags() {
+        add(target, name.as_str().to_owned(), RefKind::Tag);
+    }
+    refs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "repo")]
+    #[test]
+    fn create() {
+        use crate::test_repo::TestRepo;
+        use jj_lib::object_id::ObjectId;
+
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b\n\nBody", &[&a]);
+        repo.edit(&b);
+        repo.set_bookmark("main", &a);
+        let jj_graph = repo.jj_graph();
+        let snapshot = Snapshot::create(
+            &jj_graph,
+            "test".to_owned(),
+            &format!("{}::", a.hex()),
+            &[a.hex(), "none()".to_owned()],
+            100,
+        )
+        .unwrap();
+
+        let ids: Vec<_> = snapshot.nodes.iter().map(|n| &n.commit_id).collect();
+        assert_eq!(ids, [&b.hex(), &a.hex()]);
+        assert_eq!(snapshot.nodes[0].description, "b\n\nBody");
+        assert_eq!(snapshot.nodes[0].node_type, NodeType::WorkingCopy);
+        assert!(snapshot.nodes[0].refs.is_empty());
+        assert_eq!(
+            snapshot.nodes[1].refs,
+            [SnapshotRef {
+                name: "main".to_owned(),
+                kind: RefKind::Bookmark
+            }]
+        );
+        assert_eq!(
+            snapshot.edges,
+            [SnapshotEdge {
+                from: 0,
+                to: 1,
+                kind: EdgeKind::Direct
+            }]
+        );
+        assert_eq!(snapshot.selections[0].nodes, [1]);
+        assert!(snapshot.selections[1].nodes.is_empty());
+        assert_eq!(Snapshot::from_json(&snapshot.to_json()).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn markings() {
+        let node = |node_type| SnapshotNode {
+            commit_id: String::new(),
+            change_id: String::new(),
+            label: String::new(),
+            description: String::new(),
+            author: String::new(),
+            node_type,
+            refs: vec![],
+        };
+        let snapshot = Snapshot {
+            version: SCHEMA_VERSION,
+            repository: String::new(),
+            view: String::new(),
+            nodes: vec![node(NodeType::WorkingCopy), node(NodeType::Immutable)],
+            edges: vec![],
+            limit_hit: None,
+            selections: vec![Selection {
+                revset: "root()".to_owned(),
+                nodes: vec![1],
+            }],
+        };
+        let filter_matches: Vec<_> = snapshot
+            .markings(snapshot.selections.first())
+            .iter()
+            .map(|marking| marking.filter_match)
+            .collect();
+        assert_eq!(filter_matches, [FilterMatch::NoMatch, FilterMatch::Match]);
+        assert!(
+            snapshot
+                .markings(None)
+                .iter()
+                .all(|marking| marking.filter_match == FilterMatch::NoMatch)
+        );
+    }
+
+    /// The schema as documented. Changing this test means that external
+    /// consumers break, so [`SCHEMA_VERSION`] must be bumped.
+    const SCHEMA_V1: &str = include_str!("snapshot_v1.json");
+
+    #[test]
+    fn schema_round_trip() {
+        let snapshot = Snapshot::from_json(SCHEMA_V1).unwrap();
+        assert_eq!(snapshot.nodes[1].refs[0].kind, RefKind::Tag);
+        assert_eq!(snapshot.edges[0].kind, EdgeKind::Indirect);
+        assert_eq!(snapshot.to_json(), SCHEMA_V1.trim_end());
+    }
+
+    #[test]
+    fn schema_version() {
+        let newer = SCHEMA_V1.replace(r#""version": 1"#, r#""version": 2"#);
+        let error = Snapshot::from_json(&newer).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unsupported snapshot version 2, expected 1"
+        );
+        assert!(Snapshot::from_json(r#"{"nodes": []}"#).is_err());
+        // Fields may be added within a version
+        let extended = SCHEMA_V1.replace(r#""view""#, r#""exported_by": "x", "view""#);
+        assert!(Snapshot::from_json(&extended).is_ok());
+    }
+
+    #[test]
+    fn node_indexes_in_range() {
+        let edge = SCHEMA_V1.replace(r#""to": 1"#, r#""to": 2"#);
+        let error = Snapshot::from_json(&edge).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Node index 2 out of range, there are 2 nodes"
+        );
+        let selection = SCHEMA_V1.replace("[\n        0\n      ]", "[\n        5\n      ]");
+        assert_ne!(selection, SCHEMA_V1);
+        assert!(Snapshot::from_json(&selection).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+//! Revset functions that look up commits in mapping files, e.g. `ticket(ABC-123)`
+//! for a team that keeps a mapping from ticket IDs to commits
+
+use anyhow::Context;
+use jj_lib::backend::CommitId;
+use jj_lib::repo::Repo;
+use jj_lib::revset::{
+    self, PartialSymbolResolver, RevsetAliasesMap, RevsetDiagnostics, RevsetExtensions,
+    RevsetParseContext, RevsetParseErrorKind, RevsetResolutionError, SymbolResolverExtension,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Tables mapping symbols to commits. Each table becomes a revset function
+/// with the name of the table, taking a symbol in the table as argument.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SymbolTables {
+    tables: HashMap<String, HashMap<String, CommitId>>,
+}
+
+impl SymbolTables {
+    /// Loads all `<table>.toml` files in the directory. The files contain
+    /// `"<symbol>" = "<full commit ID>"` entries. A missing directory gives no
+    /// tables.
+    pub fn load_dir(dir: &Path) -> anyhow::Result<Self> {
+        let mut tables = Self::default();
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(tables),
+            Err(e) => return Err(e.into()),
+        };
+        for entry in entries {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "toml") {
+                continue;
+            }
+            let Some(name) = path.file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+            let content = std::fs::read_to_string(&path)?;
+            tables
+                .add_table_toml(name, &content)
+                .with_context(|| format!("Failed to load {}", path.display()))?;
+        }
+        Ok(tables)
+    }
+
+    fn add_table_toml(&mut self, name: &str, content: &str) -> anyhow::Result<()> {
+        let mut table = HashMap::new();
+        for (symbol, value) in content.parse::<toml::Table>()? {
+            let commit_id = value
+                .as_str()
+                .and_then(CommitId::try_from_hex)
+                .with_context(|| format!("\"{symbol}\" does not map to a full commit ID"))?;
+            table.insert(symbol, commit_id);
+        }
+        self.tables.insert(name.to_owned(), table);
+        Ok(())
+    }
+
+    /// Adds the revset functions and the resolver of their symbols.
+    /// Returns an error if a table has the name of an existing function.
+    pub fn register(self, revset_exts: &mut RevsetExtensions) -> anyhow::Result<()> {
+        for name in self.tables.keys() {
+            if function_exists(revset_exts, name) {
+                anyhow::bail!(
+                    "Symbol table \"{name}\" has the name of an existing revset function"
+                );
+            }
+            revset_exts.add_custom_function(intern(name), lookup_function);
+        }
+        revset_exts.add_symbol_resolver(Box::new(SymbolTableResolver(Arc::new(self))));
+        Ok(())
+    }
+
+    fn resolve(&self, symbol: &str) -> Option<&CommitId> {
+        let (name, key) = symbol.strip_suffix(')')?.split_once('(')?;
+        self.tables.get(name)?.get(key)
+    }
+}
+
+/// `name(key)` is lowered to the symbol "name(key)", which is looked up by
+/// [`SymbolTableResolver`]. Function pointers can't carry the tables
+/// themselves.
+fn lookup_function(
+    _diagnostics: &mut RevsetDiagnostics,
+    function: &revset::FunctionCallNode,
+    _context: &revset::LoweringContext,
+) -> Result<Arc<revset::UserRevsetExpression>, revset::RevsetParseError> {
+    let [arg] = function.expect_exact_arguments()?;
+    let key: String = revset::expect_literal("symbol", arg)?;
+    Ok(revset::RevsetExpression::symbol(format!(
+        "{}({key})",
+        function.name
+    )))
+}
+
+struct SymbolTableResolver(Arc<SymbolTables>);
+
+impl SymbolResolverExtension for SymbolTableResolver {
+    fn new_resolvers<'a>(
+        &self,
+        _context_repo: &'a dyn Repo,
+    ) -> Vec<Box<dyn PartialSymbolResolver + 'a>> {
+        vec![Box::new(SymbolTableResolver(self.0.clone()))]
+    }
+}
+
+impl PartialSymbolResolver for SymbolTableResolver {
+    fn resolve_symbol(
+        &self,
+        _repo: &dyn Repo,
+        symbol: &str,
+    ) -> Result<Option<CommitId>, RevsetResolutionError> {
+        Ok(self.0.resolve(symbol).cloned())
+    }
+}
+
+fn function_exists(revset_exts: &RevsetExtensions, name: &str) -> bool {
+    let aliases_map = RevsetAliasesMap::new();
+    let context = RevsetParseContext {
+        aliases_map: &aliases_map,
+        local_variables: HashMap::new(),
+        user_email: "",
+        date_pattern_context: chrono::Local::now().into(),
+        default_ignored_remote: None,
+        use_glob_by_default: false,
+        extensions: revset_exts,
+        workspace: None,
+    };
+    let result = revset::parse(
+        &mut RevsetDiagnostics::new(),
+        &format!("{name}()"),
+        &context,
+    );
+    !matches!(
+        result.as_ref().map_err(|e| e.kind()),
+        Err(RevsetParseErrorKind::NoSuchFunction { .. })
+    )
+}
+
+/// Function names must be 'static. Interning them avoids leaking a new string
+/// each time the repository is reloaded.
+fn intern(name: &str) -> &'static str {
+    static NAMES: OnceLock<Mutex<HashMap<String, &'static str>>> = OnceLock::new();
+    let mut names = NAMES.get_or_init(Default::default).lock().unwrap();
+    names
+        .entry(name.to_owned())
+        .or_insert_with(|| Box::leak(name.to_owned().into_boxed_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jjgraph::JjGraph;
+    use crate::test_repo::TestRepo;
+    use jj_lib::object_id::ObjectId;
+
+    #[test]
+    fn resolves_table_symbols() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut tables = SymbolTables::default();
+        tables
+            .add_table_toml("ticket", &format!("\"ABC-123\" = \"{}\"", a.hex()))
+            .unwrap();
+        let mut revset_exts = RevsetExtensions::new();
+        tables.register(&mut revset_exts).unwrap();
+        let jj_graph = JjGraph::with_extensions(repo.path(), revset_exts).unwrap();
+
+        let revset = jj_graph.get_revset("ticket(ABC-123)").unwrap();
+        assert_eq!(revset.iter().collect::<Result<Vec<_>, _>>().unwrap(), [a]);
+        assert!(jj_graph.get_revset("ticket(ABC-999)").is_err());
+    }
+
+    #[test]
+    fn invalid_tables() {
+        let mut tables = SymbolTables::default();
+        assert!(
+            tables
+                .add_table_toml("ticket", "\"ABC-1\" = \"main\"")
+                .is_err()
+        );
+        tables.add_table_toml("parents", "").unwrap();
+        assert!(tables.register(&mut RevsetExtensions::new()).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+//! Temporary repositories for tests
+
+use jj_lib::backend::{CommitId, CopyId, MillisSinceEpoch, Signature, Timestamp, TreeValue};
+use jj_lib::config::StackedConfig;
+use jj_lib::merge::Merge;
+use jj_lib::merged_tree::MergedTreeBuilder;
+use jj_lib::op_store::RefTarget;
+use jj_lib::ref_name::{RefName, WorkspaceName};
+use jj_lib::repo::{ReadonlyRepo, Repo};
+use jj_lib::repo_path::RepoPathBuf;
+use jj_lib::settings::UserSettings;
+use jj_lib::workspace::Workspace;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::jjgraph::JjGraph;
+
+pub struct TestRepo {
+    dir: tempfile::TempDir,
+    repo: Arc<ReadonlyRepo>,
+}
+
+impl TestRepo {
+    pub fn init() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = UserSettings::from_config(StackedConfig::with_defaults()).unwrap();
+        let (_workspace, repo) = Workspace::init_simple(&settings, dir.path()).unwrap();
+        Self { dir, repo }
+    }
+
+    /// Creates a commit on top of `parents`, or on top of the root commit if
+    /// there are no parents
+    pub fn commit(&mut self, description: &str, parents: &[&CommitId]) -> CommitId {
+        let store = self.repo.store().clone();
+        let parents = if parents.is_empty() {
+            vec![store.root_commit_id().clone()]
+        } else {
+            parents.iter().map(|&id| id.clone()).collect()
+        };
+        let mut tx = self.repo.start_transaction();
+        let commit = tx
+            .repo_mut()
+            .new_commit(parents, store.empty_merged_tree())
+            .set_description(description)
+            .write()
+            .unwrap();
+        self.repo = tx.commit("test commit").unwrap();
+        commit.id().clone()
+    }
+
+    /// Like [`Self::commit`], but authored and committed at `seconds` since
+    /// the epoch, in UTC
+    pub fn commit_at(
+        &mut self,
+        description: &str,
+        parents: &[&CommitId],
+        seconds: i64,
+    ) -> CommitId {
+        let id = self.commit(description, parents);
+        let commit = self.repo.store().get_commit(&id).unwrap();
+        let timestamp = Timestamp {
+            timestamp: MillisSinceEpoch(seconds * 1000),
+            tz_offset: 0,
+        };
+        let mut tx = self.repo.start_transaction();
+        let author = Signature {
+            timestamp,
+            ..commit.author().clone()
+        };
+        let committer = Signature {
+            timestamp,
+            ..commit.committer().clone()
+        };
+        let commit = tx
+            .repo_mut()
+            .rewrite_commit(&commit)
+            .set_author(author)
+            .set_committer(committer)
+            .write()
+            .unwrap();
+        tx.repo_mut().rebase_descendants().unwrap();
+        self.repo = tx.commit("test commit time").unwrap();
+        commit.id().clone()
+    }
+
+    /// Like [`Self::commit`], but authored by `name` and `email`
+    pub fn commit_by(
+        &mut self,
+        description: &str,
+        parents: &[&CommitId],
+        name: &str,
+        email: &str,
+    ) -> CommitId {
+        let id = self.commit(description, parents);
+        let commit = self.repo.store().get_commit(&id).unwrap();
+        let mut tx = self.repo.start_transaction();
+        let author = Signature {
+            name: name.to_owned(),
+            email: email.to_owned(),
+            ..commit.author().clone()
+        };
+        let commit = tx
+            .repo_mut()
+            .rewrite_commit(&commit)
+            .set_author(author)
+            .write()
+            .unwrap();
+        tx.repo_mut().rebase_descendants().unwrap();
+        self.repo = tx.commit("test commit author").unwrap();
+        commit.id().clone()
+    }
+
+    /// Like [`Self::commit`], but with the files of the first parent, changed
+    /// to `files`. Each file is a path, separated by `/`, and its contents.
+    pub fn commit_with_files(
+        &mut self,
+        description: &str,
+        parents: &[&CommitId],
+        files: &[(&str, &str)],
+    ) -> CommitId {
+        let store = self.repo.store().clone();
+        let base_tree = match parents.first() {
+            Some(parent) => store.get_commit(parent).unwrap().tree(),
+            None => store.empty_merged_tree(),
+        };
+        let mut builder = MergedTreeBuilder::new(base_tree);
+        for (path, contents) in files {
+            let path = RepoPathBuf::from_internal_string(*path).unwrap();
+            let id = futures::executor::block_on(store.write_file(&path, &mut contents.as_bytes()))
+                .unwrap();
+            builder.set_or_remove(
+                path,
+                Merge::normal(TreeValue::File {
+                    id,
+                    executable: false,
+                    copy_id: CopyId::placeholder(),
+                }),
+            );
+        }
+        let tree = builder.write_tree().unwrap();
+        let parents = if parents.is_empty() {
+            vec![store.root_commit_id().clone()]
+        } else {
+            parents.iter().map(|&id| id.clone()).collect()
+        };
+        let mut tx = self.repo.start_transaction();
+        let commit = tx
+            .repo_mut()
+            .new_commit(parents, tree)
+            .set_description(description)
+            .write()
+            .unwrap();
+        self.repo = tx.commit("test commit").unwrap();
+        commit.id().clone()
+    }
+
+    /// Creates a merge commit of `left` and `right`, with the trees merged
+    /// from `base`. Changes to the same lines conflict.
+    pub fn merge(
+        &mut self,
+        description: &str,
+        left: &CommitId,
+        base: &CommitId,
+        right: &CommitId,
+    ) -> CommitId {
+        let store = self.repo.store().clone();
+        let tree = |id| store.get_commit(id).unwrap().tree();
+        let tree = futures::executor::block_on(tree(left).merge(tree(base), tree(right))).unwrap();
+        let mut tx = self.repo.start_transaction();
+        let commit = tx
+            .repo_mut()
+            .new_commit(vec![left.clone(), right.clone()], tree)
+            .set_description(description)
+            .write()
+            .unwrap();
+        self.repo = tx.commit("test merge").unwrap();
+        commit.id().clone()
+    }
+
+    /// Like [`Self::commit`], but signed with the raw `signature`, as if by a
+    /// signing backend
+    pub fn commit_signed(
+        &mut self,
+        description: &str,
+        parents: &[&CommitId],
+        signature: &[u8],
+    ) -> CommitId {
+        let unsigned = self.commit(description, parents);
+        let store = self.repo.store().clone();
+        let unsigned = store.get_commit(&unsigned).unwrap();
+        let mut data = unsigned.store_commit().as_ref().clone();
+        data.predecessors = vec![unsigned.id().clone()];
+        let mut sign = |_data: &[u8]| Ok(signature.to_vec());
+        let signed =
+            futures::executor::block_on(store.write_commit(data, Some(&mut sign))).unwrap();
+        let mut tx = self.repo.start_transaction();
+        tx.repo_mut().add_head(&signed).unwrap();
+        tx.repo_mut()
+            .set_rewritten_commit(unsigned.id().clone(), signed.id().clone());
+        tx.repo_mut().rebase_descendants().unwrap();
+        self.repo = tx.commit("test sign").unwrap();
+        signed.id().clone()
+    }
+
+    /// Creates another commit with the same change ID and parents as
+    /// `commit_id`, making the change divergent
+    pub fn diverge(&mut self, commit_id: &CommitId, description: &str) -> CommitId {
+        let commit = self.repo.store().get_commit(commit_id).unwrap();
+        let mut tx = self.repo.start_transaction();
+        let divergent = tx
+            .repo_mut()
+            .new_commit(commit.parent_ids().to_vec(), commit.tree())
+            .set_change_id(commit.change_id().clone())
+            .set_description(description)
+            .write()
+            .unwrap();
+        self.repo = tx.commit("test diverge").unwrap();
+        divergent.id().clone()
+    }
+
+    /// Makes the commit the working-copy commit
+    pub fn edit(&mut self, commit_id: &CommitId) {
+        let commit = self.repo.store().get_commit(commit_id).unwrap();
+        let mut tx = self.repo.start_transaction();
+        tx.repo_mut()
+            .edit(WorkspaceName::DEFAULT.to_owned(), &commit)
+            .unwrap();
+        // The previous, empty working-copy commit is abandoned
+        tx.repo_mut().rebase_descendants().unwrap();
+        self.repo = tx.commit("test edit").unwrap();
+    }
+
+    pub fn set_bookmark(&mut self, name: &str, commit_id: &CommitId) {
+        let mut tx = self.repo.start_transaction();
+        tx.repo_mut()
+            .set_local_bookmark_target(RefName::new(name), RefTarget::normal(commit_id.clone()));
+        self.repo = tx.commit("test bookmark").unwrap();
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn jj_graph(&self) -> JjGraph {
+        JjGraph::new(self.path()).unwrap()
+    }
+}
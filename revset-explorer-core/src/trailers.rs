@@ -0,0 +1,89 @@
+//! Structured metadata in commit descriptions: trailers like `Signed-off-by:`
+//! and `Change-Id:`, and references to issues, with revsets that find the
+//! commits sharing them
+
+pub use jj_lib::trailer::{Trailer, parse_description_trailers};
+
+use crate::resolve::string_literal;
+
+/// Issue references in the description, like `#123` or `ABC-123`, in order of
+/// appearance and without duplicates
+pub fn issue_references(description: &str) -> Vec<String> {
+    let mut issues = vec![];
+    let words = description.split(|c: char| !(c.is_ascii_alphanumeric() || c == '#' || c == '-'));
+    for word in words {
+        let word = word.trim_matches('-');
+        if is_issue_reference(word) && !issues.iter().any(|issue| issue == word) {
+            issues.push(word.to_owned());
+        }
+    }
+    issues
+}
+
+fn is_issue_reference(word: &str) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if let Some(number) = word.strip_prefix('#') {
+        return is_number(number);
+    }
+    // A project key, like in Jira
+    let Some((key, number)) = word.split_once('-') else {
+        return false;
+    };
+    key.len() >= 2
+        && key.starts_with(|c: char| c.is_ascii_uppercase())
+        && key
+            .bytes()
+            .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit())
+        && is_number(number)
+}
+
+/// Commits with the trailer in their description
+pub fn trailer_revset(trailer: &Trailer) -> String {
+    let line = format!("{}: {}", trailer.key, trailer.value);
+    format!("description(substring:{})", string_literal(&line))
+}
+
+/// Commits that reference the issue. `#12` does not match `#123`.
+pub fn issue_revset(issue: &str) -> String {
+    // Issue references have no regex special characters
+    let pattern = if issue.starts_with('#') {
+        format!(r"{issue}\b")
+    } else {
+        format!(r"\b{issue}\b")
+    };
+    format!("description(regex:{})", string_literal(&pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+
+    #[test]
+    fn finds_issue_references() {
+        assert_eq!(
+            issue_references("Fix #12 and ABC-7 (see #12, x#3, #a, A-1, abc-2)\n\nRefs: GH2-40."),
+            ["#12", "ABC-7", "GH2-40"].map(String::from)
+        );
+    }
+
+    #[test]
+    fn finds_commits_with_same_metadata() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("Fix #12\n\nSigned-off-by: A <a@example.com>", &[]);
+        let b = repo.commit("Fix #123\n\nSigned-off-by: B <b@example.com>", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let ids = |revset: &str| -> Vec<_> {
+            let revset = jj_graph.get_revset(revset).unwrap();
+            revset.iter().map(Result::unwrap).collect()
+        };
+        let trailers = parse_description_trailers("Fix #12\n\nSigned-off-by: A <a@example.com>");
+        assert_eq!(trailers[0].key, "Signed-off-by");
+        assert_eq!(ids(&trailer_revset(&trailers[0])), [a]);
+        assert_eq!(
+            ids(&issue_revset("#12")),
+            ids(&trailer_revset(&trailers[0]))
+        );
+        assert_eq!(ids(&issue_revset("#123")), [b]);
+    }
+}
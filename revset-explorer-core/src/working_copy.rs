@@ -0,0 +1,94 @@
+//! Whether the working copy has changes that jj has not snapshotted yet, so
+//! that the working-copy commit in the graph may be out of date. jj snapshots
+//! the working copy when running commands, but loading the repository doesn't.
+
+use jj_lib::gitignore::GitIgnoreFile;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Whether a file or directory in the workspace, other than ignored ones, was
+/// modified after the last snapshot. Reads the whole tree, which takes a while
+/// in large workspaces.
+pub fn may_be_stale(workspace_root: &Path) -> std::io::Result<bool> {
+    let tree_state = workspace_root.join(".jj/working_copy/tree_state");
+    let Ok(snapshot_time) = tree_state.metadata().and_then(|m| m.modified()) else {
+        // Not a local working copy
+        return Ok(false);
+    };
+    modified_after(workspace_root, "", &GitIgnoreFile::empty(), snapshot_time)
+}
+
+/// `prefix` is the slash-separated path of `dir` in the workspace, ending with
+/// a slash unless empty
+fn modified_after(
+    dir: &Path,
+    prefix: &str,
+    ignores: &Arc<GitIgnoreFile>,
+    time: SystemTime,
+) -> std::io::Result<bool> {
+    // Catches removed and renamed entries
+    if dir.metadata()?.modified()? > time {
+        return Ok(true);
+    }
+    let ignores = ignores
+        .chain_with_file(prefix, dir.join(".gitignore"))
+        .unwrap_or_else(|_| ignores.clone());
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let Some(name) = entry.file_name().to_str().map(str::to_owned) else {
+            continue;
+        };
+        if prefix.is_empty() && (name == ".jj" || name == ".git") {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        let path = format!("{prefix}{name}");
+        if file_type.is_dir() {
+            let path = format!("{path}/");
+            if !ignores.matches(&path) && modified_after(&entry.path(), &path, &ignores, time)? {
+                return Ok(true);
+            }
+        } else if !ignores.matches(&path) && entry.metadata()?.modified()? > time {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_repo::TestRepo;
+    use std::fs::File;
+    use std::time::Duration;
+
+    fn touch(path: &Path, time: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn detects_files_changed_after_snapshot() {
+        let repo = TestRepo::init();
+        let root = repo.path();
+        assert!(!may_be_stale(root).unwrap());
+
+        std::fs::create_dir(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "/target/\n").unwrap();
+        let snapshot_time = SystemTime::now() + Duration::from_secs(5);
+        touch(&root.join(".jj/working_copy/tree_state"), snapshot_time);
+        let later = snapshot_time + Duration::from_secs(5);
+        std::fs::write(root.join("target/output"), "").unwrap();
+        touch(&root.join("target/output"), later);
+        assert!(!may_be_stale(root).unwrap());
+
+        std::fs::write(root.join("file"), "").unwrap();
+        touch(&root.join("file"), later);
+        assert!(may_be_stale(root).unwrap());
+    }
+}
@@ -0,0 +1,38 @@
+//! The graph view of Revset Explorer as an egui widget, for embedding in other
+//! egui apps. The widget evaluates the revsets itself and keeps its own
+//! layout and selection state.
+//!
+//! ```no_run
+//! use revset_explorer_core::JjGraph;
+//! use revset_explorer_egui::RevsetGraphWidget;
+//!
+//! struct App {
+//!     jj_graph: JjGraph,
+//!     graph: RevsetGraphWidget,
+//! }
+//!
+//! impl App {
+//!     fn ui(&mut self, ui: &mut egui::Ui) {
+//!         let mut select = self.graph.select().to_owned();
+//!         if ui.text_edit_singleline(&mut select).changed() {
+//!             self.graph.set_select(select);
+//!         }
+//!         if let Some(e) = self.graph.select_error() {
+//!             ui.label(e.to_string());
+//!         }
+//!         self.graph.show(ui, &self.jj_graph);
+//!         for commit_id in self.graph.selected_commits() {
+//!             // ...
+//!         }
+//!     }
+//! }
+//!
+//! let jj_graph = JjGraph::new("path/to/repo".as_ref())?;
+//! let graph = RevsetGraphWidget::new("::@", "mine()").max_nodes(50);
+//! let app = App { jj_graph, graph };
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+mod widget;
+
+pub use widget::RevsetGraphWidget;
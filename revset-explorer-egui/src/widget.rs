@@ -0,0 +1,311 @@
+use egui::Color32;
+use jj_lib::backend::CommitId;
+use petgraph::graph::NodeIndex;
+use revset_explorer_core::{GraphBuilder, JjGraph, ResolveError, mark_commits, resolve_revset};
+use std::collections::HashMap;
+
+type GraphType = egui_graphs::Graph<CommitId, ()>;
+type Layout = egui_graphs::LayoutHierarchical;
+type LayoutState = egui_graphs::LayoutStateHierarchical;
+
+/// Graph of a "view" revset, with the commits of a "select" revset
+/// highlighted, colored like in Revset Explorer.
+///
+/// The revsets are evaluated when the widget is shown after they have been
+/// changed. On errors, the previous graph is kept and the error is available
+/// from [`Self::view_error`] and [`Self::select_error`].
+pub struct RevsetGraphWidget {
+    id: String,
+    view: String,
+    select: String,
+    max_nodes: usize,
+    graph: GraphType,
+    node_idxs: Vec<NodeIndex>,
+    view_error: Option<ResolveError>,
+    select_error: Option<ResolveError>,
+    limit_hit: Option<usize>,
+    view_changed: bool,
+    select_changed: bool,
+}
+
+impl RevsetGraphWidget {
+    pub fn new(view: impl Into<String>, select: impl Into<String>) -> Self {
+        Self {
+            id: "revset_graph".to_owned(),
+            view: view.into(),
+            select: select.into(),
+            max_nodes: GraphBuilder::DEFAULT_MAX_NODES,
+            graph: GraphType::new(petgraph::stable_graph::StableGraph::default()),
+            node_idxs: vec![],
+            view_error: None,
+            select_error: None,
+            limit_hit: None,
+            view_changed: true,
+            select_changed: true,
+        }
+    }
+
+    /// Unique ID of the widget, needed when showing more than one, as the
+    /// layout is stored by ID
+    pub fn id_salt(mut self, id: impl Into<String>) -> Self {
+        self.id = id.into();
+        self
+    }
+
+    /// Maximum number of commits to show. Defaults to
+    /// [`GraphBuilder::DEFAULT_MAX_NODES`].
+    pub fn max_nodes(mut self, max_nodes: usize) -> Self {
+        self.max_nodes = max_nodes;
+        self.view_changed = true;
+        self
+    }
+
+    pub fn view(&self) -> &str {
+        &self.view
+    }
+
+    pub fn set_view(&mut self, view: impl Into<String>) {
+        self.view = view.into();
+        self.view_changed = true;
+    }
+
+    pub fn select(&self) -> &str {
+        &self.select
+    }
+
+    pub fn set_select(&mut self, select: impl Into<String>) {
+        self.select = select.into();
+        self.select_changed = true;
+    }
+
+    /// Evaluates the revsets again when next shown. Call it after the
+    /// repository has changed.
+    pub fn reload(&mut self) {
+        self.view_changed = true;
+    }
+
+    pub fn view_error(&self) -> Option<&ResolveError> {
+        self.view_error.as_ref()
+    }
+
+    pub fn select_error(&self) -> Option<&ResolveError> {
+        self.select_error.as_ref()
+    }
+
+    /// The node limit, if the graph is incomplete because of it
+    pub fn limit_hit(&self) -> Option<usize> {
+        self.limit_hit
+    }
+
+    /// Commits selected by clicking, in selection order
+    pub fn selected_commits(&self) -> Vec<CommitId> {
+        self.graph
+            .selected_nodes()
+            .iter()
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .map(|node| node.payload().clone())
+            .collect()
+    }
+
+    pub fn hovered_commit(&self) -> Option<CommitId> {
+        let node_idx = self.graph.hovered_node()?;
+        Some(self.graph.node(node_idx)?.payload().clone())
+    }
+
+    /// Commits in the graph, in the order the view revset yields them
+    pub fn commits(&self) -> Vec<CommitId> {
+        self.node_idxs
+            .iter()
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .map(|node| node.payload().clone())
+            .collect()
+    }
+
+    /// Color of the commit's node, if it is in the graph
+    pub fn node_color(&self, commit_id: &CommitId) -> Option<Color32> {
+        self.node_idxs
+            .iter()
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .find(|node| node.payload() == commit_id)?
+            .color()
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, jj_graph: &JjGraph) -> egui::Response {
+        let mut graph_changed = false;
+        if self.view_changed {
+            self.view_changed = false;
+            graph_changed = self.update_view(jj_graph);
+            self.select_changed |= graph_changed;
+        }
+        if self.select_changed {
+            self.select_changed = false;
+            self.update_select(jj_graph);
+        }
+        if graph_changed {
+            egui_graphs::reset_layout::<LayoutState>(ui, Some(self.id.clone()));
+        }
+
+        let navigation = egui_graphs::SettingsNavigation::default()
+            // Only fit when the graph changes, as it hinders zoom and pan
+            .with_fit_to_screen_enabled(graph_changed)
+            .with_zoom_and_pan_enabled(true);
+        let interaction = egui_graphs::SettingsInteraction::default()
+            .with_dragging_enabled(false)
+            .with_hover_enabled(true)
+            .with_node_clicking_enabled(true)
+            .with_node_selection_enabled(true)
+            .with_node_selection_multi_enabled(true);
+        let mut layout_state =
+            egui_graphs::get_layout_state::<LayoutState>(ui, Some(self.id.clone()));
+        layout_state.center_parent = true;
+        egui_graphs::set_layout_state(ui, layout_state, Some(self.id.clone()));
+        let mut graph_view =
+            egui_graphs::GraphView::<_, _, _, _, _, _, LayoutState, Layout>::new(&mut self.graph)
+                .with_id(Some(self.id.clone()))
+                .with_navigations(&navigation)
+                .with_interactions(&interaction)
+                .with_styles(&egui_graphs::SettingsStyle::default().with_labels_always(true));
+        ui.add(&mut graph_view)
+    }
+
+    /// Returns whether the graph was replaced
+    fn update_view(&mut self, jj_graph: &JjGraph) -> bool {
+        let built = resolve_revset(jj_graph, &self.view).and_then(|(revset, ..)| {
+            GraphBuilder::new(jj_graph)
+                .max_nodes(self.max_nodes)
+                .build(revset)
+        });
+        let built = match built {
+            Ok(built) => built,
+            Err(e) => {
+                self.view_error = Some(e);
+                return false;
+            }
+        };
+        self.view_error = None;
+        self.limit_hit = built.metadata.limit_hit;
+        let commit_graph = built.graph;
+        self.graph = GraphType::new(petgraph::stable_graph::StableGraph::default());
+        let mut node_map = HashMap::new();
+        self.node_idxs = commit_graph
+            .node_indices()
+            .map(|node_idx| {
+                let node = &commit_graph[node_idx];
+                let view_idx = self
+                    .graph
+                    .add_node_with_label(node.commit_id.clone(), node.label.clone());
+                node_map.insert(node_idx, view_idx);
+                view_idx
+            })
+            .collect();
+        for edge_idx in commit_graph.edge_indices() {
+            let (start, end) = commit_graph.edge_endpoints(edge_idx).unwrap();
+            self.graph
+                .add_edge_with_label(node_map[&start], node_map[&end], (), String::new());
+        }
+        true
+    }
+
+    fn update_select(&mut self, jj_graph: &JjGraph) {
+        let commit_ids = self.commits();
+        let markings = resolve_revset(jj_graph, &self.select)
+            .and_then(|(revset, ..)| mark_commits(jj_graph, &commit_ids, Some(revset)));
+        let markings = match markings {
+            Ok(markings) => {
+                self.select_error = None;
+                markings
+            }
+            Err(e) => {
+                self.select_error = Some(e);
+                // Still show the node types
+                mark_commits(jj_graph, &commit_ids, None).unwrap_or_default()
+            }
+        };
+        for (node_idx, marking) in self.node_idxs.iter().zip(markings) {
+            let [red, green, blue] = marking.color();
+            if let Some(node) = self.graph.node_mut(*node_idx) {
+                node.set_color(Color32::from_rgb(red, green, blue));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_kittest::Harness;
+    use jj_lib::object_id::ObjectId;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    struct State {
+        jj_graph: JjGraph,
+        widget: RevsetGraphWidget,
+    }
+
+    fn harness<'a>(repo: &TestRepo, view: &str, select: &str) -> Harness<'a, State> {
+        let state = State {
+            jj_graph: repo.jj_graph(),
+            widget: RevsetGraphWidget::new(view, select),
+        };
+        let mut harness = Harness::new_ui_state(
+            |ui, state: &mut State| {
+                state.widget.show(ui, &state.jj_graph);
+            },
+            state,
+        );
+        harness.run_steps(2);
+        harness
+    }
+
+    #[test]
+    fn evaluates_revsets() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let mut harness = harness(&repo, &format!("{}::", a.hex()), &a.hex());
+        let widget = &harness.state().widget;
+        assert_eq!(widget.commits(), [b.clone(), a.clone()]);
+        assert_eq!(
+            widget.node_color(&a),
+            Some(Color32::from_rgb(0xff, 0xfc, 0x00))
+        );
+        assert_eq!(
+            widget.node_color(&b),
+            Some(Color32::from_rgb(0x63, 0x62, 0x22))
+        );
+
+        harness.state_mut().widget.set_select(b.hex());
+        harness.run_steps(1);
+        let widget = &harness.state().widget;
+        assert_eq!(
+            widget.node_color(&a),
+            Some(Color32::from_rgb(0x63, 0x62, 0x22))
+        );
+        assert_eq!(
+            widget.node_color(&b),
+            Some(Color32::from_rgb(0xff, 0xfc, 0x00))
+        );
+    }
+
+    #[test]
+    fn keeps_graph_on_error() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "");
+        harness.state_mut().widget.set_view("::(");
+        harness.state_mut().widget.set_select("::(");
+        harness.run_steps(1);
+        let widget = &harness.state().widget;
+        assert!(matches!(
+            widget.view_error(),
+            Some(ResolveError::RevsetParseError(_))
+        ));
+        assert!(widget.select_error().is_some());
+        assert_eq!(widget.commits(), std::slice::from_ref(&a));
+        // Regular, not matching
+        assert_eq!(
+            widget.node_color(&a),
+            Some(Color32::from_rgb(0x63, 0x62, 0x22))
+        );
+    }
+}
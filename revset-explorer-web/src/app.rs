@@ -0,0 +1,299 @@
+use eframe::egui::{self, Color32, RichText};
+use petgraph::graph::NodeIndex;
+use revset_explorer_core::snapshot::Snapshot;
+use std::sync::mpsc;
+
+/// The payload is the index into [`Snapshot::nodes`]
+type GraphType = egui_graphs::Graph<usize, ()>;
+
+pub struct ViewerApp {
+    /// Delivers the snapshot once it has been loaded
+    receiver: mpsc::Receiver<Result<Snapshot, String>>,
+    state: State,
+}
+
+enum State {
+    Loading,
+    Failed(String),
+    Loaded(Box<Viewer>),
+}
+
+struct Viewer {
+    snapshot: Snapshot,
+    graph: GraphType,
+    /// Graph index of each snapshot node
+    node_idxs: Vec<NodeIndex>,
+    /// Index into [`Snapshot::selections`]
+    selection: Option<usize>,
+    layout_changed: bool,
+}
+
+impl ViewerApp {
+    pub fn new(receiver: mpsc::Receiver<Result<Snapshot, String>>) -> Self {
+        Self {
+            receiver,
+            state: State::Loading,
+        }
+    }
+}
+
+impl eframe::App for ViewerApp {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Ok(result) = self.receiver.try_recv() {
+            self.state = match result {
+                Ok(snapshot) => State::Loaded(Box::new(Viewer::new(snapshot))),
+                Err(e) => State::Failed(e),
+            };
+        }
+        match &mut self.state {
+            State::Loading => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.centered_and_justified(|ui| ui.spinner());
+                });
+            }
+            State::Failed(error) => {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui.colored_label(Color32::LIGHT_RED, error.as_str());
+                });
+            }
+            State::Loaded(viewer) => viewer.ui(ctx),
+        }
+    }
+}
+
+impl Viewer {
+    fn new(snapshot: Snapshot) -> Self {
+        let mut graph = GraphType::new(petgraph::stable_graph::StableGraph::default());
+        let node_idxs: Vec<_> = snapshot
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(i, node)| graph.add_node_with_label(i, node.label.clone()))
+            .collect();
+        // Snapshot::from_json checks the indexes, but snapshots may be made
+        // otherwise
+        for edge in &snapshot.edges {
+            let (Some(from), Some(to)) = (node_idxs.get(edge.from), node_idxs.get(edge.to)) else {
+                continue;
+            };
+            graph.add_edge_with_label(*from, *to, (), String::new());
+        }
+        let mut viewer = Self {
+            // Start with the selection the snapshot was exported with
+            selection: (!snapshot.selections.is_empty()).then_some(0),
+            snapshot,
+            graph,
+            node_idxs,
+            layout_changed: true,
+        };
+        viewer.mark_graph();
+        viewer
+    }
+
+    fn mark_graph(&mut self) {
+        let selection = self.selection.map(|i| &self.snapshot.selections[i]);
+        let markings = self.snapshot.markings(selection);
+        for (node_idx, marking) in self.node_idxs.iter().zip(markings) {
+            let [red, green, blue] = marking.color();
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            node.set_color(Color32::from_rgb(red, green, blue));
+        }
+    }
+
+    fn ui(&mut self, ctx: &egui::Context) {
+        egui::TopBottomPanel::top("revsets").show(ctx, |ui| {
+            ui.heading(&self.snapshot.repository);
+            egui::Grid::new("revset_grid")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("View");
+                    ui.monospace(&self.snapshot.view);
+                    ui.end_row();
+
+                    ui.label("Select");
+                    let revset = |selection: Option<usize>| match selection {
+                        Some(i) => self.snapshot.selections[i].revset.clone(),
+                        None => "(none)".to_owned(),
+                    };
+                    let mut selection = self.selection;
+                    egui::ComboBox::from_id_salt("selection")
+                        .selected_text(RichText::new(revset(selection)).monospace())
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut selection, None, revset(None));
+                            for i in 0..self.snapshot.selections.len() {
+                                ui.selectable_value(
+                                    &mut selection,
+                                    Some(i),
+                                    RichText::new(revset(Some(i))).monospace(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+                    if selection != self.selection {
+                        self.selection = selection;
+                        self.mark_graph();
+                    }
+                });
+            if let Some(limit) = self.snapshot.limit_hit {
+                ui.colored_label(
+                    Color32::YELLOW,
+                    format!("Node limit of {limit} reached. The graph is incomplete."),
+                );
+            }
+        });
+
+        let selected: Vec<_> = self
+            .graph
+            .selected_nodes()
+            .iter()
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .map(|node| *node.payload())
+            .collect();
+        if !selected.is_empty() {
+            egui::SidePanel::right("details").show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for i in selected {
+                        let node = &self.snapshot.nodes[i];
+                        ui.monospace(&node.change_id);
+                        ui.monospace(node.commit_id.get(..12).unwrap_or(&node.commit_id));
+                        if !node.refs.is_empty() {
+                            let names: Vec<_> = node.refs.iter().map(|r| r.name.as_str()).collect();
+                            ui.label(RichText::new(names.join(" ")).strong());
+                        }
+                        ui.label(&node.author);
+                        ui.label(node.description.trim_end());
+                        ui.separator();
+                    }
+                });
+            });
+        }
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            if self.layout_changed {
+                egui_graphs::reset_layout::<egui_graphs::LayoutStateHierarchical>(ui, None);
+            }
+            let navigation = egui_graphs::SettingsNavigation::default()
+                .with_fit_to_screen_enabled(self.layout_changed)
+                .with_zoom_and_pan_enabled(true);
+            let interaction = egui_graphs::SettingsInteraction::default()
+                .with_dragging_enabled(false)
+                .with_hover_enabled(true)
+                .with_node_clicking_enabled(true)
+                .with_node_selection_enabled(true)
+                .with_node_selection_multi_enabled(true);
+            self.layout_changed = false;
+            let mut graph_view = egui_graphs::GraphView::<
+                _,
+                _,
+                _,
+                _,
+                _,
+                _,
+                egui_graphs::LayoutStateHierarchical,
+                egui_graphs::LayoutHierarchical,
+            >::new(&mut self.graph)
+            .with_navigations(&navigation)
+            .with_interactions(&interaction)
+            .with_styles(&egui_graphs::SettingsStyle::default().with_labels_always(true));
+            let mut layout_state =
+                egui_graphs::get_layout_state::<egui_graphs::LayoutStateHierarchical>(ui, None);
+            layout_state.center_parent = true;
+            egui_graphs::set_layout_state(ui, layout_state, None);
+            ui.add(&mut graph_view);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_kittest::Harness;
+    use egui_kittest::kittest::Queryable;
+    use revset_explorer_core::NodeType;
+    use revset_explorer_core::snapshot::{
+        EdgeKind, SCHEMA_VERSION, Selection, SnapshotEdge, SnapshotNode,
+    };
+
+    fn snapshot() -> Snapshot {
+        let node = |label: &str, node_type| SnapshotNode {
+            commit_id: "0".repeat(40),
+            change_id: "z".repeat(32),
+            label: label.to_owned(),
+            description: String::new(),
+            author: String::new(),
+            node_type,
+            refs: vec![],
+        };
+        Snapshot {
+            version: SCHEMA_VERSION,
+            repository: "test".to_owned(),
+            view: "::@".to_owned(),
+            nodes: vec![
+                node("@ a", NodeType::WorkingCopy),
+                node("b", NodeType::Immutable),
+            ],
+            edges: vec![SnapshotEdge {
+                from: 0,
+                to: 1,
+                kind: EdgeKind::Direct,
+            }],
+            limit_hit: None,
+            selections: vec![Selection {
+                revset: "root()".to_owned(),
+                nodes: vec![1],
+            }],
+        }
+    }
+
+    fn colors(app: &ViewerApp) -> Vec<Option<Color32>> {
+        let State::Loaded(viewer) = &app.state else {
+            panic!("Not loaded");
+        };
+        (viewer.node_idxs.iter())
+            .map(|node_idx| viewer.graph.node(*node_idx).unwrap().color())
+            .collect()
+    }
+
+    #[test]
+    fn shows_first_selection() {
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Ok(snapshot())).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| ViewerApp::new(receiver));
+        harness.run_steps(2);
+        assert_eq!(
+            colors(harness.state()),
+            [
+                Some(Color32::from_rgb(0x29, 0x59, 0x23)),
+                Some(Color32::from_rgb(0x21, 0xcd, 0xff)),
+            ]
+        );
+    }
+
+    #[test]
+    fn shows_details_of_documented_snapshot() {
+        let json = include_str!("../../revset-explorer-core/src/snapshot_v1.json");
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Ok(Snapshot::from_json(json).unwrap())).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| ViewerApp::new(receiver));
+        harness.run_steps(2);
+        let State::Loaded(viewer) = &mut harness.state_mut().state else {
+            panic!("Not loaded");
+        };
+        let node_idx = viewer.node_idxs[0];
+        viewer.graph.node_mut(node_idx).unwrap().set_selected(true);
+        viewer.graph.set_selected_nodes(vec![node_idx]);
+        harness.run_steps(2);
+        // The commit ID is shorter than the 12 characters shown
+        harness.get_by_label("abc");
+        harness.get_by_label("main main@origin");
+    }
+
+    #[test]
+    fn shows_load_error() {
+        let (sender, receiver) = mpsc::channel();
+        sender.send(Err("Cannot fetch".to_owned())).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| ViewerApp::new(receiver));
+        harness.run_steps(2);
+        assert!(matches!(&harness.state().state, State::Failed(e) if e == "Cannot fetch"));
+    }
+}
@@ -0,0 +1,80 @@
+//! Viewer for graph snapshots exported by `revset-explorer export`. Builds for
+//! the web, where it is embedded in documentation, and natively for trying out
+//! snapshots.
+
+use revset_explorer_core::snapshot::Snapshot;
+use std::sync::mpsc;
+
+mod app;
+
+/// Canvas id in index.html
+#[cfg(target_arch = "wasm32")]
+const CANVAS_ID: &str = "revset_explorer";
+
+#[cfg(not(target_arch = "wasm32"))]
+fn main() -> eframe::Result {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "snapshot.json".to_owned());
+    let (sender, receiver) = mpsc::channel();
+    let result = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Cannot read {path}: {e}"))
+        .and_then(|json| parse(&json));
+    sender.send(result).unwrap();
+    eframe::run_native(
+        "Revset Explorer",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| Ok(Box::new(app::ViewerApp::new(receiver)))),
+    )
+}
+
+#[cfg(target_arch = "wasm32")]
+fn main() {
+    use eframe::wasm_bindgen::JsCast;
+
+    let window = web_sys::window().unwrap();
+    let url = window
+        .location()
+        .search()
+        .ok()
+        .and_then(|search| web_sys::UrlSearchParams::new_with_str(&search).ok())
+        .and_then(|params| params.get("snapshot"))
+        .unwrap_or_else(|| "snapshot.json".to_owned());
+    let canvas = window
+        .document()
+        .unwrap()
+        .get_element_by_id(CANVAS_ID)
+        .expect("The page has no canvas for the viewer")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+
+    wasm_bindgen_futures::spawn_local(async move {
+        eframe::WebRunner::new()
+            .start(
+                canvas,
+                eframe::WebOptions::default(),
+                Box::new(move |cc| {
+                    let (sender, receiver) = mpsc::channel();
+                    let ctx = cc.egui_ctx.clone();
+                    ehttp::fetch(ehttp::Request::get(&url), move |response| {
+                        let result = response
+                            .and_then(|response| match response.text() {
+                                Some(json) if response.ok => Ok(json.to_owned()),
+                                _ => Err(format!("{} {}", response.status, response.status_text)),
+                            })
+                            .map_err(|e| format!("Cannot fetch {url}: {e}"))
+                            .and_then(|json| parse(&json));
+                        let _ = sender.send(result);
+                        ctx.request_repaint();
+                    });
+                    Ok(Box::new(app::ViewerApp::new(receiver)))
+                }),
+            )
+            .await
+            .expect("Failed to start the viewer");
+    });
+}
+
+fn parse(json: &str) -> Result<Snapshot, String> {
+    Snapshot::from_json(json).map_err(|e| format!("Invalid snapshot: {e}"))
+}
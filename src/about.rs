@@ -0,0 +1,212 @@
+//! The About window: the versions of Revset Explorer and jj-lib, the storage
+//! of the repository, and a check for newer releases on GitHub. The check is
+//! only made when asked for. Release notes name the jj-lib version the
+//! release is built with, like "jj-lib 0.37.0", which tells whether it can
+//! read repositories written by a newer jj.
+
+use eframe::egui::{self, RichText};
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use revset_explorer_core::JjGraph;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::i18n::tr;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// The jj-lib version in Cargo.lock. A test keeps it up to date.
+pub const JJ_LIB_VERSION: &str = "0.36.0";
+
+const RELEASES_URL: &str = "https://api.github.com/repos/thomasa88/revset-explorer/releases/latest";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Release {
+    /// Like "0.2.0"
+    pub version: String,
+    /// Web page of the release
+    pub url: String,
+    /// As named in the release notes
+    pub jj_lib_version: Option<String>,
+}
+
+impl Release {
+    /// Reads the response of the GitHub API for a release
+    fn parse(response: &serde_json::Value) -> Option<Self> {
+        let tag = response.get("tag_name")?.as_str()?;
+        let notes = (response.get("body").and_then(|body| body.as_str())).unwrap_or_default();
+        let jj_lib_version = (notes.split_once("jj-lib "))
+            .map(|(_, rest)| {
+                let end =
+                    (rest.find(|c: char| !c.is_ascii_digit() && c != '.')).unwrap_or(rest.len());
+                // Without the period ending a sentence
+                rest[..end].trim_end_matches('.').to_owned()
+            })
+            .filter(|version| !version.is_empty());
+        Some(Self {
+            version: tag.trim_start_matches('v').to_owned(),
+            url: response.get("html_url")?.as_str()?.to_owned(),
+            jj_lib_version,
+        })
+    }
+
+    pub fn is_newer(&self) -> bool {
+        is_newer(&self.version, VERSION)
+    }
+
+    /// Whether it is built with a newer jj-lib, which may read repositories
+    /// that this version cannot
+    pub fn has_newer_jj(&self) -> bool {
+        (self.jj_lib_version.as_deref()).is_some_and(|v| is_newer(v, JJ_LIB_VERSION))
+    }
+}
+
+/// Compares dotted versions like "0.10.1" by their numbers
+fn is_newer(version: &str, than: &str) -> bool {
+    let numbers = |version: &str| -> Vec<u64> {
+        (version.split('.'))
+            .map(|part| part.parse().unwrap_or_default())
+            .collect()
+    };
+    numbers(version) > numbers(than)
+}
+
+#[derive(Default)]
+pub struct About {
+    pub open: bool,
+    update_check: Option<mpsc::Receiver<Result<Release, String>>>,
+    latest: Option<Result<Release, String>>,
+}
+
+impl About {
+    fn check_for_updates(&mut self, ctx: &egui::Context) {
+        let (sender, receiver) = mpsc::channel();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            let _ = sender.send(fetch_latest_release());
+            ctx.request_repaint();
+        });
+        self.update_check = Some(receiver);
+        self.latest = None;
+    }
+
+    pub fn window(&mut self, ctx: &egui::Context, jj_graph: &JjGraph) {
+        if let Some(receiver) = &self.update_check
+            && let Ok(latest) = receiver.try_recv()
+        {
+            self.latest = Some(latest);
+            self.update_check = None;
+        }
+        let mut open = self.open;
+        let mut check = false;
+        egui::Window::new(tr!("about-title"))
+            .id(egui::Id::new("about"))
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.heading("Revset Explorer");
+                let repo = jj_graph.get_repo();
+                let loader = repo.loader();
+                egui::Grid::new("about_versions")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        let rows = [
+                            (tr!("about-version"), VERSION.to_owned()),
+                            (tr!("about-jj-lib"), JJ_LIB_VERSION.to_owned()),
+                            (
+                                tr!("about-backend"),
+                                repo.store().backend().name().to_owned(),
+                            ),
+                            (tr!("about-op-store"), loader.op_store().name().to_owned()),
+                            (tr!("about-index"), loader.index_store().name().to_owned()),
+                            (tr!("about-operation"), repo.op_id().hex()[..12].to_owned()),
+                        ];
+                        for (name, value) in rows {
+                            ui.label(name);
+                            ui.label(RichText::new(value).monospace());
+                            ui.end_row();
+                        }
+                    });
+                ui.separator();
+                if self.update_check.is_some() {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label(tr!("about-checking"));
+                    });
+                } else {
+                    check = (ui.button(tr!("about-check-updates")))
+                        .on_hover_text(tr!("about-check-updates-hover"))
+                        .clicked();
+                }
+                match &self.latest {
+                    Some(Ok(release)) if release.is_newer() => {
+                        ui.hyperlink_to(
+                            tr!("about-update-available", version = release.version.as_str()),
+                            &release.url,
+                        );
+                        if release.has_newer_jj() {
+                            let jj_lib = release.jj_lib_version.as_deref().unwrap_or_default();
+                            ui.label(tr!("about-newer-jj", version = jj_lib));
+                        }
+                    }
+                    Some(Ok(_)) => {
+                        ui.label(tr!("about-up-to-date"));
+                    }
+                    Some(Err(error)) => {
+                        let error = tr!("about-check-failed", error = error.as_str());
+                        ui.colored_label(ui.visuals().error_fg_color, error);
+                    }
+                    None => {}
+                }
+            });
+        if check {
+            self.check_for_updates(ctx);
+        }
+        self.open = open;
+    }
+}
+
+fn fetch_latest_release() -> Result<Release, String> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(Duration::from_secs(10)))
+        .build()
+        .into();
+    let response: serde_json::Value = (agent.get(RELEASES_URL))
+        .header("User-Agent", "revset-explorer")
+        .call()
+        .and_then(|mut response| response.body_mut().read_json())
+        .map_err(|e| e.to_string())?;
+    Release::parse(&response).ok_or_else(|| tr!("about-no-release"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jj_lib_version_matches_lock_file() {
+        let lock: toml::Table = toml::from_str(include_str!("../Cargo.lock")).unwrap();
+        let jj_lib = (lock["package"].as_array().unwrap().iter())
+            .find(|package| package["name"].as_str() == Some("jj-lib"))
+            .unwrap();
+        assert_eq!(jj_lib["version"].as_str(), Some(JJ_LIB_VERSION));
+    }
+
+    #[test]
+    fn reads_releases() {
+        let response = serde_json::json!({
+            "tag_name": "v99.0.0",
+            "html_url": "https://github.com/thomasa88/revset-explorer/releases/tag/v99.0.0",
+            "body": "Built with jj-lib 99.1.0.\n\nChanges:",
+        });
+        let release = Release::parse(&response).unwrap();
+        assert_eq!(release.version, "99.0.0");
+        assert_eq!(release.jj_lib_version.as_deref(), Some("99.1.0"));
+        assert!(release.is_newer() && release.has_newer_jj());
+
+        let old = serde_json::json!({"tag_name": "v0.0.1", "html_url": "", "body": null});
+        let old = Release::parse(&old).unwrap();
+        assert!(!old.is_newer() && !old.has_newer_jj());
+        assert!(is_newer("0.10.0", "0.9.1"));
+    }
+}
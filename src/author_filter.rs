@@ -0,0 +1,150 @@
+//! Chips for the authors of the commits in the view. Toggling chips dims the
+//! commits of the other authors, and the chosen authors can be added to the
+//! Select revset as `author()` terms.
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use revset_explorer_core::resolve::string_literal;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::i18n::tr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AuthorAction {
+    /// Chips were toggled, so the dimming must be updated
+    Changed,
+    /// Add the authors to the Select revset
+    AddToSelect(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Author {
+    name: String,
+    email: String,
+}
+
+#[derive(Default)]
+pub struct AuthorFilter {
+    // Authors of the commits in the view
+    authors: HashMap<CommitId, Author>,
+    // Authors in the view with their number of commits, most commits first
+    chips: Vec<(Author, usize)>,
+    chosen: BTreeSet<Author>,
+}
+
+impl AuthorFilter {
+    /// Shows chips for the authors, given by commit as name and email. The
+    /// chosen authors that are still in the view stay chosen.
+    pub fn reset(&mut self, authors: impl IntoIterator<Item = (CommitId, String, String)>) {
+        self.authors = (authors.into_iter())
+            .map(|(commit_id, name, email)| (commit_id, Author { name, email }))
+            .collect();
+        let mut counts = HashMap::<&Author, usize>::new();
+        for author in self.authors.values() {
+            *counts.entry(author).or_default() += 1;
+        }
+        let mut chips: Vec<_> = (counts.into_iter())
+            .map(|(author, count)| (author.clone(), count))
+            .collect();
+        chips.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        self.chips = chips;
+        let chips = &self.chips;
+        self.chosen
+            .retain(|author| chips.iter().any(|(chip, _)| chip == author));
+    }
+
+    /// Whether the commit is by an author that is not chosen, while others are
+    pub fn dims(&self, commit_id: &CommitId) -> bool {
+        !self.chosen.is_empty()
+            && (self.authors.get(commit_id)).is_some_and(|author| !self.chosen.contains(author))
+    }
+
+    /// Shows the chips, unless there is only one author
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<AuthorAction> {
+        if self.chips.len() < 2 {
+            return None;
+        }
+        ui.label(tr!("author-filter"));
+        let mut action = None;
+        for (author, count) in &self.chips {
+            let chosen = self.chosen.contains(author);
+            let name = if author.name.is_empty() {
+                &author.email
+            } else {
+                &author.name
+            };
+            let chip = ui
+                .selectable_label(
+                    chosen,
+                    tr!("author-chip", name = name.as_str(), count = *count),
+                )
+                .on_hover_text(&author.email);
+            if chip.clicked() {
+                if chosen {
+                    self.chosen.remove(author);
+                } else {
+                    self.chosen.insert(author.clone());
+                }
+                action = Some(AuthorAction::Changed);
+            }
+        }
+        if ui
+            .add_enabled(
+                !self.chosen.is_empty(),
+                egui::Button::new(tr!("author-filter-apply")),
+            )
+            .on_hover_text(tr!("author-filter-apply-hover"))
+            .clicked()
+        {
+            action = Some(AuthorAction::AddToSelect(authors_revset(&self.chosen)));
+        }
+        action
+    }
+}
+
+/// Union of `author()` terms
+fn authors_revset(authors: &BTreeSet<Author>) -> String {
+    let terms: Vec<_> = (authors.iter())
+        .map(|author| author_revset(&author.name, &author.email))
+        .collect();
+    terms.join(" | ")
+}
+
+/// `author()` term matching the email exactly, or the name for authors
+/// without email
+pub fn author_revset(name: &str, email: &str) -> String {
+    let id = if email.is_empty() { name } else { email };
+    format!("author(exact:{})", string_literal(id))
+}
+
+/// Restricts the revset to the commits by the authors
+pub fn with_authors(revset: &str, authors: &str) -> String {
+    match revset.trim() {
+        "" => authors.to_owned(),
+        revset => format!("({revset}) & ({authors})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn author(name: &str, email: &str) -> Author {
+        Author {
+            name: name.to_owned(),
+            email: email.to_owned(),
+        }
+    }
+
+    #[test]
+    fn composes_author_union() {
+        let authors = BTreeSet::from([author("A", "a@example.com"), author("B", "")]);
+        let revset = authors_revset(&authors);
+        assert_eq!(
+            revset,
+            r#"author(exact:"a@example.com") | author(exact:"B")"#
+        );
+        assert_eq!(with_authors("", &revset), revset);
+        assert_eq!(with_authors("::@", &revset), format!("(::@) & ({revset})"));
+    }
+}
@@ -0,0 +1,78 @@
+//! Short author labels on the nodes, as initials or the part of the email
+//! before the @. The full name and email are shown when hovering the node and
+//! in the details panel.
+
+use serde::{Deserialize, Serialize};
+
+/// How the author is shown on the nodes
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthorLabel {
+    #[default]
+    None,
+    /// Like "JD" for Jane Doe
+    Initials,
+    /// Like "jane.doe" for jane.doe@example.com
+    Email,
+}
+
+impl AuthorLabel {
+    pub const ALL: [Self; 3] = [Self::None, Self::Initials, Self::Email];
+
+    /// The label for the author, falling back to the other part of the
+    /// identity when one is empty
+    pub fn text(self, name: &str, email: &str) -> Option<String> {
+        let text = match self {
+            AuthorLabel::None => return None,
+            AuthorLabel::Initials => initials(name).or_else(|| initials(email_user(email)))?,
+            AuthorLabel::Email => match email_user(email) {
+                "" => initials(name)?,
+                user => user.to_owned(),
+            },
+        };
+        Some(text)
+    }
+}
+
+/// Uppercase first letters of up to three words
+fn initials(name: &str) -> Option<String> {
+    let initials: String = (name.split(|c: char| c.is_whitespace() || c == '.' || c == '-'))
+        .filter_map(|word| word.chars().next())
+        .filter(|c| c.is_alphanumeric())
+        .take(3)
+        .flat_map(char::to_uppercase)
+        .collect();
+    (!initials.is_empty()).then_some(initials)
+}
+
+fn email_user(email: &str) -> &str {
+    email.split('@').next().unwrap_or_default().trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn abbreviates_identity() {
+        let label = |mode: AuthorLabel, name, email| mode.text(name, email);
+        assert_eq!(label(AuthorLabel::None, "Jane Doe", "jd@example.com"), None);
+        assert_eq!(
+            label(AuthorLabel::Initials, "Jane van der Doe", "jd@example.com").as_deref(),
+            Some("JVD")
+        );
+        assert_eq!(
+            label(AuthorLabel::Initials, "", "jane.doe@example.com").as_deref(),
+            Some("JD")
+        );
+        assert_eq!(
+            label(AuthorLabel::Email, "Jane Doe", "jane.doe@example.com").as_deref(),
+            Some("jane.doe")
+        );
+        assert_eq!(
+            label(AuthorLabel::Email, "Jane Doe", "").as_deref(),
+            Some("JD")
+        );
+        assert_eq!(label(AuthorLabel::Email, "", ""), None);
+    }
+}
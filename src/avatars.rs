@@ -0,0 +1,157 @@
+//! Author avatars. Identicons are generated offline and, optionally, replaced
+//! by Gravatar images that are cached on disk.
+
+use eframe::egui;
+use etcetera::BaseStrategy;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+const AVATAR_SIZE: usize = 80;
+const IDENTICON_CELLS: usize = 5;
+
+pub struct Avatars {
+    use_gravatar: bool,
+    textures: HashMap<String, egui::TextureHandle>,
+    sender: mpsc::Sender<(String, egui::ColorImage)>,
+    receiver: mpsc::Receiver<(String, egui::ColorImage)>,
+}
+
+impl Avatars {
+    pub fn new(use_gravatar: bool) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            use_gravatar,
+            textures: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Returns the avatar for the email. The identicon is returned until the
+    /// Gravatar image has been loaded.
+    pub fn get(&mut self, ctx: &egui::Context, email: &str) -> egui::TextureId {
+        let hash = email_hash(email);
+        if let Some(texture) = self.textures.get(&hash) {
+            return texture.id();
+        }
+        let texture = ctx.load_texture(
+            format!("avatar-{hash}"),
+            identicon(&hash),
+            egui::TextureOptions::NEAREST,
+        );
+        let id = texture.id();
+        self.textures.insert(hash.clone(), texture);
+        if self.use_gravatar {
+            let sender = self.sender.clone();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                if let Some(image) = load_gravatar(&hash) {
+                    let _ = sender.send((hash, image));
+                    ctx.request_repaint();
+                }
+            });
+        }
+        id
+    }
+
+    /// Receives downloaded avatars. Returns true if any avatar changed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((hash, image)) = self.receiver.try_recv() {
+            if let Some(texture) = self.textures.get_mut(&hash) {
+                texture.set(image, egui::TextureOptions::LINEAR);
+                changed = true;
+            }
+        }
+        changed
+    }
+}
+
+/// Hash used by Gravatar to identify an email address
+fn email_hash(email: &str) -> String {
+    Sha256::digest(email.trim().to_lowercase().as_bytes())
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+/// Loads the Gravatar image from the disk cache or from gravatar.com
+fn load_gravatar(hash: &str) -> Option<egui::ColorImage> {
+    let path = cache_path(hash);
+    let bytes = match std::fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            // d=404 makes Gravatar fail for emails without an avatar, so that
+            // the identicon is kept
+            let url = format!("https://gravatar.com/avatar/{hash}?s={AVATAR_SIZE}&d=404");
+            let bytes = ureq::get(url).call().ok()?.body_mut().read_to_vec().ok()?;
+            if let Some(dir) = path.parent() {
+                let _ = std::fs::create_dir_all(dir);
+            }
+            let _ = std::fs::write(&path, &bytes);
+            bytes
+        }
+    };
+    let image = image::load_from_memory(&bytes).ok()?.to_rgba8();
+    Some(egui::ColorImage::from_rgba_unmultiplied(
+        [image.width() as usize, image.height() as usize],
+        image.as_raw(),
+    ))
+}
+
+/// Creates a symmetric block pattern from the hash
+fn identicon(hash: &str) -> egui::ColorImage {
+    let bytes: Vec<u8> = (0..hash.len() / 2)
+        .filter_map(|i| u8::from_str_radix(&hash[i * 2..i * 2 + 2], 16).ok())
+        .collect();
+    let color = egui::Color32::from(egui::ecolor::Hsva::new(
+        bytes[0] as f32 / 255.,
+        0.6,
+        0.9,
+        1.,
+    ));
+    let background = egui::Color32::from_gray(0xf0);
+    let mut image = egui::ColorImage::filled([IDENTICON_CELLS, IDENTICON_CELLS], background);
+    for y in 0..IDENTICON_CELLS {
+        for x in 0..IDENTICON_CELLS.div_ceil(2) {
+            let bit = y * IDENTICON_CELLS.div_ceil(2) + x;
+            if bytes[1 + bit / 8] & (1 << (bit % 8)) != 0 {
+                image[(x, y)] = color;
+                image[(IDENTICON_CELLS - 1 - x, y)] = color;
+            }
+        }
+    }
+    image
+}
+
+fn cache_path(hash: &str) -> PathBuf {
+    let mut path = etcetera::choose_base_strategy().unwrap().cache_dir();
+    path.push("revset-explorer/avatars");
+    path.push(format!("{hash}.img"));
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn email_hash_is_normalized() {
+        assert_eq!(
+            email_hash(" User@Example.com "),
+            email_hash("user@example.com")
+        );
+    }
+
+    #[test]
+    fn identicon_is_symmetric() {
+        let image = identicon(&email_hash("user@example.com"));
+        for y in 0..IDENTICON_CELLS {
+            for x in 0..IDENTICON_CELLS {
+                assert_eq!(image[(x, y)], image[(IDENTICON_CELLS - 1 - x, y)]);
+            }
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Store factories for custom jj backends, loaded from shared libraries given
+//! by `--backend-plugin`
+//!
+//! A plugin exports a Rust function returning the factories to add to the jj
+//! defaults:
+//!
+//! ```ignore
+//! #[unsafe(no_mangle)]
+//! pub fn revset_explorer_store_factories() -> jj_lib::repo::StoreFactories {
+//!     let mut factories = jj_lib::repo::StoreFactories::empty();
+//!     factories.add_backend("my-backend", Box::new(|settings, path| {
+//!         Ok(Box::new(MyBackend::load(settings, path)?))
+//!     }));
+//!     factories
+//! }
+//! ```
+//!
+//! The Rust ABI is not stable, so the plugin must be built with the same Rust
+//! compiler and jj-lib version as Revset Explorer.
+
+use anyhow::Context;
+use jj_lib::repo::StoreFactories;
+use std::path::PathBuf;
+
+const ENTRY_POINT: &[u8] = b"revset_explorer_store_factories";
+
+type StoreFactoriesFn = fn() -> StoreFactories;
+
+#[derive(Clone, Default)]
+pub struct BackendPlugins {
+    // StoreFactories can't be shared between threads or cloned, so they are
+    // created again for each load of the repository
+    factories_fns: Vec<StoreFactoriesFn>,
+}
+
+impl BackendPlugins {
+    pub fn load(paths: &[PathBuf]) -> anyhow::Result<Self> {
+        let factories_fns = paths
+            .iter()
+            .map(|path| {
+                // Safety: Loading runs the library's initialization code. The
+                // user asked for the library to be loaded.
+                let library = unsafe { libloading::Library::new(path) }
+                    .with_context(|| format!("Failed to load backend plugin {}", path.display()))?;
+                // The function must stay valid for the rest of the run
+                let library = Box::leak(Box::new(library));
+                // Safety: The signature is documented for plugin authors, but
+                // can't be checked
+                let factories_fn = unsafe { library.get::<StoreFactoriesFn>(ENTRY_POINT) }
+                    .with_context(|| {
+                        format!(
+                            "Backend plugin {} does not export {}",
+                            path.display(),
+                            String::from_utf8_lossy(ENTRY_POINT)
+                        )
+                    })?;
+                Ok(*factories_fn)
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self { factories_fns })
+    }
+
+    /// The jj default store factories, extended with the ones of the plugins
+    pub fn store_factories(&self) -> StoreFactories {
+        let mut store_factories = StoreFactories::default();
+        for factories_fn in &self.factories_fns {
+            store_factories.merge(factories_fn());
+        }
+        store_factories
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_plugin() {
+        let error = BackendPlugins::load(&["/nonexistent/plugin.so".into()])
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "Failed to load backend plugin /nonexistent/plugin.so"
+        );
+    }
+}
@@ -0,0 +1,111 @@
+//! Evaluation of revsets on a background thread, so that the window stays
+//! responsive while large repositories are read. Used for the View in
+//! [`crate::view_evaluation`] and the Select and Compared revsets in
+//! [`crate::select_evaluation`]. Starting an evaluation cancels the one in
+//! progress.
+
+use eframe::egui;
+use revset_explorer_core::ResolveError;
+use revset_explorer_core::jjgraph::JjGraph;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+
+use crate::i18n;
+
+/// Evaluates a request, giving nothing when cancelled
+pub type Evaluate<R, T> = fn(&JjGraph, R, &AtomicBool) -> Option<Result<T, ResolveError>>;
+
+pub struct BackgroundEvaluation<R, T> {
+    evaluate: Evaluate<R, T>,
+    /// Message ids of the error when the evaluation panics and of the
+    /// spinner label
+    failed_message: &'static str,
+    evaluating_message: &'static str,
+    // Dropped when the evaluation is done
+    receiver: Option<mpsc::Receiver<Result<T, ResolveError>>>,
+    cancelled: Arc<AtomicBool>,
+    /// [`Self::poll`] waits for the result, for --script and the tests
+    wait: bool,
+}
+
+impl<R: Send + 'static, T: Send + 'static> BackgroundEvaluation<R, T> {
+    pub fn new(
+        evaluate: Evaluate<R, T>,
+        failed_message: &'static str,
+        evaluating_message: &'static str,
+        wait: bool,
+    ) -> Self {
+        Self {
+            evaluate,
+            failed_message,
+            evaluating_message,
+            receiver: None,
+            cancelled: Arc::default(),
+            wait,
+        }
+    }
+
+    /// Starts the evaluation, cancelling the previous one. The result is
+    /// returned by [`Self::poll`].
+    pub fn start(&mut self, ctx: &egui::Context, jj_graph: &JjGraph, request: R) {
+        self.cancel();
+        let (sender, receiver) = mpsc::channel();
+        self.receiver = Some(receiver);
+        let evaluate = self.evaluate;
+        let jj_graph = jj_graph.clone();
+        let cancelled = self.cancelled.clone();
+        let ctx = ctx.clone();
+        // Not queued behind the diff searches and prefetches on the shared
+        // threads
+        std::thread::spawn(move || {
+            let Some(outcome) = evaluate(&jj_graph, request, &cancelled) else {
+                return;
+            };
+            if !cancelled.load(Ordering::Relaxed) && sender.send(outcome).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+    }
+
+    /// The result, once the evaluation is done
+    pub fn poll(&mut self) -> Option<Result<T, ResolveError>> {
+        let receiver = self.receiver.as_ref()?;
+        let received = if self.wait {
+            (receiver.recv()).map_err(|_| mpsc::TryRecvError::Disconnected)
+        } else {
+            receiver.try_recv()
+        };
+        match received {
+            Ok(outcome) => {
+                self.receiver = None;
+                Some(outcome)
+            }
+            Err(mpsc::TryRecvError::Empty) => None,
+            // The evaluation panicked
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.receiver = None;
+                let message = i18n::message(self.failed_message, None);
+                Some(Err(ResolveError::JjError(message)))
+            }
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// Stops the evaluation in progress. Its result is never received.
+    pub fn cancel(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.cancelled = Arc::new(AtomicBool::new(false));
+        self.receiver = None;
+    }
+
+    /// Shown above the graph while the evaluation is in progress
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(i18n::message(self.evaluating_message, None));
+        });
+    }
+}
@@ -0,0 +1,93 @@
+//! Labeled hulls drawn behind the commits of each bookmark cluster, see
+//! [`revset_explorer_core::clusters`]
+
+use eframe::egui::{self, Color32, Pos2, Shape, Stroke};
+
+/// Shapes for the clusters, given as the bookmark name and the screen centers
+/// and radii of its nodes
+pub fn hull_shapes(ui: &egui::Ui, clusters: &[(String, Vec<(Pos2, f32)>)]) -> Vec<Shape> {
+    let mut shapes = vec![];
+    for (name, nodes) in clusters {
+        // A margin around each node, approximated by an octagon
+        let points: Vec<_> = (nodes.iter())
+            .flat_map(|&(center, radius)| {
+                (0..8).map(move |i| {
+                    let angle = i as f32 * std::f32::consts::TAU / 8.;
+                    center + egui::Vec2::angled(angle) * radius * 2.5
+                })
+            })
+            .collect();
+        let hull = convex_hull(points);
+        let Some(top) = hull.iter().min_by(|a, b| a.y.total_cmp(&b.y)).copied() else {
+            continue;
+        };
+        let color = bookmark_color(name);
+        shapes.push(Shape::convex_polygon(
+            hull,
+            color.gamma_multiply(0.12),
+            Stroke::new(1., color.gamma_multiply(0.6)),
+        ));
+        shapes.push(ui.fonts_mut(|f| {
+            Shape::text(
+                f,
+                top,
+                egui::Align2::CENTER_BOTTOM,
+                name,
+                egui::TextStyle::Small.resolve(ui.style()),
+                color,
+            )
+        }));
+    }
+    shapes
+}
+
+fn bookmark_color(name: &str) -> Color32 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::hash::DefaultHasher::new();
+    name.hash(&mut hasher);
+    let hue = (hasher.finish() % 360) as f32 / 360.;
+    egui::ecolor::Hsva::new(hue, 0.6, 0.8, 1.).into()
+}
+
+/// Counter-clockwise in screen coordinates, without collinear points
+fn convex_hull(mut points: Vec<Pos2>) -> Vec<Pos2> {
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: Pos2, a: Pos2, b: Pos2| (a - o).x * (b - o).y - (a - o).y * (b - o).x;
+    // Andrew's monotone chain, lower and then upper half
+    let mut hull: Vec<Pos2> = vec![];
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let start = hull.len();
+        for point in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.
+            {
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        // The last point starts the other half
+        hull.pop();
+    }
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_convex_hull() {
+        let points = [(0., 0.), (2., 0.), (1., 1.), (2., 2.), (0., 2.), (1., 0.)]
+            .map(|(x, y)| egui::pos2(x, y));
+        let hull = convex_hull(points.to_vec());
+        assert_eq!(
+            hull,
+            [(0., 0.), (2., 0.), (2., 2.), (0., 2.)].map(|(x, y)| egui::pos2(x, y))
+        );
+        assert_eq!(convex_hull(vec![points[0]; 2]), [points[0]]);
+    }
+}
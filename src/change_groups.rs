@@ -0,0 +1,98 @@
+//! Labeled boxes drawn around the visible commits that share a change ID, to
+//! make divergent changes and the commits that could resolve them obvious
+
+use eframe::egui::{self, Pos2, Shape, Stroke};
+use jj_lib::backend::CommitId;
+use jj_lib::repo::Repo;
+use revset_explorer_core::JjGraph;
+use std::collections::HashMap;
+
+use crate::i18n::tr;
+
+/// The shortest unique change ID prefix of each of the commits that share
+/// their change ID with another of the commits
+pub fn divergent_changes<'a>(
+    jj_graph: &JjGraph,
+    commit_ids: impl IntoIterator<Item = &'a CommitId>,
+) -> anyhow::Result<HashMap<CommitId, String>> {
+    let repo = jj_graph.get_repo();
+    let mut changes = HashMap::<_, Vec<CommitId>>::new();
+    for commit_id in commit_ids {
+        let commit = repo.store().get_commit(commit_id)?;
+        (changes.entry(commit.change_id().clone()).or_default()).push(commit_id.clone());
+    }
+    let mut groups = HashMap::new();
+    for (change_id, commit_ids) in changes {
+        if commit_ids.len() < 2 {
+            continue;
+        }
+        let prefix_len = repo.shortest_unique_change_id_prefix_len(&change_id)?;
+        let prefix = change_id.to_string()[..prefix_len].to_owned();
+        groups.extend(commit_ids.into_iter().map(|id| (id, prefix.clone())));
+    }
+    Ok(groups)
+}
+
+/// Shapes for the groups, given as the change ID prefix and the screen centers
+/// and radii of its nodes
+pub fn group_box_shapes(ui: &egui::Ui, groups: &[(String, Vec<(Pos2, f32)>)]) -> Vec<Shape> {
+    let color = ui.visuals().warn_fg_color;
+    let mut shapes = vec![];
+    for (change_id, nodes) in groups {
+        let margin = nodes.iter().map(|&(_, radius)| radius).fold(0., f32::max) * 2.5;
+        let centers: Vec<_> = nodes.iter().map(|&(center, _)| center).collect();
+        let rect = egui::Rect::from_points(&centers).expand(margin);
+        let corner_radius = (margin / 2.).min(255.) as u8;
+        shapes.push(Shape::rect_filled(
+            rect,
+            corner_radius,
+            color.gamma_multiply(0.08),
+        ));
+        shapes.push(Shape::rect_stroke(
+            rect,
+            corner_radius,
+            Stroke::new(1.5, color.gamma_multiply(0.8)),
+            egui::StrokeKind::Outside,
+        ));
+        let label = tr!(
+            "change-group",
+            change_id = change_id.as_str(),
+            count = nodes.len()
+        );
+        shapes.push(ui.fonts_mut(|f| {
+            Shape::text(
+                f,
+                rect.left_top(),
+                egui::Align2::LEFT_BOTTOM,
+                label,
+                egui::TextStyle::Small.resolve(ui.style()),
+                color,
+            )
+        }));
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn groups_commits_by_change_id() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let b2 = repo.diverge(&b, "b, rewritten elsewhere");
+        let groups = divergent_changes(&repo.jj_graph(), [&a, &b, &b2]).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&b], groups[&b2]);
+        assert!(!groups.contains_key(&a));
+        // Not divergent within the commits
+        assert!(
+            divergent_changes(&repo.jj_graph(), [&a, &b])
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
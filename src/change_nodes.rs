@@ -0,0 +1,104 @@
+//! Change-centric graph, where the commits that share a change ID, like the
+//! commits of a divergent change, are drawn as one node. Each change can be
+//! expanded to show its commits again.
+
+use jj_lib::backend::{ChangeId, CommitId};
+use petgraph::Direction;
+use petgraph::visit::EdgeRef;
+use revset_explorer_core::CommitGraph;
+use std::collections::{HashMap, HashSet};
+
+/// Merges the nodes of each change that is not expanded into its first node,
+/// moving the edges of the others to it. The label of the remaining node gets
+/// the number of commits, like "×2". Returns the change ID and number of
+/// commits of the merged nodes, by the commit of the remaining node.
+pub fn collapse(
+    graph: &mut CommitGraph,
+    change_of: impl Fn(&CommitId) -> Option<ChangeId>,
+    expanded: &HashSet<ChangeId>,
+) -> HashMap<CommitId, (ChangeId, usize)> {
+    let mut changes = HashMap::<ChangeId, Vec<_>>::new();
+    for node_idx in graph.node_indices() {
+        if let Some(change_id) = change_of(&graph[node_idx].commit_id) {
+            changes.entry(change_id).or_default().push(node_idx);
+        }
+    }
+    let mut collapsed = HashMap::new();
+    for (change_id, node_idxs) in changes {
+        if node_idxs.len() < 2 || expanded.contains(&change_id) {
+            continue;
+        }
+        let kept = node_idxs[0];
+        for &node_idx in &node_idxs[1..] {
+            let mut moved = vec![];
+            for direction in [Direction::Outgoing, Direction::Incoming] {
+                for edge in graph.edges_directed(node_idx, direction) {
+                    let (source, target) = match direction {
+                        Direction::Outgoing => (kept, edge.target()),
+                        Direction::Incoming => (edge.source(), kept),
+                    };
+                    moved.push((source, target, *edge.weight()));
+                }
+            }
+            graph.remove_node(node_idx);
+            for (source, target, edge_type) in moved {
+                // Edges between the commits of the change disappear
+                if node_idxs.contains(&source) && node_idxs.contains(&target)
+                    || source == target
+                    || graph.contains_edge(source, target)
+                {
+                    continue;
+                }
+                graph.add_edge(source, target, edge_type);
+            }
+        }
+        graph[kept].label += &format!(" ×{}", node_idxs.len());
+        collapsed.insert(graph[kept].commit_id.clone(), (change_id, node_idxs.len()));
+    }
+    collapsed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jj_lib::graph::GraphEdgeType;
+    use jj_lib::object_id::ObjectId;
+    use revset_explorer_core::graph::CommitNode;
+
+    #[test]
+    fn merges_commits_of_a_change() {
+        let commit = |byte| CommitId::new(vec![byte; 20]);
+        let mut graph = CommitGraph::default();
+        let [child, a, b, parent] = [1, 2, 3, 4].map(|byte| {
+            graph.add_node(CommitNode {
+                commit_id: commit(byte),
+                label: byte.to_string(),
+            })
+        });
+        // a and b are divergent commits of one change, both on parent
+        for (source, target) in [(child, a), (a, parent), (b, parent)] {
+            graph.add_edge(source, target, GraphEdgeType::Direct);
+        }
+        let change_of = |commit_id: &CommitId| {
+            let byte = if [commit(2), commit(3)].contains(commit_id) {
+                0xcc
+            } else {
+                commit_id.as_bytes()[0]
+            };
+            Some(ChangeId::new(vec![byte; 16]))
+        };
+
+        let mut expanded_graph = graph.clone();
+        let change = ChangeId::new(vec![0xcc; 16]);
+        let expanded = HashSet::from([change.clone()]);
+        assert!(collapse(&mut expanded_graph, change_of, &expanded).is_empty());
+        assert_eq!(expanded_graph.node_count(), 4);
+
+        let collapsed = collapse(&mut graph, change_of, &HashSet::new());
+        assert_eq!(collapsed, HashMap::from([(commit(2), (change, 2))]));
+        assert_eq!(graph.node_count(), 3);
+        assert_eq!(graph.edge_count(), 2);
+        assert!(graph.contains_edge(child, a) && graph.contains_edge(a, parent));
+        assert_eq!(graph[a].label, "2 ×2");
+    }
+}
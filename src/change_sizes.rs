@@ -0,0 +1,77 @@
+//! Number of files and lines changed by commits, for sizing the nodes. Computed
+//! on the [`crate::workers`] threads, as the whole graph may need them.
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use jj_lib::repo::ReadonlyRepo;
+use revset_explorer_core::diff::diff_stat;
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
+
+use crate::workers::{self, Subsystem};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChangeSize {
+    pub files: usize,
+    /// Inserted and deleted lines
+    pub lines: usize,
+}
+
+pub struct ChangeSizes {
+    // None while being computed. Commits never change, so they are kept.
+    sizes: HashMap<CommitId, Option<ChangeSize>>,
+    sender: mpsc::Sender<(CommitId, ChangeSize)>,
+    receiver: mpsc::Receiver<(CommitId, ChangeSize)>,
+}
+
+impl ChangeSizes {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            sizes: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Returns the size of the commit, or None until it has been computed
+    pub fn get(
+        &mut self,
+        ctx: &egui::Context,
+        repo: &Arc<ReadonlyRepo>,
+        commit_id: &CommitId,
+    ) -> Option<ChangeSize> {
+        if let Some(size) = self.sizes.get(commit_id) {
+            return *size;
+        }
+        self.sizes.insert(commit_id.clone(), None);
+        let repo = repo.clone();
+        let commit_id = commit_id.clone();
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        workers::spawn(Subsystem::Diffs, move || {
+            // Commits that cannot be diffed get the default size
+            let stats = diff_stat(repo.as_ref(), &commit_id).unwrap_or_default();
+            let size = ChangeSize {
+                files: stats.len(),
+                lines: (stats.iter())
+                    .map(|stat| stat.insertions + stat.deletions)
+                    .sum(),
+            };
+            if sender.send((commit_id, size)).is_ok() {
+                ctx.request_repaint();
+            }
+        });
+        None
+    }
+
+    /// Receives computed sizes. Returns true if any size was computed.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((commit_id, size)) = self.receiver.try_recv() {
+            self.sizes.insert(commit_id, Some(size));
+            changed = true;
+        }
+        changed
+    }
+}
@@ -0,0 +1,152 @@
+//! CI status of commits, fetched from the forge in the background and cached
+//! on disk
+
+use eframe::egui;
+use etcetera::BaseStrategy;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::mpsc;
+
+use crate::forge::{CiStatus, ForgeApi};
+use crate::i18n::tr;
+
+pub struct CiStatuses {
+    api: ForgeApi,
+    token: Option<String>,
+    statuses: HashMap<CommitId, CiStatus>,
+    requested: HashSet<CommitId>,
+    sender: mpsc::Sender<(CommitId, Result<Option<CiStatus>, String>)>,
+    receiver: mpsc::Receiver<(CommitId, Result<Option<CiStatus>, String>)>,
+    // Final statuses by commit hash
+    disk_cache: HashMap<String, CiStatus>,
+}
+
+impl CiStatuses {
+    pub fn new(api: ForgeApi, token: Option<String>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let disk_cache = std::fs::read_to_string(cache_path())
+            .ok()
+            .and_then(|s| toml::from_str(&s).ok())
+            .unwrap_or_default();
+        Self {
+            api,
+            token,
+            statuses: HashMap::new(),
+            requested: HashSet::new(),
+            sender,
+            receiver,
+            disk_cache,
+        }
+    }
+
+    pub fn get(&self, commit_id: &CommitId) -> Option<CiStatus> {
+        self.statuses.get(commit_id).copied()
+    }
+
+    /// Commits known to have passed CI
+    pub fn successful(&self) -> impl Iterator<Item = &CommitId> {
+        self.statuses
+            .iter()
+            .filter(|(_, status)| **status == CiStatus::Success)
+            .map(|(commit_id, _)| commit_id)
+    }
+
+    /// Starts fetching statuses for commits that have not been requested yet
+    pub fn request<'a>(
+        &mut self,
+        commit_ids: impl Iterator<Item = &'a CommitId>,
+        ctx: &egui::Context,
+    ) {
+        let mut to_fetch = vec![];
+        for commit_id in commit_ids {
+            if !self.requested.insert(commit_id.clone()) {
+                continue;
+            }
+            if let Some(status) = self.disk_cache.get(&commit_id.hex()) {
+                self.statuses.insert(commit_id.clone(), *status);
+            } else {
+                to_fetch.push(commit_id.clone());
+            }
+        }
+        if to_fetch.is_empty() {
+            return;
+        }
+        let api = self.api.clone();
+        let token = self.token.clone();
+        let sender = self.sender.clone();
+        let ctx = ctx.clone();
+        std::thread::spawn(move || {
+            for commit_id in to_fetch {
+                let result = fetch_status(&api, token.as_deref(), &commit_id);
+                let failed = result.is_err();
+                if sender.send((commit_id, result)).is_err() {
+                    return;
+                }
+                ctx.request_repaint();
+                if failed {
+                    // Most likely rate limiting or a bad token. Don't hammer the API.
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Receives fetched statuses. Returns true if any status changed and the
+    /// first error, if any.
+    pub fn poll(&mut self) -> (bool, Option<String>) {
+        let mut changed = false;
+        let mut error = None;
+        let mut cache_changed = false;
+        while let Ok((commit_id, result)) = self.receiver.try_recv() {
+            match result {
+                Ok(Some(status)) => {
+                    if status.is_final() {
+                        self.disk_cache.insert(commit_id.hex(), status);
+                        cache_changed = true;
+                    }
+                    self.statuses.insert(commit_id, status);
+                    changed = true;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    // Allow retrying on the next request
+                    self.requested.remove(&commit_id);
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        if cache_changed {
+            self.save_disk_cache();
+        }
+        (changed, error)
+    }
+
+    fn save_disk_cache(&self) {
+        let path = cache_path();
+        if let Some(dir) = path.parent() {
+            let _ = std::fs::create_dir_all(dir);
+        }
+        if let Ok(s) = toml::to_string(&self.disk_cache) {
+            let _ = std::fs::write(path, s);
+        }
+    }
+}
+
+fn fetch_status(
+    api: &ForgeApi,
+    token: Option<&str>,
+    commit_id: &CommitId,
+) -> Result<Option<CiStatus>, String> {
+    let response = api
+        .get(&api.status_url(&commit_id.hex()), token)
+        .map_err(|e| tr!("ci-fetch-failed", error = e.to_string()))?;
+    Ok(api.parse_status(&response))
+}
+
+fn cache_path() -> PathBuf {
+    let mut path = etcetera::choose_base_strategy().unwrap().cache_dir();
+    path.push("revset-explorer/ci-status.toml");
+    path
+}
@@ -0,0 +1,128 @@
+//! User-defined styling of the commits matching revsets, on top of the colors
+//! of the palette. Set in the settings as a list of rules:
+//!
+//! ```toml
+//! [[color_rules]]
+//! revset = "conflicts()"
+//! border = "#ff4136"
+//!
+//! [[color_rules]]
+//! revset = "mine()"
+//! bold = true
+//! ```
+
+use eframe::egui::Color32;
+use jj_lib::backend::CommitId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+use revset_explorer_core::jjgraph::JjGraph;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ColorRule {
+    pub revset: String,
+    /// Fill color of the nodes, like "#ff4136"
+    #[serde(
+        default,
+        deserialize_with = "hex_color",
+        serialize_with = "to_hex_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub color: Option<Color32>,
+    /// Color of a thick border around the nodes
+    #[serde(
+        default,
+        deserialize_with = "hex_color",
+        serialize_with = "to_hex_color",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub border: Option<Color32>,
+    /// Draw the labels in bold
+    #[serde(default)]
+    pub bold: bool,
+}
+
+/// The styling of a commit from the rules it matches
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RuleStyle {
+    pub color: Option<Color32>,
+    pub border: Option<Color32>,
+    pub bold: bool,
+}
+
+/// Evaluates the rules within the view. The rules are evaluated in order, and
+/// each part of the styling comes from the first matching rule that sets it.
+/// Returns the styles and the rules that failed, with their errors.
+pub fn evaluate(
+    jj_graph: &JjGraph,
+    view: &str,
+    rules: &[ColorRule],
+) -> (HashMap<CommitId, RuleStyle>, Vec<(String, String)>) {
+    let mut styles = HashMap::<CommitId, RuleStyle>::new();
+    let mut errors = vec![];
+    let view = view.trim();
+    for rule in rules {
+        let revset = match jj_graph.get_revset(&format!("({view}) & ({})", rule.revset.trim())) {
+            Ok(revset) => revset,
+            Err(e) => {
+                errors.push((rule.revset.clone(), e.to_string()));
+                continue;
+            }
+        };
+        for commit_id in revset.iter().filter_map(Result::ok) {
+            let style = styles.entry(commit_id).or_default();
+            style.color = style.color.or(rule.color);
+            style.border = style.border.or(rule.border);
+            style.bold |= rule.bold;
+        }
+    }
+    (styles, errors)
+}
+
+fn hex_color<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Color32>, D::Error> {
+    let hex = String::deserialize(deserializer)?;
+    Color32::from_hex(&hex)
+        .map(Some)
+        .map_err(|_| serde::de::Error::custom(format!("invalid color \"{hex}\", use \"#rrggbb\"")))
+}
+
+fn to_hex_color<S: Serializer>(color: &Option<Color32>, serializer: S) -> Result<S::Ok, S::Error> {
+    match color {
+        Some(color) => serializer.serialize_str(&crate::export::hex_color(*color)),
+        None => serializer.serialize_none(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let rules: Vec<ColorRule> = toml::from_str::<HashMap<String, Vec<ColorRule>>>(
+            r##"
+            rules = [
+                { revset = "description(a)", color = "#ff0000" },
+                { revset = "all()", color = "#00ff00", bold = true },
+                { revset = "nonexistent_function()" },
+            ]
+            "##,
+        )
+        .unwrap()
+        .remove("rules")
+        .unwrap();
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let (styles, errors) = evaluate(&repo.jj_graph(), &format!("{a}::"), &rules);
+        assert_eq!(styles[&a].color, Some(Color32::RED));
+        assert_eq!(styles[&b].color, Some(Color32::GREEN));
+        assert!(styles[&a].bold && styles[&b].border.is_none());
+        assert_eq!(errors.len(), 1);
+
+        let invalid = toml::from_str::<ColorRule>("revset = \"@\"\ncolor = \"red\"");
+        assert!(invalid.unwrap_err().to_string().contains("invalid color"));
+    }
+}
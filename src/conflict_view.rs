@@ -0,0 +1,87 @@
+//! Conflicted paths of the selected commit, with a preview of the conflict
+//! markers of a chosen file, for triaging `conflicts()` in the details panel
+
+use eframe::egui::{self, RichText};
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use revset_explorer_core::conflicts::{conflict_markers, conflicted_paths};
+
+use crate::i18n::tr;
+
+pub struct ConflictView {
+    // The commit that the paths are for
+    commit_id: Option<CommitId>,
+    paths: Result<Vec<String>, String>,
+    // Path and its contents with conflict markers
+    chosen: Option<(String, Result<Option<String>, String>)>,
+}
+
+impl Default for ConflictView {
+    fn default() -> Self {
+        Self {
+            commit_id: None,
+            paths: Ok(vec![]),
+            chosen: None,
+        }
+    }
+}
+
+impl ConflictView {
+    /// Shows the conflicts of a commit with a conflicted tree
+    pub fn ui(&mut self, ui: &mut egui::Ui, repo: &dyn Repo, commit: &Commit) {
+        if self.commit_id.as_ref() != Some(commit.id()) {
+            self.commit_id = Some(commit.id().clone());
+            self.paths = conflicted_paths(repo, commit.id()).map_err(|e| e.to_string());
+            self.chosen = None;
+        }
+        let paths = match &self.paths {
+            Ok(paths) => paths,
+            Err(e) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    tr!("conflicts-failed", error = e.as_str()),
+                );
+                return;
+            }
+        };
+        ui.strong(tr!("conflicts", count = paths.len()));
+        let chosen_path = self.chosen.as_ref().map(|(path, _)| path);
+        let mut clicked = None;
+        for path in paths {
+            let label = RichText::new(path).monospace();
+            if ui
+                .selectable_label(chosen_path == Some(path), label)
+                .clicked()
+            {
+                clicked = Some(path.clone());
+            }
+        }
+        if let Some(path) = clicked {
+            let markers = conflict_markers(repo, commit.id(), &path).map_err(|e| e.to_string());
+            self.chosen = Some((path, markers));
+        }
+        match &self.chosen {
+            None => {
+                ui.weak(tr!("conflicts-choose"));
+            }
+            Some((_, Ok(Some(markers)))) => {
+                egui::ScrollArea::both()
+                    .id_salt("conflict_markers")
+                    .max_height(300.)
+                    .show(ui, |ui| {
+                        ui.add(egui::Label::new(RichText::new(markers).monospace()).extend())
+                    });
+            }
+            Some((_, Ok(None))) => {
+                ui.weak(tr!("conflicts-not-file"));
+            }
+            Some((_, Err(e))) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    tr!("conflicts-failed", error = e.as_str()),
+                );
+            }
+        }
+    }
+}
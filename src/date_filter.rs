@@ -0,0 +1,279 @@
+//! Timeline of the committer dates of the commits in the view. Narrowing it
+//! dims the commits outside of it, and the range can be added to the Select
+//! revset as `committer_date()` terms. A histogram below the status bar shows
+//! how the dates are spread, and dragging over it narrows the range too.
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use revset_explorer_core::resolve::string_literal;
+use std::collections::HashMap;
+
+use crate::i18n::tr;
+
+/// Number of bars in the histogram
+const BUCKETS: usize = 60;
+const HISTOGRAM_HEIGHT: f32 = 28.;
+
+/// What the user did with the timeline
+#[derive(Debug, PartialEq, Eq)]
+pub enum DateAction {
+    /// The range was changed, so the dimming must be updated
+    Changed,
+    /// Add the range to the Select revset
+    AddToSelect(String),
+}
+
+#[derive(Default)]
+pub struct DateFilter {
+    // Committer times of the commits in the view, in seconds since the epoch
+    times: HashMap<CommitId, i64>,
+    // Oldest and newest time in the view
+    bounds: (i64, i64),
+    // Chosen window, including both ends
+    range: (i64, i64),
+    // Bucket where the drag over the histogram started
+    brush: Option<usize>,
+}
+
+impl DateFilter {
+    /// Spans the timeline over the times and selects all of it
+    pub fn reset(&mut self, times: HashMap<CommitId, i64>) {
+        let oldest = times.values().min().copied().unwrap_or_default();
+        let newest = times.values().max().copied().unwrap_or_default();
+        self.times = times;
+        self.bounds = (oldest, newest);
+        self.range = self.bounds;
+        self.brush = None;
+    }
+
+    /// Committer time of the commit in the view, in seconds since the epoch
+    pub fn time(&self, commit_id: &CommitId) -> Option<i64> {
+        self.times.get(commit_id).copied()
+    }
+
+    /// Whether the commits have different times, so there is a timeline
+    pub fn spans_time(&self) -> bool {
+        self.bounds.0 != self.bounds.1
+    }
+
+    fn is_narrowed(&self) -> bool {
+        self.range != self.bounds
+    }
+
+    /// Whether the commit is outside the chosen window
+    pub fn dims(&self, commit_id: &CommitId) -> bool {
+        self.is_narrowed()
+            && (self.times.get(commit_id))
+                .is_some_and(|time| *time < self.range.0 || *time > self.range.1)
+    }
+
+    /// Shows the timeline, unless all commits have the same time
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<DateAction> {
+        let (oldest, newest) = self.bounds;
+        if !self.spans_time() {
+            return None;
+        }
+        ui.label(tr!("date-filter"));
+        let slider = |value| {
+            egui::Slider::new(value, oldest..=newest)
+                .custom_formatter(|time, _| format_date(time as i64))
+                .custom_parser(|text| parse_date(text).map(|time| time as f64))
+        };
+        let (from, to) = &mut self.range;
+        let from_changed = ui.add(slider(from).text(tr!("date-filter-from"))).changed();
+        let to_changed = ui.add(slider(to).text(tr!("date-filter-to"))).changed();
+        // Dragging one end past the other pushes it along
+        if from_changed {
+            *to = (*to).max(*from);
+        } else if to_changed {
+            *from = (*from).min(*to);
+        }
+        let mut action = (from_changed || to_changed).then_some(DateAction::Changed);
+        let narrowed = self.is_narrowed();
+        if ui
+            .add_enabled(narrowed, egui::Button::new(tr!("date-filter-reset")))
+            .clicked()
+        {
+            self.range = self.bounds;
+            action = Some(DateAction::Changed);
+        }
+        if ui
+            .add_enabled(narrowed, egui::Button::new(tr!("date-filter-apply")))
+            .on_hover_text(tr!("date-filter-apply-hover"))
+            .clicked()
+        {
+            action = Some(DateAction::AddToSelect(committer_dates(self.range)));
+        }
+        action
+    }
+
+    /// The bucket of the histogram that the time falls in
+    fn bucket_of(&self, time: i64) -> usize {
+        let (oldest, newest) = self.bounds;
+        let span = i128::from(newest - oldest) + 1;
+        let bucket = i128::from(time - oldest) * BUCKETS as i128 / span;
+        bucket.clamp(0, BUCKETS as i128 - 1) as usize
+    }
+
+    /// The times covered by the buckets, including both ends
+    fn bucket_range(&self, first: usize, last: usize) -> (i64, i64) {
+        let (oldest, newest) = self.bounds;
+        let span = i128::from(newest - oldest) + 1;
+        let start = |bucket: usize| oldest + (span * bucket as i128 / BUCKETS as i128) as i64;
+        (start(first), start(last + 1) - 1)
+    }
+
+    /// Number of commits in each bucket
+    fn bucket_counts(&self) -> [usize; BUCKETS] {
+        let mut counts = [0; BUCKETS];
+        for time in self.times.values() {
+            counts[self.bucket_of(*time)] += 1;
+        }
+        counts
+    }
+
+    /// Shows the spread of the dates, unless all commits have the same time.
+    /// Dragging over the bars chooses their range and clicking chooses all.
+    pub fn histogram(&mut self, ui: &mut egui::Ui) -> Option<DateAction> {
+        if !self.spans_time() {
+            return None;
+        }
+        let size = egui::vec2(ui.available_width(), HISTOGRAM_HEIGHT);
+        let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click_and_drag());
+        let bucket_at = |pos: egui::Pos2| {
+            let x = (pos.x - rect.left()) / rect.width();
+            ((x * BUCKETS as f32) as usize).min(BUCKETS - 1)
+        };
+        let old_range = self.range;
+        if response.drag_started() {
+            self.brush = response.interact_pointer_pos().map(bucket_at);
+        }
+        if let (Some(start), Some(pos)) = (self.brush, response.interact_pointer_pos())
+            && response.dragged()
+        {
+            let end = bucket_at(pos);
+            self.range = self.bucket_range(start.min(end), start.max(end));
+        }
+        if response.drag_stopped() {
+            self.brush = None;
+        }
+        if response.clicked() {
+            self.range = self.bounds;
+        }
+
+        let counts = self.bucket_counts();
+        let max = counts.iter().max().copied().unwrap_or_default().max(1);
+        let bar_width = rect.width() / BUCKETS as f32;
+        let visuals = ui.visuals();
+        let painter = ui.painter_at(rect);
+        for (bucket, count) in counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let (from, to) = self.bucket_range(bucket, bucket);
+            let in_range = to >= self.range.0 && from <= self.range.1;
+            let color = if in_range {
+                visuals.selection.bg_fill
+            } else {
+                visuals.widgets.inactive.bg_fill
+            };
+            let height = (rect.height() * *count as f32 / max as f32).max(1.);
+            let left = rect.left() + bar_width * bucket as f32;
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(left + 0.5, rect.bottom() - height),
+                egui::pos2(left + bar_width - 0.5, rect.bottom()),
+            );
+            painter.rect_filled(bar, 0., color);
+        }
+        let response = match response.hover_pos() {
+            Some(pos) => {
+                let bucket = bucket_at(pos);
+                let (from, to) = self.bucket_range(bucket, bucket);
+                response.on_hover_text(tr!(
+                    "date-histogram-hover",
+                    from = format_date(from),
+                    to = format_date(to),
+                    count = counts[bucket]
+                ))
+            }
+            None => response,
+        };
+        response.widget_info(|| {
+            egui::WidgetInfo::labeled(egui::WidgetType::Other, true, tr!("date-histogram"))
+        });
+        (self.range != old_range).then_some(DateAction::Changed)
+    }
+}
+
+/// In the local time zone, which jj assumes for dates in revsets
+fn format_date(time: i64) -> String {
+    chrono::DateTime::from_timestamp(time, 0)
+        .map(|t| {
+            t.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_date(text: &str) -> Option<i64> {
+    let time = chrono::NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M:%S").ok()?;
+    let time = time.and_local_timezone(chrono::Local).earliest()?;
+    Some(time.timestamp())
+}
+
+/// `committer_date()` terms for the commits in the range, including both ends
+fn committer_dates((from, to): (i64, i64)) -> String {
+    format!(
+        "committer_date(after:{}) & committer_date(before:{})",
+        string_literal(&format_date(from)),
+        string_literal(&format_date(to + 1))
+    )
+}
+
+/// Restricts the revset to the commits in the date range
+pub fn with_dates(revset: &str, dates: &str) -> String {
+    match revset.trim() {
+        "" => dates.to_owned(),
+        revset => format!("({revset}) & {dates}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_local_dates() {
+        let time = 1_700_000_000;
+        assert_eq!(parse_date(&format_date(time)), Some(time));
+        assert_eq!(parse_date("yesterday"), None);
+        let dates = committer_dates((time, time + 10));
+        assert_eq!(
+            dates,
+            format!(
+                r#"committer_date(after:"{}") & committer_date(before:"{}")"#,
+                format_date(time),
+                format_date(time + 11)
+            )
+        );
+        assert_eq!(with_dates(" ", &dates), dates);
+        assert_eq!(with_dates("a | b", &dates), format!("(a | b) & {dates}"));
+    }
+
+    #[test]
+    fn buckets_cover_the_timeline() {
+        let mut filter = DateFilter::default();
+        let id = |byte| CommitId::new(vec![byte; 20]);
+        filter.reset(HashMap::from([(id(1), 1000), (id(2), 1001), (id(3), 1599)]));
+        assert_eq!(filter.bucket_of(1000), 0);
+        assert_eq!(filter.bucket_of(1599), BUCKETS - 1);
+        assert_eq!(filter.bucket_range(0, BUCKETS - 1), (1000, 1599));
+        // Neighboring buckets meet without gaps
+        assert_eq!(filter.bucket_range(0, 0), (1000, 1009));
+        assert_eq!(filter.bucket_range(1, 1), (1010, 1019));
+        let counts = filter.bucket_counts();
+        assert_eq!((counts[0], counts[BUCKETS - 1]), (2, 1));
+        assert_eq!(counts.iter().sum::<usize>(), 3);
+    }
+}
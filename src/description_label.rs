@@ -0,0 +1,73 @@
+//! Shortened descriptions after the change IDs in the node labels, as set in
+//! the settings. Cutting at a word boundary keeps prefixes like `feat(ui):`
+//! whole, where a fixed number of characters would split them.
+
+use serde::{Deserialize, Serialize};
+
+/// Where long descriptions are cut
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Truncation {
+    /// After the last whole word that fits, or like `Characters` if the first
+    /// word is too long
+    #[default]
+    Words,
+    Characters,
+}
+
+/// The first line of the description, cut to at most `max_chars` characters
+/// followed by `ellipsis`
+pub fn truncate(
+    description: &str,
+    max_chars: usize,
+    truncation: Truncation,
+    ellipsis: &str,
+) -> String {
+    let line = description.lines().next().unwrap_or_default().trim();
+    if line.chars().count() <= max_chars {
+        return line.to_owned();
+    }
+    let cut = line
+        .char_indices()
+        .nth(max_chars)
+        .map_or(line.len(), |(pos, _)| pos);
+    let mut shortened = &line[..cut];
+    if truncation == Truncation::Words
+        && !line[cut..].starts_with(char::is_whitespace)
+        && let Some(space) = shortened.rfind(char::is_whitespace)
+    {
+        shortened = &shortened[..space];
+    }
+    format!("{}{ellipsis}", shortened.trim_end())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cuts_at_words_or_characters() {
+        let description = "feat(parser): accept trailing commas\n\nDetails";
+        assert_eq!(
+            truncate(description, 100, Truncation::Words, "…"),
+            "feat(parser): accept trailing commas"
+        );
+        assert_eq!(
+            truncate(description, 24, Truncation::Words, "…"),
+            "feat(parser): accept…"
+        );
+        assert_eq!(
+            truncate(description, 20, Truncation::Words, "…"),
+            "feat(parser): accept…"
+        );
+        assert_eq!(
+            truncate(description, 10, Truncation::Words, "..."),
+            "feat(parse..."
+        );
+        assert_eq!(
+            truncate(description, 16, Truncation::Characters, "…"),
+            "feat(parser): ac…"
+        );
+        assert_eq!(truncate("", 10, Truncation::Words, "…"), "");
+    }
+}
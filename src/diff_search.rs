@@ -0,0 +1,163 @@
+//! Search for text in the diffs of the commits in the view, with the
+//! `diff_contains()` revset function. Reading the contents of every commit is
+//! slow, so the commits are checked one at a time on a [`crate::workers`]
+//! thread, which reports the progress.
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use revset_explorer_core::jjgraph::JjGraph;
+use revset_explorer_core::resolve::string_literal;
+use std::collections::HashSet;
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+use crate::workers::{self, Subsystem};
+
+type Checked = Result<(CommitId, bool), String>;
+
+#[derive(Default)]
+pub struct DiffSearch {
+    text: String,
+    search: Option<Search>,
+}
+
+struct Search {
+    /// `(<view>) & diff_contains("<text>")`
+    revset: String,
+    total: usize,
+    checked: usize,
+    matches: HashSet<CommitId>,
+    error: Option<String>,
+    // Dropping it stops the search
+    receiver: mpsc::Receiver<Checked>,
+}
+
+impl DiffSearch {
+    /// Stops the search and forgets the matches
+    pub fn clear(&mut self) {
+        self.search = None;
+    }
+
+    /// Commits found so far, or None without a search
+    pub fn matches(&self) -> Option<&HashSet<CommitId>> {
+        self.search.as_ref().map(|search| &search.matches)
+    }
+
+    /// Receives checked commits. Returns true if any commit matched.
+    pub fn poll(&mut self) -> bool {
+        let Some(search) = &mut self.search else {
+            return false;
+        };
+        let mut changed = false;
+        while let Ok(checked) = search.receiver.try_recv() {
+            search.checked += 1;
+            match checked {
+                Ok((commit_id, true)) => changed |= search.matches.insert(commit_id),
+                Ok((_, false)) => {}
+                Err(e) => search.error = Some(e),
+            }
+        }
+        changed
+    }
+
+    /// Shows the input and the progress. Entering text searches the commits
+    /// from `commit_ids`, which are in the view. Returns true if the search
+    /// was started or cleared.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        jj_graph: &JjGraph,
+        view: &str,
+        commit_ids: impl FnOnce() -> Vec<CommitId>,
+    ) -> bool {
+        let label = ui.label(tr!("diff-search"));
+        let response = ui
+            .add(
+                egui::TextEdit::singleline(&mut self.text)
+                    .desired_width(150.)
+                    .hint_text(tr!("diff-search-hint")),
+            )
+            .labelled_by(label.id);
+        let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        if entered && !self.text.is_empty() {
+            self.search = Some(Search::start(
+                ui.ctx(),
+                jj_graph,
+                view,
+                &self.text,
+                commit_ids(),
+            ));
+            return true;
+        }
+        let Some(search) = &self.search else {
+            return false;
+        };
+        let running = search.checked < search.total && search.error.is_none();
+        if running {
+            ui.spinner();
+        }
+        let status = match &search.error {
+            Some(e) => ui.colored_label(ui.visuals().error_fg_color, e),
+            None if running => ui.label(tr!(
+                "diff-search-progress",
+                checked = search.checked,
+                total = search.total,
+                matches = search.matches.len()
+            )),
+            None => ui.label(tr!("diff-search-done", matches = search.matches.len())),
+        };
+        status.on_hover_text(&search.revset);
+        let stop = if running {
+            tr!("diff-search-cancel")
+        } else {
+            tr!("diff-search-clear")
+        };
+        if ui.button(stop).clicked() {
+            self.clear();
+            return true;
+        }
+        false
+    }
+}
+
+impl Search {
+    fn start(
+        ctx: &egui::Context,
+        jj_graph: &JjGraph,
+        view: &str,
+        text: &str,
+        commit_ids: Vec<CommitId>,
+    ) -> Self {
+        let diff_contains = format!("diff_contains({})", string_literal(text));
+        let (sender, receiver) = mpsc::channel();
+        let search = Self {
+            revset: format!("({}) & {diff_contains}", view.trim()),
+            total: commit_ids.len(),
+            checked: 0,
+            matches: HashSet::new(),
+            error: None,
+            receiver,
+        };
+        let jj_graph = jj_graph.clone();
+        let ctx = ctx.clone();
+        workers::spawn(Subsystem::Evaluation, move || {
+            for commit_id in commit_ids {
+                let revset = format!("{} & {diff_contains}", commit_id.hex());
+                let checked = match jj_graph.get_revset(&revset) {
+                    Ok(revset) => match revset.iter().next().transpose() {
+                        Ok(found) => Ok((commit_id, found.is_some())),
+                        Err(e) => Err(e.to_string()),
+                    },
+                    Err(e) => Err(e.to_string()),
+                };
+                let failed = checked.is_err();
+                if sender.send(checked).is_err() || failed {
+                    break;
+                }
+                ctx.request_repaint();
+            }
+        });
+        search
+    }
+}
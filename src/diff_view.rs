@@ -0,0 +1,105 @@
+//! Unified diffs of the files changed by a commit, with syntax highlighting
+
+use eframe::egui::{self, RichText, ecolor::Color32, text::LayoutJob};
+use egui_extras::syntax_highlighting::{CodeTheme, highlight};
+use revset_explorer_core::diff::{FileDiff, FileStat, Hunk, LineKind};
+use std::hash::Hash;
+
+use crate::i18n::tr;
+
+pub const INSERTION_COLOR: Color32 = Color32::from_rgb(0x2e, 0xcc, 0x40);
+pub const DELETION_COLOR: Color32 = Color32::from_rgb(0xff, 0x41, 0x36);
+
+/// Shows the path and the number of changed lines
+pub fn stat(ui: &mut egui::Ui, stat: &FileStat) {
+    ui.label(RichText::new(&stat.path).monospace());
+    if stat.binary {
+        ui.weak(tr!("diff-binary"));
+    } else {
+        ui.colored_label(INSERTION_COLOR, format!("+{}", stat.insertions));
+        ui.colored_label(DELETION_COLOR, format!("-{}", stat.deletions));
+    }
+}
+
+/// Shows the changes of a file below a header that collapses them
+pub fn file_diff(ui: &mut egui::Ui, id_salt: impl Hash, diff: &FileDiff) {
+    if diff.stat.binary {
+        ui.horizontal(|ui| stat(ui, &diff.stat));
+        return;
+    }
+    let id = ui.make_persistent_id(id_salt);
+    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), id, true)
+        .show_header(ui, |ui| stat(ui, &diff.stat))
+        .body(|ui| {
+            // syntect finds the syntax by the file extension
+            let language = (diff.stat.path.rsplit_once('.')).map_or("", |(_, extension)| extension);
+            let theme = CodeTheme::from_style(ui.style());
+            egui::ScrollArea::horizontal().show(ui, |ui| {
+                for hunk in &diff.hunks {
+                    ui.weak(RichText::new(hunk_header(hunk)).monospace());
+                    let job = hunk_layout(ui, &theme, hunk, language);
+                    ui.add(egui::Label::new(job).extend());
+                }
+            });
+        });
+}
+
+/// Like `@@ -1,4 +1,5 @@`
+fn hunk_header(hunk: &Hunk) -> String {
+    // An empty range starts at the line before it, like in `diff -u`
+    let range = |start: usize, len| match len {
+        0 => format!("{},0", start - 1),
+        _ => format!("{start},{len}"),
+    };
+    format!(
+        "@@ -{} +{} @@",
+        range(hunk.old_start, hunk.old_len()),
+        range(hunk.new_start, hunk.new_len())
+    )
+}
+
+/// The lines of the hunk with `+`, `-` or space before them. Highlighting the
+/// whole hunk at once keeps multi-line strings and comments highlighted.
+fn hunk_layout(ui: &egui::Ui, theme: &CodeTheme, hunk: &Hunk, language: &str) -> LayoutJob {
+    let code = (hunk.lines.iter())
+        .map(|line| line.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let highlighted = highlight(ui.ctx(), ui.style(), theme, &code, language);
+    let font_id = egui::TextStyle::Monospace.resolve(ui.style());
+    let mut job = LayoutJob::default();
+    let mut section_idx = 0;
+    let mut line_start = 0;
+    for (i, line) in hunk.lines.iter().enumerate() {
+        let (prefix, background) = match line.kind {
+            LineKind::Context => (" ", Color32::TRANSPARENT),
+            LineKind::Added => ("+", INSERTION_COLOR.gamma_multiply(0.2)),
+            LineKind::Removed => ("-", DELETION_COLOR.gamma_multiply(0.2)),
+        };
+        let format = egui::TextFormat {
+            font_id: font_id.clone(),
+            color: ui.visuals().weak_text_color(),
+            background,
+            ..Default::default()
+        };
+        job.append(prefix, 0., format);
+        let newline = usize::from(i + 1 < hunk.lines.len());
+        let line_end = line_start + line.text.len() + newline;
+        while let Some(section) = highlighted.sections.get(section_idx)
+            && section.byte_range.start < line_end
+        {
+            let start = section.byte_range.start.max(line_start);
+            let end = section.byte_range.end.min(line_end);
+            let mut format = section.format.clone();
+            format.background = background;
+            job.append(&code[start..end], 0., format);
+            if section.byte_range.end > line_end {
+                // Continues on the next line
+                break;
+            }
+            section_idx += 1;
+        }
+        line_start = line_end;
+    }
+    job
+}
@@ -0,0 +1,57 @@
+//! Diffs of commits, computed on the [`crate::workers`] threads when a commit
+//! is shown in the details panel
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use jj_lib::repo::ReadonlyRepo;
+use revset_explorer_core::diff::{FileDiff, file_diffs};
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
+
+use crate::workers::{self, Subsystem};
+
+type FileDiffs = Result<Vec<FileDiff>, String>;
+
+pub struct Diffs {
+    // None while being computed. Commits never change, so they are kept.
+    diffs: HashMap<CommitId, Option<FileDiffs>>,
+    sender: mpsc::Sender<(CommitId, FileDiffs)>,
+    receiver: mpsc::Receiver<(CommitId, FileDiffs)>,
+}
+
+impl Diffs {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            diffs: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Returns the changes of the commit compared to its first parent, or None
+    /// until they have been computed
+    pub fn get(
+        &mut self,
+        ctx: &egui::Context,
+        repo: &Arc<ReadonlyRepo>,
+        commit_id: &CommitId,
+    ) -> Option<&FileDiffs> {
+        while let Ok((commit_id, diffs)) = self.receiver.try_recv() {
+            self.diffs.insert(commit_id, Some(diffs));
+        }
+        if !self.diffs.contains_key(commit_id) {
+            self.diffs.insert(commit_id.clone(), None);
+            let repo = repo.clone();
+            let commit_id = commit_id.clone();
+            let sender = self.sender.clone();
+            let ctx = ctx.clone();
+            workers::spawn(Subsystem::Diffs, move || {
+                let diffs = file_diffs(repo.as_ref(), &commit_id).map_err(|e| e.to_string());
+                let _ = sender.send((commit_id, diffs));
+                ctx.request_repaint();
+            });
+        }
+        self.diffs[commit_id].as_ref()
+    }
+}
@@ -0,0 +1,347 @@
+//! Override of DefaultEdgeShape that routes edges as curves which leave and
+//! enter the nodes at separate points, so that the many edges of megamerges do
+//! not overlap
+
+use eframe::egui::epaint::{ColorMode, CubicBezierShape};
+use eframe::egui::{Color32, Pos2, Shape, Stroke, Vec2};
+use egui_graphs::{DefaultEdgeShape, DisplayEdge, DisplayNode, DrawContext, EdgeProps, Node};
+use petgraph::{EdgeType, csr::IndexType};
+use serde::{Deserialize, Serialize};
+
+/// How the edges are drawn between the nodes
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum EdgeRouting {
+    Straight,
+    /// Curves that bend along the layout, spread out at nodes with many edges
+    #[default]
+    Curved,
+}
+
+impl EdgeRouting {
+    pub const ALL: [Self; 2] = [Self::Straight, Self::Curved];
+}
+
+/// Which end of the edges the arrowheads are at. The edges themselves always
+/// go from child to parent, like in jj.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ArrowDirection {
+    /// From child to parent, following the parent pointers
+    #[default]
+    Parents,
+    /// From parent to child, in the order the commits were made
+    Children,
+}
+
+impl ArrowDirection {
+    pub const ALL: [Self; 2] = [Self::Parents, Self::Children];
+
+    /// The ends and fan positions of a child to parent edge in drawing order
+    fn orient<T>(self, start: T, end: T, (start_fan, end_fan): (f32, f32)) -> (T, T, (f32, f32)) {
+        match self {
+            ArrowDirection::Parents => (start, end, (start_fan, end_fan)),
+            ArrowDirection::Children => (end, start, (end_fan, start_fan)),
+        }
+    }
+}
+
+/// Size of the arrowheads
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Arrowheads {
+    None,
+    Small,
+    #[default]
+    Normal,
+    Large,
+}
+
+impl Arrowheads {
+    pub const ALL: [Self; 4] = [Self::None, Self::Small, Self::Normal, Self::Large];
+
+    /// Length in canvas coordinates
+    fn size(self) -> f32 {
+        match self {
+            Arrowheads::None => 0.,
+            Arrowheads::Small => 8.,
+            Arrowheads::Normal => 15.,
+            Arrowheads::Large => 25.,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EdgeShape {
+    default_edge: DefaultEdgeShape,
+    pub routing: EdgeRouting,
+    pub arrowheads: Arrowheads,
+    pub arrow_direction: ArrowDirection,
+    /// Position among the edges of the start node, from -1 to 1, ordered
+    /// across the layout. 0 for a node with one edge.
+    pub start_fan: f32,
+    /// Position among the edges of the end node
+    pub end_fan: f32,
+    /// Under the pointer. Drawn thicker, in the selection color.
+    pub hovered: bool,
+    /// Off the highlighted path. Drawn faded.
+    pub dimmed: bool,
+}
+
+impl<E: Clone> From<EdgeProps<E>> for EdgeShape {
+    fn from(edge_props: EdgeProps<E>) -> Self {
+        Self {
+            default_edge: edge_props.into(),
+            routing: EdgeRouting::default(),
+            arrowheads: Arrowheads::default(),
+            arrow_direction: ArrowDirection::default(),
+            start_fan: 0.,
+            end_fan: 0.,
+            hovered: false,
+            dimmed: false,
+        }
+    }
+}
+
+impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType, D: DisplayNode<N, E, Ty, Ix>>
+    DisplayEdge<N, E, Ty, Ix, D> for EdgeShape
+{
+    fn shapes(
+        &mut self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+        ctx: &DrawContext,
+    ) -> Vec<Shape> {
+        self.default_edge.tip_size = self.arrowheads.size();
+        let (start, end, fans) =
+            (self.arrow_direction).orient(start, end, (self.start_fan, self.end_fan));
+        let mut r = <DefaultEdgeShape as DisplayEdge<N, E, Ty, Ix, D>>::shapes(
+            &mut self.default_edge,
+            start,
+            end,
+            ctx,
+        );
+        if self.routing == EdgeRouting::Straight || start.id() == end.id() {
+            if self.arrowheads == Arrowheads::None {
+                r.retain(|shape| !matches!(shape, Shape::Path(_)));
+            }
+        } else {
+            // The stroke hooks of the style are only reachable through the
+            // default shapes
+            let stroke = r.iter().find_map(|shape| match shape {
+                Shape::LineSegment { stroke, .. } => Some(*stroke),
+                _ => None,
+            });
+            if let Some(stroke) = stroke {
+                r = self.curve_shapes(start, end, fans, ctx, stroke);
+            }
+        }
+        if self.hovered || self.dimmed {
+            let hover_color = ctx.ctx.style().visuals.selection.stroke.color;
+            let restyle = |color: &mut Color32, width: Option<&mut f32>| {
+                if self.hovered {
+                    *color = hover_color;
+                    if let Some(width) = width {
+                        *width *= 2.;
+                    }
+                } else {
+                    *color = color.gamma_multiply(0.2);
+                }
+            };
+            for shape in &mut r {
+                match shape {
+                    Shape::LineSegment { stroke, .. } => {
+                        restyle(&mut stroke.color, Some(&mut stroke.width));
+                    }
+                    Shape::CubicBezier(curve) => {
+                        if let ColorMode::Solid(color) = &mut curve.stroke.color {
+                            restyle(color, Some(&mut curve.stroke.width));
+                        }
+                    }
+                    Shape::Path(path) => restyle(&mut path.fill, None),
+                    _ => {}
+                }
+            }
+        }
+        r
+    }
+
+    fn update(&mut self, state: &EdgeProps<E>) {
+        <DefaultEdgeShape as DisplayEdge<N, E, Ty, Ix, D>>::update(&mut self.default_edge, state);
+    }
+
+    fn is_inside(
+        &self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+        pos: Pos2,
+    ) -> bool {
+        if self.routing == EdgeRouting::Curved && start.id() != end.id() {
+            let points = self.curve_points(start, end, (self.start_fan, self.end_fan));
+            let curve = CubicBezierShape::from_points_stroke(
+                points,
+                false,
+                Color32::TRANSPARENT,
+                Stroke::NONE,
+            );
+            // In canvas coordinates
+            let tolerance = 4.;
+            let samples: Vec<_> = (0..=16).map(|i| curve.sample(i as f32 / 16.)).collect();
+            return samples
+                .windows(2)
+                .any(|segment| distance_to_segment(pos, segment[0], segment[1]) <= tolerance);
+        }
+        <DefaultEdgeShape as DisplayEdge<N, E, Ty, Ix, D>>::is_inside(
+            &self.default_edge,
+            start,
+            end,
+            pos,
+        )
+    }
+}
+
+impl EdgeShape {
+    /// The start, control and end points of the curve, in canvas coordinates
+    fn curve_points<
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        D: DisplayNode<N, E, Ty, Ix>,
+    >(
+        &self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+        (start_fan, end_fan): (f32, f32),
+    ) -> [Pos2; 4] {
+        let (axis, across) = axes(end.location() - start.location());
+        let start_point = (start.display())
+            .closest_boundary_point((axis + across * start_fan * 0.8).normalized());
+        let end_point =
+            (end.display()).closest_boundary_point((-axis + across * end_fan * 0.8).normalized());
+        let [start_control, end_control] = control_points(
+            start_point,
+            end_point,
+            axis,
+            across,
+            (start_fan, end_fan),
+            (start_point - start.location()).length(),
+        );
+        [start_point, start_control, end_control, end_point]
+    }
+
+    fn curve_shapes<
+        N: Clone,
+        E: Clone,
+        Ty: EdgeType,
+        Ix: IndexType,
+        D: DisplayNode<N, E, Ty, Ix>,
+    >(
+        &self,
+        start: &Node<N, E, Ty, Ix, D>,
+        end: &Node<N, E, Ty, Ix, D>,
+        fans: (f32, f32),
+        ctx: &DrawContext,
+        stroke: Stroke,
+    ) -> Vec<Shape> {
+        let mut points = self.curve_points(start, end, fans);
+        let [_, _, end_control, end_point] = points;
+        let mut r = vec![];
+        let tip_size = self.arrowheads.size();
+        if tip_size > 0. {
+            let tip_dir = (end_point - end_control).normalized();
+            let tip_angle = std::f32::consts::TAU / 30.;
+            let tip = [
+                end_point,
+                end_point - tip_size * eframe::egui::emath::Rot2::from_angle(tip_angle) * tip_dir,
+                end_point - tip_size * eframe::egui::emath::Rot2::from_angle(-tip_angle) * tip_dir,
+            ];
+            // The curve ends where the tip begins
+            points[3] = end_point - tip_size * tip_dir;
+            let tip = tip.map(|p| ctx.meta.canvas_to_screen_pos(p)).to_vec();
+            r.push(Shape::convex_polygon(tip, stroke.color, Stroke::NONE));
+        }
+        let points = points.map(|p| ctx.meta.canvas_to_screen_pos(p));
+        r.insert(
+            0,
+            CubicBezierShape::from_points_stroke(points, false, Color32::TRANSPARENT, stroke)
+                .into(),
+        );
+        r
+    }
+}
+
+/// Unit vectors along the layout, in the direction of the edge, and across it
+fn axes(dir: Vec2) -> (Vec2, Vec2) {
+    if dir.y.abs() >= dir.x.abs() {
+        (Vec2::new(0., dir.y.signum()), Vec2::X)
+    } else {
+        (Vec2::new(dir.x.signum(), 0.), Vec2::Y)
+    }
+}
+
+/// Control points that make the curve leave and enter along the layout, bowed
+/// out by the fan positions so that edges between the same rows or columns
+/// separate
+fn control_points(
+    start: Pos2,
+    end: Pos2,
+    axis: Vec2,
+    across: Vec2,
+    (start_fan, end_fan): (f32, f32),
+    node_radius: f32,
+) -> [Pos2; 2] {
+    let half = (end - start).dot(axis).abs() / 2.;
+    let bow = node_radius * 3.;
+    [
+        start + axis * half + across * start_fan * bow,
+        end - axis * half + across * end_fan * bow,
+    ]
+}
+
+fn distance_to_segment(pos: Pos2, a: Pos2, b: Pos2) -> f32 {
+    let ab = b - a;
+    let t = ((pos - a).dot(ab) / ab.length_sq().max(f32::EPSILON)).clamp(0., 1.);
+    (a + ab * t).distance(pos)
+}
+
+/// Fan positions of `count` edges, ordered across the layout
+pub fn fan_positions(count: usize) -> impl Iterator<Item = f32> {
+    (0..count).map(move |i| match count {
+        0 | 1 => 0.,
+        _ => i as f32 / (count - 1) as f32 * 2. - 1.,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_edges_of_megamerges() {
+        assert_eq!(fan_positions(1).collect::<Vec<_>>(), [0.]);
+        assert_eq!(fan_positions(3).collect::<Vec<_>>(), [-1., 0., 1.]);
+
+        // Parents straight below the merge, at different depths
+        let start = Pos2::new(0., 0.);
+        let (axis, across) = axes(Vec2::new(0., 100.));
+        assert_eq!((axis, across), (Vec2::Y, Vec2::X));
+        let near = control_points(start, Pos2::new(0., 50.), axis, across, (-1., 0.), 5.);
+        let far = control_points(start, Pos2::new(0., 100.), axis, across, (1., 0.), 5.);
+        assert!(near[0].x < 0. && far[0].x > 0.);
+        assert_eq!(near[1].x, 0.);
+    }
+
+    #[test]
+    fn reverses_arrows_towards_children() {
+        let fans = (-1., 0.5);
+        assert_eq!(
+            ArrowDirection::Parents.orient("child", "parent", fans),
+            ("child", "parent", fans)
+        );
+        assert_eq!(
+            ArrowDirection::Children.orient("child", "parent", fans),
+            ("parent", "child", (0.5, -1.))
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Guidance for when the View revset has no commits, instead of an empty
+//! canvas: hints about what commonly makes revsets empty, and the nearest of
+//! some wider revsets that has commits, to switch to.
+
+use eframe::egui::{self, RichText};
+use revset_explorer_core::JjGraph;
+use std::ops::Range;
+
+use crate::i18n::tr;
+
+/// The default `log` revset of jj
+const JJ_LOG: &str = "present(@) | ancestors(immutable_heads().., 2) | present(trunk())";
+/// All commits, which is never empty, as there is always the root commit
+const EVERYTHING: &str = "::heads(all())";
+/// How much wider the depths of `ancestors()` and `descendants()` are tried
+const DEPTH_FACTOR: u64 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Hint {
+    /// Uses `present()`, which hides missing names
+    Present,
+    /// Limits the depth of `ancestors()` or `descendants()`
+    Depth,
+    /// Intersects or subtracts revsets
+    Intersection,
+}
+
+impl Hint {
+    fn text(self) -> String {
+        match self {
+            Hint::Present => tr!("empty-view-present"),
+            Hint::Depth => tr!("empty-view-depth"),
+            Hint::Intersection => tr!("empty-view-intersection"),
+        }
+    }
+}
+
+/// The hints that apply to the revset
+fn hints(revset: &str) -> Vec<Hint> {
+    let mut hints = vec![];
+    if revset.contains("present(") {
+        hints.push(Hint::Present);
+    }
+    if revset.contains("ancestors(") || revset.contains("descendants(") {
+        hints.push(Hint::Depth);
+    }
+    if revset.contains('&') || revset.contains('~') {
+        hints.push(Hint::Intersection);
+    }
+    hints
+}
+
+/// The revset with the depths given to `ancestors()` and `descendants()`
+/// multiplied by `factor`, if it gives any
+fn widen_depths(revset: &str, factor: u64) -> Option<String> {
+    let mut depths = vec![];
+    for function in ["ancestors(", "descendants("] {
+        for (start, _) in revset.match_indices(function) {
+            depths.extend(last_argument(revset, start + function.len()));
+        }
+    }
+    // Replaced from the end, so that the ranges stay valid
+    depths.sort_by_key(|range| std::cmp::Reverse(range.start));
+    let mut widened = revset.to_owned();
+    let mut replaced = false;
+    for range in depths {
+        let Ok(depth) = revset[range.clone()].trim().parse::<u64>() else {
+            continue;
+        };
+        widened.replace_range(range, &format!(" {}", depth.saturating_mul(factor)));
+        replaced = true;
+    }
+    replaced.then_some(widened)
+}
+
+/// The range of the last argument of the call whose arguments start at
+/// `start`, when there are several
+fn last_argument(revset: &str, start: usize) -> Option<Range<usize>> {
+    let mut nesting = 0;
+    let mut in_string = false;
+    let mut last_comma = None;
+    for (i, c) in revset[start..].char_indices() {
+        match c {
+            '"' => in_string = !in_string,
+            _ if in_string => {}
+            '(' => nesting += 1,
+            ')' if nesting == 0 => {
+                return last_comma.map(|comma| start + comma + 1..start + i);
+            }
+            ')' => nesting -= 1,
+            ',' if nesting == 0 => last_comma = Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Wider revsets to show instead, the nearest first
+fn candidates(revset: &str) -> Vec<String> {
+    (widen_depths(revset, DEPTH_FACTOR).into_iter())
+        .chain([JJ_LOG, EVERYTHING].map(str::to_owned))
+        .collect()
+}
+
+/// The first of the wider revsets that has commits
+pub fn nearest_non_empty(jj_graph: &JjGraph, revset: &str) -> Option<String> {
+    candidates(revset).into_iter().find(|candidate| {
+        (jj_graph.get_revset(candidate)).is_ok_and(|revset| revset.iter().next().is_some())
+    })
+}
+
+/// Shows the placeholder in place of the graph. Returns the revset to show
+/// instead, when its button is clicked.
+pub fn ui(ui: &mut egui::Ui, revset: &str, fallback: Option<&str>) -> Option<String> {
+    let mut show = None;
+    ui.vertical_centered(|ui| {
+        ui.add_space(ui.available_height() / 4.);
+        ui.heading(tr!("empty-view-title"));
+        ui.label(tr!("empty-view-revset", revset = revset.trim()));
+        ui.add_space(8.);
+        for hint in hints(revset) {
+            ui.label(RichText::new(hint.text()).weak());
+        }
+        if let Some(fallback) = fallback {
+            ui.add_space(8.);
+            if (ui.button(tr!("empty-view-show", revset = fallback)))
+                .on_hover_text(tr!("empty-view-show-hover"))
+                .clicked()
+            {
+                show = Some(fallback.to_owned());
+            }
+        }
+    });
+    show
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn suggests_wider_revsets() {
+        assert_eq!(
+            widen_depths("ancestors(x, 2) & descendants(ancestors(y, 3), 1)", 10).as_deref(),
+            Some("ancestors(x, 20) & descendants(ancestors(y, 30), 10)")
+        );
+        assert_eq!(
+            widen_depths(r#"ancestors(description(","), 2)"#, 10).as_deref(),
+            Some(r#"ancestors(description(","), 20)"#)
+        );
+        assert_eq!(widen_depths("ancestors(x)", 10), None);
+        assert_eq!(candidates("x & y"), [JJ_LOG, EVERYTHING]);
+        assert_eq!(
+            hints("present(main) & ::@"),
+            [Hint::Present, Hint::Intersection]
+        );
+
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let nearest = nearest_non_empty(&jj_graph, &format!("ancestors({b}, 1) & description(a)"));
+        assert_eq!(
+            nearest,
+            Some(format!("ancestors({b}, 10) & description(a)"))
+        );
+        assert!(nearest_non_empty(&jj_graph, "none()").is_some());
+    }
+}
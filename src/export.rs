@@ -0,0 +1,457 @@
+//! Export of the graph to files that can be viewed without Revset Explorer
+
+use eframe::egui::Color32;
+use revset_explorer_core::mark::Palette;
+use revset_explorer_core::snapshot::{Selection, Snapshot};
+
+/// Snapshot of the laid out graph
+pub struct ExportGraph {
+    pub nodes: Vec<ExportNode>,
+    /// Indexes into `nodes`, from child to parent
+    pub edges: Vec<(usize, usize)>,
+}
+
+pub struct ExportNode {
+    pub label: String,
+    /// First line of the description
+    pub subject: String,
+    pub color: Color32,
+    pub x: f32,
+    pub y: f32,
+    /// Longer text, e.g. shown when hovering the node
+    pub tooltip: String,
+}
+
+/// What the colors mean and which revsets the graph shows, so that the
+/// exported graph explains itself
+pub struct Legend {
+    /// Names and texts of the revsets, like "View" and "::@"
+    pub revsets: Vec<(String, String)>,
+    pub colors: Vec<(Color32, String)>,
+}
+
+impl ExportGraph {
+    /// The nodes of the snapshot, colored by whether they are in the
+    /// selection. There is no layout, so it is only for formats that are laid
+    /// out by the viewer.
+    pub fn from_snapshot(
+        snapshot: &Snapshot,
+        selection: Option<&Selection>,
+        palette: Palette,
+    ) -> Self {
+        let markings = snapshot.markings(selection);
+        let nodes = (snapshot.nodes.iter().zip(markings))
+            .map(|(node, marking)| {
+                let [red, green, blue] = palette.color(marking);
+                ExportNode {
+                    label: node.label.clone(),
+                    subject: node
+                        .description
+                        .lines()
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned(),
+                    color: Color32::from_rgb(red, green, blue),
+                    x: 0.,
+                    y: 0.,
+                    tooltip: format!(
+                        "{}\n{}\n\n{}",
+                        node.change_id,
+                        node.author,
+                        node.description.trim_end()
+                    ),
+                }
+            })
+            .collect();
+        let edges = (snapshot.edges.iter())
+            .map(|edge| (edge.from, edge.to))
+            .collect();
+        Self { nodes, edges }
+    }
+
+    /// Keeps the matching nodes and the nodes on the paths between them, like
+    /// the `connected()` revset, moving the rows up into the gaps left by the
+    /// others
+    pub fn connected(self, matches: &[bool]) -> Self {
+        // Reachable from a match, following the edges forwards or backwards
+        let reachable = |forward: bool| {
+            let mut reached = matches.to_vec();
+            let mut queue: Vec<_> = (0..matches.len()).filter(|&i| matches[i]).collect();
+            while let Some(i) = queue.pop() {
+                for &(child, parent) in &self.edges {
+                    let (from, to) = if forward {
+                        (child, parent)
+                    } else {
+                        (parent, child)
+                    };
+                    if from == i && !reached[to] {
+                        reached[to] = true;
+                        queue.push(to);
+                    }
+                }
+            }
+            reached
+        };
+        let (ancestors, descendants) = (reachable(true), reachable(false));
+        let kept: Vec<_> = (0..matches.len())
+            .map(|i| ancestors[i] && descendants[i])
+            .collect();
+        let mut rows: Vec<f32> = (self.nodes.iter().zip(&kept))
+            .filter(|(_, kept)| **kept)
+            .map(|(node, _)| node.y)
+            .collect();
+        rows.sort_by(f32::total_cmp);
+        rows.dedup();
+        // The layout spaces the rows evenly
+        let spacing = (rows.windows(2))
+            .map(|pair| pair[1] - pair[0])
+            .reduce(f32::min)
+            .unwrap_or_default();
+        let mut positions = vec![None; self.nodes.len()];
+        let mut nodes = vec![];
+        for (i, mut node) in self.nodes.into_iter().enumerate() {
+            if !kept[i] {
+                continue;
+            }
+            let row = rows.iter().position(|&y| y == node.y).unwrap();
+            node.y = rows[0] + row as f32 * spacing;
+            positions[i] = Some(nodes.len());
+            nodes.push(node);
+        }
+        let edges = (self.edges.iter())
+            .filter_map(|&(from, to)| Some((positions[from]?, positions[to]?)))
+            .collect();
+        Self { nodes, edges }
+    }
+}
+
+const NODE_RADIUS: f32 = 5.;
+const MARGIN: f32 = 40.;
+
+/// Creates a standalone SVG image of the graph, e.g. for embedding in
+/// documentation. The title is shown at the top, on a dark background like in
+/// the app.
+pub fn to_svg(graph: &ExportGraph, title: &str) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n",
+        svg_element(graph, "", Some(title))
+    )
+}
+
+/// The SVG element, with `attributes` added to it. A `standalone_title` makes
+/// it an image of its own, with a background, styles and the title.
+fn svg_element(graph: &ExportGraph, attributes: &str, standalone_title: Option<&str>) -> String {
+    let mut svg = String::new();
+    let (min_x, min_y, max_x, max_y) = bounds(graph);
+    let (left, top) = (min_x - MARGIN, min_y - MARGIN);
+    let (width, height) = (
+        max_x - min_x + 2. * MARGIN + 150.,
+        max_y - min_y + 2. * MARGIN,
+    );
+    svg += &format!(
+        "<svg {attributes}xmlns=\"http://www.w3.org/2000/svg\" \
+         viewBox=\"{left} {top} {width} {height}\">\n"
+    );
+    if let Some(title) = standalone_title {
+        svg += &format!(
+            "<style>text {{ font-family: monospace; font-size: 12px; }}</style>\n\
+             <rect x=\"{left}\" y=\"{top}\" width=\"{width}\" height=\"{height}\" \
+             fill=\"#1b1b1b\"/>\n\
+             <text x=\"{}\" y=\"{}\" fill=\"#ddd\">{}</text>\n",
+            left + 8.,
+            top + 16.,
+            escape(title)
+        );
+    }
+    svg += "<defs><marker id=\"arrow\" viewBox=\"0 0 10 10\" refX=\"10\" refY=\"5\" \
+            markerWidth=\"6\" markerHeight=\"6\" orient=\"auto-start-reverse\">\
+            <path d=\"M 0 0 L 10 5 L 0 10 z\" fill=\"#888\"/></marker></defs>\n";
+    for (from, to) in &graph.edges {
+        let (from, to) = (&graph.nodes[*from], &graph.nodes[*to]);
+        let (x1, y1, x2, y2) = shorten_line(from.x, from.y, to.x, to.y, NODE_RADIUS);
+        svg += &format!(
+            "<line x1=\"{x1}\" y1=\"{y1}\" x2=\"{x2}\" y2=\"{y2}\" stroke=\"#888\" \
+             marker-end=\"url(#arrow)\"/>\n"
+        );
+    }
+    for node in &graph.nodes {
+        let color = hex_color(node.color);
+        svg += &format!(
+            "<g><title>{}</title><circle cx=\"{}\" cy=\"{}\" r=\"{NODE_RADIUS}\" fill=\"{color}\"/>\
+             <text x=\"{}\" y=\"{}\" fill=\"{color}\">{}</text></g>\n",
+            escape(&node.tooltip),
+            node.x,
+            node.y,
+            node.x + 2. * NODE_RADIUS,
+            node.y + NODE_RADIUS,
+            escape(&node.label)
+        );
+    }
+    svg + "</svg>"
+}
+
+/// Creates a standalone HTML page with an SVG rendering of the graph. The view
+/// can be panned by dragging and zoomed using the mouse wheel. The legend is
+/// shown in a corner.
+pub fn to_html(graph: &ExportGraph, title: &str, legend: Option<&Legend>) -> String {
+    let svg = svg_element(graph, "id=\"graph\" ", None);
+    let legend = legend.map(legend_html).unwrap_or_default();
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+body {{ margin: 0; background: #1b1b1b; color: #ddd; font-family: sans-serif; overflow: hidden; }}
+h1 {{ font-size: 14px; margin: 8px; position: absolute; }}
+svg {{ width: 100vw; height: 100vh; cursor: grab; }}
+text {{ font-family: monospace; font-size: 12px; }}
+#legend {{ position: absolute; right: 8px; top: 8px; padding: 8px; font-size: 12px; background: #262626e0; }}
+#legend dl, #legend ul {{ margin: 0; padding: 0; list-style: none; }}
+#legend dd {{ margin: 0 0 4px 0; }}
+.swatch {{ display: inline-block; width: 10px; height: 10px; margin-right: 6px; border-radius: 50%; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{legend}{svg}
+<script>
+const svg = document.getElementById("graph");
+let [x, y, w, h] = svg.getAttribute("viewBox").split(" ").map(Number);
+let drag = null;
+function update() {{ svg.setAttribute("viewBox", `${{x}} ${{y}} ${{w}} ${{h}}`); }}
+svg.addEventListener("wheel", (e) => {{
+  e.preventDefault();
+  const scale = e.deltaY > 0 ? 1.1 : 1 / 1.1;
+  const rect = svg.getBoundingClientRect();
+  const px = x + (e.clientX - rect.left) / rect.width * w;
+  const py = y + (e.clientY - rect.top) / rect.height * h;
+  x = px - (px - x) * scale; y = py - (py - y) * scale; w *= scale; h *= scale;
+  update();
+}});
+svg.addEventListener("mousedown", (e) => {{ drag = [e.clientX, e.clientY]; }});
+window.addEventListener("mouseup", () => {{ drag = null; }});
+window.addEventListener("mousemove", (e) => {{
+  if (!drag) return;
+  const rect = svg.getBoundingClientRect();
+  x -= (e.clientX - drag[0]) / rect.width * w;
+  y -= (e.clientY - drag[1]) / rect.height * h;
+  drag = [e.clientX, e.clientY];
+  update();
+}});
+</script>
+</body>
+</html>
+"#,
+        title = escape(title),
+    )
+}
+
+/// Creates a Graphviz graph, e.g. for rendering with `dot -Tsvg`. Graphviz
+/// does the layout, so the positions are not used. The nodes are filled with
+/// their colors and labeled with their labels and subjects.
+pub fn to_dot(graph: &ExportGraph, title: &str) -> String {
+    let mut dot = format!(
+        "digraph revsets {{\n  label={};\n  labelloc=t;\n  \
+         node [shape=box, style=\"rounded,filled\", fontname=monospace];\n",
+        dot_string(title)
+    );
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let label = if node.subject.is_empty() {
+            node.label.clone()
+        } else {
+            format!("{}\n{}", node.label, node.subject)
+        };
+        dot += &format!(
+            "  n{i} [label={}, tooltip={}, fillcolor=\"{}\"];\n",
+            dot_string(&label),
+            dot_string(&node.tooltip),
+            hex_color(node.color)
+        );
+    }
+    for (from, to) in &graph.edges {
+        dot += &format!("  n{from} -> n{to};\n");
+    }
+    dot + "}\n"
+}
+
+/// Quotes text as a DOT string
+fn dot_string(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{escaped}\"")
+}
+
+fn legend_html(legend: &Legend) -> String {
+    let mut html = "<div id=\"legend\">\n<dl>\n".to_owned();
+    for (name, revset) in &legend.revsets {
+        html += &format!(
+            "<dt>{}</dt><dd><code>{}</code></dd>\n",
+            escape(name),
+            escape(revset)
+        );
+    }
+    html += "</dl>\n<ul>\n";
+    for (color, meaning) in &legend.colors {
+        html += &format!(
+            "<li><span class=\"swatch\" style=\"background: {}\"></span>{}</li>\n",
+            hex_color(*color),
+            escape(meaning)
+        );
+    }
+    html + "</ul>\n</div>\n"
+}
+
+fn bounds(graph: &ExportGraph) -> (f32, f32, f32, f32) {
+    if graph.nodes.is_empty() {
+        return (0., 0., 0., 0.);
+    }
+    graph.nodes.iter().fold(
+        (f32::MAX, f32::MAX, f32::MIN, f32::MIN),
+        |(min_x, min_y, max_x, max_y), n| {
+            (
+                min_x.min(n.x),
+                min_y.min(n.y),
+                max_x.max(n.x),
+                max_y.max(n.y),
+            )
+        },
+    )
+}
+
+/// Shortens the line at both ends, so that it ends at the node boundaries
+fn shorten_line(x1: f32, y1: f32, x2: f32, y2: f32, by: f32) -> (f32, f32, f32, f32) {
+    let (dx, dy) = (x2 - x1, y2 - y1);
+    let len = (dx * dx + dy * dy).sqrt();
+    if len <= 2. * by {
+        return (x1, y1, x2, y2);
+    }
+    let (ux, uy) = (dx / len * by, dy / len * by);
+    (x1 + ux, y1 + uy, x2 - ux, y2 - uy)
+}
+
+pub fn hex_color(color: Color32) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+/// Escapes text for use in HTML and XML
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_graph() -> ExportGraph {
+        ExportGraph {
+            nodes: vec![
+                ExportNode {
+                    label: "@ abc".to_owned(),
+                    subject: "Fix \"quoted\" bug".to_owned(),
+                    color: Color32::from_rgb(0x26, 0xff, 0x00),
+                    x: 0.,
+                    y: 0.,
+                    tooltip: "Fix <bug>".to_owned(),
+                },
+                ExportNode {
+                    label: "def".to_owned(),
+                    subject: String::new(),
+                    color: Color32::from_rgb(0x63, 0x62, 0x22),
+                    x: 0.,
+                    y: 50.,
+                    tooltip: "Parent".to_owned(),
+                },
+            ],
+            edges: vec![(0, 1)],
+        }
+    }
+
+    #[test]
+    fn keeps_matches_and_the_paths_between_them() {
+        let node = |y: f32| ExportNode {
+            label: y.to_string(),
+            subject: String::new(),
+            color: Color32::WHITE,
+            x: 0.,
+            y,
+            tooltip: String::new(),
+        };
+        // A chain with a side branch from the middle
+        let graph = ExportGraph {
+            nodes: [0., 50., 100., 150., 200., 100.].map(node).into(),
+            edges: vec![(0, 1), (1, 2), (2, 3), (3, 4), (5, 2)],
+        };
+        let graph = graph.connected(&[false, true, false, true, false, false]);
+        let labels: Vec<_> = graph.nodes.iter().map(|n| n.label.as_str()).collect();
+        assert_eq!(labels, ["50", "100", "150"]);
+        assert_eq!(graph.edges, [(0, 1), (1, 2)]);
+
+        // A side branch between the matches leaves an empty row
+        let graph = ExportGraph {
+            nodes: [0., 50., 100., 150.].map(node).into(),
+            edges: vec![(0, 2), (1, 2), (2, 3)],
+        };
+        let graph = graph.connected(&[true, false, false, true]);
+        let rows: Vec<_> = graph.nodes.iter().map(|n| n.y).collect();
+        assert_eq!(rows, [0., 50., 100.]);
+        assert_eq!(graph.edges, [(0, 1), (1, 2)]);
+    }
+
+    #[test]
+    fn html_contains_nodes_and_edges() {
+        let html = to_html(&sample_graph(), "Test", None);
+        assert!(html.contains("<title>Fix &lt;bug&gt;</title>"));
+        assert!(html.contains(">@ abc</text>"));
+        assert!(html.contains("fill=\"#26ff00\""));
+        assert_eq!(html.matches("<line ").count(), 1);
+        assert!(!html.contains("<div id=\"legend\">"));
+
+        let legend = Legend {
+            revsets: vec![("View".to_owned(), "::@ & mine()".to_owned())],
+            colors: vec![(
+                Color32::from_rgb(0x26, 0xff, 0x00),
+                "Working copy".to_owned(),
+            )],
+        };
+        let html = to_html(&sample_graph(), "Test", Some(&legend));
+        assert!(html.contains("<dt>View</dt><dd><code>::@ &amp; mine()</code></dd>"));
+        assert!(html.contains("style=\"background: #26ff00\"></span>Working copy</li>"));
+    }
+
+    #[test]
+    fn svg_is_standalone() {
+        let svg = to_svg(&sample_graph(), "View: a & b");
+        assert!(svg.starts_with("<?xml "));
+        assert!(svg.contains(">View: a &amp; b</text>"));
+        assert!(svg.contains("fill=\"#1b1b1b\""));
+        assert!(svg.contains(">@ abc</text>"));
+        assert_eq!(svg.matches("<line ").count(), 1);
+        assert!(!to_html(&sample_graph(), "Test", None).contains("#1b1b1b\"/>"));
+    }
+
+    #[test]
+    fn dot_contains_nodes_and_edges() {
+        let dot = to_dot(&sample_graph(), "View: ::@");
+        assert!(dot.starts_with("digraph revsets {\n  label=\"View: ::@\";"));
+        assert!(dot.contains(
+            r##"n0 [label="@ abc\nFix \"quoted\" bug", tooltip="Fix <bug>", fillcolor="#26ff00"];"##
+        ));
+        assert!(dot.contains(r#"n1 [label="def", "#));
+        assert!(dot.contains("  n0 -> n1;\n"));
+    }
+
+    #[test]
+    fn edges_end_at_node_boundary() {
+        assert_eq!(
+            shorten_line(0., 0., 0., 50., NODE_RADIUS),
+            (0., 5., 0., 45.)
+        );
+    }
+}
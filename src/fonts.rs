@@ -0,0 +1,103 @@
+//! Fallback fonts for scripts that the fonts built into egui lack, like CJK,
+//! so that commit descriptions and author names do not show up as boxes
+
+use anyhow::Context;
+use eframe::egui;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::tr;
+
+/// Fonts commonly installed on Linux, macOS and Windows, covering at least
+/// Chinese, Japanese and Korean. The first one found is used.
+const SYSTEM_FONTS: &[&str] = &[
+    "/usr/share/fonts/opentype/noto/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/google-noto-cjk/NotoSansCJK-Regular.ttc",
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf",
+    "/System/Library/Fonts/Hiragino Sans GB.ttc",
+    "/Library/Fonts/Arial Unicode.ttf",
+    r"C:\Windows\Fonts\msyh.ttc",
+    r"C:\Windows\Fonts\YuGothM.ttc",
+    r"C:\Windows\Fonts\malgun.ttf",
+];
+
+/// A font file and its contents
+pub struct Font {
+    path: PathBuf,
+    data: Vec<u8>,
+}
+
+/// Reads the configured fonts, or the first system font found if none are
+/// configured
+pub fn load(paths: &[PathBuf]) -> anyhow::Result<Vec<Font>> {
+    if paths.is_empty() {
+        return Ok(SYSTEM_FONTS
+            .iter()
+            .find_map(|path| read(Path::new(path)).ok())
+            .into_iter()
+            .collect());
+    }
+    paths
+        .iter()
+        .map(|path| {
+            read(path).with_context(|| tr!("font-not-readable", path = path.display().to_string()))
+        })
+        .collect()
+}
+
+fn read(path: &Path) -> std::io::Result<Font> {
+    Ok(Font {
+        path: path.to_owned(),
+        data: std::fs::read(path)?,
+    })
+}
+
+/// Makes egui use the fonts, in order, for characters missing from its own
+pub fn install(ctx: &egui::Context, fonts: Vec<Font>) {
+    if !fonts.is_empty() {
+        ctx.set_fonts(font_definitions(fonts));
+    }
+}
+
+fn font_definitions(fonts: Vec<Font>) -> egui::FontDefinitions {
+    let mut definitions = egui::FontDefinitions::default();
+    for font in fonts {
+        let name = font.path.display().to_string();
+        definitions
+            .font_data
+            .insert(name.clone(), egui::FontData::from_owned(font.data).into());
+        // Labels in the graph are monospace
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            (definitions.families.entry(family).or_default()).push(name.clone());
+        }
+    }
+    definitions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_fallbacks_last() {
+        let font = |path: &str| Font {
+            path: path.into(),
+            data: vec![],
+        };
+        let defaults = egui::FontDefinitions::default();
+        let definitions = font_definitions(vec![font("a.ttf"), font("b.ttc")]);
+        for family in [egui::FontFamily::Proportional, egui::FontFamily::Monospace] {
+            let names = &definitions.families[&family];
+            let default_names = &defaults.families[&family];
+            assert_eq!(names[..default_names.len()], default_names[..]);
+            assert_eq!(names[default_names.len()..], ["a.ttf", "b.ttc"]);
+        }
+        assert!(definitions.font_data.contains_key("b.ttc"));
+    }
+
+    #[test]
+    fn fails_on_missing_configured_font() {
+        let error = load(&["/nonexistent/font.ttf".into()]).err().unwrap();
+        assert!(error.to_string().contains("/nonexistent/font.ttf"));
+    }
+}
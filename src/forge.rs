@@ -0,0 +1,323 @@
+//! Links to commits in web forges (GitHub, GitLab, ...) and their APIs
+
+use serde::{Deserialize, Serialize};
+
+/// Creates a commit URL template from a git remote URL, if the forge is known
+pub fn commit_url_template_from_remote(remote_url: &str) -> Option<String> {
+    let (host, path) = split_remote_url(remote_url)?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    if path.is_empty() {
+        return None;
+    }
+    let commit_path = if host == "github.com" || host == "codeberg.org" {
+        "commit"
+    } else if host == "bitbucket.org" {
+        "commits"
+    } else if host.contains("gitlab") {
+        "-/commit"
+    } else {
+        return None;
+    };
+    Some(format!("https://{host}/{path}/{commit_path}/{{commit_id}}"))
+}
+
+/// REST API of the forge, used for CI status and pull requests
+#[derive(Debug, Clone, PartialEq)]
+pub enum ForgeApi {
+    GitHub { owner_repo: String },
+    GitLab { host: String, project: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CiStatus {
+    Success,
+    Failure,
+    Pending,
+}
+
+impl CiStatus {
+    /// Whether the status can still change
+    pub fn is_final(self) -> bool {
+        self != CiStatus::Pending
+    }
+}
+
+pub fn api_from_remote(remote_url: &str) -> Option<ForgeApi> {
+    let (host, path) = split_remote_url(remote_url)?;
+    let path = path.trim_end_matches('/').trim_end_matches(".git");
+    if path.is_empty() {
+        return None;
+    }
+    if host == "github.com" {
+        Some(ForgeApi::GitHub {
+            owner_repo: path.to_owned(),
+        })
+    } else if host.contains("gitlab") {
+        Some(ForgeApi::GitLab {
+            host: host.to_owned(),
+            project: path.to_owned(),
+        })
+    } else {
+        None
+    }
+}
+
+impl ForgeApi {
+    pub fn status_url(&self, commit_id: &str) -> String {
+        match self {
+            // GitHub Actions report check runs rather than commit statuses
+            ForgeApi::GitHub { owner_repo } => {
+                format!("https://api.github.com/repos/{owner_repo}/commits/{commit_id}/check-runs")
+            }
+            ForgeApi::GitLab { host, project } => format!(
+                "https://{host}/api/v4/projects/{}/repository/commits/{commit_id}",
+                project.replace('/', "%2F")
+            ),
+        }
+    }
+
+    pub fn pull_request_url(&self, number: u64) -> String {
+        match self {
+            ForgeApi::GitHub { owner_repo } => {
+                format!("https://api.github.com/repos/{owner_repo}/pulls/{number}")
+            }
+            ForgeApi::GitLab { host, project } => format!(
+                "https://{host}/api/v4/projects/{}/merge_requests/{number}",
+                project.replace('/', "%2F")
+            ),
+        }
+    }
+
+    /// Extracts the (base, head) commit hashes of a pull/merge request. The
+    /// commits of the request are base..head.
+    pub fn parse_pull_request(&self, response: &serde_json::Value) -> Option<(String, String)> {
+        let sha = |value: &serde_json::Value, path: [&str; 2]| -> Option<String> {
+            Some(value.get(path[0])?.get(path[1])?.as_str()?.to_owned())
+        };
+        match self {
+            ForgeApi::GitHub { .. } => Some((
+                sha(response, ["base", "sha"])?,
+                sha(response, ["head", "sha"])?,
+            )),
+            ForgeApi::GitLab { .. } => Some((
+                sha(response, ["diff_refs", "base_sha"])?,
+                sha(response, ["diff_refs", "head_sha"])?,
+            )),
+        }
+    }
+
+    /// Fetches JSON from the API
+    pub fn get(&self, url: &str, token: Option<&str>) -> Result<serde_json::Value, ureq::Error> {
+        let agent: ureq::Agent = ureq::Agent::config_builder()
+            .timeout_global(Some(std::time::Duration::from_secs(10)))
+            .build()
+            .into();
+        let mut request = agent.get(url).header("User-Agent", "revset-explorer");
+        if let Some(token) = token {
+            let (name, value) = self.auth_header(token);
+            request = request.header(name, value);
+        }
+        request.call()?.body_mut().read_json()
+    }
+
+    /// HTTP header used to pass the access token
+    pub fn auth_header(&self, token: &str) -> (&'static str, String) {
+        match self {
+            ForgeApi::GitHub { .. } => ("Authorization", format!("Bearer {token}")),
+            ForgeApi::GitLab { .. } => ("PRIVATE-TOKEN", token.to_owned()),
+        }
+    }
+
+    /// Extracts the status from the API response. Returns None if no CI has
+    /// run for the commit.
+    pub fn parse_status(&self, response: &serde_json::Value) -> Option<CiStatus> {
+        match self {
+            ForgeApi::GitHub { .. } => {
+                let runs = response.get("check_runs")?.as_array()?;
+                if runs.is_empty() {
+                    return None;
+                }
+                let mut status = CiStatus::Success;
+                for run in runs {
+                    if run.get("status").and_then(|s| s.as_str()) != Some("completed") {
+                        status = CiStatus::Pending;
+                        continue;
+                    }
+                    match run.get("conclusion").and_then(|c| c.as_str()) {
+                        Some("success" | "neutral" | "skipped") => {}
+                        _ => return Some(CiStatus::Failure),
+                    }
+                }
+                Some(status)
+            }
+            ForgeApi::GitLab { .. } => {
+                let status = response.get("last_pipeline")?.get("status")?.as_str()?;
+                match status {
+                    "success" => Some(CiStatus::Success),
+                    "failed" => Some(CiStatus::Failure),
+                    "created"
+                    | "waiting_for_resource"
+                    | "preparing"
+                    | "pending"
+                    | "running"
+                    | "scheduled"
+                    | "manual" => Some(CiStatus::Pending),
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Returns (host, path) of a remote URL
+fn split_remote_url(remote_url: &str) -> Option<(&str, &str)> {
+    let remote_url = remote_url.trim();
+    if let Some((_scheme, rest)) = remote_url.split_once("://") {
+        // https://host/path, ssh://git@host:port/path
+        let (authority, path) = rest.split_once('/')?;
+        let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+        let host = host.split_once(':').map_or(host, |(h, _)| h);
+        Some((host, path))
+    } else {
+        // scp-like syntax: git@host:path
+        let (authority, path) = remote_url.split_once(':')?;
+        let host = authority.rsplit_once('@').map_or(authority, |(_, h)| h);
+        Some((host, path))
+    }
+}
+
+/// Replaces `{commit_id}` and `{change_id}` in the template
+pub fn expand_commit_url(template: &str, commit_id: &str, change_id: &str) -> String {
+    template
+        .replace("{commit_id}", commit_id)
+        .replace("{change_id}", change_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn github_https() {
+        assert_eq!(
+            commit_url_template_from_remote("https://github.com/org/repo.git").as_deref(),
+            Some("https://github.com/org/repo/commit/{commit_id}")
+        );
+    }
+
+    #[test]
+    fn github_scp_like() {
+        assert_eq!(
+            commit_url_template_from_remote("git@github.com:org/repo.git").as_deref(),
+            Some("https://github.com/org/repo/commit/{commit_id}")
+        );
+    }
+
+    #[test]
+    fn gitlab_ssh() {
+        assert_eq!(
+            commit_url_template_from_remote("ssh://git@gitlab.example.com:2222/group/sub/repo")
+                .as_deref(),
+            Some("https://gitlab.example.com/group/sub/repo/-/commit/{commit_id}")
+        );
+    }
+
+    #[test]
+    fn unknown_forge() {
+        assert_eq!(
+            commit_url_template_from_remote("https://git.example.com/repo.git"),
+            None
+        );
+        assert_eq!(commit_url_template_from_remote("/local/path/repo"), None);
+    }
+
+    #[test]
+    fn ci_api() {
+        let github = api_from_remote("git@github.com:org/repo.git").unwrap();
+        assert_eq!(
+            github.status_url("abc"),
+            "https://api.github.com/repos/org/repo/commits/abc/check-runs"
+        );
+        let gitlab = api_from_remote("https://gitlab.com/group/sub/repo").unwrap();
+        assert_eq!(
+            gitlab.status_url("abc"),
+            "https://gitlab.com/api/v4/projects/group%2Fsub%2Frepo/repository/commits/abc"
+        );
+        assert_eq!(api_from_remote("https://codeberg.org/org/repo"), None);
+    }
+
+    #[test]
+    fn github_status() {
+        let github = ForgeApi::GitHub {
+            owner_repo: "org/repo".to_owned(),
+        };
+        let parse = |s: &str| github.parse_status(&serde_json::from_str(s).unwrap());
+        assert_eq!(parse(r#"{"check_runs": []}"#), None);
+        assert_eq!(
+            parse(
+                r#"{"check_runs": [{"status": "completed", "conclusion": "success"},
+                                   {"status": "in_progress", "conclusion": null}]}"#
+            ),
+            Some(CiStatus::Pending)
+        );
+        assert_eq!(
+            parse(
+                r#"{"check_runs": [{"status": "in_progress", "conclusion": null},
+                                   {"status": "completed", "conclusion": "failure"}]}"#
+            ),
+            Some(CiStatus::Failure)
+        );
+    }
+
+    #[test]
+    fn gitlab_status() {
+        let gitlab = ForgeApi::GitLab {
+            host: "gitlab.com".to_owned(),
+            project: "org/repo".to_owned(),
+        };
+        let parse = |s: &str| gitlab.parse_status(&serde_json::from_str(s).unwrap());
+        assert_eq!(parse(r#"{"last_pipeline": null}"#), None);
+        assert_eq!(
+            parse(r#"{"last_pipeline": {"status": "failed"}}"#),
+            Some(CiStatus::Failure)
+        );
+    }
+
+    #[test]
+    fn pull_requests() {
+        let github = api_from_remote("https://github.com/org/repo").unwrap();
+        assert_eq!(
+            github.pull_request_url(12),
+            "https://api.github.com/repos/org/repo/pulls/12"
+        );
+        assert_eq!(
+            github.parse_pull_request(
+                &serde_json::from_str(r#"{"base": {"sha": "aaa"}, "head": {"sha": "bbb"}}"#)
+                    .unwrap()
+            ),
+            Some(("aaa".to_owned(), "bbb".to_owned()))
+        );
+        let gitlab = api_from_remote("https://gitlab.com/org/repo").unwrap();
+        assert_eq!(
+            gitlab.pull_request_url(12),
+            "https://gitlab.com/api/v4/projects/org%2Frepo/merge_requests/12"
+        );
+        assert_eq!(
+            gitlab.parse_pull_request(
+                &serde_json::from_str(r#"{"diff_refs": {"base_sha": "aaa", "head_sha": "bbb"}}"#)
+                    .unwrap()
+            ),
+            Some(("aaa".to_owned(), "bbb".to_owned()))
+        );
+        assert_eq!(github.parse_pull_request(&serde_json::Value::Null), None);
+    }
+
+    #[test]
+    fn expand() {
+        assert_eq!(
+            expand_commit_url("https://x/{commit_id}?c={change_id}", "abc", "xyz"),
+            "https://x/abc?c=xyz"
+        );
+    }
+}
@@ -0,0 +1,148 @@
+//! The `forge_pr(number)` revset function, giving the commits of a GitHub pull
+//! request or GitLab merge request of the "origin" remote
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use jj_lib::revset::{
+    self, PartialSymbolResolver, RevsetDiagnostics, RevsetExpression, RevsetExtensions,
+    RevsetResolutionError, SymbolResolverExtension,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::forge::{self, ForgeApi};
+
+pub fn register(revset_exts: &mut RevsetExtensions, token: Option<String>) {
+    revset_exts.add_custom_function("forge_pr", forge_pr_function);
+    revset_exts.add_symbol_resolver(Box::new(PullRequestResolverExtension {
+        token,
+        cache: Default::default(),
+    }));
+}
+
+/// `forge_pr(123)` is lowered to `base..head`, where base and head are symbols
+/// resolved by [`PullRequestResolver`]
+fn forge_pr_function(
+    _diagnostics: &mut RevsetDiagnostics,
+    function: &revset::FunctionCallNode,
+    _context: &revset::LoweringContext,
+) -> Result<Arc<revset::UserRevsetExpression>, revset::RevsetParseError> {
+    let [arg] = function.expect_exact_arguments()?;
+    let number: u64 = revset::expect_literal("pull request number", arg)?;
+    let base = RevsetExpression::symbol(format!("forge_pr({number}).base"));
+    let head = RevsetExpression::symbol(format!("forge_pr({number}).head"));
+    Ok(base.range(&head))
+}
+
+/// (base, head) of a pull request
+type PullRequestCache = Arc<Mutex<HashMap<u64, (CommitId, CommitId)>>>;
+
+struct PullRequestResolverExtension {
+    token: Option<String>,
+    // Revsets are resolved again on every change, so don't fetch the same
+    // pull request repeatedly
+    cache: PullRequestCache,
+}
+
+impl SymbolResolverExtension for PullRequestResolverExtension {
+    fn new_resolvers<'a>(
+        &self,
+        context_repo: &'a dyn Repo,
+    ) -> Vec<Box<dyn PartialSymbolResolver + 'a>> {
+        let api = jj_lib::git::get_git_repo(context_repo.store())
+            .ok()
+            .and_then(|git_repo| {
+                let url = git_repo.config_snapshot().string("remote.origin.url")?;
+                forge::api_from_remote(&url.to_string())
+            });
+        vec![Box::new(PullRequestResolver {
+            api,
+            token: self.token.clone(),
+            cache: self.cache.clone(),
+        })]
+    }
+}
+
+struct PullRequestResolver {
+    api: Option<ForgeApi>,
+    token: Option<String>,
+    cache: PullRequestCache,
+}
+
+impl PullRequestResolver {
+    fn fetch(&self, number: u64) -> Result<(CommitId, CommitId), String> {
+        if let Some(ids) = self.cache.lock().unwrap().get(&number) {
+            return Ok(ids.clone());
+        }
+        let Some(api) = &self.api else {
+            return Err("forge_pr() needs a GitHub or GitLab \"origin\" remote".to_owned());
+        };
+        let response = api
+            .get(&api.pull_request_url(number), self.token.as_deref())
+            .map_err(|e| format!("Failed to fetch pull request {number}: {e}"))?;
+        let (base, head) = api
+            .parse_pull_request(&response)
+            .ok_or_else(|| format!("Unexpected response for pull request {number}"))?;
+        let to_commit_id = |hex: &str| {
+            CommitId::try_from_hex(hex)
+                .ok_or_else(|| format!("Invalid commit ID in pull request {number}: {hex}"))
+        };
+        let ids = (to_commit_id(&base)?, to_commit_id(&head)?);
+        self.cache.lock().unwrap().insert(number, ids.clone());
+        Ok(ids)
+    }
+}
+
+impl PartialSymbolResolver for PullRequestResolver {
+    fn resolve_symbol(
+        &self,
+        repo: &dyn Repo,
+        symbol: &str,
+    ) -> Result<Option<CommitId>, RevsetResolutionError> {
+        let Some((number, end)) = parse_symbol(symbol) else {
+            return Ok(None);
+        };
+        let (base, head) = self
+            .fetch(number)
+            .map_err(|e| RevsetResolutionError::Other(e.into()))?;
+        let commit_id = if end == "base" { base } else { head };
+        let fetched = repo
+            .index()
+            .has_id(&commit_id)
+            .map_err(|e| RevsetResolutionError::Other(e.into()))?;
+        if !fetched {
+            return Err(RevsetResolutionError::Other(
+                format!(
+                    "Commit {} of pull request {number} has not been fetched",
+                    commit_id.hex()
+                )
+                .into(),
+            ));
+        }
+        Ok(Some(commit_id))
+    }
+}
+
+/// Parses "forge_pr(<number>).base" and "forge_pr(<number>).head"
+fn parse_symbol(symbol: &str) -> Option<(u64, &str)> {
+    let (number, end) = symbol.strip_prefix("forge_pr(")?.split_once(").")?;
+    if end != "base" && end != "head" {
+        return None;
+    }
+    Some((number.parse().ok()?, end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbols() {
+        assert_eq!(parse_symbol("forge_pr(12).base"), Some((12, "base")));
+        assert_eq!(parse_symbol("forge_pr(12).head"), Some((12, "head")));
+        assert_eq!(parse_symbol("forge_pr(12).tail"), None);
+        assert_eq!(parse_symbol("forge_pr(x).head"), None);
+        assert_eq!(parse_symbol("main"), None);
+    }
+}
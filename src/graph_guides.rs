@@ -0,0 +1,272 @@
+//! Guides drawn behind the graph, so that the rows of the layered layout can
+//! be read: shaded bands for the generations, lines where the committer dates
+//! pass into another day or month, and a line above the immutable commits.
+//! In the immutable boundary focus mode, the line is drawn more prominently,
+//! with the immutable side shaded.
+
+use eframe::egui::{self, Rect, Shape, Stroke};
+use serde::{Deserialize, Serialize};
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Guide {
+    /// Every other row shaded, numbered from the top
+    GenerationBands,
+    /// Lines between the rows whose newest commits are from different days,
+    /// or months for long timelines
+    DateLines,
+    /// Line below the lowest row with mutable commits, when immutable commits
+    /// follow
+    ImmutableBoundary,
+}
+
+impl Guide {
+    pub const ALL: [Self; 3] = [
+        Self::GenerationBands,
+        Self::DateLines,
+        Self::ImmutableBoundary,
+    ];
+}
+
+/// Timelines longer than this get lines between months instead of days
+const MONTHS_AFTER_DAYS: i64 = 90;
+
+/// Nodes at the same height in the layout
+#[derive(Debug, PartialEq)]
+struct Row {
+    /// On the screen
+    y: f32,
+    /// Newest committer time of the nodes, in seconds since the epoch
+    newest: Option<i64>,
+    mutable: bool,
+}
+
+/// A node, as its height on the screen, its committer time and whether it is
+/// mutable
+pub type GuideNode = (f32, Option<i64>, bool);
+
+/// The rows of the nodes, from the top
+fn rows(nodes: &[GuideNode]) -> Vec<Row> {
+    let mut nodes = nodes.to_vec();
+    nodes.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let mut rows: Vec<Row> = vec![];
+    for (y, time, mutable) in nodes {
+        match rows.last_mut() {
+            // The layout puts the nodes of a row at exactly the same height
+            Some(row) if (row.y - y).abs() < 0.5 => {
+                row.newest = row.newest.max(time);
+                row.mutable |= mutable;
+            }
+            _ => rows.push(Row {
+                y,
+                newest: time,
+                mutable,
+            }),
+        }
+    }
+    rows
+}
+
+/// The rows after which the period of the newest commit changes, with the
+/// period of the rows above
+fn date_lines(rows: &[Row]) -> Vec<(usize, String)> {
+    let times = || rows.iter().filter_map(|row| row.newest);
+    let (Some(oldest), Some(newest)) = (times().min(), times().max()) else {
+        return vec![];
+    };
+    let format = if newest - oldest > MONTHS_AFTER_DAYS * 24 * 60 * 60 {
+        "%Y-%m"
+    } else {
+        "%Y-%m-%d"
+    };
+    let period = |row: &Row| {
+        let time = chrono::DateTime::from_timestamp(row.newest?, 0)?;
+        Some(
+            time.with_timezone(&chrono::Local)
+                .format(format)
+                .to_string(),
+        )
+    };
+    let periods: Vec<_> = rows.iter().map(period).collect();
+    let mut lines = vec![];
+    let mut above: Option<&String> = None;
+    for (i, period) in periods.iter().enumerate() {
+        let Some(period) = period else {
+            continue;
+        };
+        if let Some(above) = above
+            && above != period
+        {
+            lines.push((i - 1, above.clone()));
+        }
+        above = Some(period);
+    }
+    lines
+}
+
+/// The last row with mutable commits, when it is followed by rows of only
+/// immutable commits
+fn immutable_boundary(rows: &[Row]) -> Option<usize> {
+    let last_mutable = rows.iter().rposition(|row| row.mutable)?;
+    (last_mutable + 1 < rows.len()).then_some(last_mutable)
+}
+
+/// Shapes for the guides, spanning the graph area and clipped to it.
+/// `emphasize_boundary` draws the immutable boundary prominently, also when it
+/// is not among the `guides`.
+pub fn guide_shapes(
+    ui: &egui::Ui,
+    guides: &[Guide],
+    emphasize_boundary: bool,
+    rect: Rect,
+    nodes: &[GuideNode],
+) -> Vec<Shape> {
+    let rows = rows(nodes);
+    if guides.is_empty() && !emphasize_boundary || rows.is_empty() {
+        return vec![];
+    }
+    // Single rows get the space of a typical row
+    let spacing = rows.get(1).map_or(40., |second| second.y - rows[0].y);
+    // Halfway to the next and the previous row
+    let below = |i: usize| match rows.get(i + 1) {
+        Some(next) => (rows[i].y + next.y) / 2.,
+        None => rows[i].y + spacing / 2.,
+    };
+    let above = |i: usize| match i.checked_sub(1) {
+        Some(previous) => below(previous),
+        None => rows[0].y - spacing / 2.,
+    };
+    let visible = |y: f32| rect.y_range().contains(y);
+    let visuals = ui.visuals();
+    let font = egui::TextStyle::Small.resolve(ui.style());
+    let text = |pos, align, text: String, color| {
+        ui.fonts_mut(|f| Shape::text(f, pos, align, text, font.clone(), color))
+    };
+    let line = |y: f32, stroke: Stroke| {
+        Shape::line_segment(
+            [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+            stroke,
+        )
+    };
+    let mut shapes = vec![];
+    if guides.contains(&Guide::GenerationBands) {
+        for i in (0..rows.len()).step_by(2) {
+            let band = Rect::from_x_y_ranges(rect.x_range(), above(i)..=below(i));
+            shapes.push(Shape::rect_filled(
+                band.intersect(rect),
+                0.,
+                visuals.faint_bg_color,
+            ));
+        }
+        for (i, row) in rows.iter().enumerate().filter(|(_, row)| visible(row.y)) {
+            let pos = egui::pos2(rect.left() + 4., row.y);
+            shapes.push(text(
+                pos,
+                egui::Align2::LEFT_CENTER,
+                i.to_string(),
+                visuals.weak_text_color(),
+            ));
+        }
+    }
+    if guides.contains(&Guide::DateLines) {
+        let stroke = visuals.widgets.noninteractive.bg_stroke;
+        for (i, period) in date_lines(&rows) {
+            let y = below(i);
+            if !visible(y) {
+                continue;
+            }
+            shapes.push(line(y, stroke));
+            let pos = egui::pos2(rect.right() - 4., y);
+            shapes.push(text(
+                pos,
+                egui::Align2::RIGHT_BOTTOM,
+                period,
+                visuals.weak_text_color(),
+            ));
+        }
+    }
+    if emphasize_boundary && let Some(y) = immutable_boundary(&rows).map(below) {
+        let color = visuals.warn_fg_color;
+        if y < rect.bottom() {
+            let immutable_side =
+                Rect::from_x_y_ranges(rect.x_range(), y.max(rect.top())..=rect.bottom());
+            shapes.push(Shape::rect_filled(
+                immutable_side,
+                0.,
+                color.gamma_multiply(0.08),
+            ));
+        }
+        if visible(y) {
+            shapes.push(line(y, Stroke::new(3., color)));
+            let right = rect.right() - 4.;
+            shapes.push(text(
+                egui::pos2(right, y - 3.),
+                egui::Align2::RIGHT_BOTTOM,
+                tr!("guide-mutable"),
+                color,
+            ));
+            shapes.push(text(
+                egui::pos2(right, y + 3.),
+                egui::Align2::RIGHT_TOP,
+                tr!("guide-immutable"),
+                color,
+            ));
+        }
+    } else if guides.contains(&Guide::ImmutableBoundary)
+        && let Some(y) = immutable_boundary(&rows).map(below)
+        && visible(y)
+    {
+        let color = visuals.warn_fg_color;
+        shapes.extend(Shape::dashed_line(
+            &[egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+            Stroke::new(1.5, color),
+            8.,
+            4.,
+        ));
+        let pos = egui::pos2(rect.right() - 4., y + 2.);
+        shapes.push(text(
+            pos,
+            egui::Align2::RIGHT_TOP,
+            tr!("guide-immutable"),
+            color,
+        ));
+    }
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn finds_rows_and_boundaries() {
+        // A day apart, so on different local days
+        let noon = 1_700_000_000;
+        let nodes = [
+            (10., Some(noon + DAY), true),
+            (30., Some(noon), false),
+            (10., Some(noon), false),
+            (20., Some(noon + DAY), true),
+            (40., None, false),
+        ];
+        let rows = rows(&nodes);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[0],
+            Row {
+                y: 10.,
+                newest: Some(noon + DAY),
+                mutable: true
+            }
+        );
+        assert_eq!(immutable_boundary(&rows), Some(1));
+        let lines = date_lines(&rows);
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].0, 1);
+        assert!(immutable_boundary(&rows[..2]).is_none());
+    }
+}
@@ -39,6 +39,17 @@ impl History {
         self.last_is_tentative = is_tentative;
     }
 
+    pub fn items(&self) -> &[String] {
+        &self.items
+    }
+
+    /// Rewrites the items in place, e.g. after a bookmark was renamed
+    pub fn map_items(&mut self, f: impl Fn(&str) -> String) {
+        for item in &mut self.items {
+            *item = f(item);
+        }
+    }
+
     pub fn prev(&mut self) {
         self.view_pos = self.view_pos.saturating_sub(1);
     }
@@ -173,6 +184,17 @@ mod tests {
         assert_eq!(h.items, vec!["::", "@--"]);
     }
 
+    #[test]
+    fn map_items() {
+        let mut h = History::new(10);
+        h.add("@", false);
+        h.add("@-", false);
+        h.prev();
+        h.map_items(|item| item.replace('@', "x"));
+        assert_eq!(h.items(), ["x", "x-"]);
+        assert_eq!(h.get(), Some("x"));
+    }
+
     #[test]
     fn keep_tentative_on_navigation() {
         let mut h = History::new(10);
@@ -0,0 +1,174 @@
+//! Translations of the user interface, using Fluent. The messages are in
+//! `locales/<language>/main.ftl`, embedded in the binary. Messages missing in a
+//! translation are shown in English.
+//!
+//! To add a language, copy `locales/en/main.ftl`, translate it and add it to
+//! [`LANGUAGES`].
+
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource};
+use std::sync::OnceLock;
+use unic_langid::LanguageIdentifier;
+
+/// Language code and messages of each translation. The first is the fallback.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", include_str!("../locales/en/main.ftl")),
+    ("sv", include_str!("../locales/sv/main.ftl")),
+];
+
+static LOCALIZER: OnceLock<Localizer> = OnceLock::new();
+
+/// Looks up a message in the chosen language, e.g. `tr!("run")` or
+/// `tr!("wrote-file", path = path.display().to_string())`.
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::message($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::message($id, Some(&args))
+    }};
+}
+pub(crate) use tr;
+
+struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    fn new(language: &str) -> Self {
+        let (fallback_language, _) = LANGUAGES[0];
+        Self {
+            bundle: bundle(language),
+            fallback: (language != fallback_language).then(|| bundle(fallback_language)),
+        }
+    }
+
+    fn message(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        [Some(&self.bundle), self.fallback.as_ref()]
+            .into_iter()
+            .flatten()
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = vec![];
+                Some(
+                    bundle
+                        .format_pattern(pattern, args, &mut errors)
+                        .into_owned(),
+                )
+            })
+            // Makes missing messages easy to spot
+            .unwrap_or_else(|| id.to_owned())
+    }
+}
+
+fn bundle(language: &str) -> FluentBundle<FluentResource> {
+    let (_, source) = LANGUAGES
+        .iter()
+        .find(|(code, _)| *code == language)
+        .expect("Unknown language");
+    let language_id: LanguageIdentifier = language.parse().unwrap();
+    let mut bundle = FluentBundle::new_concurrent(vec![language_id]);
+    // The isolation marks show up as boxes in egui
+    bundle.set_use_isolating(false);
+    let resource = FluentResource::try_new(source.to_string()).expect("Invalid translation");
+    bundle.add_resource(resource).expect("Duplicate message");
+    bundle
+}
+
+/// Chooses the language of the messages. `language` is from the settings and
+/// takes precedence over the locale environment variables. Unsupported
+/// languages give English.
+///
+/// Messages looked up before this is called are in English.
+pub fn init(language: Option<&str>) {
+    let env_language = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty());
+    let language = language
+        .or(env_language.as_deref())
+        .and_then(supported_language)
+        .unwrap_or(LANGUAGES[0].0);
+    let _ = LOCALIZER.set(Localizer::new(language));
+}
+
+/// The supported language of a language tag or POSIX locale, like "sv",
+/// "sv-FI" or "sv_SE.UTF-8"
+fn supported_language(locale: &str) -> Option<&'static str> {
+    let language = locale.split(['_', '-', '.', '@']).next()?;
+    LANGUAGES
+        .iter()
+        .map(|(code, _)| *code)
+        .find(|code| language.eq_ignore_ascii_case(code))
+}
+
+/// Use [`tr!`]
+pub fn message(id: &str, args: Option<&FluentArgs>) -> String {
+    LOCALIZER
+        .get_or_init(|| Localizer::new(LANGUAGES[0].0))
+        .message(id, args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    fn message_ids(source: &str) -> BTreeSet<String> {
+        let resource = FluentResource::try_new(source.to_owned()).unwrap();
+        resource
+            .entries()
+            .filter_map(|entry| match entry {
+                fluent_syntax::ast::Entry::Message(message) => Some(message.id.name.to_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn translations_are_complete() {
+        let (_, english) = LANGUAGES[0];
+        for (language, source) in LANGUAGES {
+            assert_eq!(
+                message_ids(source),
+                message_ids(english),
+                "Messages of {language}"
+            );
+        }
+    }
+
+    #[test]
+    fn formats_messages() {
+        let localizer = Localizer::new("sv");
+        let mut args = FluentArgs::new();
+        args.set("limit", 100);
+        assert_eq!(
+            localizer.message("node-limit", Some(&args)),
+            "Gränsen på 100 noder nåddes. Grafen är ofullständig."
+        );
+        assert_eq!(
+            Localizer::new("en").message(
+                "sample-created",
+                Some(&FluentArgs::from_iter([("path", "x")]))
+            ),
+            "Sample repository created in \"x\". Run the following command to explore it:\n\
+             revset-explorer -R x"
+        );
+        assert_eq!(
+            localizer.message("no-such-message", None),
+            "no-such-message"
+        );
+    }
+
+    #[test]
+    fn chooses_language() {
+        assert_eq!(supported_language("sv_SE.UTF-8"), Some("sv"));
+        assert_eq!(supported_language("sv-FI"), Some("sv"));
+        assert_eq!(supported_language("en"), Some("en"));
+        assert_eq!(supported_language("C"), None);
+        assert_eq!(supported_language("de_DE"), None);
+    }
+}
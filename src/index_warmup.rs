@@ -0,0 +1,87 @@
+//! Loading of the repository index on a background thread at startup.
+//! In very large repositories, the first revset evaluation otherwise stalls
+//! the window while the commit graph is read and the change IDs are indexed.
+//! The graph waits until the index is ready, showing that it is loading.
+
+use eframe::egui::{self, RichText};
+use jj_lib::repo::Repo;
+use revset_explorer_core::jjgraph::JjGraph;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::i18n::tr;
+
+/// Small repositories are ready within this, so that their graph is shown
+/// without a flash of the loading indicator
+const WAIT: Duration = Duration::from_millis(300);
+
+pub struct IndexWarmup {
+    // Dropped when the index is ready
+    receiver: Option<mpsc::Receiver<()>>,
+}
+
+impl IndexWarmup {
+    /// Starts loading the index and waits for it for a moment
+    pub fn start(jj_graph: &JjGraph) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let jj_graph = jj_graph.clone();
+        // Not queued behind other work on the shared threads
+        std::thread::spawn(move || {
+            warm(&jj_graph);
+            let _ = sender.send(());
+        });
+        let mut warmup = Self {
+            receiver: Some(receiver),
+        };
+        if let Some(receiver) = &warmup.receiver
+            && receiver.recv_timeout(WAIT).is_ok()
+        {
+            warmup.receiver = None;
+        }
+        warmup
+    }
+
+    pub fn is_ready(&mut self) -> bool {
+        if let Some(receiver) = &self.receiver
+            // Also done if the warm-up panicked
+            && !matches!(receiver.try_recv(), Err(mpsc::TryRecvError::Empty))
+        {
+            self.receiver = None;
+        }
+        self.receiver.is_none()
+    }
+
+    /// Shown instead of the graph until the index is ready
+    pub fn ui(&self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label(tr!("index-loading"));
+        });
+        ui.label(RichText::new(tr!("index-loading-hint")).weak());
+    }
+}
+
+/// Reads what the first evaluations need from the index
+fn warm(jj_graph: &JjGraph) {
+    let repo = jj_graph.get_repo();
+    // The change ID index is built on first use, for the shortest unique
+    // prefixes in the labels
+    let _ = repo.shortest_unique_change_id_prefix_len(repo.store().root_change_id());
+    if let Ok(revset) = jj_graph.get_revset("all()") {
+        for _ in revset.iter() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn small_repositories_are_ready_at_start() {
+        let mut repo = TestRepo::init();
+        repo.commit("a", &[]);
+        let jj_graph = JjGraph::new(repo.path()).unwrap();
+        assert!(IndexWarmup::start(&jj_graph).is_ready());
+    }
+}
@@ -0,0 +1,158 @@
+//! Running the jj command line tool
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+
+/// Commands that modify the repository
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mutation {
+    New(CommitId),
+    Edit(CommitId),
+    Abandon(CommitId),
+    Rebase {
+        revision: CommitId,
+        destination: CommitId,
+    },
+    MoveBookmark {
+        name: String,
+        to: CommitId,
+        /// Needed when the target is not a descendant of the bookmark
+        allow_backwards: bool,
+    },
+    /// Snapshots the working copy. Every jj command does, and `jj status`
+    /// changes nothing else.
+    Snapshot,
+}
+
+impl Mutation {
+    pub fn args(&self) -> Vec<String> {
+        match self {
+            Mutation::New(commit_id) => vec!["new".to_owned(), commit_id.hex()],
+            Mutation::Edit(commit_id) => vec!["edit".to_owned(), commit_id.hex()],
+            Mutation::Abandon(commit_id) => vec!["abandon".to_owned(), commit_id.hex()],
+            Mutation::Rebase {
+                revision,
+                destination,
+            } => vec![
+                "rebase".to_owned(),
+                "-r".to_owned(),
+                revision.hex(),
+                "-d".to_owned(),
+                destination.hex(),
+            ],
+            Mutation::MoveBookmark {
+                name,
+                to,
+                allow_backwards,
+            } => {
+                let mut args = vec![
+                    "bookmark".to_owned(),
+                    "move".to_owned(),
+                    name.clone(),
+                    "--to".to_owned(),
+                    to.hex(),
+                ];
+                if *allow_backwards {
+                    args.push("--allow-backwards".to_owned());
+                }
+                args
+            }
+            Mutation::Snapshot => vec!["status".to_owned()],
+        }
+    }
+}
+
+pub fn diff_args(commit_id: &CommitId, tool: Option<&str>) -> Vec<String> {
+    let mut args = vec!["diff".to_owned(), "-r".to_owned(), commit_id.hex()];
+    if let Some(tool) = tool {
+        args.push("--tool".to_owned());
+        args.push(tool.to_owned());
+    }
+    args
+}
+
+fn jj_command(repo_path: &Path, args: &[String]) -> Command {
+    let mut command = Command::new("jj");
+    command.arg("-R").arg(repo_path).args(args);
+    command
+}
+
+/// Runs jj and waits for it to finish
+pub fn run(repo_path: &Path, args: &[String]) -> anyhow::Result<()> {
+    let output = jj_command(repo_path, args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| anyhow::anyhow!(tr!("jj-failed", error = e.to_string())))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "jj {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+/// Runs jj in the background, without waiting for it to finish. Failures are
+/// reported through `error_sender`.
+pub fn spawn(
+    repo_path: &Path,
+    args: Vec<String>,
+    error_sender: mpsc::Sender<String>,
+    ctx: egui::Context,
+) {
+    let repo_path = PathBuf::from(repo_path);
+    std::thread::spawn(move || {
+        let result = jj_command(&repo_path, &args)
+            .stdin(Stdio::null())
+            .stderr(Stdio::piped())
+            .output();
+        let error = match result {
+            Ok(output) if output.status.success() => return,
+            Ok(output) => format!(
+                "jj {} failed: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            Err(e) => tr!("jj-failed", error = e.to_string()),
+        };
+        let _ = error_sender.send(error);
+        ctx.request_repaint();
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rebase_args() {
+        let revision = CommitId::from_hex("aaaa");
+        let destination = CommitId::from_hex("bbbb");
+        assert_eq!(
+            Mutation::Rebase {
+                revision,
+                destination
+            }
+            .args(),
+            vec!["rebase", "-r", "aaaa", "-d", "bbbb"]
+        );
+    }
+
+    #[test]
+    fn diff_args_with_tool() {
+        let commit_id = CommitId::from_hex("abcd");
+        assert_eq!(diff_args(&commit_id, None), vec!["diff", "-r", "abcd"]);
+        assert_eq!(
+            diff_args(&commit_id, Some("meld")),
+            vec!["diff", "-r", "abcd", "--tool", "meld"]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Keyboard shortcuts. The handlers and the shortcut overlay both use these,
+//! so that the overlay lists what actually works.
+
+use eframe::egui::{Key, KeyboardShortcut, Modifiers, gui_zoom::kb_shortcuts};
+
+use crate::i18n::tr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Area {
+    /// A revset input has focus
+    Revsets,
+    /// A node has focus
+    Graph,
+    Panels,
+}
+
+impl Area {
+    pub const ALL: [Self; 3] = [Self::Revsets, Self::Graph, Self::Panels];
+
+    pub fn name(self) -> String {
+        match self {
+            Area::Revsets => tr!("shortcuts-revsets"),
+            Area::Graph => tr!("shortcuts-graph"),
+            Area::Panels => tr!("shortcuts-panels"),
+        }
+    }
+}
+
+pub struct Binding {
+    pub area: Area,
+    /// Any of them triggers the action
+    pub shortcuts: &'static [KeyboardShortcut],
+    /// Message ID of the description
+    pub description: &'static str,
+}
+
+const fn key(key: Key) -> KeyboardShortcut {
+    KeyboardShortcut::new(Modifiers::NONE, key)
+}
+
+pub const PREVIOUS_REVSET: KeyboardShortcut = key(Key::ArrowUp);
+pub const NEXT_REVSET: KeyboardShortcut = key(Key::ArrowDown);
+/// Insert the first completion, while they are shown
+pub const COMPLETE_REVSET: KeyboardShortcut = key(Key::Tab);
+pub const TOGGLE_NODE: KeyboardShortcut = key(Key::Space);
+pub const SELECT_NODE: KeyboardShortcut = key(Key::Enter);
+pub const NODE_MENU: KeyboardShortcut = KeyboardShortcut::new(Modifiers::SHIFT, Key::F10);
+pub const SHORTCUTS: KeyboardShortcut = key(Key::Questionmark);
+pub const RELOAD: KeyboardShortcut = key(Key::F5);
+/// Jump to the saved viewpoints, in order
+pub const VIEWPOINTS: [KeyboardShortcut; 9] = [
+    key(Key::Num1),
+    key(Key::Num2),
+    key(Key::Num3),
+    key(Key::Num4),
+    key(Key::Num5),
+    key(Key::Num6),
+    key(Key::Num7),
+    key(Key::Num8),
+    key(Key::Num9),
+];
+
+/// All shortcuts, including those handled by egui
+pub const BINDINGS: &[Binding] = &[
+    Binding {
+        area: Area::Revsets,
+        shortcuts: &[PREVIOUS_REVSET],
+        description: "shortcut-previous-revset",
+    },
+    Binding {
+        area: Area::Revsets,
+        shortcuts: &[NEXT_REVSET],
+        description: "shortcut-next-revset",
+    },
+    Binding {
+        area: Area::Revsets,
+        shortcuts: &[COMPLETE_REVSET],
+        description: "shortcut-complete-revset",
+    },
+    Binding {
+        area: Area::Graph,
+        shortcuts: &[
+            key(Key::Tab),
+            KeyboardShortcut::new(Modifiers::SHIFT, Key::Tab),
+        ],
+        description: "shortcut-next-node",
+    },
+    Binding {
+        area: Area::Graph,
+        shortcuts: &[
+            key(Key::ArrowUp),
+            key(Key::ArrowDown),
+            key(Key::ArrowLeft),
+            key(Key::ArrowRight),
+        ],
+        description: "shortcut-nearby-node",
+    },
+    Binding {
+        area: Area::Graph,
+        shortcuts: &[TOGGLE_NODE],
+        description: "shortcut-toggle-node",
+    },
+    Binding {
+        area: Area::Graph,
+        shortcuts: &[SELECT_NODE],
+        description: "shortcut-select-node",
+    },
+    Binding {
+        area: Area::Graph,
+        shortcuts: &[NODE_MENU],
+        description: "shortcut-node-menu",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &[SHORTCUTS],
+        description: "shortcut-shortcuts",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &VIEWPOINTS,
+        description: "shortcut-viewpoint",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &[RELOAD],
+        description: "menu-reload",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &[key(Key::Escape)],
+        description: "shortcut-close",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &[kb_shortcuts::ZOOM_IN],
+        description: "menu-zoom-in",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &[kb_shortcuts::ZOOM_OUT],
+        description: "menu-zoom-out",
+    },
+    Binding {
+        area: Area::Panels,
+        shortcuts: &[kb_shortcuts::ZOOM_RESET],
+        description: "menu-zoom-reset",
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bindings_are_unique_and_described() {
+        for (i, binding) in BINDINGS.iter().enumerate() {
+            assert_ne!(
+                crate::i18n::message(binding.description, None),
+                binding.description
+            );
+            for other in &BINDINGS[i + 1..] {
+                if other.area == binding.area {
+                    assert!(
+                        (other.shortcuts.iter()).all(|s| !binding.shortcuts.contains(s)),
+                        "{} and {}",
+                        binding.description,
+                        other.description
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Placement of the node labels so that they don't cover each other or other
+//! nodes in dense parts of the graph. Each label is tried at its normal
+//! position, then shifted up or down, and hidden if it fits nowhere. Labels of
+//! hovered and selected nodes are placed first and never moved, so they win.
+
+use eframe::egui::{Rect, Vec2};
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LabelPlacement {
+    #[default]
+    Normal,
+    /// Moved down by this many label heights, up if negative
+    Shifted(f32),
+    Hidden,
+}
+
+/// Tried after the normal position
+const SHIFTS: [f32; 2] = [-0.6, 0.6];
+
+pub struct Label {
+    /// Screen area of the label at its normal position
+    pub rect: Rect,
+    /// Screen area of the node the label belongs to
+    pub node: Rect,
+    /// Hovered or selected
+    pub priority: bool,
+}
+
+/// Where to put each of the labels. Earlier labels win over later ones, so
+/// they should be given in reading order.
+pub fn place_labels(labels: &[Label]) -> Vec<LabelPlacement> {
+    let mut placements = vec![LabelPlacement::Hidden; labels.len()];
+    let mut placed: Vec<Rect> = vec![];
+    let order = (0..labels.len())
+        .filter(|&i| labels[i].priority)
+        .chain((0..labels.len()).filter(|&i| !labels[i].priority));
+    for i in order {
+        let label = &labels[i];
+        if label.priority {
+            placements[i] = LabelPlacement::Normal;
+            placed.push(label.rect);
+            continue;
+        }
+        // Touching is fine
+        let overlaps = |rect: Rect| {
+            let rect = rect.shrink(0.5);
+            placed.iter().any(|other| other.intersects(rect))
+                || (labels.iter().enumerate())
+                    .any(|(j, other)| j != i && other.node.intersects(rect))
+        };
+        let candidates = std::iter::once(0.).chain(SHIFTS);
+        let fit = candidates
+            .map(|shift| {
+                (
+                    shift,
+                    label
+                        .rect
+                        .translate(Vec2::new(0., shift * label.rect.height())),
+                )
+            })
+            .find(|(_, rect)| !overlaps(*rect));
+        if let Some((shift, rect)) = fit {
+            placements[i] = if shift == 0. {
+                LabelPlacement::Normal
+            } else {
+                LabelPlacement::Shifted(shift)
+            };
+            placed.push(rect);
+        }
+    }
+    placements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::{Pos2, pos2, vec2};
+
+    fn label(y: f32, priority: bool) -> Label {
+        let node = Rect::from_center_size(pos2(0., y), vec2(10., 10.));
+        Label {
+            rect: Rect::from_min_size(Pos2::new(10., y - 5.), vec2(100., 10.)),
+            node,
+            priority,
+        }
+    }
+
+    #[test]
+    fn shifts_and_hides_overlapping_labels() {
+        // Far apart
+        assert_eq!(
+            place_labels(&[label(0., false), label(100., false)]),
+            vec![LabelPlacement::Normal; 2]
+        );
+        // Half overlapping, so the second fits below
+        assert_eq!(
+            place_labels(&[label(0., false), label(5., false)]),
+            vec![LabelPlacement::Normal, LabelPlacement::Shifted(0.6)]
+        );
+        // On top of each other
+        let placements = place_labels(&[label(0., false), label(0., false), label(0., false)]);
+        assert_eq!(placements[0], LabelPlacement::Normal);
+        assert_eq!(placements[2], LabelPlacement::Hidden);
+        // The hovered one wins
+        let placements = place_labels(&[label(0., false), label(0., true)]);
+        assert_eq!(placements[1], LabelPlacement::Normal);
+        assert_ne!(placements[0], LabelPlacement::Normal);
+    }
+}
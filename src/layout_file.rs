@@ -0,0 +1,39 @@
+//! Node positions saved to a file, so that the same repository and revset
+//! always give the same picture, e.g. for documentation screenshots and visual
+//! regression tests. The layout has no randomness, but it changes with the
+//! graph, like when a commit is added or the layout algorithm is improved.
+
+use anyhow::Context;
+use eframe::egui::Pos2;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct LayoutFile {
+    /// Canvas position of each node, by full commit ID. Nodes that are not in
+    /// the file keep their normal positions.
+    pub positions: BTreeMap<String, [f32; 2]>,
+}
+
+impl LayoutFile {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid layout in {}", path.display()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn insert(&mut self, commit_id: &CommitId, pos: Pos2) {
+        self.positions.insert(commit_id.hex(), [pos.x, pos.y]);
+    }
+
+    pub fn position(&self, commit_id: &CommitId) -> Option<Pos2> {
+        (self.positions.get(&commit_id.hex())).map(|&[x, y]| Pos2::new(x, y))
+    }
+}
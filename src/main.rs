@@ -2,65 +2,444 @@ use anyhow::Context;
 use clap::Parser;
 use eframe::egui::{self, RichText, ecolor};
 use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use jj_lib::op_store::OperationId;
 use jj_lib::repo::Repo;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
-use crate::node_shape::NodeShape;
+use crate::edge_shape::{ArrowDirection, Arrowheads, EdgeRouting, EdgeShape};
+use crate::i18n::tr;
+use crate::node_shape::{NodeShape, NodeSize};
+use revset_explorer_core::annotations::{self, AnnotationStatus};
+use revset_explorer_core::bookmark_move::{BookmarkMove, preview_bookmark_move};
+use revset_explorer_core::clusters::bookmark_clusters;
+use revset_explorer_core::color_vision::Deficiency;
+use revset_explorer_core::distance::generation_distances;
+use revset_explorer_core::lint;
+use revset_explorer_core::mark::{FilterMatch, Marking, Membership, NodeType, Palette};
+use revset_explorer_core::rebase_preview::{RebasePreview, preview_rebase};
+use revset_explorer_core::resolve::ordering_functions;
+use revset_explorer_core::signatures::SignatureStatus;
+use revset_explorer_core::snapshot::Snapshot;
+use revset_explorer_core::trailers;
+use revset_explorer_core::working_copy;
+use revset_explorer_core::{
+    BuiltGraph, CommitGraph, NodeCount, ResolveError, jjgraph, resolve_revset,
+};
 
+mod about;
+mod author_filter;
+mod author_label;
+mod avatars;
+mod backend_plugins;
+mod background_evaluation;
+mod bookmark_hulls;
+mod change_groups;
+mod change_nodes;
+mod change_sizes;
+mod ci_status;
+mod color_rules;
+mod conflict_view;
+mod date_filter;
+mod description_label;
+mod diff_search;
+mod diff_view;
+mod diffs;
+mod edge_shape;
+mod empty_view;
+mod export;
+mod fonts;
+mod forge;
+mod forge_pr;
+mod graph_guides;
 mod history;
-mod jjgraph;
+mod i18n;
+mod index_warmup;
+mod jj_cli;
+mod keymap;
+mod label_placement;
+mod layout_file;
+mod navigation;
 mod node_shape;
+mod op_log_view;
+mod path_filter;
+mod path_highlight;
+mod perf_report;
+mod pins;
+mod prefetch;
+mod recent;
+mod repo_watch;
+mod result_set;
+mod result_watch;
+mod revset_completion;
+mod revset_simplify;
+mod revset_symbol;
+mod script;
+mod select_evaluation;
+mod session;
+mod settings;
+mod signatures;
+mod symbol_replace;
+mod tabs;
+mod tui;
+mod uri;
+mod view_evaluation;
+mod view_scopes;
+mod viewpoints;
+mod workers;
 
+/// Default of the node limit, which can be changed with --max-nodes and from
+/// the View menu
 const MAX_NODES: usize = 100;
+/// Nodes added to the node limit by the "Load more" button
+const LOAD_MORE_NODES: usize = 100;
+/// Of the graph while a new View is being typed or evaluated
+const STALE_GRAPH_OPACITY: f32 = 0.5;
+/// Default of the `revset_debounce` setting
+const DEFAULT_REVSET_DEBOUNCE_MS: u64 = 300;
+// This is the default log macro in jj: present(@) |
+// ancestors(immutable_heads().., 2) | present(trunk())
+// Set up something that will show the user's commits and a bit into the
+// past, without filling up the view with too many nodes.
+const DEFAULT_VIEW: &str = "ancestors(heads(mutable()), 7)";
+/// View of the immutable boundary focus mode: the mutable commits and the
+/// immutable commits just below them, which rewriting revsets are about
+const BOUNDARY_FOCUS_VIEW: &str = "ancestors(immutable_heads(), 3) | immutable_heads()..";
+/// Diffs with more lines, including context lines, are not shown in the
+/// details panel
+const DEFAULT_MAX_DIFF_LINES: usize = 2000;
+const HEADS_OUTLINE_COLOR: ecolor::Color32 = ecolor::Color32::from_rgb(0x00, 0x74, 0xd9);
+const ROOTS_OUTLINE_COLOR: ecolor::Color32 = ecolor::Color32::from_rgb(0xff, 0x85, 0x1b);
 
 // The undirected graph does not put nodes in nice positions when rendering a hierarchical graph view.
 // type GraphType = egui_graphs::Graph<CommitId, (), petgraph::Undirected>;
-type GraphType =
-    egui_graphs::Graph<CommitId, (), petgraph::Directed, petgraph::csr::DefaultIx, NodeShape>;
-
-enum NodeCount {
-    Exact(usize),
-    AtLeast(usize),
-}
+type GraphType = egui_graphs::Graph<
+    CommitId,
+    (),
+    petgraph::Directed,
+    petgraph::csr::DefaultIx,
+    NodeShape,
+    EdgeShape,
+>;
 
 #[derive(Parser)]
-#[command(name = "Revset Explorer")]
+#[command(name = "Revset Explorer", args_conflicts_with_subcommands = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
     /// Path to the JJ repository to explore
-    #[arg(short = 'R', long, default_value = ".")]
+    #[arg(short = 'R', long, default_value = ".", global = true)]
     repository: PathBuf,
     /// Generate a sample repository to explore. It will create the directory "revset-sample".
     #[arg(long, default_value_t = false)]
     create_sample: bool,
+    /// URL template for opening commits in a web browser, e.g.
+    /// "https://github.com/org/repo/commit/{commit_id}". {change_id} is also
+    /// supported. Overrides the setting in the config file.
+    #[arg(long)]
+    commit_url: Option<String>,
+    /// Allow running jj commands that modify the repository, like `jj new`,
+    /// from the node context menu
+    #[arg(long, default_value_t = false)]
+    allow_mutations: bool,
+    /// Print the selection to stdout when the window is closed, for use by
+    /// editor plugins and scripts. Prints the change IDs of the selected nodes,
+    /// one per line, or the final "select" revset.
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "change-ids")]
+    print_selection_to_stdout: Option<SelectionOutput>,
+    /// Initial "view" revset
+    #[arg(long, global = true)]
+    view: Option<String>,
+    /// Initial "select" revset
+    #[arg(long, global = true)]
+    select: Option<String>,
+    /// Register Revset Explorer as handler of revset-explorer:// links
+    #[arg(long, default_value_t = false)]
+    register_url_handler: bool,
+    /// Shared library adding store factories for custom jj backends, e.g.
+    /// commit backends of large monorepos. Can be repeated. See
+    /// src/backend_plugins.rs for what the library must export.
+    #[arg(long, global = true)]
+    backend_plugin: Vec<PathBuf>,
+    /// TOML or JSON file mapping commit or change IDs to a status and a label,
+    /// like bisect results, shown as badges on the nodes. Read again when the
+    /// repository is reloaded. See revset-explorer-core/src/annotations.rs
+    /// for the format.
+    #[arg(long)]
+    annotations: Option<PathBuf>,
+    /// Record the "view" and "select" revsets entered during the session to a
+    /// JSON Lines file, with timestamps, for replaying with --replay-session
+    #[arg(long)]
+    record_session: Option<PathBuf>,
+    /// Replay a session recorded with --record-session step by step, with
+    /// buttons in the toolbar
+    #[arg(long)]
+    replay_session: Option<PathBuf>,
+    /// Place the nodes at the positions of a layout exported from the File
+    /// menu, so that the same repository and revsets always give the same
+    /// picture, e.g. for screenshots and visual regression tests
+    #[arg(long)]
+    layout: Option<PathBuf>,
+    /// TOML file with steps to do at startup, like setting the revsets,
+    /// saving the graph as an image and closing the window, for making graph
+    /// images in build pipelines. See src/script.rs for the format.
+    #[arg(long)]
+    script: Option<PathBuf>,
+    /// Write the graph of the "view" revset to a Graphviz DOT file without
+    /// opening the window. Short for `export --format dot -o <file>`.
+    #[arg(long, value_name = "FILE")]
+    export_dot: Option<PathBuf>,
+    /// Maximum number of commits in the graph. Defaults to 100. Can be changed
+    /// from the View menu.
+    #[arg(
+        long,
+        global = true,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    max_nodes: Option<usize>,
+    /// Number of threads for the background work, like computing diffs.
+    /// Overrides the setting in the config file. Defaults to the number of
+    /// cores.
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Link to open, like "revset-explorer://open?repo=<path>&view=<revset>&select=<revset>".
+    /// The parameters override the corresponding flags.
+    uri: Option<String>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Show the graph in the terminal instead of in a window
+    Tui,
+    /// Write the graph of the "view" revset to a file, for viewing without the
+    /// repository
+    Export {
+        #[arg(long, value_enum, default_value = "snapshot")]
+        format: ExportFormat,
+        /// Defaults to the format's default file name
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Additional "select" revsets to evaluate, which can be switched
+        /// between in the viewer. Can be repeated.
+        #[arg(long = "selection")]
+        selections: Vec<String>,
+    },
+}
+
+impl Args {
+    /// The subcommand to run, with --export-dot as its export subcommand
+    fn take_command(&mut self) -> Option<Command> {
+        match self.export_dot.take() {
+            Some(output) => Some(Command::Export {
+                format: ExportFormat::Dot,
+                output: Some(output),
+                selections: vec![],
+            }),
+            None => self.command.take(),
+        }
+    }
+}
+
+/// How to load the repository, for each reload
+#[derive(Clone, Default)]
+struct LoadOptions {
+    forge_token: Option<String>,
+    backend_plugins: backend_plugins::BackendPlugins,
+}
+
+/// Options for the app, from the command line
+#[derive(Default)]
+struct AppOptions {
+    backend_plugins: backend_plugins::BackendPlugins,
+    allow_mutations: bool,
+    print_selection: Option<SelectionOutput>,
+    initial_view: Option<String>,
+    initial_select: Option<String>,
+    annotations: Option<PathBuf>,
+    record_session: Option<PathBuf>,
+    replay_session: Option<PathBuf>,
+    layout: Option<PathBuf>,
+    script: Option<PathBuf>,
+    max_nodes: Option<usize>,
+    /// Evaluates the revsets before the next frame is drawn instead of in the
+    /// background, for --script and the tests
+    wait_for_evaluations: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum SelectionOutput {
+    ChangeIds,
+    Revset,
 }
 
 fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
+    let mut args = Args::parse();
+    let command = args.take_command();
+    let mut settings = settings::Settings::load()?;
+    i18n::init(settings.ui.language.as_deref());
+    workers::init(args.threads.or(settings.background.threads));
 
     if args.create_sample {
         create_sample_repo()?;
         return Ok(());
     }
+    if args.register_url_handler {
+        return uri::register_handler();
+    }
+
+    let mut repository = args.repository;
+    let mut initial_view = args.view;
+    let mut initial_select = args.select;
+    let backend_plugins = backend_plugins::BackendPlugins::load(&args.backend_plugin)?;
+    if let Some(uri) = &args.uri {
+        let request = uri::parse(uri)?;
+        repository = request.repo.unwrap_or(repository);
+        initial_view = request.view.or(initial_view);
+        initial_select = request.select.or(initial_select);
+    }
 
-    let repo_path = args
-        .repository
+    let repo_path = repository
         .canonicalize()
-        .context("Cannot find the specified repository")?;
-    println!("Using repository in {}", repo_path.display());
+        .context(tr!("repository-not-found"))?;
+    // Keep stdout clean for --print-selection-to-stdout
+    eprintln!(
+        "{}",
+        tr!("using-repository", path = repo_path.display().to_string())
+    );
+
+    if args.commit_url.is_some() {
+        settings.forge.commit_url = args.commit_url;
+    }
+
+    match command {
+        Some(Command::Tui) => {
+            return tui::run(
+                &repo_path,
+                LoadOptions {
+                    forge_token: settings.forge.token,
+                    backend_plugins,
+                },
+                settings.ui.palette,
+                initial_view.unwrap_or_else(|| DEFAULT_VIEW.to_owned()),
+                initial_select.unwrap_or_default(),
+                args.max_nodes.unwrap_or(MAX_NODES),
+            );
+        }
+        Some(Command::Export {
+            format,
+            output,
+            selections,
+        }) => {
+            let jj_graph = load_jj_graph(
+                &repo_path,
+                LoadOptions {
+                    forge_token: settings.forge.token,
+                    backend_plugins,
+                },
+            )?;
+            let selections: Vec<_> = initial_select.into_iter().chain(selections).collect();
+            let snapshot = Snapshot::create(
+                &jj_graph,
+                repo_path
+                    .file_name()
+                    .unwrap_or_default()
+                    .display()
+                    .to_string(),
+                initial_view.as_deref().unwrap_or(DEFAULT_VIEW),
+                &selections,
+                args.max_nodes.unwrap_or(MAX_NODES),
+            )?;
+            let output = output.unwrap_or_else(|| format.default_path().into());
+            let content = match format {
+                ExportFormat::Snapshot => snapshot.to_json(),
+                ExportFormat::Dot => {
+                    let graph = export::ExportGraph::from_snapshot(
+                        &snapshot,
+                        snapshot.selections.first(),
+                        settings.ui.palette,
+                    );
+                    let select = selections.first().map(String::as_str).unwrap_or_default();
+                    let title = tr!(
+                        "export-html-title",
+                        view = snapshot.view.as_str(),
+                        select = select
+                    );
+                    export::to_dot(&graph, &title)
+                }
+                // Needs the node positions of the GUI
+                ExportFormat::Html
+                | ExportFormat::Layout
+                | ExportFormat::Svg
+                | ExportFormat::Png => unreachable!(),
+            };
+            std::fs::write(&output, content)?;
+            eprintln!("{}", tr!("wrote-file", path = output.display().to_string()));
+            return Ok(());
+        }
+        None => {}
+    }
 
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1024., 768.]),
         ..Default::default()
     };
+    let zoom = settings.ui.zoom;
+    let reduced_motion = settings.ui.reduced_motion;
+    let fonts = fonts::load(&settings.ui.fonts)?;
     eframe::run_native(
-        &format!(
-            "Revset Explorer - {}",
-            repo_path.file_name().unwrap_or_default().display()
+        &tr!(
+            "window-title",
+            repository = repo_path
+                .file_name()
+                .unwrap_or_default()
+                .display()
+                .to_string()
         ),
         options,
-        Box::new(|_cc| Ok(Box::new(ExplorerApp::new(&repo_path)))),
+        Box::new(|cc| {
+            if let Some(zoom) = zoom {
+                cc.egui_ctx.set_zoom_factor(zoom);
+            }
+            if reduced_motion {
+                reduce_motion(&cc.egui_ctx);
+            }
+            fonts::install(&cc.egui_ctx, fonts);
+            let allow_mutations = args.allow_mutations;
+            let app = ExplorerApp::new(
+                &repo_path,
+                settings,
+                AppOptions {
+                    backend_plugins: backend_plugins.clone(),
+                    allow_mutations,
+                    print_selection: args.print_selection_to_stdout,
+                    initial_view,
+                    initial_select,
+                    annotations: args.annotations,
+                    record_session: args.record_session,
+                    replay_session: args.replay_session,
+                    layout: args.layout,
+                    wait_for_evaluations: args.script.is_some(),
+                    script: args.script,
+                    max_nodes: args.max_nodes,
+                },
+            )?;
+            // Tabs get the default revsets, and no options that are about a
+            // single session
+            let open = move |path: &Path| {
+                let options = AppOptions {
+                    backend_plugins: backend_plugins.clone(),
+                    allow_mutations,
+                    max_nodes: args.max_nodes,
+                    ..Default::default()
+                };
+                ExplorerApp::new(path, settings::Settings::load()?, options)
+            };
+            let tabs = tabs::Tabs::new(&repo_path, app, Box::new(open));
+            Ok(Box::new(tabs.with_recent(recent::Recent::load())))
+        }),
     )
     .unwrap();
     Ok(())
@@ -69,10 +448,10 @@ fn main() -> anyhow::Result<()> {
 fn create_sample_repo() -> Result<(), anyhow::Error> {
     let sample_repo_path = PathBuf::from("revset-sample");
     if sample_repo_path.exists() {
-        anyhow::bail!(
-            "Sample repository directory \"{}\" already exists. Please remove it first.",
-            sample_repo_path.display()
-        );
+        anyhow::bail!(tr!(
+            "sample-exists",
+            path = sample_repo_path.display().to_string()
+        ));
     }
     let sample_script = include_str!("create_sample_repo.sh");
     let output = std::process::Command::new("bash")
@@ -83,11 +462,14 @@ fn create_sample_repo() -> Result<(), anyhow::Error> {
         .wait_with_output()?;
     if !output.status.success() {
         println!("{}", String::from_utf8_lossy(&output.stderr));
-        anyhow::bail!("Failed to create sample repository");
+        anyhow::bail!(tr!("sample-failed"));
     }
     println!(
-        "Sample repository created in \"{0}\". Run the following command to explore it:\nrevset-explorer -R {0}",
-        sample_repo_path.display()
+        "{}",
+        tr!(
+            "sample-created",
+            path = sample_repo_path.display().to_string()
+        )
     );
     Ok(())
 }
@@ -99,18 +481,329 @@ struct ExplorerApp {
     last_filter_node_count: Option<NodeCount>,
     view_revset: RevsetEntry,
     last_view_node_count: Option<NodeCount>,
+    // Names completed in the revset inputs, like the bookmarks
+    revset_vocabulary: revset_completion::Vocabulary,
     graph: GraphType,
     node_idxs: Vec<petgraph::graph::NodeIndex>,
     jj_graph: jjgraph::JjGraph,
-    working_copy_commit_id: Option<CommitId>,
+    /// The graph is built once the index has been loaded
+    index_warmup: index_warmup::IndexWarmup,
+    view_evaluation: background_evaluation::BackgroundEvaluation<
+        view_evaluation::Request,
+        view_evaluation::Evaluated,
+    >,
+    select_evaluation: background_evaluation::BackgroundEvaluation<
+        select_evaluation::Request,
+        select_evaluation::Marked,
+    >,
+    repository_path: PathBuf,
+    load_options: LoadOptions,
+    commit_url_template: Option<String>,
+    diff_tool: Option<String>,
+    /// Larger diffs are only shown in the diff tool
+    max_diff_lines: usize,
+    allow_mutations: bool,
+    // Mutation waiting for confirmation by the user
+    pending_mutation: Option<jj_cli::Mutation>,
+    /// Files in the working copy changed after the last snapshot
+    working_copy_stale: bool,
+    repo_reloaded: bool,
+    print_selection: Option<SelectionOutput>,
+    // Node that the context menu was opened for
+    context_menu_node: Option<petgraph::graph::NodeIndex>,
+    // Errors from background jj commands
+    jj_error_sender: mpsc::Sender<String>,
+    jj_error_receiver: mpsc::Receiver<String>,
+    status_message: Option<String>,
+    ci_statuses: Option<ci_status::CiStatuses>,
+    color_by_author: bool,
+    /// Size of the graph labels, relative to the default size
+    label_scale: f32,
+    palette: Palette,
+    // The palette was changed and the nodes need to be colored again
+    recolor: bool,
+    // The view needs to be evaluated again, e.g. after replacing a symbol in
+    // the revsets or pinning a commit
+    view_invalidated: bool,
+    /// Shown instead, when the View has no commits
+    empty_view_fallback: Option<String>,
+    color_rules: Vec<color_rules::ColorRule>,
+    // Select revset chosen outside the toolbar, e.g. in the details panel
+    requested_filter: Option<String>,
+    /// Color vision deficiency to preview the graph colors with
+    simulation: Option<Deficiency>,
+    node_size: NodeSize,
+    /// How the author is shown on the nodes
+    author_label: author_label::AuthorLabel,
+    /// Drawn behind the graph
+    guides: Vec<graph_guides::Guide>,
+    // Immutable commits in the view, for the guides
+    immutable_commits: HashSet<CommitId>,
+    edge_routing: EdgeRouting,
+    arrowheads: Arrowheads,
+    arrow_direction: ArrowDirection,
+    change_sizes: change_sizes::ChangeSizes,
+    signature_badges: bool,
+    repo_watch: repo_watch::RepoWatch,
+    // Shell command run when watching changes the Select result
+    select_changed_hook: Option<String>,
+    /// Restored when leaving the immutable boundary focus mode
+    view_before_boundary_focus: Option<String>,
+    // Commits that joined and left the Select result at the latest reload
+    result_change: Option<result_watch::ResultChange>,
+    signatures: signatures::Signatures,
+    /// Label the nodes with their generation distance from the reference
+    show_distances: bool,
+    // None for the working-copy commit
+    distance_reference: Option<CommitId>,
+    /// Label the nodes with their position in the Select results
+    number_results: bool,
+    /// Label the nodes with their commit IDs
+    show_commit_ids: bool,
+    /// Move or hide labels that would overlap
+    avoid_label_overlaps: bool,
+    /// Dim everything but the ancestors and descendants of the hovered node
+    highlight_path: bool,
+    /// Show the histogram of the committer dates below the status bar
+    date_histogram: bool,
+    // The node whose lineage is highlighted
+    path_node: Option<petgraph::graph::NodeIndex>,
+    hovered_edge: Option<petgraph::graph::EdgeIndex>,
+    // The Select results in evaluation order, when numbering or listing them
+    select_order: Vec<CommitId>,
+    /// Draw hulls around the commits of each bookmark
+    cluster_by_bookmark: bool,
+    // Bookmark of each commit, when clustering
+    bookmark_clusters: HashMap<CommitId, String>,
+    /// Draw boxes around the commits of divergent changes
+    group_divergent: bool,
+    /// Draw the commits that share a change ID as one node
+    change_nodes: bool,
+    /// Changes whose commits are drawn separately with `change_nodes`
+    expanded_changes: HashSet<jj_lib::backend::ChangeId>,
+    // Change ID and number of commits of the nodes that stand for several
+    // commits, by the commit of the node
+    collapsed_changes: HashMap<CommitId, (jj_lib::backend::ChangeId, usize)>,
+    // Change ID prefix of each commit of a divergent change, when grouping
+    change_groups: HashMap<CommitId, String>,
+    /// Outline heads(<view>)
+    outline_heads: bool,
+    /// Outline roots(<view>)
+    outline_roots: bool,
+    navigation: settings::NavigationSettings,
+    long_press: navigation::LongPress,
+    avatars: avatars::Avatars,
+    diffs: diffs::Diffs,
+    conflict_view: conflict_view::ConflictView,
+    export_dialog: Option<ExportDialog>,
+    settings_dialog: Option<SettingsDialog>,
+    replace_dialog: Option<ReplaceDialog>,
+    /// The UI settings as loaded or imported, for exporting them along with
+    /// the changes made from the menus
+    ui_settings: settings::UiSettings,
+    path_filter: path_filter::PathFilter,
+    diff_search: diff_search::DiffSearch,
+    prefetch: prefetch::Prefetch,
+    /// What the node limit left out of the view that is worth seeing, like
+    /// the working copy
+    truncated: Vec<String>,
+    /// Maximum number of commits in the graph
+    node_limit: usize,
+    /// The view has more commits than the node limit
+    node_limit_hit: bool,
+    /// The revset of the graph and the graph it was built into, for loading
+    /// more of it. Not kept when changes are shown as single nodes.
+    extendable: Option<(String, BuiltGraph)>,
+    /// "Load more" was clicked
+    more_requested: bool,
+    /// Positions of the nodes shown before more were loaded, kept when the
+    /// graph is laid out again
+    kept_positions: HashMap<CommitId, egui::Pos2>,
+    date_filter: date_filter::DateFilter,
+    author_filter: author_filter::AuthorFilter,
+    show_shortcuts: bool,
+    about: about::About,
+    // Screen area of the graph view, in points
+    graph_rect: egui::Rect,
+    undo_preview: Option<UndoPreview>,
+    result_set_dialog: Option<ResultSetDialog>,
+    result_overlay: Option<ResultOverlay>,
+    perf_report: perf_report::PerfReport,
+    /// Size of the graph that is laid out when it is next drawn, for timing
+    /// the layout
+    pending_layout: Option<perf_report::GraphSize>,
+    /// Path chosen in the "Export performance report" dialog
+    perf_report_dialog: Option<String>,
+    viewpoints: viewpoints::Viewpoints,
+    /// Zoom and pan of the viewpoint jumped to, applied after the graph has
+    /// been drawn and fitted to the view
+    pending_viewpoint: Option<(f32, egui::Vec2)>,
+    /// What moving a bookmark to a commit would do, chosen in the context menu
+    bookmark_move: Option<BookmarkMove>,
+    /// Source revset of the rebase preview, when previewing
+    rebase_source: Option<RevsetEntry>,
+    /// Where the source would be rebased onto the last selected node
+    rebase_preview: Option<RebasePreview>,
+    // The last selected node when the rebase was previewed
+    rebase_destination: Option<CommitId>,
+    /// Revset compared with the Select revset, when comparing
+    compare_revset: Option<RevsetEntry>,
+    /// Number of nodes in only one or both of the compared revsets
+    memberships: HashMap<Membership, usize>,
+    annotations_path: Option<PathBuf>,
+    /// Commits always added to the view
+    pins: pins::Pins,
+    annotations: HashMap<CommitId, annotations::Annotation>,
+    recorder: Option<session::Recorder>,
+    replay: Option<session::Replay>,
+    /// Node positions from --layout, replacing the laid out ones
+    fixed_layout: Option<layout_file::LayoutFile>,
+    /// Steps from --script that are left to do
+    script: Option<script::Script>,
+    /// Where the image requested by the script is saved
+    script_image: Option<PathBuf>,
+    /// Where to save the next screenshot of the graph, from File > Export
+    export_image: Option<PathBuf>,
+    graph_tab: GraphTab,
+    op_log: op_log_view::OpLogView,
+    /// The operation the repository is shown at, from the Operations tab,
+    /// when it is not the latest one
+    past_operation: Option<OperationId>,
+    /// Distinguishes the pan, zoom and layout of the graph from those of the
+    /// other tabs. None for the first tab.
+    graph_id: Option<String>,
+    /// Set by the menu, handled by the tabs
+    open_tab_requested: bool,
+    /// For File > Open recent, kept up to date by the tabs
+    recent_repositories: Vec<PathBuf>,
+    /// Picked from File > Open recent, opened by the tabs
+    open_recent_request: Option<PathBuf>,
+}
+
+/// Difference between the view at the current operation and at the operation
+/// that `jj undo` would restore
+#[derive(Default)]
+struct UndoPreview {
+    operation: String,
+    appearing: HashSet<CommitId>,
+    disappearing: HashSet<CommitId>,
+}
+
+/// What a requested screenshot is used for
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ScreenshotPurpose {
+    CopyGraph,
+    /// Saved to `script_image`
+    ScriptImage,
+    /// Saved to `export_image`
+    ExportImage,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum ExportFormat {
+    /// Not available from the command line, as it needs the graph layout
+    #[value(skip)]
+    Html,
+    /// JSON, for the web viewer
+    Snapshot,
+    /// Node positions as JSON, for --layout
+    #[value(skip)]
+    Layout,
+    /// Graphviz, laid out by Graphviz
+    Dot,
+    /// Image of the graph as laid out
+    #[value(skip)]
+    Svg,
+    /// Image of the graph as drawn in the window
+    #[value(skip)]
+    Png,
+}
+
+impl ExportFormat {
+    fn name(self) -> String {
+        match self {
+            ExportFormat::Html => tr!("format-html"),
+            ExportFormat::Snapshot => tr!("format-snapshot"),
+            ExportFormat::Layout => tr!("format-layout"),
+            ExportFormat::Dot => tr!("format-dot"),
+            ExportFormat::Svg => tr!("format-svg"),
+            ExportFormat::Png => tr!("format-png"),
+        }
+    }
+
+    fn default_path(self) -> &'static str {
+        match self {
+            ExportFormat::Html => "revset-graph.html",
+            ExportFormat::Snapshot => "snapshot.json",
+            ExportFormat::Layout => "layout.json",
+            ExportFormat::Dot => "revset-graph.dot",
+            ExportFormat::Svg => "revset-graph.svg",
+            ExportFormat::Png => "revset-graph.png",
+        }
+    }
+}
+
+/// What the central panel shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum GraphTab {
+    Commits,
+    Results,
+    Operations,
+}
+
+struct ExportDialog {
+    format: ExportFormat,
+    path: String,
+    /// Include a legend in HTML exports
+    legend: bool,
+    /// Leave out the commits outside the Select matches and the paths between
+    /// them from HTML exports
+    select_only: bool,
 }
 
+struct SettingsDialog {
+    import: bool,
+    path: String,
+}
+
+const SETTINGS_BUNDLE_PATH: &str = "revset-explorer-settings.toml";
+
+struct ResultSetDialog {
+    load: bool,
+    path: String,
+}
+
+const RESULT_SET_PATH: &str = "result-set.json";
+
+const PERF_REPORT_PATH: &str = "performance-report.json";
+
+/// A loaded result set, compared with the current view
+struct ResultOverlay {
+    saved: result_set::ResultSet,
+    comparison: result_set::Comparison,
+}
+
+#[derive(Default)]
+struct ReplaceDialog {
+    from: String,
+    to: String,
+    /// Session recording to rewrite as well, optional
+    recording: String,
+}
+
+/// Shown length of the commit IDs on the nodes, as in jj log
+const COMMIT_ID_LENGTH: usize = 8;
+
 const HISTORY_SIZE: usize = 50;
 struct RevsetEntry {
     value: String,
     error: Option<String>,
     // History of previous values
     history: history::History,
+    // Keeps the completions open while clicking them
+    completions_hovered: bool,
+    // When the value was typed, while it waits to be evaluated
+    edited: Option<Instant>,
 }
 
 impl RevsetEntry {
@@ -123,468 +816,6433 @@ impl RevsetEntry {
                 h.add(initial_value, false);
                 h
             },
+            completions_hovered: false,
+            edited: None,
         }
     }
-}
-
-fn create_graph<'a>(
-    jj_graph: &jjgraph::JjGraph,
-    all_revset: Box<dyn jj_lib::revset::Revset + 'a>,
-) -> Result<(GraphType, Vec<petgraph::graph::NodeIndex>, Option<usize>), ResolveError> {
-    let mut graph: GraphType =
-        egui_graphs::Graph::new(petgraph::stable_graph::StableGraph::default());
 
-    let repo = jj_graph.get_repo();
-    let working_copy_commit_id = repo
-        .view()
-        .get_wc_commit_id(jj_lib::ref_name::WorkspaceName::DEFAULT);
-    let store = repo.store();
-    let mut node_idxs = vec![];
-    let mut node_map = HashMap::new();
-    let mut edges = vec![];
-    // TODO: Warn when max nodes is hit
-    for rev in all_revset.iter_graph().take(MAX_NODES) {
-        let (commit_id, commit_edges) = rev?;
-        let commit = store
-            .get_commit(&commit_id)
-            .map_err(|e| ResolveError::JjError(e.to_string()))?;
-        let change_id = commit.change_id();
-        let change_id_len = repo
-            .shortest_unique_change_id_prefix_len(change_id)
-            .map_err(|e| ResolveError::JjError(e.to_string()))?;
-        let change_id_prefix = change_id.to_string()[..change_id_len].to_string();
-
-        let mut desc: String = commit
-            .description()
-            .lines()
-            .next()
-            .unwrap_or("")
-            .chars()
-            .take(12)
-            .collect();
-        if desc.len() == 12 {
-            desc += "...";
-        }
-        let mut label = change_id_prefix;
-        if Some(&commit_id) == working_copy_commit_id {
-            label = format!("@ {label}");
-        }
-        let node_idx = graph.add_node_with_label(commit_id.clone(), label);
-        node_idxs.push(node_idx);
-        node_map.insert(commit_id.clone(), node_idx);
-
-        for commit_edge in commit_edges {
-            edges.push((commit_id.clone(), commit_edge.target));
-        }
-    }
-    for edge in edges {
-        let Some(start) = node_map.get(&edge.0) else {
-            continue;
+    /// Whether the typed value is due to be evaluated: `debounce` after the
+    /// last keystroke, or at once when leaving the input, like with Enter.
+    /// Evaluating on every keystroke would make typing lag in large
+    /// repositories.
+    fn typing_paused(&mut self, response: &egui::Response, debounce: Duration) -> bool {
+        let Some(edited) = self.edited else {
+            return false;
         };
-        let Some(end) = node_map.get(&edge.1) else {
-            continue;
-        };
-        graph.add_edge_with_label(*start, *end, (), "".to_owned());
+        let waited = edited.elapsed();
+        if waited >= debounce || response.lost_focus() {
+            self.edited = None;
+            true
+        } else {
+            response.ctx.request_repaint_after(debounce - waited);
+            false
+        }
     }
 
-    let limit_hit = if node_idxs.len() == MAX_NODES {
-        Some(MAX_NODES)
-    } else {
-        None
-    };
-
-    Ok((graph, node_idxs, limit_hit))
-}
-
-#[derive(Debug, PartialEq)]
-enum ResolveError {
-    RevsetParseError(String),
-    JjError(String),
-}
-
-impl From<jj_lib::revset::RevsetEvaluationError> for ResolveError {
-    fn from(e: jj_lib::revset::RevsetEvaluationError) -> Self {
-        ResolveError::JjError(e.to_string())
+    /// Sets a revset composed by the app, simplified, and adds it to the
+    /// history
+    fn set_generated(&mut self, revset: &str) {
+        self.value = revset_simplify::simplify(revset);
+        self.history.add(&self.value, false);
     }
 }
 
 impl ExplorerApp {
-    fn new(repository_path: &Path) -> Self {
-        let initial_filter = "".to_owned();
-        // This is the default log macro in jj: present(@) |
-        // ancestors(immutable_heads().., 2) | present(trunk())
-        // Set up something that will show the user's commits and a bit into the
-        // past, without filling up the view with too many nodes.
-        let initial_view = "ancestors(heads(mutable()), 7)".to_owned();
-        let jj_graph = jjgraph::JjGraph::new(repository_path).unwrap();
-        let (view_revset, ..) = resolve_revset(&jj_graph, &initial_view).unwrap();
-        let (g, node_idxs, _) = create_graph(&jj_graph, view_revset).unwrap();
-        let repo = jj_graph.get_repo();
-        let working_copy_commit_id = repo
-            .view()
-            .get_wc_commit_id(jj_lib::ref_name::WorkspaceName::DEFAULT);
-        Self {
+    fn new(
+        repository_path: &Path,
+        settings: settings::Settings,
+        options: AppOptions,
+    ) -> anyhow::Result<Self> {
+        let initial_filter = options.initial_select.unwrap_or_default();
+        let initial_view = options
+            .initial_view
+            .unwrap_or_else(|| DEFAULT_VIEW.to_owned());
+        let load_options = LoadOptions {
+            forge_token: settings.forge.token.clone(),
+            backend_plugins: options.backend_plugins,
+        };
+        let jj_graph = load_jj_graph(repository_path, load_options.clone())?;
+        let commit_url_template = settings.forge.commit_url.or_else(|| {
+            jj_graph
+                .git_remote_url("origin")
+                .and_then(|url| forge::commit_url_template_from_remote(&url))
+        });
+        let (jj_error_sender, jj_error_receiver) = mpsc::channel();
+        let mut status_message = None;
+        let ci_statuses = if settings.forge.ci_status {
+            let ci_api = jj_graph
+                .git_remote_url("origin")
+                .and_then(|url| forge::api_from_remote(&url));
+            if ci_api.is_none() {
+                status_message = Some(tr!("ci-no-remote"));
+            }
+            ci_api.map(|api| ci_status::CiStatuses::new(api, settings.forge.token.clone()))
+        } else {
+            None
+        };
+        let pins = pins::Pins::load(repository_path).unwrap_or_else(|e| {
+            status_message = Some(tr!("pins-failed", error = format!("{e:#}")));
+            pins::Pins::default()
+        });
+        let mut app = Self {
             initialized: false,
             filter_revset: RevsetEntry::new(&initial_filter),
             last_filter_calc_time: None,
             last_filter_node_count: None,
             view_revset: RevsetEntry::new(&initial_view),
             last_view_node_count: None,
-            graph: g,
-            node_idxs,
+            // The graph is created in the first update, which also reports
+            // errors in the initial view
+            graph: egui_graphs::Graph::new(petgraph::stable_graph::StableGraph::default()),
+            node_idxs: vec![],
+            index_warmup: index_warmup::IndexWarmup::start(&jj_graph),
+            view_evaluation: background_evaluation::BackgroundEvaluation::new(
+                view_evaluation::evaluate,
+                "view-evaluation-failed",
+                "view-evaluating",
+                options.wait_for_evaluations,
+            ),
+            select_evaluation: background_evaluation::BackgroundEvaluation::new(
+                select_evaluation::evaluate,
+                "select-evaluation-failed",
+                "select-evaluating",
+                options.wait_for_evaluations,
+            ),
+            revset_vocabulary: revset_completion::Vocabulary::load(&jj_graph),
             jj_graph,
-            working_copy_commit_id: working_copy_commit_id.cloned(),
-        }
-    }
-
-    fn update_view(&mut self, ui: &mut egui::Ui) {
-        let result = self.update_view_graph(ui);
-        match result {
-            Ok(_) => {
-                self.view_revset.error = None;
-            }
-            Err(ResolveError::RevsetParseError(msg) | ResolveError::JjError(msg)) => {
-                self.view_revset.history.set_last_tentative(true);
-                self.view_revset.error = Some(msg);
-            }
+            repository_path: repository_path.to_owned(),
+            load_options,
+            commit_url_template,
+            diff_tool: settings.diff.tool,
+            max_diff_lines: settings.diff.max_lines.unwrap_or(DEFAULT_MAX_DIFF_LINES),
+            allow_mutations: options.allow_mutations,
+            pending_mutation: None,
+            working_copy_stale: false,
+            repo_reloaded: false,
+            print_selection: options.print_selection,
+            context_menu_node: None,
+            jj_error_sender,
+            jj_error_receiver,
+            status_message,
+            ci_statuses,
+            color_by_author: false,
+            label_scale: settings.ui.graph_label_scale.unwrap_or(1.),
+            palette: settings.ui.palette,
+            recolor: false,
+            view_invalidated: false,
+            empty_view_fallback: None,
+            color_rules: settings.color_rules,
+            requested_filter: None,
+            simulation: None,
+            node_size: settings.ui.node_size,
+            author_label: settings.ui.author_label,
+            guides: settings.ui.guides.clone(),
+            immutable_commits: HashSet::new(),
+            edge_routing: settings.ui.edge_routing,
+            arrowheads: settings.ui.arrowheads,
+            arrow_direction: settings.ui.arrow_direction,
+            change_sizes: change_sizes::ChangeSizes::new(),
+            signature_badges: settings.ui.signature_badges,
+            repo_watch: repo_watch::RepoWatch::new(
+                settings.ui.watch,
+                (settings.ui.watch_interval).unwrap_or(repo_watch::DEFAULT_POLL_SECONDS),
+            ),
+            select_changed_hook: settings.hooks.select_changed,
+            view_before_boundary_focus: None,
+            result_change: None,
+            signatures: signatures::Signatures::new(),
+            show_distances: false,
+            number_results: false,
+            show_commit_ids: false,
+            avoid_label_overlaps: true,
+            highlight_path: false,
+            date_histogram: true,
+            path_node: None,
+            hovered_edge: None,
+            select_order: vec![],
+            distance_reference: None,
+            cluster_by_bookmark: false,
+            bookmark_clusters: HashMap::new(),
+            group_divergent: false,
+            change_nodes: false,
+            expanded_changes: HashSet::new(),
+            collapsed_changes: HashMap::new(),
+            change_groups: HashMap::new(),
+            outline_heads: false,
+            outline_roots: false,
+            navigation: settings.navigation,
+            long_press: navigation::LongPress::default(),
+            avatars: avatars::Avatars::new(settings.avatars.gravatar),
+            diffs: diffs::Diffs::new(),
+            conflict_view: conflict_view::ConflictView::default(),
+            export_dialog: None,
+            settings_dialog: None,
+            replace_dialog: None,
+            ui_settings: settings.ui.clone(),
+            path_filter: path_filter::PathFilter::default(),
+            diff_search: diff_search::DiffSearch::default(),
+            prefetch: prefetch::Prefetch::default(),
+            truncated: vec![],
+            node_limit: options.max_nodes.unwrap_or(MAX_NODES),
+            node_limit_hit: false,
+            extendable: None,
+            more_requested: false,
+            kept_positions: HashMap::new(),
+            date_filter: date_filter::DateFilter::default(),
+            author_filter: author_filter::AuthorFilter::default(),
+            show_shortcuts: false,
+            about: about::About::default(),
+            graph_rect: egui::Rect::NOTHING,
+            undo_preview: None,
+            result_set_dialog: None,
+            result_overlay: None,
+            perf_report: perf_report::PerfReport::default(),
+            pending_layout: None,
+            perf_report_dialog: None,
+            viewpoints: viewpoints::Viewpoints::default(),
+            pending_viewpoint: None,
+            bookmark_move: None,
+            rebase_source: None,
+            rebase_preview: None,
+            rebase_destination: None,
+            compare_revset: None,
+            memberships: HashMap::new(),
+            annotations_path: options.annotations,
+            pins,
+            annotations: HashMap::new(),
+            recorder: None,
+            replay: None,
+            fixed_layout: (options.layout.as_deref())
+                .map(layout_file::LayoutFile::load)
+                .transpose()?,
+            script: (options.script.as_deref())
+                .map(script::Script::load)
+                .transpose()?,
+            script_image: None,
+            export_image: None,
+            graph_tab: GraphTab::Commits,
+            op_log: op_log_view::OpLogView::default(),
+            past_operation: None,
+            graph_id: None,
+            open_tab_requested: false,
+            recent_repositories: vec![],
+            open_recent_request: None,
         };
+        app.load_annotations();
+        app.check_working_copy();
+        app.start_session(options.record_session, options.replay_session);
+        Ok(app)
     }
 
-    fn update_view_graph(&mut self, ui: &mut egui::Ui) -> Result<(), ResolveError> {
-        let resolve_result = resolve_revset(&self.jj_graph, &self.view_revset.value)
-            .inspect_err(|_| self.last_view_node_count = None)?;
-        let (revset, _calc_time, node_count) = resolve_result;
-        self.last_view_node_count = Some(node_count);
+    fn start_session(&mut self, record: Option<PathBuf>, replay: Option<PathBuf>) {
+        let record = record.map(|path| session::Recorder::create(&path));
+        match record.transpose() {
+            Ok(recorder) => self.recorder = recorder,
+            Err(e) => self.status_message = Some(tr!("session-failed", error = format!("{e:#}"))),
+        }
+        let replay = replay.map(|path| session::Replay::load(&path));
+        match replay.transpose() {
+            Ok(replay) => self.replay = replay,
+            Err(e) => self.status_message = Some(tr!("session-failed", error = format!("{e:#}"))),
+        }
+    }
 
-        let create_result = create_graph(&self.jj_graph, revset)?;
-        let (g, node_idxs, limit_hit) = create_result;
-        self.graph = g;
-        self.node_idxs = node_idxs;
-        egui_graphs::reset_layout::<egui_graphs::LayoutStateHierarchical>(ui, None);
-        if let Some(limit) = limit_hit {
-            Err(ResolveError::RevsetParseError(format!(
-                "Node limit of {limit} reached. The graph is incomplete."
-            )))
-        } else {
-            Ok(())
+    /// Adds the current revsets to the recording, if recording
+    fn record_session_step(&mut self) {
+        let Some(recorder) = &mut self.recorder else {
+            return;
+        };
+        if let Err(e) = recorder.record(&self.view_revset.value, &self.filter_revset.value) {
+            self.status_message = Some(tr!("session-failed", error = format!("{e:#}")));
+            // Not retried for every change
+            self.recorder = None;
         }
     }
 
-    fn update_filter(&mut self) {
-        let resolve_result = resolve_revset_measure(&self.jj_graph, &self.filter_revset.value);
-        match resolve_result {
-            Ok((filter_revset, calc_time, node_count)) => {
-                let _ = mark_graph(
-                    &mut self.graph,
-                    &self.node_idxs,
-                    self.working_copy_commit_id.as_ref(),
-                    &self.jj_graph,
-                    Some(filter_revset),
-                );
-                self.last_filter_calc_time = Some(calc_time);
-                self.last_filter_node_count = Some(node_count);
-                self.filter_revset.error = None;
+    /// Reads the annotations file and looks up its IDs in the repository
+    fn load_annotations(&mut self) {
+        let Some(path) = &self.annotations_path else {
+            return;
+        };
+        match annotations::Annotations::load(path) {
+            Ok(annotations) => {
+                let resolved = annotations.resolve(&self.jj_graph);
+                if !resolved.unresolved.is_empty() {
+                    self.status_message = Some(tr!(
+                        "annotations-unresolved",
+                        count = resolved.unresolved.len(),
+                        ids = resolved.unresolved.join(", ")
+                    ));
+                }
+                self.annotations = resolved.by_commit;
             }
-            Err(ResolveError::RevsetParseError(msg) | ResolveError::JjError(msg)) => {
-                self.last_filter_calc_time = None;
-                self.last_filter_node_count = None;
-                self.filter_revset.history.set_last_tentative(true);
-                self.filter_revset.error = Some(msg);
+            Err(e) => {
+                self.annotations.clear();
+                self.status_message = Some(tr!("annotations-failed", error = format!("{e:#}")));
             }
         }
     }
-}
 
-fn mark_graph<'a>(
-    graph: &mut GraphType,
-    node_idxs: &[petgraph::graph::NodeIndex],
-    working_copy_commit_id: Option<&CommitId>,
-    jj_graph: &jjgraph::JjGraph,
-    filter_revset: Option<Box<dyn jj_lib::revset::Revset + 'a>>,
-) -> anyhow::Result<(), ResolveError> {
-    // TODO: Global var
-    let immutable_revset = jj_graph
-        .get_revset("immutable()")
-        .map_err(|e| ResolveError::JjError(e.to_string()))?;
-    let is_immutable = immutable_revset.containing_fn();
-
-    let in_filter: Box<dyn Fn(&CommitId) -> Result<_, _>> =
-        if let Some(filter_revset) = filter_revset {
-            filter_revset.containing_fn()
-        } else {
-            Box::new(|_| Ok(false))
+    fn node_context_menu(&mut self, ui: &mut egui::Ui, node_idx: petgraph::graph::NodeIndex) {
+        let Some(commit_id) = self.graph.node(node_idx).map(|n| n.payload().clone()) else {
+            ui.close();
+            return;
         };
-
-    for node_idx in node_idxs.iter() {
-        let node = graph.node_mut(*node_idx).unwrap();
-        let commit_id = node.payload();
-        let immutable = is_immutable(commit_id)?;
-        let matches_filter = in_filter(commit_id)?;
-        let is_wc_commit = working_copy_commit_id
-            .as_ref()
-            .is_some_and(|wc| commit_id == *wc);
-
-        #[derive(Debug, PartialEq, Eq, Hash)]
-        enum NodeType {
-            WorkingCopy,
-            Immutable,
-            Regular,
-        }
-        #[derive(Debug, PartialEq, Eq, Hash)]
-        enum FilterMatch {
-            Match,
-            NoMatch,
-        }
-        let node_type = if is_wc_commit {
-            NodeType::WorkingCopy
-        } else if immutable {
-            NodeType::Immutable
+        let commit_url = self.commit_url_template.as_ref().and_then(|template| {
+            let commit = self
+                .jj_graph
+                .get_repo()
+                .store()
+                .get_commit(&commit_id)
+                .ok()?;
+            Some(forge::expand_commit_url(
+                template,
+                &commit_id.hex(),
+                &commit.change_id().to_string(),
+            ))
+        });
+        let open_button = ui.add_enabled(
+            commit_url.is_some(),
+            egui::Button::new(tr!("open-in-browser")),
+        );
+        let open_button = if self.commit_url_template.is_none() {
+            open_button.on_disabled_hover_text(tr!("no-forge"))
         } else {
-            NodeType::Regular
+            open_button
         };
-        let filter_match = if matches_filter {
-            FilterMatch::Match
-        } else {
-            FilterMatch::NoMatch
-        };
-        #[rustfmt::skip]
-            let color_map = HashMap::from([
-                ((NodeType::WorkingCopy, FilterMatch::Match), ecolor::Color32::from_hex("#26ff00ff").unwrap()),
-                ((NodeType::WorkingCopy, FilterMatch::NoMatch), ecolor::Color32::from_hex("#295923").unwrap()),
-                ((NodeType::Immutable, FilterMatch::Match), ecolor::Color32::from_hex("#21cdff").unwrap()),
-                ((NodeType::Immutable, FilterMatch::NoMatch), ecolor::Color32::from_hex("#2e5059").unwrap()),
-                ((NodeType::Regular, FilterMatch::Match), ecolor::Color32::from_hex("#fffc00").unwrap()),
-                ((NodeType::Regular, FilterMatch::NoMatch), ecolor::Color32::from_hex("#636222").unwrap()),
-                // ((NodeType::Regular, FilterMatch::Match), ecolor::Color32::from_hex("#ffa400").unwrap()),
-                // ((NodeType::Regular, FilterMatch::NoMatch), ecolor::Color32::from_hex("#634c22").unwrap()),
-            ]);
-        node.set_color(color_map[&(node_type, filter_match)]);
-    }
-    Ok(())
-}
-
-fn resolve_revset<'g>(
-    jj_graph: &'g jjgraph::JjGraph,
-    revset_str: &str,
-) -> Result<(Box<dyn jj_lib::revset::Revset + 'g>, Duration, NodeCount), ResolveError> {
-    resolve_revset_helper(jj_graph, revset_str, false)
-}
-
-/// Resolves the revset multiple times to measure average time
-fn resolve_revset_measure<'g>(
-    jj_graph: &'g jjgraph::JjGraph,
-    revset_str: &str,
-) -> Result<(Box<dyn jj_lib::revset::Revset + 'g>, Duration, NodeCount), ResolveError> {
-    resolve_revset_helper(jj_graph, revset_str, true)
-}
-
-fn resolve_revset_helper<'g>(
-    jj_graph: &'g jjgraph::JjGraph,
-    revset_str: &str,
-    measure: bool,
-) -> Result<(Box<dyn jj_lib::revset::Revset + 'g>, Duration, NodeCount), ResolveError> {
-    let revset_str = if revset_str.trim().is_empty() {
-        "empty()"
-    } else {
-        revset_str
-    };
-    let (filter_revset, first_calc_time) = get_revset_timed(jj_graph, &revset_str)
-        .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
-
-    let runs = if measure { 100 } else { 1 };
-    let mut total_time = first_calc_time;
-    let mut run_count = 1;
-    for _ in 0..(runs - 1) {
-        if total_time > Duration::from_millis(100) {
-            // Don't let the user wait too long
-            // TODO: Do calculation in a background task
-            break;
-        }
-        let (_, calc_time) = get_revset_timed(jj_graph, revset_str)
-            .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
-        run_count += 1;
-        total_time += calc_time;
-    }
-    let avg_time = total_time.checked_div(run_count).unwrap();
-
-    let node_count = filter_revset.count_estimate()?;
-    let node_count = if Some(node_count.0) == node_count.1 {
-        NodeCount::Exact(node_count.0)
-    } else {
-        NodeCount::AtLeast(node_count.0)
-    };
-
-    Ok((filter_revset, avg_time, node_count))
-}
+        if open_button.clicked()
+            && let Some(url) = commit_url
+        {
+            ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+        }
 
-fn get_revset_timed<'a>(
-    jj_graph: &'a jjgraph::JjGraph,
-    value: &str,
-) -> Result<(Box<dyn jj_lib::revset::Revset + 'a>, Duration), jjgraph::RevsetError> {
-    let start = Instant::now();
-    let revset = jj_graph.get_revset(value)?;
-    let end = Instant::now();
-    Ok((revset, end - start))
-}
+        if ui.button(self.show_diff_label()).clicked() {
+            self.show_diff(ui.ctx(), &commit_id);
+        }
 
-/// Revset text edit box with error message display
-fn revset_edit(
-    ui: &mut egui::Ui,
-    label: &str,
-    value: &mut String,
-    error: &Option<String>,
-    calculation_time: Option<Duration>,
-    node_count: Option<&NodeCount>,
-) -> egui::Response {
-    ui.horizontal(|ui| {
-        let revset_label = ui.label(label);
-        let response = ui
-            .scope(|ui| {
-                if error.is_some() {
-                    ui.visuals_mut().extreme_bg_color = ecolor::Color32::DARK_RED;
+        if ui
+            .button(tr!("copy-revset-symbol"))
+            .on_hover_text(tr!("copy-revset-symbol-hover"))
+            .clicked()
+        {
+            match revset_symbol::revset_symbol(&self.jj_graph, &commit_id) {
+                Ok(symbol) => {
+                    self.status_message = Some(tr!("copied", text = symbol.as_str()));
+                    ui.ctx().copy_text(symbol);
                 }
-                ui.add(
-                    egui::TextEdit::singleline(value)
-                        .desired_width(500.)
-                        .cursor_at_end(true)
-                        .hint_text(
-                            "Enter a revset here, like \"@\". Navigate to previous entries using up/down keys.",
-                        ),
-                )
-                .labelled_by(revset_label.id)
-            })
-            .inner;
-        let err_msg = if let Some(err_msg) = error.as_ref() {
-            // Remove empty lines, to make the error message more compact
-            err_msg.replace("  |\n", "")
+                Err(e) => self.status_message = Some(e.to_string()),
+            }
+        }
+
+        let pin_label = if self.pins.contains(&commit_id) {
+            tr!("unpin-commit")
         } else {
-            "".to_owned()
+            tr!("pin-commit")
         };
-        if let Some(count) = node_count {
-            ui.label(match count {
-                NodeCount::Exact(count) => format!("{count} ○"),
-                NodeCount::AtLeast(count) => format!("{count}+ ○"),
-            });
+        if ui
+            .button(pin_label)
+            .on_hover_text(tr!("pin-commit-hover"))
+            .clicked()
+        {
+            self.toggle_pin(&commit_id);
         }
-        if err_msg.is_empty() && let Some(time) = calculation_time {
-            ui.label(format!("{:.1} ms", time.as_micros() as f64 / 1000.0));
+
+        if self.change_nodes {
+            self.change_node_menu(ui, &commit_id);
         }
-        let _error_label = ui.add_sized(
-            [1., ui.text_style_height(&egui::TextStyle::Monospace) * 4.],
-            egui::Label::new(RichText::new(err_msg).family(egui::FontFamily::Monospace)),
-        );
-        response
-    })
-    .inner
-}
 
-fn revset_edit_with_history(
-    ui: &mut egui::Ui,
-    label: &str,
-    revset_entry: &mut RevsetEntry,
-    calculation_time: Option<Duration>,
-    node_count: Option<&NodeCount>,
-) -> (egui::Response, bool) {
-    let resp = revset_edit(
-        ui,
-        &format!("{}: ", label),
-        &mut revset_entry.value,
-        &revset_entry.error,
-        calculation_time,
-        node_count,
-    );
+        if ui.button(tr!("measure-distances")).clicked() {
+            self.show_distances = true;
+            self.distance_reference = Some(commit_id.clone());
+            self.apply_distances();
+        }
 
-    let mut value_from_history = false;
-    let mut value_changed = false;
+        for bookmark in self.selected_bookmarks(node_idx) {
+            if ui
+                .button(tr!("preview-bookmark-move", bookmark = bookmark.as_str()))
+                .clicked()
+            {
+                self.preview_bookmark_move(&bookmark, &commit_id);
+            }
+        }
 
-    if resp.has_focus() {
-        if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp)) {
-            revset_entry.history.prev();
-            revset_entry.value = revset_entry.history.get().unwrap_or("").to_owned();
-            value_from_history = true;
-        } else if ui.input_mut(|i| i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown)) {
-            revset_entry.history.next();
-            revset_entry.value = revset_entry.history.get().unwrap_or("").to_owned();
-            value_from_history = true;
+        if self.allow_mutations {
+            ui.separator();
+            if ui.button(tr!("new-commit")).clicked() {
+                self.pending_mutation = Some(jj_cli::Mutation::New(commit_id.clone()));
+            }
+            if ui.button(tr!("edit-commit")).clicked() {
+                self.pending_mutation = Some(jj_cli::Mutation::Edit(commit_id.clone()));
+            }
+            if ui.button(tr!("abandon-commit")).clicked() {
+                self.pending_mutation = Some(jj_cli::Mutation::Abandon(commit_id.clone()));
+            }
+            let rebase_button = ui
+                .add_enabled(
+                    self.graph.selected_nodes().len() == 2,
+                    egui::Button::new(self.rebase_label()),
+                )
+                .on_disabled_hover_text(tr!("rebase-hover"));
+            if rebase_button.clicked()
+                && let [revision, destination] = self.graph.selected_nodes()
+            {
+                self.pending_mutation = Some(jj_cli::Mutation::Rebase {
+                    revision: self.graph.node(*revision).unwrap().payload().clone(),
+                    destination: self.graph.node(*destination).unwrap().payload().clone(),
+                });
+            }
         }
     }
 
-    if value_from_history {
-        value_changed = true;
-        // Move cursor to the end of the textbox
-        if let Some(mut s) = egui::text_edit::TextEdit::load_state(&resp.ctx, resp.id) {
-            s.cursor.set_char_range(Some(egui::text::CCursorRange::one(
-                egui::text::CCursor::new(revset_entry.value.len()),
-            )));
-            s.store(&resp.ctx, resp.id);
+    /// Expanding the change of the node into its commits, or collapsing them
+    /// back into one node
+    fn change_node_menu(&mut self, ui: &mut egui::Ui, commit_id: &CommitId) {
+        if let Some((change_id, count)) = self.collapsed_changes.get(commit_id) {
+            if ui.button(tr!("expand-change", count = *count)).clicked() {
+                self.expanded_changes.insert(change_id.clone());
+                self.view_invalidated = true;
+            }
+            return;
+        }
+        let Ok(commit) = self.jj_graph.get_repo().store().get_commit(commit_id) else {
+            return;
+        };
+        if self.expanded_changes.contains(commit.change_id())
+            && ui.button(tr!("collapse-change")).clicked()
+        {
+            self.expanded_changes.remove(commit.change_id());
+            self.view_invalidated = true;
         }
-    } else if resp.changed() {
-        value_changed = true;
-        revset_entry.history.add(revset_entry.value.trim(), false);
     }
-    (resp, value_changed)
-}
 
-impl eframe::App for ExplorerApp {
-    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        egui::CentralPanel::default().show(ctx, |ui| {
-            let (filter_edit, filter_changed) = revset_edit_with_history(
-                ui,
-                "Select",
-                &mut self.filter_revset,
-                self.last_filter_calc_time,
-                self.last_filter_node_count.as_ref(),
-            );
-            let (_view_edit, view_changed) = revset_edit_with_history(
-                ui,
-                "View",
-                &mut self.view_revset,
-                None,
-                self.last_view_node_count.as_ref(),
+    /// Adds a focusable widget for each node, so that the nodes can be reached
+    /// with Tab and the arrow keys and are announced by screen readers. Space
+    /// toggles the selection of the focused node, Enter selects only it and
+    /// Shift+F10 opens its context menu.
+    fn node_widgets(&mut self, ui: &mut egui::Ui, graph_origin: egui::Pos2) {
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        for node_idx in self.node_idxs.clone() {
+            let Some(node) = self.graph.node(node_idx) else {
+                continue;
+            };
+            // Relative to the graph view
+            let center = graph_origin + meta.canvas_to_screen_pos(node.location()).to_vec2();
+            let radius = meta.canvas_to_screen_size(node.display().radius()).max(6.);
+            let rect = egui::Rect::from_center_size(center, egui::Vec2::splat(radius * 2.));
+            let response = ui.interact(
+                rect,
+                ui.id().with(("node", node_idx)),
+                egui::Sense::focusable_noninteractive(),
             );
+            let selected = node.selected();
+            let name = self.accessible_name(node.payload(), &node.label());
+            response.widget_info(|| {
+                egui::WidgetInfo::selected(egui::WidgetType::SelectableLabel, true, selected, &name)
+            });
 
-            if view_changed || !self.initialized {
-                self.update_view(ui);
-            }
-            if filter_changed || view_changed || !self.initialized {
-                self.update_filter();
+            let focused = response.has_focus();
+            let mut open_menu = false;
+            if focused {
+                let (toggle, select_only) = ui.input_mut(|i| {
+                    (
+                        i.consume_shortcut(&keymap::TOGGLE_NODE),
+                        i.consume_shortcut(&keymap::SELECT_NODE),
+                    )
+                });
+                open_menu = ui.input_mut(|i| i.consume_shortcut(&keymap::NODE_MENU));
+                if toggle {
+                    self.set_node_selected(node_idx, !selected);
+                } else if select_only {
+                    for other in self.graph.selected_nodes().to_vec() {
+                        self.set_node_selected(other, false);
+                    }
+                    self.set_node_selected(node_idx, true);
+                }
             }
+            self.graph.node_mut(node_idx).unwrap().display_mut().focused = focused;
 
-            if !self.initialized {
-                filter_edit.request_focus();
-                self.initialized = true;
+            egui::Popup::menu(&response)
+                .open_memory(open_menu.then_some(egui::SetOpenCommand::Bool(true)))
+                .show(|ui| self.node_context_menu(ui, node_idx));
+        }
+    }
+
+    /// Selects like egui_graphs does, but with the configured mouse button
+    fn select_by_button(&mut self, graph_response: &egui::Response) {
+        let button = self.navigation.select_button;
+        if button == navigation::MouseButton::Primary || !graph_response.clicked_by(button.into()) {
+            return;
+        }
+        match self.graph.hovered_node() {
+            Some(node_idx) => {
+                let selected = self
+                    .graph
+                    .node(node_idx)
+                    .is_some_and(|node| node.selected());
+                self.set_node_selected(node_idx, !selected);
+            }
+            None => {
+                for node_idx in self.graph.selected_nodes().to_vec() {
+                    self.set_node_selected(node_idx, false);
+                }
+            }
+        }
+    }
+
+    fn set_node_selected(&mut self, node_idx: petgraph::graph::NodeIndex, selected: bool) {
+        if let Some(node) = self.graph.node_mut(node_idx) {
+            node.set_selected(selected);
+        }
+        // The graph view only syncs the list when drawing
+        let mut selected_nodes = self.graph.selected_nodes().to_vec();
+        selected_nodes.retain(|&idx| idx != node_idx);
+        if selected {
+            selected_nodes.push(node_idx);
+        }
+        self.graph.set_selected_nodes(selected_nodes);
+    }
+
+    /// The label and the first line of the description
+    fn accessible_name(&self, commit_id: &CommitId, label: &str) -> String {
+        let description = self
+            .jj_graph
+            .get_repo()
+            .store()
+            .get_commit(commit_id)
+            .ok()
+            .and_then(|commit| commit.description().lines().next().map(str::to_owned))
+            .unwrap_or_default();
+        let label = label.strip_prefix("@ ").map_or_else(
+            || label.to_owned(),
+            |change_id| tr!("node-working-copy", change_id = change_id),
+        );
+        if description.is_empty() {
+            tr!("node-name-no-description", label = label)
+        } else {
+            tr!("node-name", label = label, description = description)
+        }
+    }
+
+    /// Local bookmarks of the selected nodes, other than `target`
+    fn selected_bookmarks(&self, target: petgraph::graph::NodeIndex) -> Vec<String> {
+        let selected: HashSet<_> = (self.graph.selected_nodes().iter())
+            .filter(|node_idx| **node_idx != target)
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .map(|node| node.payload())
+            .collect();
+        let repo = self.jj_graph.get_repo();
+        (repo.view().local_bookmarks())
+            .filter(|(_, target)| target.added_ids().any(|id| selected.contains(id)))
+            .map(|(name, _)| name.as_str().to_owned())
+            .collect()
+    }
+
+    fn preview_bookmark_move(&mut self, bookmark: &str, to: &CommitId) {
+        match preview_bookmark_move(&self.jj_graph, bookmark, to, MAX_NODES) {
+            Ok(preview) => self.bookmark_move = Some(preview),
+            Err(e) => {
+                self.bookmark_move = None;
+                self.status_message = Some(tr!("bookmark-move-failed", error = e.to_string()));
+            }
+        }
+        self.apply_preview_rings();
+    }
+
+    /// Lists the commits that the previewed bookmark move would make reachable
+    /// or unreachable, with a button for moving when mutations are allowed
+    fn bookmark_move_window(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.bookmark_move else {
+            return;
+        };
+        let labels: HashMap<_, _> = (self.node_idxs.iter())
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .map(|node| (node.payload().clone(), node.label()))
+            .collect();
+        // Commits outside the view have no node
+        let label = |commit_id: &CommitId| {
+            (labels.get(commit_id).cloned()).unwrap_or_else(|| commit_id.hex()[..12].to_owned())
+        };
+        let mut open = true;
+        let mut move_bookmark = false;
+        let title = tr!(
+            "bookmark-move-title",
+            bookmark = preview.name.as_str(),
+            target = label(&preview.to)
+        );
+        egui::Window::new(title)
+            .id(egui::Id::new("bookmark_move"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                let reachable = tr!("bookmark-move-reachable", count = preview.reachable.len());
+                let unreachable = tr!(
+                    "bookmark-move-unreachable",
+                    count = preview.unreachable.len()
+                );
+                for (i, (heading, commit_ids)) in [
+                    (reachable, &preview.reachable),
+                    (unreachable, &preview.unreachable),
+                ]
+                .into_iter()
+                .enumerate()
+                {
+                    ui.strong(heading);
+                    egui::ScrollArea::vertical()
+                        .id_salt(("bookmark_move", i))
+                        .max_height(150.)
+                        .show(ui, |ui| {
+                            for commit_id in commit_ids {
+                                ui.label(label(commit_id));
+                            }
+                        });
+                }
+                if preview.is_backwards() {
+                    ui.colored_label(ui.visuals().warn_fg_color, tr!("bookmark-move-backwards"));
+                }
+                if self.allow_mutations {
+                    move_bookmark = ui.button(tr!("move-bookmark")).clicked();
+                }
+            });
+        if move_bookmark {
+            self.pending_mutation = Some(jj_cli::Mutation::MoveBookmark {
+                name: preview.name.clone(),
+                to: preview.to.clone(),
+                allow_backwards: preview.is_backwards(),
+            });
+        }
+        if !open || move_bookmark {
+            self.bookmark_move = None;
+            self.apply_preview_rings();
+        }
+    }
+
+    /// Arrows from the current targets of the previewed bookmark move to the
+    /// new one, in screen coordinates
+    fn bookmark_move_arrows(&self, ui: &egui::Ui, graph_origin: egui::Pos2) -> Vec<egui::Shape> {
+        let Some(preview) = &self.bookmark_move else {
+            return vec![];
+        };
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let node_circle = |commit_id: &CommitId| {
+            let node = (self.node_idxs.iter())
+                .filter_map(|node_idx| self.graph.node(*node_idx))
+                .find(|node| node.payload() == commit_id)?;
+            let center = graph_origin + meta.canvas_to_screen_pos(node.location()).to_vec2();
+            Some((center, meta.canvas_to_screen_size(node.display().radius())))
+        };
+        let Some((to, to_radius)) = node_circle(&preview.to) else {
+            return vec![];
+        };
+        let stroke = egui::Stroke::new(2.5, ui.visuals().selection.stroke.color);
+        let mut shapes = vec![];
+        for (from, from_radius) in preview.from.iter().filter_map(node_circle) {
+            let direction = (to - from).normalized();
+            let start = from + direction * from_radius;
+            let tip = to - direction * to_radius;
+            if (tip - start).dot(direction) <= 0. {
+                continue;
+            }
+            let head = 4. * stroke.width;
+            let back = tip - direction * head;
+            let side = direction.rot90() * head / 2.;
+            shapes.push(egui::Shape::line_segment([start, back], stroke));
+            shapes.push(egui::Shape::convex_polygon(
+                vec![tip, back + side, back - side],
+                stroke.color,
+                egui::Stroke::NONE,
+            ));
+        }
+        shapes
+    }
+
+    fn rebase_label(&self) -> String {
+        if let [revision, destination] = self.graph.selected_nodes() {
+            tr!(
+                "rebase",
+                revision = self.graph.node(*revision).unwrap().label(),
+                destination = self.graph.node(*destination).unwrap().label()
+            )
+        } else {
+            tr!("rebase-selected")
+        }
+    }
+
+    /// Asks the user to confirm the pending mutation and runs it
+    fn mutation_confirmation(&mut self, ctx: &egui::Context) {
+        let Some(mutation) = self.pending_mutation.clone() else {
+            return;
+        };
+        let args = mutation.args();
+        let mut run = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("mutation_confirmation")).show(ctx, |ui| {
+            ui.label(tr!("confirm-command"));
+            ui.label(RichText::new(format!("jj {}", args.join(" "))).monospace());
+            ui.horizontal(|ui| {
+                run = ui.button(tr!("run")).clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if cancel || modal.should_close() {
+            self.pending_mutation = None;
+        } else if run {
+            self.pending_mutation = None;
+            if let Err(e) = jj_cli::run(&self.repository_path, &args) {
+                self.status_message = Some(e.to_string());
+            }
+            self.reload_repo();
+        }
+    }
+
+    fn check_working_copy(&mut self) {
+        self.working_copy_stale =
+            working_copy::may_be_stale(&self.repository_path).unwrap_or_default();
+    }
+
+    /// Warns next to the working-copy commit when the working copy has changed
+    /// since the last snapshot, with a button for snapshotting when
+    /// mutations are allowed
+    fn stale_indicator(&mut self, ui: &mut egui::Ui, graph_origin: egui::Pos2) {
+        if !self.working_copy_stale {
+            return;
+        }
+        let working_copy = self.jj_graph.working_copy_commit_id();
+        let Some(node) = (self.node_idxs.iter())
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .find(|node| Some(node.payload()) == working_copy.as_ref())
+        else {
+            return;
+        };
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let center = graph_origin + meta.canvas_to_screen_pos(node.location()).to_vec2();
+        let radius = meta.canvas_to_screen_size(node.display().radius());
+        egui::Area::new(ui.id().with("stale_indicator"))
+            .fixed_pos(center - egui::vec2(0., radius * 2.5))
+            .pivot(egui::Align2::CENTER_BOTTOM)
+            .constrain_to(self.graph_rect)
+            .show(ui.ctx(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(
+                        RichText::new(tr!("working-copy-stale"))
+                            .small()
+                            .color(ui.visuals().warn_fg_color),
+                    )
+                    .on_hover_text(tr!("working-copy-stale-hover"));
+                    if self.allow_mutations
+                        && ui
+                            .small_button(tr!("snapshot"))
+                            .on_hover_text(tr!("snapshot-hover"))
+                            .clicked()
+                    {
+                        self.pending_mutation = Some(jj_cli::Mutation::Snapshot);
+                    }
+                });
+            });
+    }
+
+    fn watch_menu(&mut self, ui: &mut egui::Ui) {
+        let watch = &mut self.repo_watch;
+        ui.radio_value(
+            &mut watch.watch,
+            repo_watch::Watch::Paused,
+            tr!("watch-paused"),
+        )
+        .on_hover_text(tr!("watch-paused-hover"));
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut watch.watch, repo_watch::Watch::Poll, tr!("watch-poll"));
+            ui.add(
+                egui::DragValue::new(&mut watch.poll_seconds)
+                    .range(1..=3600)
+                    .suffix(tr!("watch-seconds")),
+            );
+        });
+        ui.radio_value(
+            &mut watch.watch,
+            repo_watch::Watch::Instant,
+            tr!("watch-instant"),
+        )
+        .on_hover_text(tr!("watch-instant-hover"));
+        ui.separator();
+        ui.checkbox(&mut self.ui_settings.watch_notify, tr!("watch-notify"))
+            .on_hover_text(tr!("watch-notify-hover"));
+    }
+
+    /// Reloads the repository after it changed, telling which commits joined
+    /// and left the Select result
+    fn reload_watched_repo(&mut self) {
+        let select = self.filter_revset.value.trim().to_owned();
+        let evaluate = |jj_graph: &jjgraph::JjGraph| {
+            result_set::evaluate(jj_graph, &select, result_watch::LIMIT).ok()
+        };
+        let before = (self.ui_settings.watch_notify && !select.is_empty())
+            .then(|| evaluate(&self.jj_graph))
+            .flatten();
+        self.reload_repo();
+        let Some(before) = before else {
+            return;
+        };
+        let Some(after) = evaluate(&self.jj_graph) else {
+            return;
+        };
+        let Some(change) = result_watch::ResultChange::between(&select, &before, &after) else {
+            return;
+        };
+        if let Some(command) = &self.select_changed_hook {
+            change.run_hook(command, &self.repository_path, self.jj_error_sender.clone());
+        }
+        self.result_change = Some(change);
+    }
+
+    fn result_change_window(&mut self, ctx: &egui::Context) {
+        let Some(change) = &self.result_change else {
+            return;
+        };
+        let store = self.jj_graph.get_repo().store().clone();
+        // The commits that left may have been abandoned, but are still stored
+        let label = |commit_id: &CommitId| {
+            let description = store.get_commit(commit_id).ok().map(|commit| {
+                let description = commit.description().lines().next().unwrap_or_default();
+                description.to_owned()
+            });
+            format!(
+                "{} {}",
+                &commit_id.hex()[..12],
+                description.unwrap_or_default()
+            )
+        };
+        let mut open = true;
+        egui::Window::new(tr!("result-change-title"))
+            .id(egui::Id::new("result_change"))
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(egui::RichText::new(&change.revset).monospace());
+                let joined = tr!("result-change-joined", count = change.joined.len());
+                let left = tr!("result-change-left", count = change.left.len());
+                for (i, (heading, commit_ids)) in [(joined, &change.joined), (left, &change.left)]
+                    .into_iter()
+                    .enumerate()
+                {
+                    if commit_ids.is_empty() {
+                        continue;
+                    }
+                    ui.strong(heading);
+                    egui::ScrollArea::vertical()
+                        .id_salt(("result_change", i))
+                        .max_height(150.)
+                        .show(ui, |ui| {
+                            for commit_id in commit_ids {
+                                ui.label(label(commit_id));
+                            }
+                        });
+                }
+            });
+        if !open {
+            self.result_change = None;
+        }
+    }
+
+    fn reload_repo(&mut self) {
+        match load_jj_graph(&self.repository_path, self.load_options.clone()) {
+            Ok(jj_graph) => {
+                self.revset_vocabulary = revset_completion::Vocabulary::load(&jj_graph);
+                self.jj_graph = jj_graph;
+                self.repo_reloaded = true;
+                // Its commits and their order may have changed
+                self.extendable = None;
+                self.op_log.clear();
+                self.bookmark_move = None;
+                self.past_operation = None;
+            }
+            Err(e) => {
+                self.status_message = Some(tr!("reload-failed", error = e.to_string()));
+            }
+        }
+    }
+
+    /// Shows the repository as it was at the operation. Reloading it goes
+    /// back to the latest operation.
+    fn show_operation(&mut self, op_id: &OperationId) {
+        match self.jj_graph.at_operation(op_id) {
+            Ok(jj_graph) => {
+                self.revset_vocabulary = revset_completion::Vocabulary::load(&jj_graph);
+                // The latest operation is shown as after reloading
+                self.past_operation =
+                    (jj_graph.is_outdated().unwrap_or_default()).then(|| op_id.clone());
+                self.jj_graph = jj_graph;
+                self.repo_reloaded = true;
+                self.extendable = None;
+                self.bookmark_move = None;
+            }
+            Err(e) => {
+                self.status_message = Some(tr!("operation-load-failed", error = e.to_string()));
+            }
+        }
+    }
+
+    /// Text printed by --print-selection-to-stdout
+    fn selection_output(&self, output: SelectionOutput) -> String {
+        match output {
+            SelectionOutput::ChangeIds => {
+                let store = self.jj_graph.get_repo().store().clone();
+                self.graph
+                    .selected_nodes()
+                    .iter()
+                    .filter_map(|node_idx| {
+                        let commit_id = self.graph.node(*node_idx)?.payload();
+                        let commit = store.get_commit(commit_id).ok()?;
+                        Some(format!("{}\n", commit.change_id()))
+                    })
+                    .collect()
+            }
+            SelectionOutput::Revset => format!("{}\n", self.filter_revset.value.trim()),
+        }
+    }
+
+    /// Shows information about the last selected node
+    fn details_panel(&mut self, ui: &mut egui::Ui) {
+        let Some(commit_id) = self
+            .graph
+            .selected_nodes()
+            .last()
+            .and_then(|idx| self.graph.node(*idx))
+            .map(|node| node.payload().clone())
+        else {
+            return;
+        };
+        let Ok(commit) = self.jj_graph.get_repo().store().get_commit(&commit_id) else {
+            return;
+        };
+        let author = commit.author();
+        ui.horizontal(|ui| {
+            let avatar = self.avatars.get(ui.ctx(), &author.email);
+            ui.image((avatar, egui::vec2(40., 40.)));
+            ui.vertical(|ui| {
+                ui.strong(&author.name);
+                let revset = author_filter::author_revset(&author.name, &author.email);
+                if (ui.link(&author.email))
+                    .on_hover_text(tr!("select-matching", revset = revset.as_str()))
+                    .clicked()
+                {
+                    self.requested_filter = Some(revset);
+                }
+            });
+        });
+        ui.label(format_timestamp(&author.timestamp));
+        ui.separator();
+        egui::Grid::new("commit_ids").num_columns(2).show(ui, |ui| {
+            ui.label(tr!("change-id"));
+            ui.label(RichText::new(commit.change_id().to_string()).monospace());
+            ui.end_row();
+            ui.label(tr!("commit-id"));
+            ui.label(RichText::new(commit_id.hex()).monospace());
+            ui.end_row();
+            ui.label(tr!("signature"));
+            let repo = self.jj_graph.get_repo();
+            let status = match self.signatures.get(ui.ctx(), &repo, &commit_id) {
+                None => tr!("signature-verifying"),
+                Some(signature) => {
+                    let status = signature_name(signature.status);
+                    match &signature.signer {
+                        Some(signer) => {
+                            tr!("signature-by", status = status, signer = signer.as_str())
+                        }
+                        None => status,
+                    }
+                }
+            };
+            ui.label(status).on_hover_text(tr!("signature-revsets"));
+            ui.end_row();
+            if let Some(annotation) = self.annotations.get(&commit_id) {
+                ui.label(tr!("annotation"));
+                let status = annotation_status_name(annotation.status);
+                match &annotation.label {
+                    Some(label) => ui.label(format!("{label} ({status})")),
+                    None => ui.label(status),
+                };
+                ui.end_row();
+            }
+        });
+        ui.separator();
+        let description = commit.description().trim_end();
+        if description.is_empty() {
+            ui.weak(tr!("no-description"));
+        } else {
+            ui.label(description);
+        }
+        self.description_metadata(ui, description);
+        ui.separator();
+        if commit.has_conflict() {
+            let repo = self.jj_graph.get_repo();
+            self.conflict_view.ui(ui, repo.as_ref(), &commit);
+            ui.separator();
+        }
+        self.diff_summary(ui, &commit_id);
+    }
+
+    /// Trailers and issue references of the description. Clicking one selects
+    /// the commits that have it.
+    fn description_metadata(&mut self, ui: &mut egui::Ui, description: &str) {
+        let trailers = trailers::parse_description_trailers(description);
+        let issues = trailers::issue_references(description);
+        if trailers.is_empty() && issues.is_empty() {
+            return;
+        }
+        ui.separator();
+        let mut select_link = |ui: &mut egui::Ui, text: &str, revset: String| {
+            if (ui.link(text))
+                .on_hover_text(tr!("select-matching", revset = revset.as_str()))
+                .clicked()
+            {
+                self.requested_filter = Some(revset);
+            }
+        };
+        egui::Grid::new("trailers").num_columns(2).show(ui, |ui| {
+            for trailer in &trailers {
+                ui.label(&trailer.key);
+                select_link(ui, &trailer.value, trailers::trailer_revset(trailer));
+                ui.end_row();
+            }
+            if !issues.is_empty() {
+                ui.label(tr!("issues"));
+                ui.horizontal_wrapped(|ui| {
+                    for issue in &issues {
+                        select_link(ui, issue, trailers::issue_revset(issue));
+                    }
+                });
+                ui.end_row();
+            }
+        });
+    }
+
+    fn show_diff_label(&self) -> String {
+        match &self.diff_tool {
+            Some(tool) => tr!("show-diff-in", tool = tool.as_str()),
+            None => tr!("show-diff"),
+        }
+    }
+
+    /// Runs `jj diff` for the commit, in the configured diff tool
+    fn show_diff(&self, ctx: &egui::Context, commit_id: &CommitId) {
+        jj_cli::spawn(
+            &self.repository_path,
+            jj_cli::diff_args(commit_id, self.diff_tool.as_deref()),
+            self.jj_error_sender.clone(),
+            ctx.clone(),
+        );
+    }
+
+    /// Files changed by the commit, compared to its first parent, and their
+    /// diffs unless they are too large
+    fn diff_summary(&mut self, ui: &mut egui::Ui, commit_id: &CommitId) {
+        let repo = self.jj_graph.get_repo();
+        let diffs = match self.diffs.get(ui.ctx(), &repo, commit_id) {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(tr!("diff-loading"));
+                });
+                return;
+            }
+            Some(Err(e)) => {
+                ui.colored_label(
+                    ui.visuals().error_fg_color,
+                    tr!("diff-failed", error = e.as_str()),
+                );
+                return;
+            }
+            Some(Ok(diffs)) => diffs,
+        };
+        if diffs.is_empty() {
+            ui.weak(tr!("diff-no-changes"));
+            return;
+        }
+        ui.label(tr!(
+            "diff-summary",
+            files = diffs.len(),
+            insertions = diffs.iter().map(|diff| diff.stat.insertions).sum::<usize>(),
+            deletions = diffs.iter().map(|diff| diff.stat.deletions).sum::<usize>()
+        ));
+        let lines = (diffs.iter().flat_map(|diff| &diff.hunks))
+            .map(|hunk| hunk.lines.len())
+            .sum::<usize>();
+        if lines <= self.max_diff_lines {
+            for diff in diffs {
+                diff_view::file_diff(ui, (commit_id, &diff.stat.path), diff);
+            }
+            return;
+        }
+        egui::Grid::new("diff_stats").num_columns(3).show(ui, |ui| {
+            for diff in diffs {
+                diff_view::stat(ui, &diff.stat);
+                if diff.stat.binary {
+                    ui.label("");
+                }
+                ui.end_row();
+            }
+        });
+        ui.weak(tr!("diff-too-large", lines = lines));
+        if ui.button(self.show_diff_label()).clicked() {
+            self.show_diff(ui.ctx(), commit_id);
+        }
+    }
+
+    /// Shows author avatars on the nodes in color-by-author mode
+    fn apply_avatars(&mut self, ctx: &egui::Context) {
+        let store = self.jj_graph.get_repo().store().clone();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let avatar = if self.color_by_author {
+                store
+                    .get_commit(node.payload())
+                    .ok()
+                    .map(|commit| self.avatars.get(ctx, &commit.author().email))
+            } else {
+                None
+            };
+            node.display_mut().avatar = avatar;
+        }
+    }
+
+    fn menu_bar(&mut self, ui: &mut egui::Ui) {
+        egui::MenuBar::new().ui(ui, |ui| {
+            ui.menu_button(tr!("menu-file"), |ui| {
+                if ui.button(tr!("menu-open-tab")).clicked() {
+                    self.open_tab_requested = true;
+                }
+                ui.menu_button(tr!("menu-open-recent"), |ui| {
+                    if self.recent_repositories.is_empty() {
+                        ui.weak(tr!("menu-no-recent"));
+                    }
+                    for path in &self.recent_repositories {
+                        if ui.button(path.display().to_string()).clicked() {
+                            self.open_recent_request = Some(path.clone());
+                        }
+                    }
+                });
+                ui.separator();
+                let reload = egui::Button::new(tr!("menu-reload"))
+                    .shortcut_text(ui.ctx().format_shortcut(&keymap::RELOAD));
+                if ui.add(reload).clicked() {
+                    self.reload_repo();
+                }
+                ui.menu_button(tr!("menu-watch"), |ui| self.watch_menu(ui));
+                ui.separator();
+                for format in [
+                    ExportFormat::Html,
+                    ExportFormat::Snapshot,
+                    ExportFormat::Layout,
+                    ExportFormat::Dot,
+                    ExportFormat::Svg,
+                    ExportFormat::Png,
+                ] {
+                    if ui
+                        .button(tr!("menu-export-as", format = format.name()))
+                        .clicked()
+                    {
+                        self.export_dialog = Some(ExportDialog {
+                            format,
+                            path: format.default_path().to_owned(),
+                            legend: true,
+                            select_only: false,
+                        });
+                    }
+                }
+                ui.separator();
+                for (import, label) in [
+                    (false, tr!("menu-export-settings")),
+                    (true, tr!("menu-import-settings")),
+                ] {
+                    if ui.button(label).clicked() {
+                        self.settings_dialog = Some(SettingsDialog {
+                            import,
+                            path: SETTINGS_BUNDLE_PATH.to_owned(),
+                        });
+                    }
+                }
+                ui.separator();
+                for (load, label, hover) in [
+                    (
+                        false,
+                        tr!("menu-save-result-set"),
+                        tr!("menu-save-result-set-hover"),
+                    ),
+                    (
+                        true,
+                        tr!("menu-load-result-set"),
+                        tr!("menu-load-result-set-hover"),
+                    ),
+                ] {
+                    if ui.button(label).on_hover_text(hover).clicked() {
+                        self.result_set_dialog = Some(ResultSetDialog {
+                            load,
+                            path: RESULT_SET_PATH.to_owned(),
+                        });
+                    }
+                }
+                ui.separator();
+                if ui
+                    .button(tr!("menu-export-perf-report"))
+                    .on_hover_text(tr!("menu-export-perf-report-hover"))
+                    .clicked()
+                {
+                    self.perf_report_dialog = Some(PERF_REPORT_PATH.to_owned());
+                }
+            });
+            ui.menu_button(tr!("menu-edit"), |ui| {
+                if ui.button(tr!("menu-copy-graph")).clicked() {
+                    ui.ctx()
+                        .send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(
+                            ScreenshotPurpose::CopyGraph,
+                        )));
+                }
+                if ui.button(tr!("menu-replace-symbol")).clicked() {
+                    self.replace_dialog = Some(ReplaceDialog::default());
+                }
+            });
+            ui.menu_button(tr!("menu-view"), |ui| self.view_menu(ui));
+            ui.menu_button(tr!("menu-help"), |ui| {
+                if ui.button(tr!("menu-about")).clicked() {
+                    self.about.open = true;
+                }
+            });
+        });
+    }
+
+    /// Saving the current viewpoint and jumping to the saved ones
+    fn viewpoints_menu(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            let next_name = self.viewpoints.next_name();
+            let name = egui::TextEdit::singleline(&mut self.viewpoints.name)
+                .hint_text(next_name)
+                .desired_width(120.);
+            ui.add(name);
+            if ui.button(tr!("viewpoint-save")).clicked() {
+                let name = self.viewpoints.name.trim();
+                let name = if name.is_empty() {
+                    self.viewpoints.next_name()
+                } else {
+                    name.to_owned()
+                };
+                let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+                let saved = self.viewpoints.save(viewpoints::Viewpoint {
+                    name,
+                    view: self.view_revset.value.clone(),
+                    zoom: meta.zoom,
+                    pan: meta.pan,
+                });
+                if saved {
+                    self.viewpoints.name.clear();
+                } else {
+                    self.status_message =
+                        Some(tr!("viewpoints-full", count = viewpoints::MAX_VIEWPOINTS));
+                }
+            }
+        });
+        let mut jump = None;
+        let mut remove = None;
+        for (i, viewpoint) in self.viewpoints.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let shortcut = ui.ctx().format_shortcut(&keymap::VIEWPOINTS[i]);
+                let button = egui::Button::new(&viewpoint.name).shortcut_text(shortcut);
+                if ui.add(button).on_hover_text(&viewpoint.view).clicked() {
+                    jump = Some(i);
+                }
+                if ui.small_button("✖").clicked() {
+                    remove = Some(i);
+                }
+            });
+        }
+        if let Some(i) = jump {
+            self.jump_to_viewpoint(i);
+            ui.close();
+        }
+        if let Some(i) = remove {
+            self.viewpoints.remove(i);
+        }
+    }
+
+    /// Shows the View revset of the viewpoint, zoomed and panned as it was
+    fn jump_to_viewpoint(&mut self, index: usize) {
+        let Some(viewpoint) = self.viewpoints.get(index) else {
+            return;
+        };
+        if self.view_revset.value != viewpoint.view {
+            self.view_revset.value = viewpoint.view.clone();
+            self.view_revset.history.add(viewpoint.view.trim(), false);
+            self.view_invalidated = true;
+        }
+        self.pending_viewpoint = Some((viewpoint.zoom, viewpoint.pan));
+    }
+
+    fn view_menu(&mut self, ui: &mut egui::Ui) {
+        use egui::gui_zoom::{self, kb_shortcuts};
+        // egui handles the shortcuts
+        let button = |text, shortcut| {
+            egui::Button::new(text).shortcut_text(ui.ctx().format_shortcut(&shortcut))
+        };
+        let zoom_in = button(tr!("menu-zoom-in"), kb_shortcuts::ZOOM_IN);
+        let zoom_out = button(tr!("menu-zoom-out"), kb_shortcuts::ZOOM_OUT);
+        let zoom_reset = button(tr!("menu-zoom-reset"), kb_shortcuts::ZOOM_RESET);
+        if ui.add(zoom_in).clicked() {
+            gui_zoom::zoom_in(ui.ctx());
+        }
+        if ui.add(zoom_out).clicked() {
+            gui_zoom::zoom_out(ui.ctx());
+        }
+        if ui.add(zoom_reset).clicked() {
+            ui.ctx().set_zoom_factor(1.);
+        }
+        ui.menu_button(tr!("menu-viewpoints"), |ui| self.viewpoints_menu(ui));
+        ui.horizontal(|ui| {
+            ui.label(tr!("menu-node-limit"));
+            let limit = egui::DragValue::new(&mut self.node_limit).range(1..=100_000);
+            let limit = ui.add(limit).on_hover_text(tr!("menu-node-limit-hover"));
+            // Not while dragging, as each change evaluates the view again
+            if limit.drag_stopped() || limit.changed() && !limit.dragged() {
+                self.view_invalidated = true;
+            }
+        });
+        ui.separator();
+        let label_scale = self.label_scale;
+        if ui.button(tr!("menu-larger-labels")).clicked() {
+            self.label_scale = (self.label_scale + 0.25).min(4.);
+        }
+        if ui.button(tr!("menu-smaller-labels")).clicked() {
+            self.label_scale = (self.label_scale - 0.25).max(0.5);
+        }
+        if ui.button(tr!("menu-reset-labels")).clicked() {
+            self.label_scale = 1.;
+        }
+        ui.separator();
+        ui.menu_button(tr!("menu-palette"), |ui| {
+            for palette in Palette::ALL {
+                self.recolor |= ui
+                    .radio_value(&mut self.palette, palette, palette_name(palette))
+                    .changed();
+            }
+        });
+        let node_size = self.node_size;
+        ui.menu_button(tr!("menu-node-size"), |ui| {
+            for size in NodeSize::ALL {
+                ui.radio_value(&mut self.node_size, size, node_size_name(size));
+            }
+        });
+        if self.node_size != node_size {
+            self.apply_node_sizes(ui.ctx());
+        }
+        let author_label = self.author_label;
+        ui.menu_button(tr!("menu-author-label"), |ui| {
+            for label in author_label::AuthorLabel::ALL {
+                ui.radio_value(&mut self.author_label, label, author_label_name(label));
+            }
+        });
+        if self.author_label != author_label {
+            self.apply_author_labels();
+        }
+        ui.menu_button(tr!("menu-guides"), |ui| {
+            for guide in graph_guides::Guide::ALL {
+                let mut shown = self.guides.contains(&guide);
+                if ui.checkbox(&mut shown, guide_name(guide)).changed() {
+                    self.guides.retain(|g| *g != guide);
+                    if shown {
+                        self.guides.push(guide);
+                    }
+                }
+            }
+        });
+        let edge_style = (self.edge_routing, self.arrowheads, self.arrow_direction);
+        ui.menu_button(tr!("menu-edges"), |ui| {
+            for routing in EdgeRouting::ALL {
+                ui.radio_value(&mut self.edge_routing, routing, edge_routing_name(routing));
+            }
+            ui.separator();
+            ui.label(tr!("menu-arrowheads"));
+            for arrowheads in Arrowheads::ALL {
+                ui.radio_value(
+                    &mut self.arrowheads,
+                    arrowheads,
+                    arrowheads_name(arrowheads),
+                );
+            }
+            ui.separator();
+            ui.label(tr!("menu-arrow-direction"));
+            for direction in ArrowDirection::ALL {
+                ui.radio_value(
+                    &mut self.arrow_direction,
+                    direction,
+                    arrow_direction_name(direction),
+                );
+            }
+        });
+        if (self.edge_routing, self.arrowheads, self.arrow_direction) != edge_style {
+            self.apply_edge_style();
+        }
+        if ui
+            .checkbox(&mut self.signature_badges, tr!("menu-signature-badges"))
+            .changed()
+        {
+            self.apply_signature_badges(ui.ctx());
+        }
+        if ui
+            .checkbox(&mut self.show_distances, tr!("menu-distances"))
+            .on_hover_text(tr!("menu-distances-hover"))
+            .changed()
+        {
+            self.distance_reference = None;
+            self.apply_distances();
+        }
+        ui.checkbox(&mut self.avoid_label_overlaps, tr!("menu-label-overlaps"))
+            .on_hover_text(tr!("menu-label-overlaps-hover"));
+        ui.checkbox(&mut self.highlight_path, tr!("menu-highlight-path"))
+            .on_hover_text(tr!("menu-highlight-path-hover"));
+        ui.checkbox(&mut self.date_histogram, tr!("menu-date-histogram"))
+            .on_hover_text(tr!("menu-date-histogram-hover"));
+        if ui
+            .checkbox(&mut self.number_results, tr!("menu-number-results"))
+            .on_hover_text(tr!("menu-number-results-hover"))
+            .changed()
+        {
+            self.update_select_order();
+        }
+        if ui
+            .add_enabled(
+                !self.change_nodes,
+                egui::Checkbox::new(&mut self.show_commit_ids, tr!("menu-commit-ids")),
+            )
+            .on_hover_text(tr!("menu-commit-ids-hover"))
+            .changed()
+        {
+            self.apply_commit_ids();
+        }
+        if ui
+            .checkbox(&mut self.change_nodes, tr!("menu-change-nodes"))
+            .on_hover_text(tr!("menu-change-nodes-hover"))
+            .changed()
+        {
+            self.view_invalidated = true;
+        }
+        if ui
+            .checkbox(&mut self.cluster_by_bookmark, tr!("menu-clusters"))
+            .on_hover_text(tr!("menu-clusters-hover"))
+            .changed()
+        {
+            self.update_bookmark_clusters();
+        }
+        if ui
+            .checkbox(&mut self.group_divergent, tr!("menu-change-groups"))
+            .on_hover_text(tr!("menu-change-groups-hover"))
+            .changed()
+        {
+            self.update_change_groups();
+        }
+        let simulation = self.simulation;
+        ui.menu_button(tr!("menu-simulate"), |ui| {
+            ui.radio_value(&mut self.simulation, None, tr!("vision-normal"));
+            for deficiency in Deficiency::ALL {
+                ui.radio_value(
+                    &mut self.simulation,
+                    Some(deficiency),
+                    deficiency_name(deficiency),
+                );
+            }
+        });
+        if self.label_scale != label_scale || self.simulation != simulation {
+            self.apply_display_settings();
+        }
+        ui.separator();
+        let shortcuts = egui::Button::new(tr!("menu-shortcuts"))
+            .shortcut_text(ui.ctx().format_shortcut(&keymap::SHORTCUTS));
+        if ui.add(shortcuts).clicked() {
+            self.show_shortcuts = true;
+        }
+    }
+
+    /// Sizes the nodes by the changes of their commits. Nodes get their size
+    /// when it has been computed.
+    fn apply_node_sizes(&mut self, ctx: &egui::Context) {
+        let repo = self.jj_graph.get_repo();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let scale = match self.node_size {
+                NodeSize::Uniform => 1.,
+                node_size => (self.change_sizes.get(ctx, &repo, node.payload()))
+                    .map_or(1., |size| node_size.scale(size)),
+            };
+            node.display_mut().set_size_scale(scale);
+        }
+    }
+
+    /// Shows the signature status of the commits, when enabled. Nodes get
+    /// their badge when the signature has been verified.
+    fn apply_signature_badges(&mut self, ctx: &egui::Context) {
+        let repo = self.jj_graph.get_repo();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let badge = if self.signature_badges {
+                (self.signatures.get(ctx, &repo, node.payload()))
+                    .and_then(|signature| signature_color(signature.status))
+            } else {
+                None
+            };
+            node.display_mut().signature_badge = badge;
+        }
+    }
+
+    /// Labels the nodes with their generation distance from the reference,
+    /// when enabled. Nodes that are neither ancestors nor descendants of it
+    /// get no label.
+    fn apply_distances(&mut self) {
+        let reference =
+            (self.distance_reference.clone()).or_else(|| self.jj_graph.working_copy_commit_id());
+        let distances = match reference {
+            Some(reference) if self.show_distances => {
+                generation_distances(&self.jj_graph, &reference, &self.view_with_pins())
+                    .unwrap_or_else(|e| {
+                        self.status_message = Some(tr!("distances-failed", error = e.to_string()));
+                        HashMap::new()
+                    })
+            }
+            _ => HashMap::new(),
+        };
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let label = (distances.get(node.payload())).map(|distance| match distance {
+                1.. => format!("+{distance}"),
+                _ => distance.to_string(),
+            });
+            node.display_mut().distance_label = label;
+        }
+    }
+
+    /// Places the labels for the next frame from where they were drawn, so
+    /// that they don't overlap
+    fn place_labels(&mut self, ui: &egui::Ui, graph_origin: egui::Pos2) {
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let mut labels = vec![];
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node(*node_idx).unwrap();
+            // Relative to the graph view
+            let Some(rect) = node
+                .display()
+                .label_rect
+                .filter(|_| self.avoid_label_overlaps)
+            else {
+                continue;
+            };
+            if !rect.intersects(self.graph_rect.translate(-graph_origin.to_vec2())) {
+                continue;
+            }
+            let center = meta.canvas_to_screen_pos(node.location());
+            let radius = meta.canvas_to_screen_size(node.display().radius());
+            let label = label_placement::Label {
+                rect,
+                node: egui::Rect::from_center_size(center, egui::Vec2::splat(radius * 2.)),
+                priority: node.selected() || node.hovered(),
+            };
+            labels.push((*node_idx, label));
+        }
+        // Top to bottom
+        labels.sort_by(|(_, a), (_, b)| a.node.center().y.total_cmp(&b.node.center().y));
+        let (node_idxs, labels): (Vec<_>, Vec<_>) = labels.into_iter().unzip();
+        let placements = label_placement::place_labels(&labels);
+        for node_idx in &self.node_idxs {
+            self.graph
+                .node_mut(*node_idx)
+                .unwrap()
+                .display_mut()
+                .label_placement = Default::default();
+        }
+        for (node_idx, placement) in node_idxs.into_iter().zip(placements) {
+            self.graph
+                .node_mut(node_idx)
+                .unwrap()
+                .display_mut()
+                .label_placement = placement;
+        }
+    }
+
+    /// Adds the shortened descriptions to the labels, when enabled in the
+    /// settings
+    fn apply_description_labels(&mut self) {
+        let max_chars = self.ui_settings.label_description_length;
+        let ellipsis = self.ui_settings.label_ellipsis.as_deref().unwrap_or("…");
+        let store = self.jj_graph.get_repo().store().clone();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let description = (max_chars > 0)
+                .then(|| store.get_commit(node.payload()).ok())
+                .flatten()
+                .map(|commit| {
+                    description_label::truncate(
+                        commit.description(),
+                        max_chars,
+                        self.ui_settings.label_truncation,
+                        ellipsis,
+                    )
+                })
+                .filter(|description| !description.is_empty());
+            node.display_mut().description = description;
+        }
+    }
+
+    /// Shows the full author and description of the hovered node, when the
+    /// labels only have the beginning of them
+    fn node_tooltip(&self, graph_response: &egui::Response) {
+        let show_author = self.author_label != author_label::AuthorLabel::None;
+        let show_description = self.ui_settings.label_description_length > 0;
+        if !show_author && !show_description {
+            return;
+        }
+        let Some(commit) = (self.graph.hovered_node())
+            .and_then(|node_idx| self.graph.node(node_idx))
+            .and_then(|node| {
+                (self.jj_graph.get_repo().store())
+                    .get_commit(node.payload())
+                    .ok()
+            })
+        else {
+            return;
+        };
+        let mut lines = vec![];
+        if show_author {
+            let author = commit.author();
+            lines.push(format!("{} <{}>", author.name, author.email));
+        }
+        let description = commit.description().trim_end();
+        if show_description && !description.is_empty() {
+            lines.push(description.to_owned());
+        }
+        if !lines.is_empty() {
+            graph_response
+                .clone()
+                .on_hover_text_at_pointer(lines.join("\n\n"));
+        }
+    }
+
+    /// Labels the nodes with their shortened authors, when enabled
+    fn apply_author_labels(&mut self) {
+        let store = self.jj_graph.get_repo().store().clone();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let label = (self.author_label != author_label::AuthorLabel::None)
+                .then(|| store.get_commit(node.payload()).ok())
+                .flatten()
+                .and_then(|commit| {
+                    let author = commit.author();
+                    self.author_label.text(&author.name, &author.email)
+                });
+            node.display_mut().author_label = label;
+        }
+    }
+
+    /// Labels the nodes with their commit IDs and the length of the shortest
+    /// unique prefixes, when enabled
+    fn apply_commit_ids(&mut self) {
+        let repo = self.jj_graph.get_repo();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let label = (self.show_commit_ids && !self.change_nodes).then(|| {
+                let commit_id = node.payload();
+                let unique_len = (repo.index().shortest_unique_commit_id_prefix_len(commit_id))
+                    .unwrap_or(COMMIT_ID_LENGTH);
+                let hex = commit_id.hex();
+                (
+                    hex[..unique_len.max(COMMIT_ID_LENGTH)].to_owned(),
+                    unique_len,
+                )
+            });
+            node.display_mut().commit_id_label = label;
+        }
+    }
+
+    /// Lists the Select results in the order the revset yields them and
+    /// numbers their nodes, when needed
+    fn update_select_order(&mut self) {
+        self.select_order = if self.number_results || self.graph_tab == GraphTab::Results {
+            resolve_revset(&self.jj_graph, &self.filter_revset.value)
+                .and_then(|(revset, ..)| {
+                    (revset.iter().take(self.node_limit))
+                        .collect::<Result<_, _>>()
+                        .map_err(ResolveError::from)
+                })
+                // The error is shown by the Select field
+                .unwrap_or_default()
+        } else {
+            vec![]
+        };
+        let positions: HashMap<_, _> = if self.number_results {
+            (self.select_order.iter().enumerate())
+                .map(|(i, commit_id)| (commit_id, i + 1))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            node.display_mut().order_label =
+                (positions.get(node.payload())).map(|position| format!("#{position}"));
+        }
+    }
+
+    /// Table of the Select results in evaluation order, for checking
+    /// ordering-sensitive revsets like `latest(mine(), 5)`
+    fn results_table(&mut self, ui: &mut egui::Ui) {
+        let functions = ordering_functions(&self.filter_revset.value);
+        ui.label(if functions.is_empty() {
+            tr!("results-count", count = self.select_order.len())
+        } else {
+            let functions: Vec<_> = functions.iter().map(|f| format!("{f}()")).collect();
+            tr!(
+                "results-count-ordering",
+                count = self.select_order.len(),
+                functions = functions.join(", ")
+            )
+        });
+        let store = self.jj_graph.get_repo().store().clone();
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + ui.spacing().item_spacing.y;
+        let mut select = None;
+        egui::ScrollArea::vertical().show_rows(
+            ui,
+            row_height,
+            self.select_order.len() + 1,
+            |ui, rows| {
+                egui::Grid::new("results")
+                    .num_columns(5)
+                    .striped(true)
+                    .start_row(rows.start)
+                    .show(ui, |ui| {
+                        for row in rows {
+                            let Some(commit_id) = row.checked_sub(1).map(|i| &self.select_order[i])
+                            else {
+                                for heading in [
+                                    "#".to_owned(),
+                                    tr!("results-commit"),
+                                    tr!("results-description"),
+                                    tr!("results-author"),
+                                    tr!("results-date"),
+                                ] {
+                                    ui.strong(heading);
+                                }
+                                ui.end_row();
+                                continue;
+                            };
+                            ui.label(row.to_string());
+                            if ui.link(self.node_label(commit_id)).clicked() {
+                                select = Some(commit_id.clone());
+                            }
+                            if let Ok(commit) = store.get_commit(commit_id) {
+                                ui.label(commit.description().lines().next().unwrap_or_default());
+                                ui.label(&commit.author().name);
+                                ui.label(format_timestamp(&commit.committer().timestamp));
+                            }
+                            ui.end_row();
+                        }
+                    });
+            },
+        );
+        // Shown in the graph, if it is in the view
+        if let Some(commit_id) = select {
+            for node_idx in self.node_idxs.clone() {
+                let selected = self.graph.node(node_idx).unwrap().payload() == &commit_id;
+                self.set_node_selected(node_idx, selected);
+            }
+            self.graph_tab = GraphTab::Commits;
+        }
+    }
+
+    /// Groups the commits in the view by their nearest descendant bookmark,
+    /// when enabled
+    fn update_bookmark_clusters(&mut self) {
+        self.bookmark_clusters = if self.cluster_by_bookmark {
+            bookmark_clusters(&self.jj_graph, &self.view_with_pins()).unwrap_or_else(|e| {
+                self.status_message = Some(tr!("clusters-failed", error = e.to_string()));
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+    }
+
+    fn update_change_groups(&mut self) {
+        self.change_groups = if self.group_divergent {
+            let commit_ids = (self.node_idxs.iter())
+                .map(|node_idx| self.graph.node(*node_idx).unwrap().payload());
+            change_groups::divergent_changes(&self.jj_graph, commit_ids).unwrap_or_else(|e| {
+                self.status_message = Some(tr!("change-groups-failed", error = e.to_string()));
+                HashMap::new()
+            })
+        } else {
+            HashMap::new()
+        };
+    }
+
+    /// Boxes around the nodes of each divergent change, in screen coordinates
+    fn change_group_boxes(&self, ui: &egui::Ui, graph_origin: egui::Pos2) -> Vec<egui::Shape> {
+        if self.change_groups.is_empty() {
+            return vec![];
+        }
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let mut groups = BTreeMap::<&str, Vec<_>>::new();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node(*node_idx).unwrap();
+            let Some(change_id) = self.change_groups.get(node.payload()) else {
+                continue;
+            };
+            let center = graph_origin + meta.canvas_to_screen_pos(node.location()).to_vec2();
+            let radius = meta.canvas_to_screen_size(node.display().radius());
+            groups.entry(change_id).or_default().push((center, radius));
+        }
+        let groups: Vec<_> = (groups.into_iter())
+            .map(|(change_id, nodes)| (change_id.to_owned(), nodes))
+            .collect();
+        change_groups::group_box_shapes(ui, &groups)
+    }
+
+    /// Guides behind the graph, when enabled, in screen coordinates
+    fn guide_shapes(&self, ui: &egui::Ui, graph_rect: egui::Rect) -> Vec<egui::Shape> {
+        let boundary_focus = self.boundary_focus();
+        if self.guides.is_empty() && !boundary_focus {
+            return vec![];
+        }
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let nodes: Vec<_> = (self.node_idxs.iter())
+            .map(|node_idx| {
+                let node = self.graph.node(*node_idx).unwrap();
+                let y = graph_rect.top() + meta.canvas_to_screen_pos(node.location()).y;
+                let commit_id = node.payload();
+                let mutable = !self.immutable_commits.contains(commit_id);
+                (y, self.date_filter.time(commit_id), mutable)
+            })
+            .collect();
+        graph_guides::guide_shapes(ui, &self.guides, boundary_focus, graph_rect, &nodes)
+    }
+
+    /// How long after the last keystroke the revset inputs are evaluated
+    fn revset_debounce(&self) -> Duration {
+        let millis = self.ui_settings.revset_debounce;
+        Duration::from_millis(millis.unwrap_or(DEFAULT_REVSET_DEBOUNCE_MS))
+    }
+
+    /// The View of the immutable boundary focus mode, as the app writes it
+    fn boundary_focus_view(&self) -> String {
+        let view = self.ui_settings.boundary_focus_view.as_deref();
+        revset_simplify::simplify(view.unwrap_or(BOUNDARY_FOCUS_VIEW))
+    }
+
+    /// Whether the View is that of the immutable boundary focus mode
+    fn boundary_focus(&self) -> bool {
+        self.view_revset.value.trim() == self.boundary_focus_view()
+    }
+
+    /// Finds the immutable commits in the view, for the guides
+    fn update_immutable_commits(&mut self) {
+        let Ok(immutable) = self.jj_graph.get_revset("immutable()") else {
+            self.immutable_commits.clear();
+            return;
+        };
+        let is_immutable = immutable.containing_fn();
+        self.immutable_commits = (self.node_idxs.iter())
+            .map(|idx| self.graph.node(*idx).unwrap().payload())
+            .filter(|commit_id| is_immutable(commit_id).unwrap_or_default())
+            .cloned()
+            .collect();
+    }
+
+    /// Hulls around the nodes of each bookmark cluster, in screen coordinates
+    fn bookmark_hulls(&self, ui: &egui::Ui, graph_origin: egui::Pos2) -> Vec<egui::Shape> {
+        if self.bookmark_clusters.is_empty() {
+            return vec![];
+        }
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let mut clusters = BTreeMap::<&str, Vec<_>>::new();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node(*node_idx).unwrap();
+            let Some(bookmark) = self.bookmark_clusters.get(node.payload()) else {
+                continue;
+            };
+            let center = graph_origin + meta.canvas_to_screen_pos(node.location()).to_vec2();
+            let radius = meta.canvas_to_screen_size(node.display().radius());
+            clusters.entry(bookmark).or_default().push((center, radius));
+        }
+        let clusters: Vec<_> = (clusters.into_iter())
+            .map(|(bookmark, nodes)| (bookmark.to_owned(), nodes))
+            .collect();
+        bookmark_hulls::hull_shapes(ui, &clusters)
+    }
+
+    /// Outlines the heads and the roots of the view, when enabled
+    fn apply_boundary_outlines(&mut self) {
+        let view = self.view_with_pins();
+        let mut boundary = |enabled: bool, function: &str| -> HashSet<CommitId> {
+            if !enabled {
+                return HashSet::new();
+            }
+            let revset = format!("{function}({view})");
+            match self.jj_graph.get_revset(&revset) {
+                Ok(revset) => revset.iter().filter_map(Result::ok).collect(),
+                Err(e) => {
+                    self.status_message = Some(tr!("outline-failed", error = e.to_string()));
+                    HashSet::new()
+                }
+            }
+        };
+        let heads = boundary(self.outline_heads, "heads");
+        let roots = boundary(self.outline_roots, "roots");
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let mut outlines = vec![];
+            if heads.contains(node.payload()) {
+                outlines.push(HEADS_OUTLINE_COLOR);
+            }
+            if roots.contains(node.payload()) {
+                outlines.push(ROOTS_OUTLINE_COLOR);
+            }
+            node.display_mut().outlines = outlines;
+        }
+    }
+
+    /// Styles the nodes matching the color rules of the settings, over the
+    /// colors of the palette
+    fn apply_color_rules(&mut self) {
+        let (styles, errors) =
+            color_rules::evaluate(&self.jj_graph, &self.view_with_pins(), &self.color_rules);
+        if let Some((revset, error)) = errors.first() {
+            self.status_message = Some(tr!(
+                "color-rule-failed",
+                revset = revset.as_str(),
+                error = error.as_str()
+            ));
+        }
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let style = styles.get(node.payload()).cloned().unwrap_or_default();
+            if let Some(color) = style.color {
+                node.set_color(color);
+            }
+            node.display_mut().border = style.border;
+            node.display_mut().bold = style.bold;
+        }
+    }
+
+    /// Moves the nodes to the positions of the layout file, if any, when the
+    /// graph is to be laid out. The nodes that are not in the file are laid
+    /// out as usual.
+    fn apply_fixed_layout(&mut self, ui: &mut egui::Ui) {
+        use egui_graphs::Layout as _;
+        if self.fixed_layout.is_none() && self.kept_positions.is_empty() {
+            return;
+        }
+        let state = egui_graphs::get_layout_state::<egui_graphs::LayoutStateHierarchical>(
+            ui,
+            self.graph_id.clone(),
+        );
+        if state.triggered {
+            return;
+        }
+        let mut hierarchical = egui_graphs::LayoutHierarchical::from_state(state);
+        hierarchical.next(&mut self.graph, ui);
+        egui_graphs::set_layout_state(ui, hierarchical.state(), self.graph_id.clone());
+        // Nodes that were shown before more were loaded stay in place, and the
+        // loaded ones are moved along with them
+        let kept_positions = std::mem::take(&mut self.kept_positions);
+        let offset = (self.node_idxs.iter()).find_map(|node_idx| {
+            let node = self.graph.node(*node_idx).unwrap();
+            let kept = kept_positions.get(node.payload())?;
+            Some(*kept - node.location())
+        });
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let pos = if let Some(pos) =
+                (self.fixed_layout.as_ref()).and_then(|layout| layout.position(node.payload()))
+            {
+                pos
+            } else if let Some(pos) = kept_positions.get(node.payload()) {
+                *pos
+            } else if let Some(offset) = offset {
+                node.location() + offset
+            } else {
+                continue;
+            };
+            node.set_location(pos);
+        }
+    }
+
+    fn apply_edge_style(&mut self) {
+        let edge_idxs: Vec<_> = self.graph.edges_iter().map(|(idx, _)| idx).collect();
+        for edge_idx in edge_idxs {
+            let edge = self.graph.edge_mut(edge_idx).unwrap().display_mut();
+            edge.routing = self.edge_routing;
+            edge.arrowheads = self.arrowheads;
+            edge.arrow_direction = self.arrow_direction;
+        }
+    }
+
+    /// Orders the edges of each node across the layout, so that curved edges
+    /// leave and enter it at separate points. Follows the nodes as they move.
+    fn apply_edge_fans(&mut self) {
+        let mut outgoing = HashMap::<_, Vec<_>>::new();
+        let mut incoming = HashMap::<_, Vec<_>>::new();
+        for (edge_idx, _) in self.graph.edges_iter() {
+            let (start, end) = self.graph.edge_endpoints(edge_idx).unwrap();
+            let start_pos = self.graph.node(start).unwrap().location();
+            let end_pos = self.graph.node(end).unwrap().location();
+            outgoing.entry(start).or_default().push((end_pos, edge_idx));
+            incoming.entry(end).or_default().push((start_pos, edge_idx));
+        }
+        let fans = |edges: HashMap<_, Vec<(egui::Pos2, _)>>| -> Vec<_> {
+            (edges.into_values())
+                .flat_map(|mut edges| {
+                    edges.sort_by(|(a, _), (b, _)| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+                    let count = edges.len();
+                    (edges.into_iter())
+                        .zip(edge_shape::fan_positions(count))
+                        .map(|((_, edge_idx), fan)| (edge_idx, fan))
+                })
+                .collect()
+        };
+        for (edge_idx, fan) in fans(outgoing) {
+            self.graph
+                .edge_mut(edge_idx)
+                .unwrap()
+                .display_mut()
+                .start_fan = fan;
+        }
+        for (edge_idx, fan) in fans(incoming) {
+            self.graph.edge_mut(edge_idx).unwrap().display_mut().end_fan = fan;
+        }
+    }
+
+    /// Spans the timeline over the committer dates of the commits in the view
+    fn reset_date_filter(&mut self) {
+        let store = self.jj_graph.get_repo().store().clone();
+        let times = (self.node_idxs.iter())
+            .map(|idx| self.graph.node(*idx).unwrap().payload())
+            .filter_map(|commit_id| {
+                let commit = store.get_commit(commit_id).ok()?;
+                Some((
+                    commit_id.clone(),
+                    commit.committer().timestamp.timestamp.0 / 1000,
+                ))
+            })
+            .collect();
+        self.date_filter.reset(times);
+    }
+
+    /// Shows chips for the authors of the commits in the view
+    fn reset_author_filter(&mut self) {
+        let store = self.jj_graph.get_repo().store().clone();
+        let authors: Vec<_> = (self.node_idxs.iter())
+            .map(|idx| self.graph.node(*idx).unwrap().payload())
+            .filter_map(|commit_id| {
+                let commit = store.get_commit(commit_id).ok()?;
+                let author = commit.author();
+                Some((commit_id.clone(), author.name.clone(), author.email.clone()))
+            })
+            .collect();
+        self.author_filter.reset(authors);
+    }
+
+    /// Fades the commits outside the range of the timeline and the commits by
+    /// authors that are not chosen
+    fn apply_dimming(&mut self) {
+        let lineage =
+            (self.path_node).map(|node_idx| path_highlight::Lineage::of(self.graph.g(), node_idx));
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let dimmed = self.date_filter.dims(node.payload())
+                || self.author_filter.dims(node.payload())
+                || lineage.as_ref().is_some_and(|l| !l.contains(*node_idx));
+            node.display_mut().dimmed = dimmed;
+        }
+        let edge_idxs: Vec<_> = self.graph.edges_iter().map(|(idx, _)| idx).collect();
+        for edge_idx in edge_idxs {
+            let (child, parent) = self.graph.edge_endpoints(edge_idx).unwrap();
+            let dimmed = lineage
+                .as_ref()
+                .is_some_and(|l| !l.contains_edge(child, parent));
+            self.graph.edge_mut(edge_idx).unwrap().display_mut().dimmed = dimmed;
+        }
+    }
+
+    /// Highlights the lineage of the hovered node, when enabled
+    fn update_path_highlight(&mut self) {
+        let path_node = self.graph.hovered_node().filter(|_| self.highlight_path);
+        if path_node != self.path_node {
+            self.path_node = path_node;
+            self.apply_dimming();
+        }
+    }
+
+    /// Marks the edge under the pointer and names its ends in a tooltip.
+    /// Nodes go before edges.
+    fn hover_edge(&mut self, ui: &egui::Ui, graph_response: &egui::Response) {
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let hovered_edge = graph_response
+            .hover_pos()
+            .filter(|_| self.graph.hovered_node().is_none())
+            .and_then(|pos| {
+                // Relative to the graph view
+                let pos = pos - graph_response.rect.min.to_vec2();
+                self.graph.edge_by_screen_pos(&meta, pos)
+            });
+        if hovered_edge != self.hovered_edge {
+            for (edge_idx, hovered) in [(self.hovered_edge, false), (hovered_edge, true)] {
+                if let Some(edge) = edge_idx.and_then(|idx| self.graph.edge_mut(idx)) {
+                    edge.display_mut().hovered = hovered;
+                }
+            }
+            self.hovered_edge = hovered_edge;
+        }
+        let Some((child, parent)) =
+            (self.hovered_edge).and_then(|idx| self.graph.edge_endpoints(idx))
+        else {
+            return;
+        };
+        let label = |node_idx| self.graph.node(node_idx).unwrap().label();
+        graph_response.clone().on_hover_text_at_pointer(tr!(
+            "edge-hover",
+            child = label(child),
+            parent = label(parent)
+        ));
+    }
+
+    /// The View revset with the pinned commits
+    fn view_with_pins(&self) -> String {
+        (self.pins).add_to_view(&self.view_revset.value, |hex| {
+            self.jj_graph.get_revset(hex).is_ok()
+        })
+    }
+
+    fn toggle_pin(&mut self, commit_id: &CommitId) {
+        self.pins.toggle(commit_id);
+        if let Err(e) = self.pins.save(&self.repository_path) {
+            self.status_message = Some(tr!("pins-failed", error = format!("{e:#}")));
+        }
+        self.view_invalidated = true;
+    }
+
+    fn apply_pins(&mut self) {
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let pinned = self.pins.contains(node.payload());
+            node.display_mut().pinned = pinned;
+        }
+    }
+
+    /// Shows the status of the commits in the annotations file
+    fn apply_annotation_badges(&mut self) {
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let badge = (self.annotations.get(node.payload()))
+                .map(|annotation| annotation_color(annotation.status));
+            node.display_mut().annotation_badge = badge;
+        }
+    }
+
+    /// Highlights the commits found by the diff search
+    fn apply_search_matches(&mut self) {
+        let matches = self.diff_search.matches();
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let highlighted = matches.is_some_and(|matches| matches.contains(node.payload()));
+            node.display_mut().highlighted = highlighted;
+        }
+    }
+
+    /// Applies the View menu settings that only affect the drawing
+    fn apply_display_settings(&mut self) {
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            node.display_mut().label_scale = self.label_scale;
+            node.display_mut().simulation = self.simulation;
+        }
+    }
+
+    fn handle_screenshots(&mut self, ctx: &egui::Context) {
+        let screenshots: Vec<_> = ctx.input(|i| {
+            i.raw
+                .events
+                .iter()
+                .filter_map(|e| match e {
+                    egui::Event::Screenshot {
+                        user_data, image, ..
+                    } => {
+                        let purpose = user_data
+                            .data
+                            .as_ref()?
+                            .downcast_ref::<ScreenshotPurpose>()
+                            .copied()?;
+                        Some((purpose, image.clone()))
+                    }
+                    _ => None,
+                })
+                .collect()
+        });
+        for (purpose, image) in screenshots {
+            let graph_image = image.region(&self.graph_rect, Some(ctx.pixels_per_point()));
+            match purpose {
+                ScreenshotPurpose::CopyGraph => {
+                    ctx.copy_image(graph_image);
+                    self.status_message = Some(tr!("copied-graph"));
+                }
+                ScreenshotPurpose::ScriptImage => {
+                    let Some(path) = self.script_image.take() else {
+                        continue;
+                    };
+                    if let Err(e) = save_png(&graph_image, &path) {
+                        script_failed(e.context(path.display().to_string()));
+                    }
+                }
+                ScreenshotPurpose::ExportImage => {
+                    let Some(path) = self.export_image.take() else {
+                        continue;
+                    };
+                    self.status_message = Some(match save_png(&graph_image, &path) {
+                        Ok(()) => tr!("exported-graph", path = path.display().to_string()),
+                        Err(e) => tr!("export-failed", error = e.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Does the next action of the --script file, when the graph has settled
+    /// after the previous one
+    fn run_script(&mut self, ctx: &egui::Context) {
+        let Some(script) = &mut self.script else {
+            return;
+        };
+        // Keeps the frames coming while the window is not touched
+        ctx.request_repaint();
+        if self.script_image.is_some()
+            || !self.initialized
+            || self.view_evaluation.is_pending()
+            || self.select_evaluation.is_pending()
+        {
+            return;
+        }
+        let Some(action) = script.next() else {
+            if script.is_done() {
+                self.script = None;
+            }
+            return;
+        };
+        match action {
+            script::Action::SetView(view) => {
+                self.view_revset.history.add(view.trim(), false);
+                self.view_revset.value = view;
+                self.view_invalidated = true;
+            }
+            script::Action::SetSelect(select) => {
+                self.filter_revset.history.add(select.trim(), false);
+                self.filter_revset.value = select;
+                self.recolor = true;
+            }
+            script::Action::Layout(path) => match layout_file::LayoutFile::load(&path) {
+                Ok(layout) => {
+                    self.fixed_layout = Some(layout);
+                    self.view_invalidated = true;
+                }
+                Err(e) => script_failed(e),
+            },
+            script::Action::ExportImage(path) => {
+                for error in [&self.view_revset.error, &self.filter_revset.error]
+                    .into_iter()
+                    .flatten()
+                {
+                    eprintln!("{error}");
+                }
+                self.script_image = Some(path);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(
+                    ScreenshotPurpose::ScriptImage,
+                )));
+            }
+            script::Action::Exit => ctx.send_viewport_cmd(egui::ViewportCommand::Close),
+        }
+    }
+
+    fn export_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.export_dialog else {
+            return;
+        };
+        let mut export = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("export_dialog")).show(ctx, |ui| {
+            ui.heading(tr!("export-title", format = dialog.format.name()));
+            ui.horizontal(|ui| {
+                ui.label(tr!("export-file"));
+                let path_edit = ui.text_edit_singleline(&mut dialog.path);
+                export = path_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
+            if dialog.format == ExportFormat::Html {
+                ui.checkbox(&mut dialog.legend, tr!("export-legend"))
+                    .on_hover_text(tr!("export-legend-hover"));
+            }
+            if matches!(
+                dialog.format,
+                ExportFormat::Html | ExportFormat::Dot | ExportFormat::Svg
+            ) {
+                let has_select = !self.filter_revset.value.trim().is_empty();
+                ui.add_enabled(
+                    has_select,
+                    egui::Checkbox::new(&mut dialog.select_only, tr!("export-select-only")),
+                )
+                .on_hover_text(tr!("export-select-only-hover"));
+            }
+            ui.horizontal(|ui| {
+                export |= ui.button(tr!("export")).clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if export {
+            let (format, legend, select_only) = (dialog.format, dialog.legend, dialog.select_only);
+            let path = PathBuf::from(&dialog.path);
+            self.export_dialog = None;
+            if format == ExportFormat::Png {
+                // Saved when the screenshot arrives
+                self.export_image = Some(path);
+                ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(egui::UserData::new(
+                    ScreenshotPurpose::ExportImage,
+                )));
+                return;
+            }
+            self.status_message = Some(match self.export(format, &path, legend, select_only) {
+                Ok(()) => tr!("exported-graph", path = path.display().to_string()),
+                Err(e) => tr!("export-failed", error = e.to_string()),
+            });
+        } else if cancel || modal.should_close() {
+            self.export_dialog = None;
+        }
+    }
+
+    fn settings_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.settings_dialog else {
+            return;
+        };
+        let mut confirm = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("settings_dialog")).show(ctx, |ui| {
+            ui.heading(if dialog.import {
+                tr!("settings-import-title")
+            } else {
+                tr!("settings-export-title")
+            });
+            if dialog.import {
+                ui.label(tr!(
+                    "settings-import-note",
+                    path = settings::config_path().display().to_string()
+                ));
+            }
+            ui.horizontal(|ui| {
+                ui.label(tr!("export-file"));
+                let path_edit = ui.text_edit_singleline(&mut dialog.path);
+                confirm = path_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
+            ui.horizontal(|ui| {
+                confirm |= (ui.button(if dialog.import {
+                    tr!("import")
+                } else {
+                    tr!("export")
+                }))
+                .clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if confirm {
+            let import = dialog.import;
+            let path = PathBuf::from(&dialog.path);
+            self.settings_dialog = None;
+            let path_str = path.display().to_string();
+            self.status_message = Some(if import {
+                match self.import_settings(&path, &settings::config_path()) {
+                    Ok(()) => tr!("imported-settings", path = path_str),
+                    Err(e) => tr!("import-failed", error = format!("{e:#}")),
+                }
+            } else {
+                match std::fs::write(&path, self.settings_bundle().to_toml()) {
+                    Ok(()) => tr!("exported-settings", path = path_str),
+                    Err(e) => tr!("export-failed", error = e.to_string()),
+                }
+            });
+        } else if cancel || modal.should_close() {
+            self.settings_dialog = None;
+        }
+    }
+
+    fn result_set_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.result_set_dialog else {
+            return;
+        };
+        let mut confirm = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("result_set_dialog")).show(ctx, |ui| {
+            ui.heading(if dialog.load {
+                tr!("result-set-load-title")
+            } else {
+                tr!("result-set-save-title")
+            });
+            ui.horizontal(|ui| {
+                ui.label(tr!("export-file"));
+                let path_edit = ui.text_edit_singleline(&mut dialog.path);
+                confirm = path_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
+            ui.horizontal(|ui| {
+                confirm |= (ui.button(if dialog.load {
+                    tr!("load")
+                } else {
+                    tr!("save")
+                }))
+                .clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if confirm {
+            let load = dialog.load;
+            let path = PathBuf::from(&dialog.path);
+            self.result_set_dialog = None;
+            let path_str = path.display().to_string();
+            if load {
+                match result_set::ResultSet::load(&path) {
+                    Ok(saved) => {
+                        self.result_overlay = Some(ResultOverlay {
+                            saved,
+                            comparison: Default::default(),
+                        });
+                        self.view_invalidated = true;
+                        self.status_message = None;
+                    }
+                    Err(e) => {
+                        self.status_message =
+                            Some(tr!("result-set-failed", error = format!("{e:#}")));
+                    }
+                }
+            } else {
+                let view = self.view_with_pins();
+                self.status_message = Some(
+                    match result_set::ResultSet::evaluate(&self.jj_graph, &view, self.node_limit) {
+                        Ok(saved) => match std::fs::write(&path, saved.to_json()) {
+                            Ok(()) => tr!(
+                                "saved-result-set",
+                                count = saved.commits.len(),
+                                path = path_str
+                            ),
+                            Err(e) => tr!("export-failed", error = e.to_string()),
+                        },
+                        Err(e) => tr!("result-set-failed", error = e.to_string()),
+                    },
+                );
+            }
+        } else if cancel || modal.should_close() {
+            self.result_set_dialog = None;
+        }
+    }
+
+    fn perf_report_dialog(&mut self, ctx: &egui::Context) {
+        let Some(path) = &mut self.perf_report_dialog else {
+            return;
+        };
+        let mut confirm = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("perf_report_dialog")).show(ctx, |ui| {
+            ui.heading(tr!("perf-report-title"));
+            ui.horizontal(|ui| {
+                ui.label(tr!("export-file"));
+                let path_edit = ui.text_edit_singleline(path);
+                confirm = path_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
+            ui.horizontal(|ui| {
+                confirm |= ui.button(tr!("export")).clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if confirm {
+            let path = PathBuf::from(&*path);
+            self.perf_report_dialog = None;
+            let report = self.perf_report.to_json(self.node_limit);
+            self.status_message = Some(match std::fs::write(&path, report) {
+                Ok(()) => tr!("wrote-file", path = path.display().to_string()),
+                Err(e) => tr!("export-failed", error = e.to_string()),
+            });
+        } else if cancel || modal.should_close() {
+            self.perf_report_dialog = None;
+        }
+    }
+
+    fn replace_dialog(&mut self, ctx: &egui::Context) {
+        let Some(mut dialog) = self.replace_dialog.take() else {
+            return;
+        };
+        let (from, to) = (dialog.from.trim().to_owned(), dialog.to.trim().to_owned());
+        let revsets = self.revsets_with_symbol(&from);
+        let mut replace = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("replace_dialog")).show(ctx, |ui| {
+            ui.heading(tr!("replace-symbol-title"));
+            egui::Grid::new("replace_fields")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for (label, text, hint) in [
+                        (tr!("replace-symbol-from"), &mut dialog.from, String::new()),
+                        (tr!("replace-symbol-to"), &mut dialog.to, String::new()),
+                        (
+                            tr!("replace-symbol-recording"),
+                            &mut dialog.recording,
+                            tr!("replace-symbol-recording-hint"),
+                        ),
+                    ] {
+                        let label = ui.label(label);
+                        ui.add(egui::TextEdit::singleline(text).hint_text(hint))
+                            .labelled_by(label.id);
+                        ui.end_row();
+                    }
+                });
+            if !from.is_empty() {
+                ui.label(tr!("replace-symbol-count", count = revsets.len()));
+                egui::ScrollArea::vertical()
+                    .max_height(200.)
+                    .show(ui, |ui| {
+                        for revset in &revsets {
+                            ui.label(
+                                RichText::new(format!(
+                                    "{revset}  →  {}",
+                                    symbol_replace::replace(revset, &from, &to)
+                                ))
+                                .monospace(),
+                            );
+                        }
+                    });
+            }
+            ui.horizontal(|ui| {
+                let enabled = !from.is_empty() && !to.is_empty() && from != to;
+                replace =
+                    (ui.add_enabled(enabled, egui::Button::new(tr!("replace-symbol")))).clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if replace {
+            let recording = dialog.recording.trim();
+            let recording = (!recording.is_empty()).then(|| PathBuf::from(recording));
+            self.status_message = Some(
+                match self.replace_symbol(
+                    &from,
+                    &to,
+                    &settings::config_path(),
+                    recording.as_deref(),
+                ) {
+                    Ok(count) => tr!(
+                        "replaced-symbol",
+                        from = from.as_str(),
+                        to = to.as_str(),
+                        count = count
+                    ),
+                    Err(e) => tr!("replace-symbol-failed", error = format!("{e:#}")),
+                },
+            );
+        } else if !cancel && !modal.should_close() {
+            self.replace_dialog = Some(dialog);
+        }
+    }
+
+    /// The distinct revsets of the session and the color rules that contain
+    /// the symbol
+    fn revsets_with_symbol(&self, symbol: &str) -> Vec<String> {
+        let entries = [&self.view_revset, &self.filter_revset]
+            .into_iter()
+            .chain(&self.rebase_source)
+            .chain(&self.compare_revset);
+        let revsets = entries
+            .flat_map(|entry| std::iter::once(&entry.value).chain(entry.history.items()))
+            .chain(self.color_rules.iter().map(|rule| &rule.revset));
+        let mut found: Vec<String> = vec![];
+        for revset in revsets {
+            if !symbol_replace::occurrences(revset, symbol).is_empty() && !found.contains(revset) {
+                found.push(revset.clone());
+            }
+        }
+        found
+    }
+
+    /// Replaces the symbol in the revsets of the session, in the color rules,
+    /// which are saved to the settings file at `config_path`, and in the
+    /// session recording, if given. Returns the number of revsets changed.
+    fn replace_symbol(
+        &mut self,
+        from: &str,
+        to: &str,
+        config_path: &Path,
+        recording: Option<&Path>,
+    ) -> anyhow::Result<usize> {
+        let replace = |revset: &str| symbol_replace::replace(revset, from, to);
+        let mut count = self.revsets_with_symbol(from).len();
+        let entries = [&mut self.view_revset, &mut self.filter_revset]
+            .into_iter()
+            .chain(&mut self.rebase_source)
+            .chain(&mut self.compare_revset);
+        for entry in entries {
+            entry.value = replace(&entry.value);
+            entry.history.map_items(replace);
+        }
+        let rules_changed = (self.color_rules.iter())
+            .any(|rule| !symbol_replace::occurrences(&rule.revset, from).is_empty());
+        if rules_changed {
+            for rule in &mut self.color_rules {
+                rule.revset = replace(&rule.revset);
+            }
+            settings::save_color_rules(config_path, &self.color_rules)?;
+        }
+        if let Some(recording) = recording {
+            count += session::map_recording(recording, replace)?;
+        }
+        self.view_invalidated = true;
+        Ok(count)
+    }
+
+    /// The shareable settings, as currently set from the menus
+    fn settings_bundle(&self) -> settings::Bundle {
+        settings::Bundle {
+            ui: settings::UiSettings {
+                graph_label_scale: Some(self.label_scale),
+                palette: self.palette,
+                node_size: self.node_size,
+                author_label: self.author_label,
+                guides: self.guides.clone(),
+                edge_routing: self.edge_routing,
+                arrowheads: self.arrowheads,
+                arrow_direction: self.arrow_direction,
+                signature_badges: self.signature_badges,
+                watch: self.repo_watch.watch,
+                watch_interval: Some(self.repo_watch.poll_seconds),
+                ..self.ui_settings.clone()
+            },
+            navigation: self.navigation.clone(),
+            color_rules: self.color_rules.clone(),
+        }
+    }
+
+    /// Installs the settings bundle at `path` into the settings file at
+    /// `config_path` and applies it. The language, zoom, fonts and reduced
+    /// motion are only read at startup.
+    fn import_settings(&mut self, path: &Path, config_path: &Path) -> anyhow::Result<()> {
+        let bundle = settings::Bundle::load(path)?;
+        bundle.install(config_path)?;
+        self.label_scale = bundle.ui.graph_label_scale.unwrap_or(1.);
+        self.palette = bundle.ui.palette;
+        self.node_size = bundle.ui.node_size;
+        self.author_label = bundle.ui.author_label;
+        self.guides = bundle.ui.guides.clone();
+        self.edge_routing = bundle.ui.edge_routing;
+        self.arrowheads = bundle.ui.arrowheads;
+        self.arrow_direction = bundle.ui.arrow_direction;
+        self.signature_badges = bundle.ui.signature_badges;
+        self.repo_watch = repo_watch::RepoWatch::new(
+            bundle.ui.watch,
+            (bundle.ui.watch_interval).unwrap_or(repo_watch::DEFAULT_POLL_SECONDS),
+        );
+        self.ui_settings = bundle.ui;
+        self.navigation = bundle.navigation;
+        self.color_rules = bundle.color_rules;
+        self.apply_display_settings();
+        self.recolor = true;
+        Ok(())
+    }
+
+    /// Lists the keyboard shortcuts. Toggled with `?`.
+    fn shortcuts_overlay(&mut self, ctx: &egui::Context) {
+        if !text_edit_focused(ctx) && ctx.input_mut(|i| i.consume_shortcut(&keymap::SHORTCUTS)) {
+            self.show_shortcuts = !self.show_shortcuts;
+        }
+        if !self.show_shortcuts {
+            return;
+        }
+        let modal = egui::Modal::new(egui::Id::new("shortcuts")).show(ctx, |ui| {
+            ui.heading(tr!("shortcuts-title"));
+            for area in keymap::Area::ALL {
+                ui.add_space(ui.spacing().item_spacing.y * 2.);
+                ui.strong(area.name());
+                egui::Grid::new(("shortcuts", area as u8))
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for binding in keymap::BINDINGS.iter().filter(|b| b.area == area) {
+                            let shortcuts: Vec<_> = (binding.shortcuts.iter())
+                                .map(|shortcut| ui.ctx().format_shortcut(shortcut))
+                                .collect();
+                            ui.label(RichText::new(shortcuts.join(" / ")).monospace());
+                            ui.label(i18n::message(binding.description, None));
+                            ui.end_row();
+                        }
+                    });
+            }
+        });
+        if modal.should_close() {
+            self.show_shortcuts = false;
+        }
+    }
+
+    fn export(
+        &self,
+        format: ExportFormat,
+        path: &Path,
+        legend: bool,
+        select_only: bool,
+    ) -> anyhow::Result<()> {
+        let mut graph = self.export_graph();
+        let select = self.filter_revset.value.trim();
+        if select_only && !select.is_empty() {
+            let (revset, ..) = resolve_revset(&self.jj_graph, select)?;
+            let is_match = revset.containing_fn();
+            let matches = (self.node_idxs.iter())
+                .map(|idx| is_match(self.graph.node(*idx).unwrap().payload()))
+                .collect::<Result<Vec<_>, _>>()?;
+            graph = graph.connected(&matches);
+        }
+        let title = tr!(
+            "export-html-title",
+            view = self.view_revset.value.trim(),
+            select = self.filter_revset.value.trim()
+        );
+        let content = match format {
+            ExportFormat::Html => {
+                let legend = legend.then(|| self.export_legend());
+                export::to_html(&graph, &title, legend.as_ref())
+            }
+            ExportFormat::Dot => export::to_dot(&graph, &title),
+            ExportFormat::Svg => export::to_svg(&graph, &title),
+            // Saved from a screenshot, see export_dialog()
+            ExportFormat::Png => unreachable!(),
+            ExportFormat::Snapshot => Snapshot::create(
+                &self.jj_graph,
+                (self.repository_path.file_name().unwrap_or_default())
+                    .display()
+                    .to_string(),
+                &self.view_with_pins(),
+                std::slice::from_ref(&self.filter_revset.value),
+                self.node_limit,
+            )?
+            .to_json(),
+            ExportFormat::Layout => {
+                let mut layout = layout_file::LayoutFile::default();
+                for node_idx in &self.node_idxs {
+                    let node = self.graph.node(*node_idx).unwrap();
+                    layout.insert(node.payload(), node.location());
+                }
+                layout.to_json()
+            }
+        };
+        std::fs::write(path, content)?;
+        Ok(())
+    }
+
+    /// The revsets of the graph and the meanings of the node colors
+    fn export_legend(&self) -> export::Legend {
+        let select = self.filter_revset.value.trim();
+        let mut revsets = vec![(tr!("legend-view"), self.view_with_pins())];
+        if !select.is_empty() {
+            revsets.push((tr!("legend-select"), select.to_owned()));
+        }
+        if let Some(compare) = &self.compare_revset {
+            revsets.push((tr!("legend-compare"), compare.value.trim().to_owned()));
+        }
+        let mut colors = vec![];
+        // Author colors are not explained
+        if !self.color_by_author {
+            let filter_matches: &[_] = if select.is_empty() {
+                &[FilterMatch::NoMatch]
+            } else {
+                &[FilterMatch::Match, FilterMatch::NoMatch]
+            };
+            for node_type in [
+                NodeType::WorkingCopy,
+                NodeType::Immutable,
+                NodeType::Regular,
+            ] {
+                let name = match node_type {
+                    NodeType::WorkingCopy => tr!("legend-working-copy"),
+                    NodeType::Immutable => tr!("legend-immutable"),
+                    NodeType::Regular => tr!("legend-mutable"),
+                };
+                for &filter_match in filter_matches {
+                    let [red, green, blue] = self.palette.color(Marking {
+                        node_type,
+                        filter_match,
+                    });
+                    let meaning = match filter_match {
+                        FilterMatch::Match => tr!("legend-in-select", kind = name.as_str()),
+                        FilterMatch::NoMatch => name.clone(),
+                    };
+                    colors.push((ecolor::Color32::from_rgb(red, green, blue), meaning));
+                }
+            }
+        }
+        if self.compare_revset.is_some() {
+            for membership in Membership::ALL {
+                let Some(&count) = self.memberships.get(&membership) else {
+                    continue;
+                };
+                let [red, green, blue] = self.palette.membership_color(membership);
+                let meaning = membership_name(membership, count);
+                colors.push((ecolor::Color32::from_rgb(red, green, blue), meaning));
+            }
+        }
+        for rule in &self.color_rules {
+            if let Some(color) = rule.color.or(rule.border) {
+                colors.push((color, tr!("legend-rule", revset = rule.revset.as_str())));
+            }
+        }
+        export::Legend { revsets, colors }
+    }
+
+    /// Snapshot of the graph, as currently laid out and colored
+    fn export_graph(&self) -> export::ExportGraph {
+        let store = self.jj_graph.get_repo().store().clone();
+        let mut positions = HashMap::new();
+        let mut nodes = vec![];
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node(*node_idx).unwrap();
+            let (tooltip, subject) = match store.get_commit(node.payload()) {
+                Ok(commit) => (
+                    format!(
+                        "{}\n{} <{}>\n\n{}",
+                        commit.change_id(),
+                        commit.author().name,
+                        commit.author().email,
+                        commit.description().trim_end()
+                    ),
+                    (commit.description().lines().next())
+                        .unwrap_or_default()
+                        .to_owned(),
+                ),
+                Err(_) => (node.payload().hex(), String::new()),
+            };
+            positions.insert(*node_idx, nodes.len());
+            nodes.push(export::ExportNode {
+                label: node.label(),
+                subject,
+                color: node.color().unwrap_or_default(),
+                x: node.location().x,
+                y: node.location().y,
+                tooltip,
+            });
+        }
+        let edges = self
+            .graph
+            .edges_iter()
+            .filter_map(|(edge_idx, _)| {
+                let (start, end) = self.graph.edge_endpoints(edge_idx)?;
+                Some((*positions.get(&start)?, *positions.get(&end)?))
+            })
+            .collect();
+        export::ExportGraph { nodes, edges }
+    }
+
+    fn status_bar(&mut self, ui: &mut egui::Ui) {
+        while let Ok(msg) = self.jj_error_receiver.try_recv() {
+            self.status_message = Some(msg);
+        }
+        if let Some(output) = self.print_selection {
+            ui.horizontal(|ui| {
+                if ui.button(tr!("done")).clicked() {
+                    ui.ctx().send_viewport_cmd(egui::ViewportCommand::Close);
+                }
+                ui.label(match output {
+                    SelectionOutput::ChangeIds => tr!("choose-nodes"),
+                    SelectionOutput::Revset => tr!("choose-revset"),
+                });
+            });
+        }
+        if self.past_operation.is_some() {
+            ui.horizontal(|ui| {
+                let operation = self.jj_graph.get_repo().op_id().hex();
+                let description = self.jj_graph.operation_description();
+                ui.label(
+                    RichText::new(tr!(
+                        "past-operation",
+                        operation = &operation[..12],
+                        description = description
+                    ))
+                    .color(ui.visuals().warn_fg_color),
+                );
+                if ui.button(tr!("past-operation-back")).clicked() {
+                    self.reload_repo();
+                }
+            });
+        }
+        let busy = workers::busy();
+        if !busy.is_empty() {
+            ui.horizontal(|ui| {
+                // Keeps repainting until the work is done
+                ui.spinner();
+                for subsystem in busy {
+                    ui.label(subsystem.busy_text());
+                }
+            });
+        }
+        let Some(msg) = self.status_message.clone() else {
+            return;
+        };
+        ui.horizontal(|ui| {
+            if ui.small_button("✖").clicked() {
+                self.status_message = None;
+            }
+            ui.label(RichText::new(msg).color(ui.visuals().warn_fg_color));
+        });
+    }
+
+    /// Starts building the graph of the view on a background thread. It is
+    /// shown by [`Self::poll_view_evaluation`].
+    fn update_view(&mut self, ui: &mut egui::Ui) {
+        self.extendable = None;
+        match self.view_to_evaluate() {
+            Ok(view) => {
+                self.prefetch.poll();
+                let request = view_evaluation::Request {
+                    view,
+                    select: self.filter_revset.value.clone(),
+                    node_limit: self.node_limit,
+                    prefetched: self.prefetch.commits().clone(),
+                    change_nodes: (self.change_nodes).then(|| self.expanded_changes.clone()),
+                    extend: None,
+                };
+                self.view_evaluation
+                    .start(ui.ctx(), &self.jj_graph, request);
+            }
+            Err(error) => {
+                self.view_evaluation.cancel();
+                self.finish_view_update(ui, Err(error));
+            }
+        }
+    }
+
+    /// Adds the next commits of the view to the graph, up to the raised node
+    /// limit, or evaluates the view again when the graph can't be extended.
+    /// A graph that is being replaced is not extended, as that would cancel
+    /// the evaluation of the new one.
+    fn load_more(&mut self, ui: &mut egui::Ui) {
+        let extendable = self.extendable.take();
+        let Some((view, built)) = extendable.filter(|_| !self.view_evaluation.is_pending()) else {
+            self.update_view(ui);
+            return;
+        };
+        self.prefetch.poll();
+        let request = view_evaluation::Request {
+            view,
+            select: self.filter_revset.value.clone(),
+            node_limit: self.node_limit,
+            prefetched: self.prefetch.commits().clone(),
+            change_nodes: None,
+            extend: Some(built),
+        };
+        (self.view_evaluation).start(ui.ctx(), &self.jj_graph, request);
+    }
+
+    /// Shows the graph of the view once it has been evaluated. Returns
+    /// whether a new graph was shown, rather than more of the same.
+    fn poll_view_evaluation(&mut self, ui: &mut egui::Ui) -> bool {
+        let Some(outcome) = self.view_evaluation.poll() else {
+            return false;
+        };
+        let extended = matches!(&outcome, Ok(evaluated) if evaluated.added.is_some());
+        self.finish_view_update(ui, outcome);
+        !extended
+    }
+
+    fn finish_view_update(&mut self, ui: &mut egui::Ui, outcome: view_evaluation::Outcome) {
+        let result = match outcome {
+            Ok(evaluated) => self.update_view_graph(ui, evaluated),
+            Err(error) => {
+                self.last_view_node_count = None;
+                Err(error)
+            }
+        };
+        self.apply_preview_rings();
+        self.apply_display_settings();
+        self.reset_date_filter();
+        self.update_immutable_commits();
+        // Chosen authors stay chosen when they are still in the view
+        self.reset_author_filter();
+        self.apply_dimming();
+        match result {
+            Ok(_) => {
+                self.view_revset.error = None;
+                self.empty_view_fallback = (self.node_idxs.is_empty())
+                    .then(|| empty_view::nearest_non_empty(&self.jj_graph, &self.view_revset.value))
+                    .flatten();
+            }
+            Err(ResolveError::RevsetParseError(msg) | ResolveError::JjError(msg)) => {
+                self.view_revset.history.set_last_tentative(true);
+                self.view_revset.error = Some(msg);
+            }
+        };
+        if let Some(ci_statuses) = &mut self.ci_statuses {
+            let commit_ids: Vec<_> = self
+                .node_idxs
+                .iter()
+                .map(|idx| self.graph.node(*idx).unwrap().payload().clone())
+                .collect();
+            ci_statuses.request(commit_ids.iter(), ui.ctx());
+            self.apply_ci_badges();
+        }
+    }
+
+    fn poll_ci_statuses(&mut self) {
+        let Some(ci_statuses) = &mut self.ci_statuses else {
+            return;
+        };
+        let (changed, error) = ci_statuses.poll();
+        if error.is_some() {
+            self.status_message = error;
+        }
+        if changed {
+            self.apply_ci_badges();
+        }
+    }
+
+    fn apply_ci_badges(&mut self) {
+        let Some(ci_statuses) = &self.ci_statuses else {
+            return;
+        };
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let badge = ci_statuses.get(node.payload()).map(|status| match status {
+                forge::CiStatus::Success => ecolor::Color32::from_rgb(0x2e, 0xcc, 0x40),
+                forge::CiStatus::Failure => ecolor::Color32::from_rgb(0xff, 0x41, 0x36),
+                forge::CiStatus::Pending => ecolor::Color32::from_rgb(0xff, 0x85, 0x1b),
+            });
+            node.display_mut().badge = badge;
+        }
+    }
+
+    /// Buttons above the revset inputs. Returns whether the filter and the view
+    /// were changed.
+    fn toolbar(&mut self, ui: &mut egui::Ui) -> (bool, bool) {
+        let mut filter_changed = false;
+        let mut view_changed = false;
+        ui.horizontal(|ui| {
+            let shortcut = ui.ctx().format_shortcut(&keymap::RELOAD);
+            if (ui.button(tr!("reload")))
+                .on_hover_text(tr!("reload-hover", shortcut = shortcut))
+                .clicked()
+            {
+                self.reload_repo();
+            }
+            // Coloring is done when marking the graph
+            filter_changed |= ui
+                .checkbox(&mut self.color_by_author, tr!("color-by-author"))
+                .changed();
+            let mut undo_preview = self.undo_preview.is_some();
+            if ui
+                .checkbox(&mut undo_preview, tr!("undo-preview"))
+                .on_hover_text(tr!("undo-preview-hover"))
+                .changed()
+            {
+                self.undo_preview = undo_preview.then(UndoPreview::default);
+                view_changed = true;
+            }
+            if let Some(preview) = &self.undo_preview {
+                ui.label(tr!(
+                    "undo-preview-summary",
+                    operation = preview.operation.as_str(),
+                    appearing = preview.appearing.len(),
+                    disappearing = preview.disappearing.len()
+                ));
+            }
+            if let Some(overlay) = &self.result_overlay {
+                let operation = &overlay.saved.operation_id;
+                ui.label(tr!(
+                    "result-set-summary",
+                    joined = overlay.comparison.joined.len(),
+                    left = overlay.comparison.left.len()
+                ))
+                .on_hover_text(tr!(
+                    "result-set-summary-hover",
+                    revset = overlay.saved.revset.as_str(),
+                    operation = &operation[..operation.len().min(12)]
+                ));
+                if ui.small_button(tr!("result-set-clear")).clicked() {
+                    self.result_overlay = None;
+                    view_changed = true;
+                }
+            }
+            for message in &self.truncated {
+                ui.label(RichText::new(message).color(ui.visuals().warn_fg_color));
+            }
+            if self.node_limit_hit
+                && ui
+                    .button(tr!("load-more", count = LOAD_MORE_NODES))
+                    .on_hover_text(tr!("load-more-hover", limit = self.node_limit))
+                    .clicked()
+            {
+                self.node_limit += LOAD_MORE_NODES;
+                self.more_requested = true;
+            }
+            let mut rebase_preview = self.rebase_source.is_some();
+            if ui
+                .checkbox(&mut rebase_preview, tr!("rebase-preview"))
+                .on_hover_text(tr!("rebase-preview-hover"))
+                .changed()
+            {
+                self.rebase_source = rebase_preview.then(|| RevsetEntry::new("@"));
+                self.update_rebase_preview();
+            }
+            if self.rebase_source.is_some() {
+                ui.label(match &self.rebase_preview {
+                    Some(preview) => tr!(
+                        "rebase-preview-summary",
+                        count = preview.commits.len(),
+                        destination = self.node_label(&preview.destination)
+                    ),
+                    None => tr!("rebase-preview-destination"),
+                });
+            }
+            let (_, scopes) = view_scopes::decompose(&self.view_revset.value);
+            for scope in view_scopes::Scope::ALL {
+                let mut enabled = scopes.contains(&scope);
+                if ui
+                    .toggle_value(&mut enabled, scope.name())
+                    .on_hover_text(tr!("scope-hover", revset = scope.suffix().trim()))
+                    .changed()
+                {
+                    let toggled = view_scopes::toggle(&self.view_revset.value, scope);
+                    self.view_revset.set_generated(&toggled);
+                    view_changed = true;
+                }
+            }
+            let mut boundary_focus = self.boundary_focus();
+            if ui
+                .toggle_value(&mut boundary_focus, tr!("boundary-focus"))
+                .on_hover_text(tr!(
+                    "boundary-focus-hover",
+                    revset = self.boundary_focus_view()
+                ))
+                .changed()
+            {
+                let view = if boundary_focus {
+                    let view = self.boundary_focus_view();
+                    self.view_before_boundary_focus = Some(self.view_revset.value.clone());
+                    view
+                } else {
+                    (self.view_before_boundary_focus.take())
+                        .unwrap_or_else(|| DEFAULT_VIEW.to_owned())
+                };
+                self.view_revset.set_generated(&view);
+                view_changed = true;
+            }
+            let heads = ui
+                .toggle_value(&mut self.outline_heads, tr!("outline-heads"))
+                .on_hover_text(tr!("outline-heads-hover"));
+            let roots = ui
+                .toggle_value(&mut self.outline_roots, tr!("outline-roots"))
+                .on_hover_text(tr!("outline-roots-hover"));
+            if heads.changed() || roots.changed() {
+                self.apply_boundary_outlines();
+            }
+            let Some(ci_statuses) = &self.ci_statuses else {
+                return;
+            };
+            let green: Vec<_> = ci_statuses.successful().map(|id| id.hex()).collect();
+            if ui
+                .add_enabled(
+                    !green.is_empty(),
+                    egui::Button::new(tr!("select-since-green")),
+                )
+                .on_hover_text(tr!("select-since-green-hover"))
+                .clicked()
+            {
+                (self.filter_revset).set_generated(&format!("heads({})..", green.join(" | ")));
+                filter_changed = true;
+            }
+        });
+        // Wraps in narrow windows
+        ui.horizontal_wrapped(|ui| {
+            if let Some(action) = self.path_filter.ui(ui, &self.jj_graph) {
+                let revset = match action {
+                    path_filter::PathAction::AddToSelect(path) => {
+                        path_filter::with_files(&self.filter_revset.value, &path)
+                    }
+                    path_filter::PathAction::LastTouched(path) => {
+                        path_filter::last_touched(&self.view_with_pins(), &path)
+                    }
+                };
+                self.filter_revset.set_generated(&revset);
+                filter_changed = true;
+            }
+            ui.separator();
+            let commit_ids = || {
+                (self.node_idxs.iter())
+                    .map(|idx| self.graph.node(*idx).unwrap().payload().clone())
+                    .collect()
+            };
+            let view = self.view_with_pins();
+            if self.diff_search.ui(ui, &self.jj_graph, &view, commit_ids) {
+                self.apply_search_matches();
+            }
+            ui.separator();
+            let mut compare = self.compare_revset.is_some();
+            if ui
+                .checkbox(&mut compare, tr!("compare"))
+                .on_hover_text(tr!("compare-hover"))
+                .changed()
+            {
+                self.compare_revset = compare.then(|| RevsetEntry::new(&self.filter_revset.value));
+                self.recolor = true;
+            }
+            if self.compare_revset.is_some() {
+                for membership in Membership::ALL {
+                    let [red, green, blue] = self.palette.membership_color(membership);
+                    let count = self
+                        .memberships
+                        .get(&membership)
+                        .copied()
+                        .unwrap_or_default();
+                    ui.colored_label(
+                        ecolor::Color32::from_rgb(red, green, blue),
+                        membership_name(membership, count),
+                    );
+                }
+            }
+        });
+        ui.horizontal_wrapped(|ui| match self.date_filter.ui(ui) {
+            Some(date_filter::DateAction::Changed) => self.apply_dimming(),
+            Some(date_filter::DateAction::AddToSelect(dates)) => {
+                let revset = date_filter::with_dates(&self.filter_revset.value, &dates);
+                self.filter_revset.set_generated(&revset);
+                filter_changed = true;
+            }
+            None => {}
+        });
+        if let Some(replay) = &mut self.replay {
+            ui.horizontal(|ui| {
+                if let Some(step) = replay.ui(ui) {
+                    self.view_revset.value = step.view.clone();
+                    self.view_revset.history.add(&step.view, false);
+                    self.filter_revset.value = step.select.clone();
+                    self.filter_revset.history.add(&step.select, false);
+                    view_changed = true;
+                    filter_changed = true;
+                }
+            });
+        }
+        ui.horizontal_wrapped(|ui| match self.author_filter.ui(ui) {
+            Some(author_filter::AuthorAction::Changed) => self.apply_dimming(),
+            Some(author_filter::AuthorAction::AddToSelect(authors)) => {
+                let revset = author_filter::with_authors(&self.filter_revset.value, &authors);
+                self.filter_revset.set_generated(&revset);
+                filter_changed = true;
+            }
+            None => {}
+        });
+        (filter_changed, view_changed)
+    }
+
+    /// Compares the view at the current operation with the view at the parent
+    /// operation. Returns the revset to show, covering both, which refers to
+    /// the parent operation with `at_operation()`, so that it stays short when
+    /// many commits would appear.
+    fn update_undo_preview(&mut self) -> Result<String, ResolveError> {
+        let view = self.view_with_pins();
+        let jj_err = |e: anyhow::Error| ResolveError::JjError(e.to_string());
+        let Some(parent_graph) = self.jj_graph.at_parent_operation().map_err(jj_err)? else {
+            self.undo_preview = Some(UndoPreview {
+                operation: tr!("undo-preview-nothing"),
+                ..Default::default()
+            });
+            return Ok(view);
+        };
+        // Compared in full, also when the graph is cut off by the node limit.
+        // Not with revsets, as all() includes the hidden commits that the
+        // revset refers to.
+        let commit_ids = |jj_graph: &jjgraph::JjGraph| -> Result<HashSet<CommitId>, ResolveError> {
+            let (revset, ..) = resolve_revset(jj_graph, &view)?;
+            (revset.iter().collect::<Result<_, _>>()).map_err(ResolveError::from)
+        };
+        let current = commit_ids(&self.jj_graph)?;
+        let parent = commit_ids(&parent_graph)?;
+        let appearing: HashSet<_> = parent.difference(&current).cloned().collect();
+        let disappearing = current.difference(&parent).cloned().collect();
+        // Hidden commits can still be read at the current operation
+        let parent_op = parent_graph.get_repo().op_id().hex();
+        let revset = format!("({view}) | at_operation({parent_op}, {view})");
+        self.undo_preview = Some(UndoPreview {
+            operation: self.jj_graph.operation_description(),
+            appearing,
+            disappearing,
+        });
+        Ok(revset)
+    }
+
+    /// Compares the view with the loaded result set. Returns the revset to
+    /// show, with the commits that have left the result added.
+    fn update_result_overlay(&mut self) -> Result<String, ResolveError> {
+        let view = self.view_with_pins();
+        let current = result_set::evaluate(&self.jj_graph, &view, self.node_limit)?;
+        let Some(overlay) = &mut self.result_overlay else {
+            return Ok(view);
+        };
+        overlay.comparison = overlay.saved.compare(&current);
+        // Commits that are gone from the repository would make the revset fail
+        let mut revset = format!("({view})");
+        for commit_id in &overlay.comparison.left {
+            let hex = commit_id.hex();
+            if self.jj_graph.get_revset(&hex).is_ok() {
+                revset += &format!(" | {hex}");
+            }
+        }
+        Ok(revset)
+    }
+
+    /// Rings the commits that the previewed bookmark move or undo would add
+    /// (green) or remove (red), or that have joined (green) or left (red) the
+    /// loaded result set. The bookmark move is previewed on top.
+    fn apply_preview_rings(&mut self) {
+        let (added, removed): (HashSet<_>, HashSet<_>) = match (
+            &self.bookmark_move,
+            &self.undo_preview,
+            &self.result_overlay,
+        ) {
+            (Some(preview), ..) => (
+                preview.reachable.iter().collect(),
+                preview.unreachable.iter().collect(),
+            ),
+            (None, Some(preview), _) => (
+                preview.appearing.iter().collect(),
+                preview.disappearing.iter().collect(),
+            ),
+            (None, None, Some(overlay)) => (
+                overlay.comparison.joined.iter().collect(),
+                overlay.comparison.left.iter().collect(),
+            ),
+            (None, None, None) => Default::default(),
+        };
+        for node_idx in &self.node_idxs {
+            let node = self.graph.node_mut(*node_idx).unwrap();
+            let ring = if added.contains(node.payload()) {
+                Some(ecolor::Color32::from_rgb(0x2e, 0xcc, 0x40))
+            } else if removed.contains(node.payload()) {
+                Some(ecolor::Color32::from_rgb(0xff, 0x41, 0x36))
+            } else {
+                None
+            };
+            node.display_mut().ring = ring;
+        }
+    }
+
+    /// Previews rebasing the source onto the last selected node, if any
+    fn update_rebase_preview(&mut self) {
+        self.rebase_preview = None;
+        self.rebase_destination = (self.graph.selected_nodes().last())
+            .and_then(|node_idx| self.graph.node(*node_idx))
+            .map(|node| node.payload().clone());
+        let Some(source) = &mut self.rebase_source else {
+            return;
+        };
+        source.error = None;
+        let Some(destination) = &self.rebase_destination else {
+            return;
+        };
+        if source.value.trim().is_empty() {
+            return;
+        }
+        match preview_rebase(&self.jj_graph, &source.value, destination, MAX_NODES) {
+            Ok(preview) => self.rebase_preview = Some(preview),
+            Err(e) => source.error = Some(e.to_string()),
+        }
+    }
+
+    /// The label of the commit's node, or a short commit ID if it has none
+    fn node_label(&self, commit_id: &CommitId) -> String {
+        (self.node_idxs.iter())
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .find(|node| node.payload() == commit_id)
+            .map_or_else(|| commit_id.hex()[..12].to_owned(), |node| node.label())
+    }
+
+    /// Faded copies of the rebased commits, stacked above the destination
+    /// node, and their edges, in screen coordinates
+    fn rebase_ghosts(&self, ui: &egui::Ui, graph_origin: egui::Pos2) -> Vec<egui::Shape> {
+        let Some(preview) = &self.rebase_preview else {
+            return vec![];
+        };
+        let nodes: HashMap<_, _> = (self.node_idxs.iter())
+            .filter_map(|node_idx| self.graph.node(*node_idx))
+            .map(|node| (node.payload(), node))
+            .collect();
+        let Some(destination) = nodes.get(&preview.destination) else {
+            return vec![];
+        };
+        let meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+        let layout = egui_graphs::get_layout_state::<egui_graphs::LayoutStateHierarchical>(
+            ui,
+            self.graph_id.clone(),
+        );
+        let to_screen = |pos: egui::Pos2| graph_origin + meta.canvas_to_screen_pos(pos).to_vec2();
+        let radius = meta.canvas_to_screen_size(destination.display().radius());
+
+        // Children are above their parents, one row per step from the
+        // destination, and side by side within the row
+        let mut row_counts = HashMap::<usize, usize>::new();
+        let mut positions = HashMap::new();
+        for commit in &preview.commits {
+            let column = row_counts.entry(commit.depth).or_default();
+            let offset = egui::vec2(
+                *column as f32 * layout.col_dist,
+                -(commit.depth as f32) * layout.row_dist,
+            );
+            positions.insert(
+                &commit.commit_id,
+                to_screen(destination.location() + offset),
+            );
+            *column += 1;
+        }
+
+        let color = ui.visuals().text_color().gamma_multiply(0.4);
+        let stroke = egui::Stroke::new(1.5, color);
+        let mut shapes = vec![];
+        for commit in &preview.commits {
+            let pos = positions[&commit.commit_id];
+            for parent in &commit.parents {
+                let parent_pos = (positions.get(parent).copied())
+                    .or_else(|| nodes.get(parent).map(|node| to_screen(node.location())));
+                if let Some(parent_pos) = parent_pos {
+                    shapes.extend(egui::Shape::dashed_line(&[pos, parent_pos], stroke, 6., 4.));
+                }
+            }
+        }
+        for commit in &preview.commits {
+            let pos = positions[&commit.commit_id];
+            let fill = (nodes.get(&commit.commit_id))
+                .and_then(|node| node.color())
+                .unwrap_or(color)
+                .gamma_multiply(0.4);
+            shapes.push(egui::Shape::circle_filled(pos, radius, fill));
+            shapes.push(egui::Shape::circle_stroke(pos, radius, stroke));
+            shapes.push(ui.fonts_mut(|f| {
+                egui::Shape::text(
+                    f,
+                    pos + egui::vec2(radius * 1.5, 0.),
+                    egui::Align2::LEFT_CENTER,
+                    (nodes.get(&commit.commit_id)).map_or_else(
+                        || commit.commit_id.hex()[..12].to_owned(),
+                        |node| node.label(),
+                    ),
+                    egui::TextStyle::Small.resolve(ui.style()),
+                    color,
+                )
+            }));
+        }
+        shapes
+    }
+
+    /// The revset to build the graph of: the view, with the pinned commits and
+    /// the commits of the undo preview or the result overlay
+    fn view_to_evaluate(&mut self) -> Result<String, ResolveError> {
+        if self.undo_preview.is_some() {
+            self.update_undo_preview()
+        } else if self.result_overlay.is_some() {
+            self.update_result_overlay()
+        } else {
+            Ok(self.view_with_pins())
+        }
+    }
+
+    /// Shows the graph built on the background thread
+    fn update_view_graph(
+        &mut self,
+        ui: &mut egui::Ui,
+        evaluated: view_evaluation::Evaluated,
+    ) -> Result<(), ResolveError> {
+        let view_evaluation::Evaluated {
+            view,
+            built,
+            collapsed_changes,
+            node_count,
+            duration,
+            truncated,
+            added,
+        } = evaluated;
+        self.last_view_node_count = Some(node_count);
+        self.collapsed_changes = collapsed_changes;
+        let graph_size = perf_report::GraphSize {
+            nodes: built.graph.node_count(),
+            edges: built.graph.edge_count(),
+        };
+        self.perf_report
+            .record_evaluation(perf_report::Evaluation::new(
+                perf_report::Kind::View,
+                &view,
+                duration,
+                node_count,
+                Some(graph_size),
+            ));
+        self.pending_layout = Some(graph_size);
+        match &added {
+            Some(added) => {
+                self.add_view_nodes(&built.graph, added);
+                // The new nodes are colored like after a change of the Select
+                self.recolor = true;
+            }
+            None => (self.graph, self.node_idxs) = view_graph(&built.graph),
+        }
+        if self.undo_preview.is_none() {
+            self.prefetch.start(&self.jj_graph, &view, self.node_limit);
+        }
+        self.path_node = None;
+        self.hovered_edge = None;
+        egui_graphs::reset_layout::<egui_graphs::LayoutStateHierarchical>(
+            ui,
+            self.graph_id.clone(),
+        );
+        self.node_limit_hit = built.metadata.limit_hit.is_some();
+        self.truncated = truncated;
+        let limit_hit = built.metadata.limit_hit;
+        // The undo preview and the result overlay are evaluated on their own
+        let extendable = built.metadata.continuation.is_some()
+            && !self.change_nodes
+            && self.undo_preview.is_none()
+            && self.result_overlay.is_none();
+        self.extendable = extendable.then_some((view, built));
+        if let Some(limit) = limit_hit {
+            Err(ResolveError::RevsetParseError(tr!(
+                "node-limit",
+                limit = limit
+            )))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Adds the nodes that were added to the commit graph, and the edges
+    /// reaching them, keeping the positions of the nodes already shown
+    fn add_view_nodes(&mut self, commit_graph: &CommitGraph, added: &[petgraph::graph::NodeIndex]) {
+        self.kept_positions = (self.node_idxs.iter())
+            .map(|node_idx| {
+                let node = self.graph.node(*node_idx).unwrap();
+                (node.payload().clone(), node.location())
+            })
+            .collect();
+        for node_idx in added {
+            let node = &commit_graph[*node_idx];
+            let view_idx =
+                (self.graph).add_node_with_label(node.commit_id.clone(), node.label.clone());
+            debug_assert_eq!(self.node_idxs.len(), node_idx.index());
+            self.node_idxs.push(view_idx);
+        }
+        let added: HashSet<_> = added.iter().collect();
+        for edge_idx in commit_graph.edge_indices() {
+            let (start, end) = commit_graph.edge_endpoints(edge_idx).unwrap();
+            if added.contains(&start) || added.contains(&end) {
+                let (start, end) = (self.node_idxs[start.index()], self.node_idxs[end.index()]);
+                self.graph
+                    .add_edge_with_label(start, end, (), "".to_owned());
+            }
+        }
+    }
+
+    /// Starts evaluating the Select and Compared revsets for the nodes on a
+    /// background thread. They are colored by
+    /// [`Self::poll_select_evaluation`].
+    fn update_filter(&mut self, ctx: &egui::Context) {
+        let commit_ids = (self.node_idxs.iter())
+            .map(|node_idx| self.graph.node(*node_idx).unwrap().payload().clone())
+            .collect();
+        let compare = (self.compare_revset.as_ref())
+            .filter(|entry| !entry.value.trim().is_empty())
+            .map(|entry| entry.value.clone());
+        let request = select_evaluation::Request {
+            select: self.filter_revset.value.clone(),
+            compare,
+            commit_ids,
+            authors: self.color_by_author,
+        };
+        self.select_evaluation.start(ctx, &self.jj_graph, request);
+    }
+
+    /// Colors the nodes once the Select and Compared revsets have been
+    /// evaluated. Returns whether they were.
+    fn poll_select_evaluation(&mut self) -> bool {
+        let Some(outcome) = self.select_evaluation.poll() else {
+            return false;
+        };
+        let marked = match outcome {
+            Ok(marked) => marked,
+            Err(ResolveError::RevsetParseError(msg) | ResolveError::JjError(msg)) => {
+                self.last_filter_calc_time = None;
+                self.last_filter_node_count = None;
+                self.memberships.clear();
+                self.filter_revset.history.set_last_tentative(true);
+                self.filter_revset.error = Some(msg);
+                return true;
+            }
+        };
+        // A failing Compared revset leaves the Select coloring, instead of
+        // some of the nodes colored
+        let compared = match (&mut self.compare_revset, &marked.compared) {
+            (Some(entry), Some(Ok(compared))) => {
+                entry.error = None;
+                Some(compared)
+            }
+            (Some(entry), Some(Err(error))) => {
+                entry.history.set_last_tentative(true);
+                entry.error = Some(error.to_string());
+                None
+            }
+            (Some(entry), None) => {
+                entry.error = None;
+                None
+            }
+            (None, _) => None,
+        };
+        self.memberships = mark_graph(
+            &mut self.graph,
+            &self.node_idxs,
+            &marked,
+            compared,
+            self.palette,
+            self.color_by_author,
+        );
+        self.filter_revset.error = None;
+        self.last_filter_calc_time = Some(marked.calc_time);
+        self.last_filter_node_count = Some(marked.node_count);
+        self.perf_report
+            .record_evaluation(perf_report::Evaluation::new(
+                perf_report::Kind::Select,
+                &marked.select,
+                marked.calc_time,
+                marked.node_count,
+                None,
+            ));
+        true
+    }
+}
+
+/// Loads the repository with the custom revset functions: forge_pr() and the
+/// symbol tables in the config directory
+fn load_jj_graph(repository_path: &Path, options: LoadOptions) -> anyhow::Result<jjgraph::JjGraph> {
+    let mut revset_exts = jj_lib::revset::RevsetExtensions::new();
+    forge_pr::register(&mut revset_exts, options.forge_token);
+    revset_explorer_core::symbols::SymbolTables::load_dir(&settings::symbols_dir())?
+        .register(&mut revset_exts)?;
+    jjgraph::JjGraph::with_store_factories(
+        repository_path,
+        revset_exts,
+        &options.backend_plugins.store_factories(),
+    )
+}
+
+/// Saves the image as a PNG file
+fn save_png(image: &egui::ColorImage, path: &Path) -> anyhow::Result<()> {
+    let [width, height] = image.size;
+    image::save_buffer_with_format(
+        path,
+        image.as_raw(),
+        width as u32,
+        height as u32,
+        image::ColorType::Rgba8,
+        image::ImageFormat::Png,
+    )?;
+    Ok(())
+}
+
+/// Stops the app with an error, as the --script steps must not be skipped in
+/// a build pipeline
+fn script_failed(error: anyhow::Error) -> ! {
+    eprintln!("{}", tr!("script-failed", error = format!("{error:#}")));
+    std::process::exit(1);
+}
+
+/// Converts the graph from the core library into the graph shown in the view
+fn view_graph(commit_graph: &CommitGraph) -> (GraphType, Vec<petgraph::graph::NodeIndex>) {
+    let mut graph: GraphType =
+        egui_graphs::Graph::new(petgraph::stable_graph::StableGraph::default());
+    let mut node_map = HashMap::new();
+    for node_idx in commit_graph.node_indices() {
+        let node = &commit_graph[node_idx];
+        let view_idx = graph.add_node_with_label(node.commit_id.clone(), node.label.clone());
+        node_map.insert(node_idx, view_idx);
+    }
+    for edge_idx in commit_graph.edge_indices() {
+        let (start, end) = commit_graph.edge_endpoints(edge_idx).unwrap();
+        graph.add_edge_with_label(node_map[&start], node_map[&end], (), "".to_owned());
+    }
+    let node_idxs = commit_graph
+        .node_indices()
+        .map(|node_idx| node_map[&node_idx])
+        .collect();
+    (graph, node_idxs)
+}
+
+/// Colors the nodes by the Select revset, and by the commits of the Compared
+/// revset over that
+fn mark_graph(
+    graph: &mut GraphType,
+    node_idxs: &[petgraph::graph::NodeIndex],
+    marked: &select_evaluation::Marked,
+    compared: Option<&HashSet<CommitId>>,
+    palette: Palette,
+    color_by_author: bool,
+) -> HashMap<Membership, usize> {
+    let mut memberships = HashMap::new();
+    for node_idx in node_idxs {
+        let node = graph.node_mut(*node_idx).unwrap();
+        let commit_id = node.payload().clone();
+        // Not evaluated for the graph yet
+        let Some(&marking) = marked.markings.get(&commit_id) else {
+            continue;
+        };
+        let in_select = marking.filter_match == FilterMatch::Match;
+        if color_by_author {
+            let email = marked.authors.get(&commit_id).map_or("", String::as_str);
+            node.set_color(author_color(email, in_select));
+        } else {
+            let [red, green, blue] = palette.color(marking);
+            node.set_color(ecolor::Color32::from_rgb(red, green, blue));
+        }
+        // The comparison is colored over the other colors
+        if let Some(compared) = compared
+            && let Some(membership) = Membership::new(in_select, compared.contains(&commit_id))
+        {
+            let [red, green, blue] = palette.membership_color(membership);
+            node.set_color(ecolor::Color32::from_rgb(red, green, blue));
+            *memberships.entry(membership).or_default() += 1;
+        }
+    }
+    memberships
+}
+
+/// Whether typing goes to a text field, so shortcuts without modifiers must be
+/// ignored
+fn text_edit_focused(ctx: &egui::Context) -> bool {
+    (ctx.memory(|m| m.focused())).is_some_and(|id| egui::TextEdit::load_state(ctx, id).is_some())
+}
+
+/// Turns off the animations of egui, for light and dark mode. The graph view
+/// is never animated.
+fn reduce_motion(ctx: &egui::Context) {
+    ctx.all_styles_mut(|style| {
+        style.animation_time = 0.;
+        style.scroll_animation = egui::style::ScrollAnimation::none();
+    });
+}
+
+/// Legend of a color of the comparison
+fn membership_name(membership: Membership, count: usize) -> String {
+    match membership {
+        Membership::OnlyA => tr!("compare-only-select", count = count),
+        Membership::OnlyB => tr!("compare-only-compared", count = count),
+        Membership::Both => tr!("compare-both", count = count),
+    }
+}
+
+fn palette_name(palette: Palette) -> String {
+    match palette {
+        Palette::Standard => tr!("palette-standard"),
+        Palette::ColorBlind => tr!("palette-color-blind"),
+        Palette::HighContrast => tr!("palette-high-contrast"),
+    }
+}
+
+/// The high-contrast palette also gets thick node borders and edges in the
+/// strongest text color
+fn graph_style(palette: Palette) -> egui_graphs::SettingsStyle {
+    let style = egui_graphs::SettingsStyle::default().with_labels_always(true);
+    if palette != Palette::HighContrast {
+        return style;
+    }
+    style
+        .with_node_stroke_hook(|selected, _dragged, _color, _stroke, egui_style| {
+            let width = if selected { 5. } else { 3. };
+            egui::Stroke::new(width, egui_style.visuals.strong_text_color())
+        })
+        .with_edge_stroke_hook(|_selected, _order, _stroke, egui_style| {
+            egui::Stroke::new(3., egui_style.visuals.strong_text_color())
+        })
+}
+
+fn edge_routing_name(routing: EdgeRouting) -> String {
+    match routing {
+        EdgeRouting::Straight => tr!("edge-routing-straight"),
+        EdgeRouting::Curved => tr!("edge-routing-curved"),
+    }
+}
+
+fn arrowheads_name(arrowheads: Arrowheads) -> String {
+    match arrowheads {
+        Arrowheads::None => tr!("arrowheads-none"),
+        Arrowheads::Small => tr!("arrowheads-small"),
+        Arrowheads::Normal => tr!("arrowheads-normal"),
+        Arrowheads::Large => tr!("arrowheads-large"),
+    }
+}
+
+fn arrow_direction_name(direction: ArrowDirection) -> String {
+    match direction {
+        ArrowDirection::Parents => tr!("arrow-direction-parents"),
+        ArrowDirection::Children => tr!("arrow-direction-children"),
+    }
+}
+
+fn author_label_name(label: author_label::AuthorLabel) -> String {
+    match label {
+        author_label::AuthorLabel::None => tr!("author-label-none"),
+        author_label::AuthorLabel::Initials => tr!("author-label-initials"),
+        author_label::AuthorLabel::Email => tr!("author-label-email"),
+    }
+}
+
+fn guide_name(guide: graph_guides::Guide) -> String {
+    match guide {
+        graph_guides::Guide::GenerationBands => tr!("guide-generation-bands"),
+        graph_guides::Guide::DateLines => tr!("guide-date-lines"),
+        graph_guides::Guide::ImmutableBoundary => tr!("guide-immutable-boundary"),
+    }
+}
+
+fn node_size_name(size: NodeSize) -> String {
+    match size {
+        NodeSize::Uniform => tr!("node-size-uniform"),
+        NodeSize::Files => tr!("node-size-files"),
+        NodeSize::Lines => tr!("node-size-lines"),
+    }
+}
+
+fn signature_name(status: SignatureStatus) -> String {
+    match status {
+        SignatureStatus::Unsigned => tr!("signature-unsigned"),
+        SignatureStatus::Good => tr!("signature-good"),
+        SignatureStatus::Unknown => tr!("signature-unknown"),
+        SignatureStatus::Invalid => tr!("signature-invalid"),
+    }
+}
+
+/// Color of the signature badge. Unsigned commits get no badge.
+fn signature_color(status: SignatureStatus) -> Option<ecolor::Color32> {
+    match status {
+        SignatureStatus::Unsigned => None,
+        SignatureStatus::Good => Some(ecolor::Color32::from_rgb(0x2e, 0xcc, 0x40)),
+        SignatureStatus::Unknown => Some(ecolor::Color32::from_rgb(0xff, 0xdc, 0x00)),
+        SignatureStatus::Invalid => Some(ecolor::Color32::from_rgb(0xff, 0x41, 0x36)),
+    }
+}
+
+fn annotation_status_name(status: AnnotationStatus) -> String {
+    match status {
+        AnnotationStatus::Good => tr!("annotation-good"),
+        AnnotationStatus::Bad => tr!("annotation-bad"),
+        AnnotationStatus::Neutral => tr!("annotation-neutral"),
+    }
+}
+
+fn annotation_color(status: AnnotationStatus) -> ecolor::Color32 {
+    match status {
+        AnnotationStatus::Good => ecolor::Color32::from_rgb(0x2e, 0xcc, 0x40),
+        AnnotationStatus::Bad => ecolor::Color32::from_rgb(0xff, 0x41, 0x36),
+        AnnotationStatus::Neutral => ecolor::Color32::from_rgb(0x00, 0x74, 0xd9),
+    }
+}
+
+fn deficiency_name(deficiency: Deficiency) -> String {
+    match deficiency {
+        Deficiency::Protanopia => tr!("vision-protanopia"),
+        Deficiency::Deuteranopia => tr!("vision-deuteranopia"),
+        Deficiency::Tritanopia => tr!("vision-tritanopia"),
+        Deficiency::Achromatopsia => tr!("vision-achromatopsia"),
+    }
+}
+
+fn author_color(email: &str, matches_filter: bool) -> ecolor::Color32 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::hash::DefaultHasher::new();
+    email.hash(&mut hasher);
+    let hue = (hasher.finish() % 360) as f32 / 360.;
+    let value = if matches_filter { 1. } else { 0.4 };
+    ecolor::Hsva::new(hue, 0.7, value, 1.).into()
+}
+
+fn format_timestamp(timestamp: &jj_lib::backend::Timestamp) -> String {
+    let offset = chrono::FixedOffset::east_opt(timestamp.tz_offset * 60)
+        .unwrap_or(chrono::FixedOffset::east_opt(0).unwrap());
+    chrono::DateTime::from_timestamp_millis(timestamp.timestamp.0)
+        .map(|t| {
+            t.with_timezone(&offset)
+                .format("%Y-%m-%d %H:%M:%S %:z")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+/// Revset text edit box with error message display
+fn revset_edit(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut String,
+    error: &Option<String>,
+    calculation_time: Option<Duration>,
+    node_count: Option<&NodeCount>,
+    pending: bool,
+) -> egui::Response {
+    ui.horizontal(|ui| {
+        let revset_label = ui.label(label);
+        let response = ui
+            .scope(|ui| {
+                if error.is_some() {
+                    ui.visuals_mut().extreme_bg_color = ecolor::Color32::DARK_RED;
+                }
+                ui.add(
+                    egui::TextEdit::singleline(value)
+                        .desired_width(500.)
+                        .cursor_at_end(true)
+                        .hint_text(tr!("revset-hint")),
+                )
+                .labelled_by(revset_label.id)
+            })
+            .inner;
+        let err_msg = if let Some(err_msg) = error.as_ref() {
+            // Remove empty lines, to make the error message more compact
+            err_msg.replace("  |\n", "")
+        } else {
+            "".to_owned()
+        };
+        if pending {
+            ui.weak(tr!("revset-pending"))
+                .on_hover_text(tr!("revset-pending-hover"));
+        } else if let Some(count) = node_count {
+            ui.label(match count {
+                NodeCount::Exact(count) => format!("{count} ○"),
+                NodeCount::AtLeast(count) => format!("{count}+ ○"),
+            });
+        }
+        if err_msg.is_empty()
+            && let Some(time) = calculation_time
+        {
+            ui.label(format!("{:.1} ms", time.as_micros() as f64 / 1000.0));
+        }
+        let _error_label = ui.add_sized(
+            [1., ui.text_style_height(&egui::TextStyle::Monospace) * 4.],
+            egui::Label::new(RichText::new(err_msg).family(egui::FontFamily::Monospace)),
+        );
+        response
+    })
+    .inner
+}
+
+/// Revset input going through the entered revsets with the up and down keys,
+/// with completions and hints about likely mistakes. `glob_by_default` is from
+/// the jj config, see [`lint::lint`]. Typing is not reported as a change, see
+/// [`RevsetEntry::typing_paused`].
+fn revset_edit_with_history(
+    ui: &mut egui::Ui,
+    label: &str,
+    revset_entry: &mut RevsetEntry,
+    calculation_time: Option<Duration>,
+    node_count: Option<&NodeCount>,
+    glob_by_default: bool,
+    vocabulary: &revset_completion::Vocabulary,
+) -> (egui::Response, bool) {
+    let resp = revset_edit(
+        ui,
+        &format!("{}: ", label),
+        &mut revset_entry.value,
+        &revset_entry.error,
+        calculation_time,
+        node_count,
+        revset_entry.edited.is_some(),
+    );
+
+    let mut value_from_history = false;
+    let mut value_changed = false;
+
+    if resp.has_focus() {
+        if ui.input_mut(|i| i.consume_shortcut(&keymap::PREVIOUS_REVSET)) {
+            revset_entry.history.prev();
+            revset_entry.value = revset_entry.history.get().unwrap_or("").to_owned();
+            value_from_history = true;
+        } else if ui.input_mut(|i| i.consume_shortcut(&keymap::NEXT_REVSET)) {
+            revset_entry.history.next();
+            revset_entry.value = revset_entry.history.get().unwrap_or("").to_owned();
+            value_from_history = true;
+        }
+    }
+
+    if value_from_history {
+        value_changed = true;
+        // Move cursor to the end of the textbox
+        if let Some(mut s) = egui::text_edit::TextEdit::load_state(&resp.ctx, resp.id) {
+            s.cursor.set_char_range(Some(egui::text::CCursorRange::one(
+                egui::text::CCursor::new(revset_entry.value.len()),
+            )));
+            s.store(&resp.ctx, resp.id);
+        }
+    } else if resp.changed() {
+        revset_entry.history.add(revset_entry.value.trim(), false);
+        revset_entry.edited = Some(Instant::now());
+    }
+    if vocabulary.popup(
+        ui,
+        &resp,
+        &mut revset_entry.value,
+        &mut revset_entry.completions_hovered,
+    ) {
+        value_changed = true;
+        revset_entry.history.add(revset_entry.value.trim(), false);
+    }
+    if let Some(fixed) = lint_hints(ui, &revset_entry.value, glob_by_default) {
+        revset_entry.value = fixed;
+        value_changed = true;
+        revset_entry.history.add(revset_entry.value.trim(), false);
+    }
+    if value_changed {
+        revset_entry.edited = None;
+    }
+    (resp, value_changed)
+}
+
+/// Shows the likely mistakes in the revset, each with a button fixing it.
+/// Returns the fixed revset when one is clicked.
+fn lint_hints(ui: &mut egui::Ui, revset: &str, glob_by_default: bool) -> Option<String> {
+    let mut fixed = None;
+    for lint in lint::lint(revset, glob_by_default) {
+        ui.horizontal(|ui| {
+            let text = &revset[lint.range.clone()];
+            let hint = match lint.kind {
+                lint::LintKind::EmptyRange => tr!("lint-empty-range", text = text),
+                lint::LintKind::NameForEmail => tr!("lint-name-for-email", text = text),
+                lint::LintKind::MissingGlob => tr!("lint-missing-glob", text = text),
+            };
+            ui.label(RichText::new(hint).color(ui.visuals().warn_fg_color));
+            if let Some(fix) = &lint.fix
+                && ui
+                    .small_button(tr!("lint-fix"))
+                    .on_hover_text(tr!("lint-fix-hover", fix = fix.as_str()))
+                    .clicked()
+            {
+                fixed = lint.fixed(revset);
+            }
+        });
+    }
+    fixed
+}
+
+impl eframe::App for ExplorerApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        if let Some(output) = self.print_selection {
+            print!("{}", self.selection_output(output));
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_screenshots(ctx);
+        self.run_script(ctx);
+        self.mutation_confirmation(ctx);
+        self.export_dialog(ctx);
+        self.settings_dialog(ctx);
+        self.result_set_dialog(ctx);
+        self.perf_report_dialog(ctx);
+        self.replace_dialog(ctx);
+        self.bookmark_move_window(ctx);
+        self.result_change_window(ctx);
+        self.shortcuts_overlay(ctx);
+        if self.about.open {
+            self.about.window(ctx, &self.jj_graph);
+        }
+        if !text_edit_focused(ctx) {
+            let pressed = (keymap::VIEWPOINTS.iter())
+                .position(|shortcut| ctx.input_mut(|i| i.consume_shortcut(shortcut)));
+            if let Some(index) = pressed {
+                self.jump_to_viewpoint(index);
+            }
+        }
+        egui::TopBottomPanel::top("menu_bar").show(ctx, |ui| self.menu_bar(ui));
+        if self.date_histogram
+            && self.graph_tab == GraphTab::Commits
+            && self.date_filter.spans_time()
+        {
+            egui::TopBottomPanel::bottom("date_histogram").show(ctx, |ui| {
+                if self.date_filter.histogram(ui).is_some() {
+                    self.apply_dimming();
+                }
+            });
+        }
+        egui::TopBottomPanel::bottom("status_bar").show(ctx, |ui| self.status_bar(ui));
+        self.poll_ci_statuses();
+        // The files may have changed in another window
+        if ctx.input(|i| (i.events.iter()).any(|e| matches!(e, egui::Event::WindowFocused(true)))) {
+            self.check_working_copy();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&keymap::RELOAD)) {
+            self.reload_repo();
+        }
+        // Watching would go back to the latest operation
+        if self.past_operation.is_none()
+            && self.repo_watch.due(ctx, &self.repository_path)
+            && self.jj_graph.is_outdated().unwrap_or_default()
+        {
+            self.reload_watched_repo();
+        }
+        if let Some(error) = self.repo_watch.take_error() {
+            self.status_message = Some(tr!("watch-failed", error = error));
+        }
+        self.avatars.poll();
+        if self.change_sizes.poll() {
+            self.apply_node_sizes(ctx);
+        }
+        if self.signatures.poll() {
+            self.apply_signature_badges(ctx);
+        }
+        if self.diff_search.poll() {
+            self.apply_search_matches();
+        }
+        self.prefetch.poll();
+        if self.graph_tab == GraphTab::Commits && !self.graph.selected_nodes().is_empty() {
+            egui::SidePanel::right("details")
+                .default_width(300.)
+                .show(ctx, |ui| {
+                    egui::ScrollArea::vertical().show(ui, |ui| self.details_panel(ui))
+                });
+        }
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut self.graph_tab, GraphTab::Commits, tr!("tab-commits"));
+                if ui
+                    .selectable_value(&mut self.graph_tab, GraphTab::Results, tr!("tab-results"))
+                    .clicked()
+                {
+                    self.update_select_order();
+                }
+                ui.selectable_value(
+                    &mut self.graph_tab,
+                    GraphTab::Operations,
+                    tr!("tab-operations"),
+                );
+            });
+            if self.graph_tab == GraphTab::Operations {
+                let graph_id = format!("{}-operations", self.graph_id.as_deref().unwrap_or(""));
+                let show = self.op_log.ui(
+                    ui,
+                    &self.jj_graph,
+                    &graph_style(self.palette),
+                    &self.navigation,
+                    Some(graph_id),
+                );
+                if let Some(op_id) = show {
+                    self.show_operation(&op_id);
+                    self.graph_tab = GraphTab::Commits;
+                }
+                return;
+            }
+            let (toolbar_filter_changed, toolbar_view_changed) = self.toolbar(ui);
+            let glob_by_default = self.jj_graph.uses_glob_by_default();
+            let (filter_edit, filter_changed) = revset_edit_with_history(
+                ui,
+                &tr!("revset-select"),
+                &mut self.filter_revset,
+                self.last_filter_calc_time,
+                self.last_filter_node_count.as_ref(),
+                glob_by_default,
+                &self.revset_vocabulary,
+            );
+            let (view_edit, view_changed) = revset_edit_with_history(
+                ui,
+                &tr!("revset-view"),
+                &mut self.view_revset,
+                None,
+                self.last_view_node_count.as_ref(),
+                glob_by_default,
+                &self.revset_vocabulary,
+            );
+            let debounce = self.revset_debounce();
+            let filter_changed =
+                filter_changed || self.filter_revset.typing_paused(&filter_edit, debounce);
+            let view_changed = view_changed || self.view_revset.typing_paused(&view_edit, debounce);
+
+            let rebase_source_changed = match &mut self.rebase_source {
+                Some(source) => {
+                    let label = tr!("rebase-source");
+                    let vocabulary = &self.revset_vocabulary;
+                    let (edit, changed) = revset_edit_with_history(
+                        ui,
+                        &label,
+                        source,
+                        None,
+                        None,
+                        glob_by_default,
+                        vocabulary,
+                    );
+                    changed || source.typing_paused(&edit, debounce)
+                }
+                None => false,
+            };
+            let compare_changed = match &mut self.compare_revset {
+                Some(compare) => {
+                    let label = tr!("revset-compare");
+                    let vocabulary = &self.revset_vocabulary;
+                    let (edit, changed) = revset_edit_with_history(
+                        ui,
+                        &label,
+                        compare,
+                        None,
+                        None,
+                        glob_by_default,
+                        vocabulary,
+                    );
+                    changed || compare.typing_paused(&edit, debounce)
+                }
+                None => false,
+            };
+
+            let view_changed =
+                view_changed || toolbar_view_changed || std::mem::take(&mut self.view_invalidated);
+            let repo_reloaded = std::mem::take(&mut self.repo_reloaded);
+            if repo_reloaded {
+                self.path_filter.reload();
+                self.load_annotations();
+                self.check_working_copy();
+            }
+            if view_changed || repo_reloaded {
+                // The search only covers the commits that were in the view
+                self.diff_search.clear();
+            }
+            if !self.index_warmup.is_ready() {
+                self.index_warmup.ui(ui);
+                return;
+            }
+            let more_requested = std::mem::take(&mut self.more_requested);
+            if view_changed || repo_reloaded || !self.initialized {
+                self.update_view(ui);
+            } else if more_requested {
+                self.load_more(ui);
+            }
+            // A view that was evaluated in the background counts as changed
+            let view_changed = self.poll_view_evaluation(ui) || view_changed;
+            if self.view_evaluation.is_pending() {
+                self.view_evaluation.ui(ui);
+            } else if self.select_evaluation.is_pending() {
+                self.select_evaluation.ui(ui);
+            }
+            let requested_filter_changed = match self.requested_filter.take() {
+                Some(revset) => {
+                    self.filter_revset.set_generated(&revset);
+                    true
+                }
+                None => false,
+            };
+            let filter_changed = filter_changed
+                || compare_changed
+                || toolbar_filter_changed
+                || requested_filter_changed
+                || std::mem::take(&mut self.recolor);
+            let recolor = filter_changed || view_changed || repo_reloaded || !self.initialized;
+            if recolor {
+                self.update_filter(ui.ctx());
+            }
+            // The color rules are colored over the Select coloring
+            if self.poll_select_evaluation() {
+                self.apply_color_rules();
+            }
+            if recolor {
+                self.apply_avatars(ui.ctx());
+                self.apply_node_sizes(ui.ctx());
+                self.apply_signature_badges(ui.ctx());
+                self.apply_annotation_badges();
+                self.apply_pins();
+                self.apply_commit_ids();
+                self.apply_author_labels();
+                self.apply_description_labels();
+                self.apply_distances();
+                self.apply_boundary_outlines();
+                self.apply_edge_style();
+                self.update_bookmark_clusters();
+                self.update_change_groups();
+                self.update_select_order();
+                self.record_session_step();
+            }
+            let rebase_destination = (self.graph.selected_nodes().last())
+                .and_then(|node_idx| self.graph.node(*node_idx))
+                .map(|node| node.payload());
+            if rebase_source_changed
+                || repo_reloaded
+                || view_changed
+                || (self.rebase_source.is_some()
+                    && rebase_destination != self.rebase_destination.as_ref())
+            {
+                self.update_rebase_preview();
+            }
+            self.apply_edge_fans();
+
+            if !self.initialized {
+                filter_edit.request_focus();
+                self.initialized = true;
+            }
+            if self.graph_tab == GraphTab::Results {
+                self.results_table(ui);
+                return;
+            }
+            if self.node_idxs.is_empty() && self.view_revset.error.is_none() {
+                let fallback = self.empty_view_fallback.as_deref();
+                if let Some(revset) = empty_view::ui(ui, &self.view_revset.value, fallback) {
+                    self.view_revset.set_generated(&revset);
+                    self.view_invalidated = true;
+                }
+                return;
+            }
+
+            let graph_navigation = egui_graphs::SettingsNavigation::default()
+                // Disable fit to screen by default, as it hinders zoom and pan
+                .with_fit_to_screen_enabled(view_changed)
+                // Done by navigation::navigate()
+                .with_zoom_and_pan_enabled(false);
+            // egui_graphs only selects with the primary button
+            let select_primary = self.navigation.select_button == navigation::MouseButton::Primary;
+            let graph_interaction = egui_graphs::SettingsInteraction::default()
+                .with_dragging_enabled(false)
+                .with_edge_clicking_enabled(false)
+                .with_edge_selection_enabled(false)
+                .with_hover_enabled(true)
+                .with_node_clicking_enabled(true)
+                .with_node_selection_enabled(select_primary)
+                .with_node_selection_multi_enabled(select_primary);
+
+            let mut s = egui_graphs::get_layout_state::<egui_graphs::LayoutStateHierarchical>(
+                ui,
+                self.graph_id.clone(),
+            );
+            s.center_parent = true;
+            egui_graphs::set_layout_state(ui, s, self.graph_id.clone());
+            self.apply_fixed_layout(ui);
+
+            let mut graph_view = egui_graphs::GraphView::<
+                _,
+                _,
+                _,
+                _,
+                NodeShape,
+                EdgeShape,
+                egui_graphs::LayoutStateHierarchical,
+                egui_graphs::LayoutHierarchical,
+            >::new(&mut self.graph)
+            .with_navigations(&graph_navigation)
+            .with_interactions(&graph_interaction)
+            .with_styles(&graph_style(self.palette))
+            .with_id(self.graph_id.clone());
+            // Behind the graph, but placed after it is laid out
+            // Faded while it doesn't show the View revset yet
+            if self.view_revset.edited.is_some() || self.view_evaluation.is_pending() {
+                ui.multiply_opacity(STALE_GRAPH_OPACITY);
+            }
+            let guides = ui.painter().add(egui::Shape::Noop);
+            let hulls = ui.painter().add(egui::Shape::Noop);
+            let started = Instant::now();
+            let graph_response = ui.add(&mut graph_view);
+            // The layout is computed when the graph is first drawn
+            if let Some(graph_size) = self.pending_layout.take() {
+                self.perf_report
+                    .record_layout(graph_size, started.elapsed());
+            }
+            if let Some((zoom, pan)) = self.pending_viewpoint.take() {
+                let mut meta = egui_graphs::MetadataFrame::new(self.graph_id.clone()).load(ui);
+                (meta.zoom, meta.pan) = (zoom, pan);
+                meta.save(ui);
+                ui.ctx().request_repaint();
+            }
+            graph_response.widget_info(|| {
+                egui::WidgetInfo::labeled(egui::WidgetType::Other, true, tr!("commit-graph"))
+            });
+            self.graph_rect = graph_response.rect;
+            self.place_labels(ui, graph_response.rect.min);
+            self.update_path_highlight();
+            self.hover_edge(ui, &graph_response);
+            self.node_tooltip(&graph_response);
+            let mut hull_shapes = self.bookmark_hulls(ui, graph_response.rect.min);
+            hull_shapes.extend(self.change_group_boxes(ui, graph_response.rect.min));
+            ui.painter().set(hulls, hull_shapes);
+            let guide_shapes = self.guide_shapes(ui, graph_response.rect);
+            ui.painter().set(guides, guide_shapes);
+            navigation::navigate(ui, &graph_response, &self.navigation, &self.graph_id);
+            self.select_by_button(&graph_response);
+            self.node_widgets(ui, graph_response.rect.min);
+            let mut overlay = self.bookmark_move_arrows(ui, graph_response.rect.min);
+            overlay.extend(self.rebase_ghosts(ui, graph_response.rect.min));
+            ui.painter().extend(overlay);
+            self.stale_indicator(ui, graph_response.rect.min);
+            // Touch screens get secondary clicks from long presses
+            let long_pressed =
+                self.navigation.long_press_menu && self.long_press.detect(ui, &graph_response);
+            if graph_response.secondary_clicked() || long_pressed {
+                self.context_menu_node = self.graph.hovered_node();
+            }
+            if let Some(node_idx) = self.context_menu_node {
+                egui::Popup::context_menu(&graph_response)
+                    .open_memory(long_pressed.then_some(egui::SetOpenCommand::Bool(true)))
+                    .show(|ui| self.node_context_menu(ui, node_idx));
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_kittest::Harness;
+    use egui_kittest::kittest::Queryable;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    /// Runs the app on the repository without a window
+    fn harness<'a>(repo: &TestRepo, view: &str, select: &str) -> Harness<'a, ExplorerApp> {
+        harness_with_settings(repo, view, select, settings::Settings::default())
+    }
+
+    fn harness_with_settings<'a>(
+        repo: &TestRepo,
+        view: &str,
+        select: &str,
+        settings: settings::Settings,
+    ) -> Harness<'a, ExplorerApp> {
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(view.to_owned()),
+            initial_select: Some(select.to_owned()),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings, options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        harness
+    }
+
+    fn node_colors(app: &ExplorerApp) -> Vec<(CommitId, Option<ecolor::Color32>)> {
+        app.node_idxs
+            .iter()
+            .map(|node_idx| {
+                let node = app.graph.node(*node_idx).unwrap();
+                (node.payload().clone(), node.color())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shows_and_marks_view() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        repo.edit(&b);
+        let harness = harness(&repo, &format!("{}::", a.hex()), &a.hex());
+        let app = harness.state();
+        assert_eq!(
+            node_colors(app),
+            [
+                (b, Some(ecolor::Color32::from_rgb(0x29, 0x59, 0x23))),
+                (a, Some(ecolor::Color32::from_rgb(0xff, 0xfc, 0x00))),
+            ]
+        );
+        assert_eq!(app.graph.edge_count(), 1);
+        assert_eq!(app.view_revset.error, None);
+    }
+
+    #[test]
+    fn reports_invalid_view() {
+        let repo = TestRepo::init();
+        let harness = harness(&repo, "::(", "");
+        assert!(harness.state().view_revset.error.is_some());
+        assert!(harness.state().node_idxs.is_empty());
+    }
+
+    #[test]
+    fn warns_about_stale_working_copy() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        repo.edit(&a);
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(a.hex()),
+            allow_mutations: true,
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        assert!(
+            harness
+                .query_by_label("⚠ Working copy may be stale")
+                .is_none()
+        );
+
+        let file = repo.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        let later = std::time::SystemTime::now() + Duration::from_secs(10);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(2);
+        harness.get_by_label("⚠ Working copy may be stale");
+        harness.get_by_label("Snapshot").click();
+        harness.run_steps(2);
+        assert_eq!(
+            harness.state().pending_mutation,
+            Some(jj_cli::Mutation::Snapshot)
+        );
+        harness.get_by_label("jj status");
+    }
+
+    #[test]
+    fn selects_commits_changing_path() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("src/a.rs", "")]);
+        let b = repo.commit_with_files("b", &[&a], &[("docs/b.md", "")]);
+        repo.edit(&b);
+        let mut harness = harness(&repo, &format!("{}::", a.hex()), "");
+        harness.get_by_label("Changed path:").click();
+        harness.run_steps(1);
+        harness.get_by_label("Changed path:").type_text("sr");
+        harness.run_steps(2);
+        harness.get_by_label("src/a.rs");
+        harness.get_by_label("src/").click();
+        harness.run_steps(2);
+        harness.get_by_label("Add to Select").click();
+        harness.run_steps(2);
+        let app = harness.state();
+        assert_eq!(app.filter_revset.value, r#"files(root:"src/")"#);
+        assert_eq!(app.filter_revset.error, None);
+        assert_eq!(app.last_filter_node_count, Some(NodeCount::Exact(1)));
+    }
+
+    #[test]
+    fn evaluates_revsets_when_typing_pauses() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut settings = settings::Settings::default();
+        settings.ui.revset_debounce = Some(60_000);
+        let mut harness = harness_with_settings(&repo, "", "", settings);
+        harness.get_by_label("View: ").click();
+        harness.run_steps(1);
+        harness.get_by_label("View: ").type_text(&a.hex());
+        harness.run_steps(2);
+        harness.get_by_label("Not evaluated");
+        assert!(harness.state().node_idxs.is_empty());
+        harness.key_press(egui::Key::Enter);
+        harness.run_steps(2);
+        assert_eq!(harness.state().node_idxs.len(), 1);
+        assert!(harness.query_by_label("Not evaluated").is_none());
+    }
+
+    #[test]
+    fn completes_revsets() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        repo.set_bookmark("feature", &a);
+        let mut harness = harness(&repo, "all()", "");
+        harness.get_by_label("Select: ").click();
+        harness.run_steps(1);
+        harness.get_by_label("Select: ").type_text("heads(fea");
+        harness.run_steps(2);
+        harness.get_by_label("bookmark");
+        harness.key_press(egui::Key::Tab);
+        harness.run_steps(2);
+        assert_eq!(harness.state().filter_revset.value, "heads(feature");
+        harness.get_by_label("Select: ").type_text(") ");
+        harness.run_steps(2);
+        assert_eq!(harness.query_all_by_label("operator").count(), 5);
+        harness.get_by_label("| ").click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().filter_revset.value, "heads(feature) | ");
+    }
+
+    #[test]
+    fn selects_commits_that_last_touched_path() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("a.txt", "1\n")]);
+        let b = repo.commit_with_files("b", &[&a], &[("a.txt", "2\n")]);
+        let c = repo.commit_with_files("c", &[&b], &[("c.txt", "")]);
+        let view = format!("{a}::{c}");
+        let mut harness = harness(&repo, &view, "");
+        harness.get_by_label("Changed path:").click();
+        harness.run_steps(1);
+        harness.get_by_label("Changed path:").type_text("a.txt");
+        harness.run_steps(2);
+        harness.get_by_label("Last touched").click();
+        harness.run_steps(2);
+        let app = harness.state();
+        assert_eq!(
+            app.filter_revset.value,
+            format!(r#"heads(({view}) & files(root:"a.txt"))"#)
+        );
+        assert_eq!(app.last_filter_node_count, Some(NodeCount::Exact(1)));
+        let marking = |commit_id: &CommitId| {
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().color()
+        };
+        // Only b is bright
+        assert_ne!(marking(&b), marking(&a));
+        assert_eq!(marking(&a), marking(&c));
+    }
+
+    #[test]
+    fn searches_in_diffs() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("a.txt", "a needle\n")]);
+        let b = repo.commit_with_files("b", &[&a], &[("b.txt", "hay\n")]);
+        let mut harness = harness(&repo, &format!("{}::", a.hex()), "");
+        harness.get_by_label("Search in diffs:").click();
+        harness.run_steps(1);
+        harness.get_by_label("Search in diffs:").type_text("needle");
+        harness.key_press(egui::Key::Enter);
+        harness.run_steps(1);
+        for _ in 0..100 {
+            if harness.query_by_label("1 matching commit").is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+            harness.run_steps(1);
+        }
+        harness.get_by_label("1 matching commit");
+        let highlighted = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().display().highlighted
+        };
+        assert!(highlighted(&harness, &a));
+        assert!(!highlighted(&harness, &b));
+
+        harness.get_by_label("Clear").click();
+        harness.run_steps(1);
+        assert!(!highlighted(&harness, &a));
+    }
+
+    #[test]
+    fn nodes_are_keyboard_accessible() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("first", &[]);
+        let b = repo.commit("second\n\nBody", &[&a]);
+        repo.edit(&b);
+        let mut harness = harness(&repo, &format!("{}::", a.hex()), "");
+        let node = harness.get_by_label_contains("working copy: second");
+        node.focus();
+        harness.run_steps(1);
+        harness.key_press(egui::Key::Enter);
+        harness.run_steps(2);
+        let b_idx = harness.state().node_idxs[0];
+        assert_eq!(harness.state().graph.selected_nodes(), [b_idx]);
+        // The details panel is shown
+        harness.get_by_label("Change ID");
+
+        harness.key_press(egui::Key::Space);
+        harness.run_steps(2);
+        assert!(harness.state().graph.selected_nodes().is_empty());
+        // Mouse clicks still reach the graph
+        harness.get_by_label_contains(": first").hover();
+        harness.run_steps(1);
+        harness.get_by_label_contains(": first").click();
+        harness.run_steps(2);
+        let a_idx = harness.state().node_idxs[1];
+        assert_eq!(harness.state().graph.selected_nodes(), [a_idx]);
+    }
+
+    /// Selects the node with the label and waits for the diff, which is
+    /// computed in the background
+    fn select_and_load_diff(harness: &mut Harness<ExplorerApp>, label: &str) {
+        harness.get_by_label_contains(label).hover();
+        harness.run_steps(1);
+        harness.get_by_label_contains(label).click();
+        harness.run_steps(3);
+        for _ in 0..100 {
+            if harness.query_by_label("Loading changes…").is_none() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+            harness.run_steps(1);
+        }
+    }
+
+    #[test]
+    fn summarizes_diff_of_selected_commit() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("a.txt", "1\n2\n")]);
+        let b = repo.commit_with_files("b", &[&a], &[("a.txt", "1\nx\n3\n")]);
+        let mut harness = harness(&repo, &b.hex(), "");
+        select_and_load_diff(&mut harness, ": b");
+        harness.get_by_label("1 file changed, 2 insertions, 1 deletion");
+        harness.get_by_label("a.txt");
+        harness.get_by_label("+2");
+        harness.get_by_label("-1");
+        harness.get_by_label("@@ -1,2 +1,3 @@");
+        harness.get_by_label(" 1\n-2\n+x\n+3");
+    }
+
+    #[test]
+    fn previews_conflicts() {
+        let mut repo = TestRepo::init();
+        let base = repo.commit_with_files("base", &[], &[("a.txt", "1\n")]);
+        let left = repo.commit_with_files("left", &[&base], &[("a.txt", "2\n")]);
+        let right = repo.commit_with_files("right", &[&base], &[("a.txt", "3\n")]);
+        let merge = repo.merge("merge", &left, &base, &right);
+        let mut harness = harness(&repo, &merge.hex(), "");
+        select_and_load_diff(&mut harness, ": merge");
+        harness.get_by_label("1 conflicted file");
+        harness.get_by_label("Choose a file to preview its conflict markers.");
+        // The conflict list comes before the diff
+        harness.query_all_by_label("a.txt").next().unwrap().click();
+        harness.run_steps(2);
+        harness.get_by_label_contains("<<<<<<< Conflict 1 of 1");
+    }
+
+    #[test]
+    fn offers_diff_tool_for_large_diffs() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("a.txt", "1\n2\n")]);
+        let mut settings = settings::Settings::default();
+        settings.diff.max_lines = Some(1);
+        let mut harness = harness_with_settings(&repo, &a.hex(), "", settings);
+        select_and_load_diff(&mut harness, ": a");
+        harness.get_by_label("The diff has 2 lines, which is too many to show here.");
+        harness.get_by_label("+2");
+        assert!(harness.query_by_label("@@ -0,0 +1,2 @@").is_none());
+        assert_eq!(harness.query_all_by_label("Show diff (jj diff)").count(), 1);
+    }
+
+    #[test]
+    fn sizes_nodes_by_changed_files() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_with_files("a", &[], &[("a.txt", "1\n")]);
+        let files: Vec<_> = (0..10).map(|i| (format!("{i}.txt"), "1\n")).collect();
+        let files: Vec<_> = (files.iter())
+            .map(|(path, text)| (path.as_str(), *text))
+            .collect();
+        let b = repo.commit_with_files("b", &[&a], &files);
+        // Unchanged, so it keeps the default size
+        let c = repo.commit_with_files("c", &[&b], &[]);
+        let mut settings = settings::Settings::default();
+        settings.ui.node_size = NodeSize::Files;
+        let mut harness = harness_with_settings(&repo, &format!("{a}::{c}"), "", settings);
+        let radius = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().display().radius()
+        };
+        // Computed in the background
+        let sized = |harness: &Harness<ExplorerApp>| {
+            radius(harness, &b) > radius(harness, &a) && radius(harness, &a) > radius(harness, &c)
+        };
+        for _ in 0..100 {
+            if sized(&harness) {
+                break;
             }
+            std::thread::sleep(Duration::from_millis(10));
+            harness.run_steps(1);
+        }
+        assert!(sized(&harness));
 
-            let graph_navigation = egui_graphs::SettingsNavigation::default()
-                // Disable fit to screen by default, as it hinders zoom and pan
-                .with_fit_to_screen_enabled(view_changed)
-                .with_zoom_and_pan_enabled(true);
-            let graph_interaction = egui_graphs::SettingsInteraction::default()
-                .with_dragging_enabled(false)
-                .with_edge_clicking_enabled(false)
-                .with_edge_selection_enabled(false)
-                .with_hover_enabled(true)
-                .with_node_clicking_enabled(false)
-                .with_node_selection_enabled(false);
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Node size ⏵").click();
+        harness.run_steps(1);
+        harness.get_by_label("Uniform").click();
+        harness.run_steps(1);
+        assert_eq!(radius(&harness, &a), radius(&harness, &c));
+        assert_eq!(radius(&harness, &b), radius(&harness, &c));
+    }
 
-            let mut graph_view = egui_graphs::GraphView::<
-                _,
-                _,
-                _,
-                _,
-                NodeShape,
-                _,
-                egui_graphs::LayoutStateHierarchical,
-                egui_graphs::LayoutHierarchical,
-            >::new(&mut self.graph)
-            .with_navigations(&graph_navigation)
-            .with_interactions(&graph_interaction)
-            .with_styles(&egui_graphs::SettingsStyle::default().with_labels_always(true));
-            let mut s =
-                egui_graphs::get_layout_state::<egui_graphs::LayoutStateHierarchical>(ui, None);
-            s.center_parent = true;
-            egui_graphs::set_layout_state(ui, s, None);
-            ui.add(&mut graph_view);
+    #[test]
+    fn shows_about_window() {
+        let mut repo = TestRepo::init();
+        repo.commit("a", &[]);
+        let mut harness = harness(&repo, "all()", "");
+        harness.get_by_label("Help").click();
+        harness.run_steps(1);
+        harness.get_by_label("About Revset Explorer").click();
+        harness.run_steps(2);
+        harness.get_by_label(about::VERSION);
+        harness.get_by_label(about::JJ_LIB_VERSION);
+        harness.get_by_label("Check for updates");
+    }
+
+    #[test]
+    fn spreads_edges_of_megamerge() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&a]);
+        let d = repo.commit("d", &[&a]);
+        let merge = repo.commit("merge", &[&b, &c, &d]);
+        let mut harness = harness(&repo, &format!("{a}::{merge}"), "");
+        let edges = |harness: &Harness<ExplorerApp>| -> Vec<EdgeShape> {
+            let app = harness.state();
+            (app.graph.edges_iter())
+                .map(|(_, edge)| edge.display().clone())
+                .collect()
+        };
+        let mut start_fans: Vec<_> = (edges(&harness).iter())
+            .filter(|edge| edge.start_fan != 0.)
+            .map(|edge| edge.start_fan)
+            .collect();
+        start_fans.sort_by(f32::total_cmp);
+        // The merge and the root both have three edges
+        assert_eq!(start_fans, [-1., 1.]);
+        assert_eq!(
+            (edges(&harness).iter())
+                .filter(|edge| edge.end_fan != 0.)
+                .count(),
+            2
+        );
+        assert!((edges(&harness).iter()).all(
+            |edge| edge.routing == EdgeRouting::Curved && edge.arrowheads == Arrowheads::Normal
+        ));
+
+        assert!(
+            (edges(&harness).iter()).all(|edge| edge.arrow_direction == ArrowDirection::Parents)
+        );
+
+        for (submenu, item) in [
+            ("Edges ⏵", "Straight"),
+            ("Edges ⏵", "None"),
+            ("Edges ⏵", "Children"),
+        ] {
+            harness.get_by_label("View").click();
+            harness.run_steps(1);
+            harness.get_by_label(submenu).click();
+            harness.run_steps(1);
+            harness.get_by_label(item).click();
+            harness.run_steps(1);
+        }
+        assert!((edges(&harness).iter()).all(
+            |edge| edge.routing == EdgeRouting::Straight && edge.arrowheads == Arrowheads::None
+        ));
+        assert!(
+            (edges(&harness).iter()).all(|edge| edge.arrow_direction == ArrowDirection::Children)
+        );
+    }
+
+    #[test]
+    fn selects_commits_with_trailer() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a for #12\n\nSigned-off-by: A <a@example.com>", &[]);
+        let b = repo.commit("b\n\nSigned-off-by: A <a@example.com>", &[&a]);
+        let mut harness = harness(&repo, &format!("{a}::{b}"), "");
+        select_and_load_diff(&mut harness, ": b");
+        harness.get_by_label("Signed-off-by");
+        assert!(harness.query_by_label("Issues").is_none());
+        harness.get_by_label("A <a@example.com>").click();
+        harness.run_steps(1);
+        assert_eq!(
+            harness.state().filter_revset.value,
+            r#"description(substring:"Signed-off-by: A <a@example.com>")"#
+        );
+        assert_eq!(
+            harness.state().last_filter_node_count,
+            Some(NodeCount::Exact(2))
+        );
+    }
+
+    #[test]
+    fn exports_html_with_legend() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut settings = settings::Settings::default();
+        settings.color_rules = toml::from_str::<settings::Settings>(
+            r##"
+            [[color_rules]]
+            revset = "description(a)"
+            color = "#ff4136"
+            "##,
+        )
+        .unwrap()
+        .color_rules;
+        let harness = harness_with_settings(&repo, &format!("::{a}"), &a.hex(), settings);
+        let path = repo.path().join("graph.html");
+        (harness.state())
+            .export(ExportFormat::Html, &path, true, false)
+            .unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(html.contains(&format!("<dt>View</dt><dd><code>::{a}</code></dd>")));
+        assert!(html.contains(&format!("<dt>Select</dt><dd><code>{a}</code></dd>")));
+        assert!(html.contains("</span>Working copy, in Select</li>"));
+        assert!(html.contains("#ff4136\"></span>Color rule: description(a)</li>"));
+
+        (harness.state())
+            .export(ExportFormat::Html, &path, false, false)
+            .unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert!(!html.contains("<div id=\"legend\">"));
+        assert_eq!(html.matches("<circle ").count(), 2);
+
+        // Without the root commit
+        (harness.state())
+            .export(ExportFormat::Html, &path, false, true)
+            .unwrap();
+        let html = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(html.matches("<circle ").count(), 1);
+    }
+
+    #[test]
+    fn exports_svg() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let harness = harness(&repo, &format!("::{a}"), &a.hex());
+        let path = repo.path().join("graph.svg");
+        (harness.state())
+            .export(ExportFormat::Svg, &path, false, true)
+            .unwrap();
+        let svg = std::fs::read_to_string(&path).unwrap();
+        // Without the root commit
+        assert_eq!(svg.matches("<circle ").count(), 1);
+        assert!(svg.contains(&format!("View: ::{a}")));
+    }
+
+    #[test]
+    fn exports_dot() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("Add \"b\"\n\nDetails", &[&a]);
+        let harness = harness(&repo, &format!("::{b}"), &b.hex());
+        let path = repo.path().join("graph.dot");
+        (harness.state())
+            .export(ExportFormat::Dot, &path, false, false)
+            .unwrap();
+        let dot = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(dot.matches(" -> ").count(), 2);
+        assert!(dot.contains(r#"\nAdd \"b\"", tooltip="#));
+        assert!(dot.contains("fillcolor=\"#fffc00\""));
+    }
+
+    #[test]
+    fn exports_and_imports_layout() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&a]);
+        let view = format!("{a}::");
+        let harness = harness(&repo, &view, "");
+        let path = repo.path().join("layout.json");
+        (harness.state())
+            .export(ExportFormat::Layout, &path, false, false)
+            .unwrap();
+        let mut layout = layout_file::LayoutFile::load(&path).unwrap();
+        assert_eq!(layout.positions.len(), 3);
+        let laid_out = layout.position(&c).unwrap();
+        layout.insert(&b, egui::pos2(-500., 200.));
+        layout.positions.remove(&a.hex());
+        std::fs::write(&path, layout.to_json()).unwrap();
+        drop(harness);
+
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(view),
+            layout: Some(path),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        let location = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().location()
+        };
+        assert_eq!(location(&harness, &b), egui::pos2(-500., 200.));
+        assert_eq!(location(&harness, &c), laid_out);
+        // Laid out as usual
+        assert_ne!(location(&harness, &a), egui::Pos2::ZERO);
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(1);
+        assert_eq!(location(&harness, &b), egui::pos2(-500., 200.));
+    }
+
+    #[test]
+    fn switches_to_operation_log() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &format!("{a}"), "");
+        harness.get_by_label("Operations").click();
+        harness.run_steps(2);
+        harness.get_by_label("Operation graph");
+        harness.get_by_label_contains("3 operations");
+        assert!(harness.query_by_label("Commit graph").is_none());
+
+        repo.commit("b", &[&a]);
+        harness.state_mut().reload_repo();
+        harness.run_steps(2);
+        harness.get_by_label_contains("4 operations");
+        harness.get_by_label("Commits").click();
+        harness.run_steps(2);
+        harness.get_by_label("Commit graph");
+    }
+
+    #[test]
+    fn replaces_symbol_in_revsets() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        // Renamed from main
+        repo.set_bookmark("trunk", &b);
+        repo.set_bookmark("main", &b);
+        let mut settings = settings::Settings::default();
+        settings.color_rules = toml::from_str::<settings::Settings>(
+            r##"
+            [[color_rules]]
+            revset = "main"
+            bold = true
+            "##,
+        )
+        .unwrap()
+        .color_rules;
+        let mut harness = harness_with_settings(&repo, "::main", "main", settings);
+        harness.get_by_label("Edit").click();
+        harness.run_steps(1);
+        harness.get_by_label("Replace symbol in revsets…").click();
+        harness.run_steps(2);
+        harness.get_by_label("Find:").focus();
+        harness.run_steps(1);
+        harness.get_by_label("Find:").type_text("main");
+        harness.run_steps(2);
+        harness.get_by_label("2 revsets of the session and the color rules to change:");
+        harness.get_by_label("::main  →  ::");
+
+        let config_path = repo.path().join("config.toml");
+        let recording = repo.path().join("session.jsonl");
+        std::fs::write(
+            &recording,
+            "{\"elapsed_ms\": 0, \"view\": \"main-old | ::main\", \"select\": \"\"}\n",
+        )
+        .unwrap();
+        let count = (harness.state_mut())
+            .replace_symbol("main", "trunk", &config_path, Some(&recording))
+            .unwrap();
+        assert_eq!(count, 3);
+        harness.run_steps(2);
+        let app = harness.state();
+        assert_eq!(app.view_revset.value, "::trunk");
+        assert_eq!(app.filter_revset.value, "trunk");
+        assert!(
+            app.view_revset
+                .history
+                .items()
+                .iter()
+                .all(|item| !item.contains("main"))
+        );
+        assert_eq!(app.color_rules[0].revset, "trunk");
+        assert_eq!(app.filter_revset.error, None);
+        let config: settings::Settings =
+            toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config.color_rules, app.color_rules);
+        assert!(
+            std::fs::read_to_string(&recording)
+                .unwrap()
+                .contains("main-old | ::trunk")
+        );
+    }
+
+    #[test]
+    fn exports_and_imports_settings() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut settings = settings::Settings::default();
+        settings.ui.palette = Palette::ColorBlind;
+        settings.navigation.ctrl_scroll = navigation::ScrollAction::Pan;
+        settings.color_rules = toml::from_str::<settings::Settings>(
+            r##"
+            [[color_rules]]
+            revset = "all()"
+            border = "#ff4136"
+            "##,
+        )
+        .unwrap()
+        .color_rules;
+        let mut exporting = harness_with_settings(&repo, &a.hex(), "", settings);
+        // Changed from the View menu
+        exporting.state_mut().edge_routing = EdgeRouting::Straight;
+        let bundle_path = repo.path().join("bundle.toml");
+        let bundle = exporting.state().settings_bundle().to_toml();
+        assert!(bundle.contains(r#"palette = "color-blind""#));
+        assert!(bundle.contains(r#"edge_routing = "straight""#));
+        assert!(bundle.contains(r##"border = "#ff4136""##));
+        std::fs::write(&bundle_path, bundle).unwrap();
+        drop(exporting);
+
+        let config_path = repo.path().join("config/config.toml");
+        std::fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &config_path,
+            "[forge]\ntoken = \"secret\"\n\n[ui]\npalette = \"high-contrast\"\n",
+        )
+        .unwrap();
+        let mut harness = harness(&repo, &a.hex(), "");
+        (harness.state_mut())
+            .import_settings(&bundle_path, &config_path)
+            .unwrap();
+        harness.run_steps(1);
+        let app = harness.state();
+        assert_eq!(app.palette, Palette::ColorBlind);
+        assert_eq!(app.edge_routing, EdgeRouting::Straight);
+        assert_eq!(app.navigation.ctrl_scroll, navigation::ScrollAction::Pan);
+        let node = app.graph.node(app.node_idxs[0]).unwrap();
+        assert_eq!(
+            node.display().border,
+            Some(egui::Color32::from_rgb(0xff, 0x41, 0x36))
+        );
+        // The token stays, the rest is replaced
+        let config: settings::Settings =
+            toml::from_str(&std::fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config.forge.token.as_deref(), Some("secret"));
+        assert_eq!(config.ui.palette, Palette::ColorBlind);
+        assert_eq!(config.color_rules.len(), 1);
+
+        std::fs::write(&bundle_path, "[forge]\ntoken = \"leaked\"\n").unwrap();
+        assert!(
+            (harness.state_mut())
+                .import_settings(&bundle_path, &config_path)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn records_and_replays_session() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let path = repo.path().join("session.jsonl");
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(format!("{a}::{b}")),
+            record_session: Some(path.clone()),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        harness.state_mut().requested_filter = Some(b.hex());
+        harness.run_steps(1);
+        drop(harness);
+
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            replay_session: Some(path),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        harness.get_by_label("Replay of 2 steps");
+        harness.get_by_label("Next step").click();
+        // The label shows the new step in the next frame
+        harness.run_steps(2);
+        assert_eq!(harness.state().view_revset.value, format!("{a}::{b}"));
+        assert_eq!(harness.state().filter_revset.value, "");
+        harness.get_by_label("Next step").click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().filter_revset.value, b.hex());
+        assert_eq!(
+            harness.state().last_filter_node_count,
+            Some(NodeCount::Exact(1))
+        );
+        harness.get_by_label_contains("Step 2 of 2");
+    }
+
+    #[test]
+    fn shows_annotations_from_file() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let path = repo.path().join("bisect.toml");
+        let content =
+            format!("[{a}]\nstatus = \"good\"\n[{b}]\nstatus = \"bad\"\nlabel = \"first bad\"\n");
+        std::fs::write(&path, content).unwrap();
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(format!("{a}::{b}")),
+            annotations: Some(path.clone()),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        let badge = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph
+                .node(*node_idx)
+                .unwrap()
+                .display()
+                .annotation_badge
+        };
+        assert_eq!(
+            badge(&harness, &a),
+            Some(annotation_color(AnnotationStatus::Good))
+        );
+        assert_eq!(
+            badge(&harness, &b),
+            Some(annotation_color(AnnotationStatus::Bad))
+        );
+        select_and_load_diff(&mut harness, ": b");
+        harness.get_by_label("first bad (bad)");
+
+        // Read again on reload
+        std::fs::write(&path, "[no-such-id]\n").unwrap();
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(1);
+        assert_eq!(badge(&harness, &b), None);
+        assert_eq!(
+            harness.state().status_message.as_deref(),
+            Some("1 annotated ID matches no commit: no-such-id")
+        );
+    }
+
+    #[test]
+    fn labels_generation_distances() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        repo.edit(&b);
+        let mut harness = harness(&repo, &format!("{a}::{c}"), "");
+        let distance = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph
+                .node(*node_idx)
+                .unwrap()
+                .display()
+                .distance_label
+                .clone()
+        };
+        assert_eq!(distance(&harness, &b), None);
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Generation distances").click();
+        harness.run_steps(1);
+        assert_eq!(distance(&harness, &a).as_deref(), Some("-1"));
+        assert_eq!(distance(&harness, &b).as_deref(), Some("0"));
+        assert_eq!(distance(&harness, &c).as_deref(), Some("+1"));
+
+        let node = harness.get_by_label_contains(": a");
+        node.focus();
+        harness.run_steps(1);
+        harness.key_press_modifiers(egui::Modifiers::SHIFT, egui::Key::F10);
+        harness.run_steps(2);
+        harness.get_by_label("Measure distances from here").click();
+        harness.run_steps(1);
+        assert_eq!(distance(&harness, &a).as_deref(), Some("0"));
+        assert_eq!(distance(&harness, &c).as_deref(), Some("+2"));
+    }
+
+    #[test]
+    fn shortens_descriptions_in_labels() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("feat(parser): accept trailing commas", &[]);
+        let mut settings = settings::Settings::default();
+        settings.ui.label_description_length = 20;
+        let mut harness = harness_with_settings(&repo, &a.hex(), "", settings);
+        let app = harness.state();
+        let node = app.graph.node(app.node_idxs[0]).unwrap();
+        assert_eq!(
+            node.display().description.as_deref(),
+            Some("feat(parser): accept…")
+        );
+        let pos = harness.get_by_label_contains(": feat").rect().center();
+        harness.hover_at(pos);
+        harness.run_steps(4);
+        harness.get_by_label("feat(parser): accept trailing commas");
+    }
+
+    #[test]
+    fn labels_nodes_with_commit_ids() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "");
+        let label = |harness: &Harness<ExplorerApp>| {
+            let app = harness.state();
+            let node = app.graph.node(app.node_idxs[0]).unwrap();
+            node.display().commit_id_label.clone()
+        };
+        assert_eq!(label(&harness), None);
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Commit IDs").click();
+        harness.run_steps(1);
+        let (shown, unique_len) = label(&harness).unwrap();
+        assert_eq!(shown, a.hex()[..8]);
+        // Also unique against the root commit
+        assert!((1..=8).contains(&unique_len));
+        let root = harness
+            .state()
+            .jj_graph
+            .get_repo()
+            .store()
+            .root_commit_id()
+            .hex();
+        assert_ne!(root[..unique_len], a.hex()[..unique_len]);
+    }
+
+    #[test]
+    fn pins_commits_to_view() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let mut pinning = harness(&repo, &format!("{a}::"), "");
+        let pin_menu = |harness: &mut Harness<ExplorerApp>, item: &str| {
+            harness.get_by_label_contains(": a").focus();
+            harness.run_steps(1);
+            harness.key_press_modifiers(egui::Modifiers::SHIFT, egui::Key::F10);
+            harness.run_steps(2);
+            harness.get_by_label(item).click();
+            harness.run_steps(2);
+        };
+        pin_menu(&mut pinning, "Pin to the view");
+        let shown = |harness: &Harness<ExplorerApp>| -> Vec<(CommitId, bool)> {
+            let app = harness.state();
+            let mut shown: Vec<_> = (app.node_idxs.iter())
+                .map(|idx| app.graph.node(*idx).unwrap())
+                .map(|node| (node.payload().clone(), node.display().pinned))
+                .collect();
+            shown.sort();
+            shown
+        };
+        pinning.state_mut().view_revset.value = c.hex();
+        pinning.state_mut().view_invalidated = true;
+        pinning.run_steps(2);
+        let mut expected = vec![(a.clone(), true), (c.clone(), false)];
+        expected.sort();
+        assert_eq!(shown(&pinning), expected);
+        drop(pinning);
+
+        // Stored in the repository
+        let mut harness = harness(&repo, &c.hex(), "");
+        assert_eq!(shown(&harness), expected);
+        pin_menu(&mut harness, "Unpin from the view");
+        assert_eq!(shown(&harness), [(c, false)]);
+    }
+
+    #[test]
+    fn fixes_revset_lints() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "@..@");
+        harness
+            .get_by_label("@..@ is always empty, as a range leaves out the ancestors of its start");
+        harness.get_by_label("Fix").click();
+        harness.run_steps(2);
+        let app = harness.state();
+        assert_eq!(app.filter_revset.value, "::@");
+        assert_eq!(app.filter_revset.history.get(), Some("::@"));
+        assert!(harness.query_by_label("Fix").is_none());
+    }
+
+    #[test]
+    fn exports_performance_report() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let path = repo.path().join("report.json");
+        let mut harness = harness(&repo, &a.hex(), "all()");
+        harness.get_by_label("File").click();
+        harness.run_steps(1);
+        harness.get_by_label("Export performance report…").click();
+        harness.run_steps(2);
+        *harness.state_mut().perf_report_dialog.as_mut().unwrap() = path.display().to_string();
+        harness.get_by_label("Export").click_accesskit();
+        harness.run_steps(2);
+        let report = std::fs::read_to_string(&path).unwrap();
+        let report: serde_json::Value = serde_json::from_str(&report).unwrap();
+        let evaluations = report["evaluations"].as_array().unwrap();
+        let view = (evaluations.iter())
+            .find(|evaluation| evaluation["kind"] == "view")
+            .unwrap();
+        assert_eq!(view["revset"], a.hex());
+        assert_eq!(view["graph"]["nodes"], 1);
+        assert!(evaluations.iter().any(|e| e["revset"] == "all()"));
+        assert_eq!(report["layouts"][0]["graph"]["nodes"], 1);
+    }
+
+    #[test]
+    fn overlays_saved_result_set() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let path = repo.path().join("result.json");
+        let mut harness = harness(&repo, &format!("{a} | {b}"), "");
+        let open_dialog = |harness: &mut Harness<ExplorerApp>, item| {
+            harness.get_by_label("File").click();
+            harness.run_steps(1);
+            harness.get_by_label(item).click();
+            harness.run_steps(2);
+            let dialog = harness.state_mut().result_set_dialog.as_mut().unwrap();
+            dialog.path = path.display().to_string();
+        };
+        open_dialog(&mut harness, "Save result set…");
+        harness.get_by_label("Save").click_accesskit();
+        harness.run_steps(2);
+        assert!(path.exists());
+
+        harness.state_mut().view_revset.value = format!("{b} | {c}");
+        harness.state_mut().view_invalidated = true;
+        harness.run_steps(2);
+        open_dialog(&mut harness, "Load result set…");
+        harness.get_by_label("Load").click_accesskit();
+        harness.run_steps(2);
+        harness.get_by_label("Since the saved result: 1 joined, 1 left");
+        let rings = |harness: &Harness<ExplorerApp>| {
+            let app = harness.state();
+            (app.node_idxs.iter())
+                .map(|idx| app.graph.node(*idx).unwrap())
+                .map(|node| (node.payload().clone(), node.display().ring))
+                .collect::<HashMap<_, _>>()
+        };
+        // a has left the result, so it is shown again
+        let green = ecolor::Color32::from_rgb(0x2e, 0xcc, 0x40);
+        let red = ecolor::Color32::from_rgb(0xff, 0x41, 0x36);
+        assert_eq!(
+            rings(&harness),
+            HashMap::from([(a, Some(red)), (b.clone(), None), (c.clone(), Some(green))])
+        );
+
+        harness.get_by_label("Clear").click();
+        harness.run_steps(2);
+        assert_eq!(rings(&harness), HashMap::from([(b, None), (c, None)]));
+    }
+
+    #[test]
+    fn reloads_when_watching() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, "all()", "");
+        let node_count = |harness: &Harness<ExplorerApp>| harness.state().node_idxs.len();
+        assert_eq!(node_count(&harness), 3);
+        repo.commit("b", &[&a]);
+        harness.run_steps(2);
+        // Paused by default
+        assert_eq!(node_count(&harness), 3);
+        harness.state_mut().repo_watch.watch = repo_watch::Watch::Instant;
+        harness.run_steps(2);
+        assert_eq!(node_count(&harness), 4);
+    }
+
+    #[test]
+    fn reloads_with_button_and_shortcut() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, "all()", "");
+        let node_count = |harness: &Harness<ExplorerApp>| harness.state().node_idxs.len();
+        assert_eq!(node_count(&harness), 3);
+        let b = repo.commit("b", &[&a]);
+        harness.key_press(egui::Key::F5);
+        harness.run_steps(2);
+        assert_eq!(node_count(&harness), 4);
+        repo.commit("c", &[&b]);
+        harness.get_by_label("Reload").click();
+        harness.run_steps(2);
+        assert_eq!(node_count(&harness), 5);
+    }
+
+    #[test]
+    fn shows_earlier_operations() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, "all()", "");
+        let before = harness.state().jj_graph.get_repo().op_id().clone();
+        repo.commit("b", &[&a]);
+        harness.get_by_label("Reload").click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().node_idxs.len(), 4);
+
+        harness.state_mut().show_operation(&before);
+        harness.run_steps(2);
+        assert_eq!(harness.state().node_idxs.len(), 3);
+        assert_eq!(harness.state().past_operation, Some(before.clone()));
+        harness.get_by_label_contains(&before.hex()[..12]);
+
+        harness.get_by_label("Back to the latest operation").click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().node_idxs.len(), 4);
+        assert_eq!(harness.state().past_operation, None);
+    }
+
+    #[test]
+    fn notifies_about_select_result_changes() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, "all()", "heads(all())");
+        harness.state_mut().repo_watch.watch = repo_watch::Watch::Instant;
+        harness.state_mut().ui_settings.watch_notify = true;
+        let b = repo.commit("b", &[&a]);
+        harness.run_steps(2);
+        let change = harness.state().result_change.clone().unwrap();
+        assert_eq!(change.joined, [b]);
+        assert!(!change.left.is_empty());
+        harness.get_by_label("The Select result changed");
+    }
+
+    #[test]
+    fn prioritizes_commits_at_node_limit() {
+        let mut repo = TestRepo::init();
+        let first = repo.commit("first", &[]);
+        let mut last = first.clone();
+        for i in 0..MAX_NODES {
+            last = repo.commit(&i.to_string(), &[&last]);
+        }
+        repo.edit(&first);
+        let root = repo.jj_graph().get_repo().store().root_commit_id().clone();
+        let mut harness = harness(&repo, "all()", &first.hex());
+        let shown = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter()).any(|idx| app.graph.node(*idx).unwrap().payload() == commit_id)
+        };
+        // The working copy, trunk() and the head, in place of the oldest ones
+        assert!(shown(&harness, &first));
+        assert!(shown(&harness, &root));
+        assert!(shown(&harness, &last));
+        assert!(harness.state().truncated.is_empty());
+        assert_eq!(harness.state().node_idxs.len(), MAX_NODES);
+
+        // Too many Select matches to show all of them
+        harness.state_mut().filter_revset.value = "all()".to_owned();
+        harness.state_mut().view_invalidated = true;
+        harness.run_steps(2);
+        assert_eq!(
+            harness.state().truncated,
+            ["2 Select matches not shown due to the node limit."]
+        );
+    }
+
+    #[test]
+    fn evaluates_in_background() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let options = AppOptions {
+            initial_view: Some(format!("{a} | {b}")),
+            initial_select: Some(b.hex()),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        let started = Instant::now();
+        while (harness.state().node_idxs.len() < 2
+            || harness.state().view_evaluation.is_pending()
+            || harness.state().select_evaluation.is_pending())
+            && started.elapsed().as_secs() < 10
+        {
+            std::thread::sleep(Duration::from_millis(10));
+            harness.run_steps(1);
+        }
+        let app = harness.state();
+        assert_eq!(app.node_idxs.len(), 2);
+        assert_eq!(app.last_filter_node_count, Some(NodeCount::Exact(1)));
+    }
+
+    #[test]
+    fn loads_more_nodes() {
+        let mut repo = TestRepo::init();
+        let mut last = repo.commit("0", &[]);
+        for i in 1..5 {
+            last = repo.commit(&i.to_string(), &[&last]);
+        }
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(format!("::{last}")),
+            max_nodes: Some(2),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        assert_eq!(harness.state().node_idxs.len(), 2);
+        let positions = |harness: &Harness<ExplorerApp>| -> Vec<_> {
+            let app = harness.state();
+            (app.node_idxs.iter())
+                .map(|node_idx| app.graph.node(*node_idx).unwrap().location())
+                .collect()
+        };
+        let shown = positions(&harness);
+        harness.get_by_label("Load 100 more").click();
+        harness.run_steps(2);
+        // With the root commit
+        assert_eq!(harness.state().node_idxs.len(), 6);
+        assert!(!harness.state().node_limit_hit);
+        assert!(harness.query_by_label("Load 100 more").is_none());
+        // The shown nodes stay in place
+        assert_eq!(positions(&harness)[..2], shown);
+    }
+
+    #[test]
+    fn loads_more_after_reload() {
+        let mut repo = TestRepo::init();
+        let mut last = repo.commit("0", &[]);
+        for i in 1..5 {
+            last = repo.commit(&i.to_string(), &[&last]);
+        }
+        // Not waiting, so that the reloaded view is still being evaluated
+        let options = AppOptions {
+            initial_view: Some("all()".to_owned()),
+            max_nodes: Some(2),
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        let settle = |harness: &mut Harness<ExplorerApp>| {
+            let started = Instant::now();
+            harness.run_steps(1);
+            while (harness.state().view_evaluation.is_pending()
+                || harness.state().select_evaluation.is_pending())
+                && started.elapsed().as_secs() < 10
+            {
+                std::thread::sleep(Duration::from_millis(10));
+                harness.run_steps(1);
+            }
+        };
+        settle(&mut harness);
+        assert!(harness.state().extendable.is_some());
+        let added = repo.commit("added", &[&last]);
+        // Abandons the working copy commit, which the graph shows
+        let abandoned = repo.jj_graph().working_copy_commit_id().unwrap();
+        assert!(harness.state().node_idxs.iter().any(|node_idx| {
+            harness.state().graph.node(*node_idx).unwrap().payload() == &abandoned
+        }));
+        repo.edit(&last);
+        harness.state_mut().reload_repo();
+        assert!(harness.state().extendable.is_none());
+        // The reloaded view is being evaluated, as in the frame after
+        // reloading, and Load more is clicked before it is polled for
+        let ctx = harness.ctx.clone();
+        let app = harness.state_mut();
+        app.repo_reloaded = false;
+        let request = view_evaluation::Request {
+            view: "all()".to_owned(),
+            select: String::new(),
+            node_limit: 2,
+            prefetched: HashMap::new(),
+            change_nodes: None,
+            extend: None,
+        };
+        app.view_evaluation.start(&ctx, &app.jj_graph, request);
+        harness.get_by_label("Load 100 more").click();
+        settle(&mut harness);
+        let app = harness.state();
+        // The graph of the reloaded repository, not more of the old one
+        let shown: Vec<_> = (app.node_idxs.iter())
+            .map(|node_idx| app.graph.node(*node_idx).unwrap().payload().clone())
+            .collect();
+        assert!(shown.contains(&added));
+        assert!(!shown.contains(&abandoned));
+        assert!(!app.node_limit_hit);
+    }
+
+    #[test]
+    fn rejects_zero_max_nodes() {
+        assert!(Args::try_parse_from(["revset-explorer", "--max-nodes", "0"]).is_err());
+        let args = Args::try_parse_from(["revset-explorer", "--max-nodes", "1"]).unwrap();
+        assert_eq!(args.max_nodes, Some(1));
+    }
+
+    #[test]
+    fn exports_dot_from_flag() {
+        let mut args =
+            Args::try_parse_from(["revset-explorer", "--export-dot", "graph.dot"]).unwrap();
+        assert!(matches!(
+            args.take_command(),
+            Some(Command::Export {
+                format: ExportFormat::Dot,
+                output: Some(output),
+                ..
+            }) if output == Path::new("graph.dot")
+        ));
+    }
+
+    #[test]
+    fn clusters_commits_by_bookmark() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&a]);
+        repo.set_bookmark("main", &b);
+        repo.set_bookmark("topic", &c);
+        let mut harness = harness(&repo, &format!("{a}::"), "");
+        assert!(harness.state().bookmark_clusters.is_empty());
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Cluster by bookmark").click();
+        harness.run_steps(1);
+        let clusters = &harness.state().bookmark_clusters;
+        assert_eq!(clusters[&b], "main");
+        assert_eq!(clusters[&c], "topic");
+        // Equally near, so the first bookmark
+        assert_eq!(clusters[&a], "main");
+
+        harness.state_mut().view_revset.value = format!("{a}::{c}");
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(1);
+        let clusters = &harness.state().bookmark_clusters;
+        assert_eq!(clusters[&a], "topic");
+        assert!(!clusters.contains_key(&b));
+    }
+
+    #[test]
+    fn groups_divergent_commits() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let b2 = repo.diverge(&b, "b2");
+        let mut harness = harness(&repo, &format!("{a}::"), "");
+        assert!(harness.state().change_groups.is_empty());
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Group divergent commits").click();
+        harness.run_steps(1);
+        let groups = &harness.state().change_groups;
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&b], groups[&b2]);
+
+        // Only the commits in the view
+        harness.state_mut().view_revset.value = format!("{a}::{b}");
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(1);
+        assert!(harness.state().change_groups.is_empty());
+    }
+
+    #[test]
+    fn previews_bookmark_move() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let side = repo.commit("side", &[&a]);
+        repo.set_bookmark("main", &b);
+        let options = AppOptions {
+            wait_for_evaluations: true,
+            initial_view: Some(format!("{a}::")),
+            allow_mutations: true,
+            ..Default::default()
+        };
+        let app = ExplorerApp::new(repo.path(), settings::Settings::default(), options).unwrap();
+        let mut harness = Harness::builder().build_eframe(|_cc| app);
+        harness.run_steps(2);
+        let ring = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter())
+                .map(|idx| app.graph.node(*idx).unwrap())
+                .find(|node| node.payload() == commit_id)
+                .unwrap()
+                .display()
+                .ring
+        };
+
+        // The bookmark, then the target
+        let b_idx = (harness.state().node_idxs.iter().copied())
+            .find(|idx| harness.state().graph.node(*idx).unwrap().payload() == &b)
+            .unwrap();
+        harness.state_mut().set_node_selected(b_idx, true);
+        harness.get_by_label_contains(": side").focus();
+        harness.run_steps(1);
+        harness.key_press_modifiers(egui::Modifiers::SHIFT, egui::Key::F10);
+        harness.run_steps(2);
+        harness.get_by_label("Preview moving main here").click();
+        harness.run_steps(2);
+        let preview = harness.state().bookmark_move.clone().unwrap();
+        assert_eq!(
+            (preview.reachable, preview.unreachable),
+            (vec![side.clone()], vec![b.clone()])
+        );
+        harness.get_by_label("1 commit becomes reachable");
+        harness.get_by_label("1 commit stops being reachable");
+        harness.get_by_label_contains("backwards or sideways");
+        assert!(ring(&harness, &side).is_some());
+        assert!(ring(&harness, &b).is_some());
+        assert_eq!(ring(&harness, &a), None);
+
+        harness.get_by_label("Move (jj bookmark move)").click();
+        harness.run_steps(2);
+        assert_eq!(
+            harness.state().pending_mutation,
+            Some(jj_cli::Mutation::MoveBookmark {
+                name: "main".to_owned(),
+                to: side.clone(),
+                allow_backwards: true,
+            })
+        );
+        assert!(harness.state().bookmark_move.is_none());
+        assert_eq!(ring(&harness, &side), None);
+    }
+
+    #[test]
+    fn previews_undo() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let working_copy = repo.jj_graph().working_copy_commit_id().unwrap();
+        // Abandons the previous working-copy commit
+        repo.edit(&a);
+        let mut harness = harness(&repo, "all()", "");
+        let shown = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter()).any(|idx| app.graph.node(*idx).unwrap().payload() == commit_id)
+        };
+        assert!(!shown(&harness, &working_copy));
+        harness.get_by_label("Preview jj undo").click();
+        harness.run_steps(2);
+        let preview = harness.state().undo_preview.as_ref().unwrap();
+        assert_eq!(preview.appearing, HashSet::from([working_copy.clone()]));
+        assert!(preview.disappearing.is_empty());
+        assert!(shown(&harness, &working_copy));
+        harness.get_by_label_contains("1 appear, 0 disappear");
+    }
+
+    #[test]
+    fn previews_rebase() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let destination = repo.commit("destination", &[&a]);
+        let mut harness = harness(&repo, &format!("{a}::"), "");
+        harness.get_by_label("Preview jj rebase").click();
+        harness.run_steps(2);
+        harness.get_by_label("Select the destination commit");
+        harness.state_mut().rebase_source.as_mut().unwrap().value = b.hex();
+
+        let node_idx = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter().copied())
+                .find(|idx| app.graph.node(*idx).unwrap().payload() == commit_id)
+                .unwrap()
+        };
+        let destination_idx = node_idx(&harness, &destination);
+        harness.state_mut().set_node_selected(destination_idx, true);
+        harness.run_steps(2);
+        let preview = harness.state().rebase_preview.clone().unwrap();
+        let rebased: Vec<_> = (preview.commits.iter())
+            .map(|commit| (commit.commit_id.clone(), commit.depth))
+            .collect();
+        assert_eq!(rebased, vec![(b.clone(), 1), (c, 2)]);
+        harness.get_by_label_contains("2 commits would move onto");
+
+        // Onto a commit that would be rebased itself
+        harness
+            .state_mut()
+            .set_node_selected(destination_idx, false);
+        let b_idx = node_idx(&harness, &b);
+        harness.state_mut().set_node_selected(b_idx, true);
+        harness.run_steps(2);
+        assert!(harness.state().rebase_preview.is_none());
+        assert!(
+            harness
+                .state()
+                .rebase_source
+                .as_ref()
+                .unwrap()
+                .error
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn compares_revsets() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let mut harness = harness(&repo, &format!("{a}::"), &format!("{a} | {b}"));
+        harness.get_by_label("Compare").click();
+        harness.run_steps(2);
+        // Starts out as the Select revset
+        harness.get_by_label_contains("Both: 2");
+        harness.state_mut().compare_revset.as_mut().unwrap().value = format!("{b} | {c}");
+        harness.state_mut().recolor = true;
+        harness.run_steps(2);
+        harness.get_by_label_contains("Only Select: 1");
+        harness.get_by_label_contains("Only Compared: 1");
+        harness.get_by_label_contains("Both: 1");
+        let palette = harness.state().palette;
+        let color = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter())
+                .map(|idx| app.graph.node(*idx).unwrap())
+                .find(|node| node.payload() == commit_id)
+                .and_then(|node| node.color())
+                .unwrap()
+        };
+        for (commit_id, membership) in [
+            (&a, Membership::OnlyA),
+            (&b, Membership::Both),
+            (&c, Membership::OnlyB),
+        ] {
+            let [red, green, blue] = palette.membership_color(membership);
+            assert_eq!(
+                color(&harness, commit_id),
+                ecolor::Color32::from_rgb(red, green, blue)
+            );
+        }
+
+        harness.state_mut().compare_revset.as_mut().unwrap().value = "(".to_owned();
+        harness.state_mut().recolor = true;
+        harness.run_steps(2);
+        assert!(
+            harness
+                .state()
+                .compare_revset
+                .as_ref()
+                .unwrap()
+                .error
+                .is_some()
+        );
+        assert!(harness.state().memberships.is_empty());
+        // Colored by the Select alone again
+        let [red, green, blue] = palette.membership_color(Membership::OnlyB);
+        assert_ne!(
+            color(&harness, &c),
+            ecolor::Color32::from_rgb(red, green, blue)
+        );
+
+        harness.get_by_label("Compare").click();
+        harness.run_steps(2);
+        assert!(harness.state().compare_revset.is_none());
+        assert!(harness.state().memberships.is_empty());
+    }
+
+    #[test]
+    fn lists_select_results_in_order() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let mut harness = harness(&repo, &format!("{a}::"), &format!("latest({a} | {b})"));
+        let order_label = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter())
+                .map(|idx| app.graph.node(*idx).unwrap())
+                .find(|node| node.payload() == commit_id)
+                .unwrap()
+                .display()
+                .order_label
+                .clone()
+        };
+        assert_eq!(order_label(&harness, &a), None);
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Number Select results").click();
+        harness.run_steps(1);
+        assert_eq!(order_label(&harness, &b).as_deref(), Some("#1"));
+        assert_eq!(order_label(&harness, &a), None);
+
+        harness.state_mut().requested_filter = Some(format!("{a} | {b}"));
+        harness.run_steps(1);
+        assert_eq!(order_label(&harness, &b).as_deref(), Some("#1"));
+        assert_eq!(order_label(&harness, &a).as_deref(), Some("#2"));
+
+        harness.state_mut().requested_filter = Some(format!("latest({a} | {b})"));
+        harness.get_by_label("Results").click();
+        harness.run_steps(2);
+        harness.get_by_label_contains("depends on the order in latest()");
+        let label = harness.state().node_label(&b);
+        harness.get_by_label(&label).click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().graph_tab, GraphTab::Commits);
+        let selected = harness.state().graph.selected_nodes().to_vec();
+        assert_eq!(selected.len(), 1);
+        let selected = harness.state().graph.node(selected[0]).unwrap().payload();
+        assert_eq!(selected, &b);
+    }
+
+    #[test]
+    fn toggles_view_scopes() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let view = format!("::{a}");
+        let mut harness = harness(&repo, &view, "");
+        // With the root commit
+        assert_eq!(harness.state().node_idxs.len(), 2);
+        harness.get_by_label("Mutable").click();
+        harness.run_steps(2);
+        assert_eq!(
+            harness.state().view_revset.value,
+            format!("({view}) & mutable()")
+        );
+        assert_eq!(harness.state().node_idxs.len(), 1);
+        harness.get_by_label("Mutable").click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().view_revset.value, view);
+        assert_eq!(harness.state().node_idxs.len(), 2);
+
+        // Without needless parentheses
+        harness.state_mut().view_revset.value = a.hex();
+        harness.get_by_label("Mutable").click();
+        harness.run_steps(2);
+        assert_eq!(
+            harness.state().view_revset.value,
+            format!("{a} & mutable()")
+        );
+    }
+
+    #[test]
+    fn guides_out_of_empty_views() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let view = format!("ancestors({b}, 1) & description(a)");
+        let mut harness = harness(&repo, &view, "");
+        assert!(harness.state().node_idxs.is_empty());
+        harness.get_by_label("The View is empty");
+        let widened = format!("ancestors({b}, 10) & description(a)");
+        harness.get_by_label_contains(&widened).click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().view_revset.value, widened);
+        assert_eq!(harness.state().node_idxs.len(), 1);
+    }
+
+    #[test]
+    fn focuses_on_immutable_boundary() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let view = b.hex();
+        let mut harness = harness(&repo, &view, "");
+        assert!(!harness.state().boundary_focus());
+        harness.get_by_label("Boundary focus").click();
+        harness.run_steps(2);
+        assert!(harness.state().boundary_focus());
+        // With the working copy and the immutable root commit
+        assert_eq!(harness.state().node_idxs.len(), 4);
+        assert_eq!(harness.state().immutable_commits.len(), 1);
+        harness.get_by_label("Boundary focus").click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().view_revset.value, view);
+        assert_eq!(harness.state().node_idxs.len(), 1);
+    }
+
+    #[test]
+    fn highlights_lineage_and_hovered_edges() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let side = repo.commit("side", &[&a]);
+        let mut harness = harness(&repo, &format!("{a}::"), "");
+        let dimmed = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            (app.node_idxs.iter())
+                .map(|idx| app.graph.node(*idx).unwrap())
+                .find(|node| node.payload() == commit_id)
+                .unwrap()
+                .display()
+                .dimmed
+        };
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Highlight lineage on hover").click();
+        harness.run_steps(1);
+
+        let b_pos = harness.get_by_label_contains(": b").rect().center();
+        let c_pos = harness.get_by_label_contains(": c").rect().center();
+        harness.hover_at(b_pos);
+        harness.run_steps(2);
+        assert!(!dimmed(&harness, &a) && !dimmed(&harness, &c));
+        assert!(dimmed(&harness, &side));
+        let dimmed_edges = (harness.state().graph.edges_iter())
+            .filter(|(_, edge)| edge.display().dimmed)
+            .count();
+        // side -> a
+        assert_eq!(dimmed_edges, 1);
+
+        harness.hover_at(b_pos + (c_pos - b_pos) / 2.);
+        harness.run_steps(2);
+        assert!(!dimmed(&harness, &side));
+        let edge_idx = harness.state().hovered_edge.unwrap();
+        assert!(
+            harness
+                .state()
+                .graph
+                .edge(edge_idx)
+                .unwrap()
+                .display()
+                .hovered
+        );
+        // After the tooltip delay
+        harness.run_steps(4);
+        harness.get_by_label_contains("→ parent");
+    }
+
+    #[test]
+    fn dims_commits_outside_date_range() {
+        let mut repo = TestRepo::init();
+        let day = 24 * 60 * 60;
+        let a = repo.commit_at("a", &[], 1_700_000_000);
+        let b = repo.commit_at("b", &[&a], 1_700_000_000 + day);
+        let c = repo.commit_at("c", &[&b], 1_700_000_000 + 2 * day);
+        let mut harness = harness(&repo, &format!("{a}::{c}"), "");
+        let dimmed = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().display().dimmed
+        };
+        assert!(!dimmed(&harness, &a));
+        // The slider, followed by its text
+        harness.query_all_by_label("from").next().unwrap().focus();
+        harness.run_steps(1);
+        harness.key_press(egui::Key::ArrowRight);
+        harness.run_steps(1);
+        assert!(dimmed(&harness, &a));
+        assert!(!dimmed(&harness, &b) && !dimmed(&harness, &c));
+
+        harness.get_by_label("Add dates to Select").click();
+        harness.run_steps(1);
+        let app = harness.state();
+        assert!(app.filter_revset.value.starts_with("committer_date(after:"));
+        assert_eq!(app.filter_revset.error, None);
+        assert_eq!(app.last_filter_node_count, Some(NodeCount::Exact(2)));
+    }
+
+    #[test]
+    fn outlines_heads_and_roots() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let d = repo.commit("d", &[&b]);
+        let mut harness = harness(&repo, &format!("{a}::"), "");
+        let outlines = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph
+                .node(*node_idx)
+                .unwrap()
+                .display()
+                .outlines
+                .clone()
+        };
+        harness.get_by_label("Heads").click();
+        harness.run_steps(1);
+        assert_eq!(outlines(&harness, &c), [HEADS_OUTLINE_COLOR]);
+        assert_eq!(outlines(&harness, &d), [HEADS_OUTLINE_COLOR]);
+        assert!(outlines(&harness, &a).is_empty() && outlines(&harness, &b).is_empty());
+
+        harness.get_by_label("Roots").click();
+        harness.run_steps(1);
+        assert_eq!(outlines(&harness, &a), [ROOTS_OUTLINE_COLOR]);
+        harness.get_by_label("Heads").click();
+        harness.run_steps(1);
+        assert!(outlines(&harness, &c).is_empty());
+        // Kept when the view changes
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(1);
+        assert_eq!(outlines(&harness, &a), [ROOTS_OUTLINE_COLOR]);
+    }
+
+    #[test]
+    fn styles_commits_by_color_rules() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let mut settings = settings::Settings::default();
+        settings.color_rules = toml::from_str::<settings::Settings>(
+            r##"
+            [[color_rules]]
+            revset = "description(b)"
+            color = "#0074d9"
+            border = "#ff4136"
+
+            [[color_rules]]
+            revset = "all()"
+            bold = true
+            "##,
+        )
+        .unwrap()
+        .color_rules;
+        let mut harness = harness_with_settings(&repo, &format!("{a}::"), &a.hex(), settings);
+        let colors: HashMap<_, _> = node_colors(harness.state()).into_iter().collect();
+        assert_eq!(
+            colors[&b],
+            Some(ecolor::Color32::from_rgb(0x00, 0x74, 0xd9))
+        );
+        // The palette colors the rest
+        assert_eq!(
+            colors[&a],
+            Some(ecolor::Color32::from_rgb(0xff, 0xfc, 0x00))
+        );
+        let style = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            let display = app.graph.node(*node_idx).unwrap().display();
+            (display.border, display.bold)
+        };
+        assert_eq!(
+            style(&harness, &b),
+            (Some(ecolor::Color32::from_rgb(0xff, 0x41, 0x36)), true)
+        );
+        assert_eq!(style(&harness, &a), (None, true));
+
+        harness.state_mut().requested_filter = Some(b.hex());
+        harness.run_steps(1);
+        let colors: HashMap<_, _> = node_colors(harness.state()).into_iter().collect();
+        assert_eq!(
+            colors[&b],
+            Some(ecolor::Color32::from_rgb(0x00, 0x74, 0xd9))
+        );
+    }
+
+    #[test]
+    fn dims_commits_by_other_authors() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit_by("a", &[], "Alice", "alice@example.com");
+        let b = repo.commit_by("b", &[&a], "Bob", "bob@example.com");
+        let c = repo.commit_by("c", &[&b], "Alice", "alice@example.com");
+        let mut harness = harness(&repo, &format!("{a}::{c}"), "");
+        let dimmed = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().display().dimmed
+        };
+        assert!(!dimmed(&harness, &b));
+        harness.get_by_label_contains("Bob").click();
+        harness.run_steps(1);
+        assert!(dimmed(&harness, &a) && dimmed(&harness, &c));
+        assert!(!dimmed(&harness, &b));
+
+        harness.get_by_label("Add authors to Select").click();
+        harness.run_steps(1);
+        let app = harness.state();
+        assert_eq!(
+            app.filter_revset.value,
+            r#"author(exact:"bob@example.com")"#
+        );
+        assert_eq!(app.filter_revset.error, None);
+        assert_eq!(app.last_filter_node_count, Some(NodeCount::Exact(1)));
+    }
+
+    #[test]
+    fn shows_signature_status() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        // No signing backend can read it
+        let b = repo.commit_signed("b", &[&a], b"not a signature");
+        let mut settings = settings::Settings::default();
+        settings.ui.signature_badges = true;
+        let mut harness = harness_with_settings(&repo, &format!("{a}::{b}"), "", settings);
+        let badge = |harness: &Harness<ExplorerApp>, commit_id: &CommitId| {
+            let app = harness.state();
+            let node_idx = (app.node_idxs.iter())
+                .find(|idx| app.graph.node(**idx).unwrap().payload() == commit_id)
+                .unwrap();
+            app.graph.node(*node_idx).unwrap().display().signature_badge
+        };
+        // Verified in the background
+        for _ in 0..100 {
+            if badge(&harness, &b).is_some() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+            harness.run_steps(1);
+        }
+        assert_eq!(
+            badge(&harness, &b),
+            signature_color(SignatureStatus::Unknown)
+        );
+        assert_eq!(badge(&harness, &a), None);
+
+        select_and_load_diff(&mut harness, ": b");
+        harness.get_by_label("Not verified");
+
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Signature badges").click();
+        harness.run_steps(1);
+        assert_eq!(badge(&harness, &b), None);
+    }
+
+    #[test]
+    fn scales_graph_labels() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "");
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Larger graph labels").click();
+        harness.run_steps(1);
+        let app = harness.state();
+        let node = app.graph.node(app.node_idxs[0]).unwrap();
+        assert_eq!(node.display().label_scale, 1.25);
+        // Kept when the graph is rebuilt
+        harness.state_mut().repo_reloaded = true;
+        harness.run_steps(1);
+        let app = harness.state();
+        let node = app.graph.node(app.node_idxs[0]).unwrap();
+        assert_eq!(node.display().label_scale, 1.25);
+    }
+
+    #[test]
+    fn navigates_with_gestures() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "");
+        let meta = |harness: &Harness<ExplorerApp>| {
+            let id = egui::Id::new(egui_graphs::MetadataFrame::new(None).get_id());
+            harness
+                .ctx
+                .data_mut(|d| d.get_persisted::<egui_graphs::MetadataFrame>(id))
+                .unwrap()
+        };
+        let center = harness.state().graph_rect.center();
+        harness.hover_at(center);
+        harness.run_steps(1);
+        let before = meta(&harness);
+
+        // Two-finger swipe on a touchpad
+        harness.event(egui::Event::MouseWheel {
+            unit: egui::MouseWheelUnit::Point,
+            delta: egui::vec2(0., -40.),
+            modifiers: egui::Modifiers::NONE,
+        });
+        harness.run_steps(10);
+        let panned = meta(&harness);
+        assert_eq!(panned.zoom, before.zoom);
+        assert!(panned.pan.y < before.pan.y);
+
+        // Pinch on a touchpad
+        harness.event(egui::Event::Zoom(1.5));
+        harness.run_steps(1);
+        let zoomed = meta(&harness);
+        assert!((zoomed.zoom / panned.zoom - 1.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn remaps_mouse_buttons_and_wheel() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut settings = settings::Settings::default();
+        settings.navigation.ctrl_scroll = navigation::ScrollAction::Pan;
+        settings.navigation.select_button = navigation::MouseButton::Middle;
+        let mut harness = harness_with_settings(&repo, &a.hex(), "", settings);
+        let meta = |harness: &Harness<ExplorerApp>| {
+            let id = egui::Id::new(egui_graphs::MetadataFrame::new(None).get_id());
+            harness
+                .ctx
+                .data_mut(|d| d.get_persisted::<egui_graphs::MetadataFrame>(id))
+                .unwrap()
+        };
+        harness.hover_at(harness.state().graph_rect.center());
+        harness.run_steps(1);
+        let before = meta(&harness);
+        harness.event_modifiers(
+            egui::Event::MouseWheel {
+                unit: egui::MouseWheelUnit::Point,
+                delta: egui::vec2(0., -40.),
+                modifiers: egui::Modifiers::COMMAND,
+            },
+            egui::Modifiers::COMMAND,
+        );
+        harness.run_steps(10);
+        let after = meta(&harness);
+        assert!((after.zoom - before.zoom).abs() < 1e-4);
+        assert!(after.pan.y < before.pan.y);
+
+        // The primary button no longer selects
+        let node = harness.get_by_label_contains(": a").rect().center();
+        harness.get_by_label_contains(": a").click();
+        harness.run_steps(2);
+        assert!(harness.state().graph.selected_nodes().is_empty());
+        for pressed in [true, false] {
+            harness.event(egui::Event::PointerButton {
+                pos: node,
+                button: egui::PointerButton::Middle,
+                pressed,
+                modifiers: egui::Modifiers::NONE,
+            });
+        }
+        harness.run_steps(2);
+        let a_idx = harness.state().node_idxs[0];
+        assert_eq!(harness.state().graph.selected_nodes(), [a_idx]);
+    }
+
+    #[test]
+    fn lists_shortcuts() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "");
+        // Typed into the focused revset input instead
+        harness.key_press(egui::Key::Questionmark);
+        harness.run_steps(1);
+        assert!(harness.query_by_label("Graph navigation").is_none());
+
+        harness.key_press(egui::Key::Escape);
+        harness.run_steps(1);
+        harness.key_press(egui::Key::Questionmark);
+        harness.run_steps(1);
+        harness.get_by_label("Graph navigation");
+        harness.get_by_label("Open the context menu of the node");
+
+        harness.key_press(egui::Key::Escape);
+        harness.run_steps(1);
+        assert!(harness.query_by_label("Graph navigation").is_none());
+    }
+
+    #[test]
+    fn long_press_opens_context_menu() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), "");
+        let node = harness.get_by_label_contains(": a").rect().center();
+        harness.hover_at(node);
+        harness.run_steps(1);
+        harness.event(egui::Event::PointerButton {
+            pos: node,
+            button: egui::PointerButton::Primary,
+            pressed: true,
+            modifiers: egui::Modifiers::NONE,
+        });
+        // Longer than the click duration, at a frame time of 0.25 s
+        harness.run_steps(5);
+        harness.event(egui::Event::PointerButton {
+            pos: node,
+            button: egui::PointerButton::Primary,
+            pressed: false,
+            modifiers: egui::Modifiers::NONE,
         });
+        harness.run_steps(2);
+        harness.get_by_label("Show diff (jj diff)");
+        // Not a click
+        assert!(harness.state().graph.selected_nodes().is_empty());
+    }
+
+    #[test]
+    fn changes_palette_and_simulation() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let mut harness = harness(&repo, &a.hex(), &a.hex());
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Palette ⏵").click();
+        harness.run_steps(1);
+        harness.get_by_label("Color-blind safe").click();
+        harness.run_steps(2);
+        assert_eq!(
+            node_colors(harness.state()),
+            [(a.clone(), Some(ecolor::Color32::WHITE))]
+        );
+
+        harness.get_by_label("View").click();
+        harness.run_steps(1);
+        harness.get_by_label("Simulate color vision ⏵").click();
+        harness.run_steps(1);
+        harness.get_by_label("Tritanopia (no blue)").click();
+        harness.run_steps(1);
+        let app = harness.state();
+        let node = app.graph.node(app.node_idxs[0]).unwrap();
+        assert_eq!(node.display().simulation, Some(Deficiency::Tritanopia));
+        // Only the drawing is affected
+        assert_eq!(node.color(), Some(ecolor::Color32::WHITE));
     }
 }
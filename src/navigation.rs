@@ -0,0 +1,228 @@
+//! Zooming and panning of the graph view. Replaces the navigation of
+//! egui_graphs, which zooms in fixed steps on pinch gestures and cannot pan by
+//! scrolling, making touchpads hard to use.
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::NavigationSettings;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScrollAction {
+    /// Vertical, and horizontal with Shift
+    Pan,
+    /// Scrolling horizontally pans
+    Zoom,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MouseButton {
+    Primary,
+    Secondary,
+    Middle,
+}
+
+impl From<MouseButton> for egui::PointerButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Primary => egui::PointerButton::Primary,
+            MouseButton::Secondary => egui::PointerButton::Secondary,
+            MouseButton::Middle => egui::PointerButton::Middle,
+        }
+    }
+}
+
+/// Pans and zooms the graph view by the input of this frame: dragging,
+/// scrolling, pinching on a touchpad and two-finger gestures on a touch
+/// screen. `response` is the graph view's, and `graph_id` its custom ID.
+pub fn navigate(
+    ui: &mut egui::Ui,
+    response: &egui::Response,
+    settings: &NavigationSettings,
+    graph_id: &Option<String>,
+) {
+    if !response.contains_pointer() && !response.dragged() {
+        return;
+    }
+    let options = ui.ctx().options(|o| o.input_options);
+    let (touch, center) = ui.input(|i| (i.multi_touch(), i.pointer.hover_pos()));
+    let (mut zoom_factor, mut pan_delta, center) = match touch {
+        Some(touch) => (
+            touch.zoom_delta,
+            touch.translation_delta,
+            Some(touch.center_pos),
+        ),
+        None => (1., egui::Vec2::ZERO, center),
+    };
+    // The first finger would otherwise drag
+    if touch.is_none() {
+        // egui's scroll and zoom deltas mix plain and Ctrl+scroll
+        let wheel = ui.input(|i| wheel_input(&i.raw.events, &options, response.rect.height()));
+        zoom_factor *= wheel.pinch;
+        for (delta, action) in [
+            (wheel.plain, settings.scroll),
+            (wheel.ctrl, settings.ctrl_scroll),
+        ] {
+            match action {
+                ScrollAction::Pan => pan_delta += delta,
+                ScrollAction::Zoom => {
+                    zoom_factor *= (delta.y * options.scroll_zoom_speed).exp();
+                    pan_delta.x += delta.x;
+                }
+            }
+        }
+        if (settings.pan_buttons.iter()).any(|button| response.dragged_by((*button).into())) {
+            pan_delta += response.drag_delta();
+        }
+    }
+    if zoom_factor == 1. && pan_delta == egui::Vec2::ZERO {
+        return;
+    }
+
+    let mut meta = egui_graphs::MetadataFrame::new(graph_id.clone()).load(ui);
+    // The pan is relative to the graph view
+    let center = center.unwrap_or(response.rect.center()) - response.rect.min;
+    (meta.zoom, meta.pan) = zoom_and_pan(meta.zoom, meta.pan, zoom_factor, pan_delta, center);
+    meta.save(ui);
+    // Shown in the next frame
+    ui.ctx().request_repaint();
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct WheelInput {
+    /// Scrolling without Ctrl
+    plain: egui::Vec2,
+    /// Scrolling with Ctrl (Cmd on macOS)
+    ctrl: egui::Vec2,
+    /// Zoom factor of touchpad pinches
+    pinch: f32,
+}
+
+fn wheel_input(events: &[egui::Event], options: &egui::InputOptions, page: f32) -> WheelInput {
+    let mut input = WheelInput {
+        pinch: 1.,
+        ..Default::default()
+    };
+    for event in events {
+        match event {
+            egui::Event::MouseWheel {
+                unit,
+                delta,
+                modifiers,
+            } => {
+                let mut delta = match unit {
+                    egui::MouseWheelUnit::Point => *delta,
+                    egui::MouseWheelUnit::Line => *delta * options.line_scroll_speed,
+                    egui::MouseWheelUnit::Page => *delta * page,
+                };
+                if modifiers.shift {
+                    delta = egui::vec2(delta.x + delta.y, 0.);
+                }
+                if modifiers.command {
+                    input.ctrl += delta;
+                } else {
+                    input.plain += delta;
+                }
+            }
+            egui::Event::Zoom(factor) => input.pinch *= factor,
+            _ => {}
+        }
+    }
+    input
+}
+
+/// Returns the new zoom and pan. The canvas point at `center` stays in place
+/// when zooming.
+fn zoom_and_pan(
+    zoom: f32,
+    pan: egui::Vec2,
+    zoom_factor: f32,
+    pan_delta: egui::Vec2,
+    center: egui::Vec2,
+) -> (f32, egui::Vec2) {
+    let pan = center - (center - pan) * zoom_factor + pan_delta;
+    (zoom * zoom_factor, pan)
+}
+
+/// Detects pressing and holding the primary button without moving, to open the
+/// context menu with a mouse or pen, like egui does for touches
+#[derive(Default)]
+pub struct LongPress {
+    triggered: bool,
+}
+
+impl LongPress {
+    /// Returns true once per press
+    pub fn detect(&mut self, ui: &egui::Ui, response: &egui::Response) -> bool {
+        let options = ui.ctx().options(|o| o.input_options);
+        let (down, touches, held, moved) = ui.input(|i| {
+            let pointer = &i.pointer;
+            // Releasing later is not a click, which would close the menu
+            let held = (pointer.press_start_time())
+                .is_some_and(|start| i.time - start > options.max_click_duration);
+            let moved = (pointer.press_origin().zip(pointer.interact_pos()))
+                .is_some_and(|(origin, pos)| origin.distance(pos) > options.max_click_dist);
+            (pointer.primary_down(), i.any_touches(), held, moved)
+        });
+        if !down {
+            self.triggered = false;
+            return false;
+        }
+        if self.triggered || touches || !held || moved || !response.is_pointer_button_down_on() {
+            return false;
+        }
+        self.triggered = true;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zooms_around_center() {
+        let zoom = 2.;
+        let pan = egui::vec2(10., 20.);
+        let center = egui::vec2(100., 50.);
+        let canvas_center = (center - pan) / zoom;
+        let (zoom, pan) = zoom_and_pan(zoom, pan, 1.5, egui::Vec2::ZERO, center);
+        assert_eq!(zoom, 3.);
+        assert_eq!(canvas_center * zoom + pan, center);
+
+        let (zoom, pan) = zoom_and_pan(zoom, pan, 1., egui::vec2(5., -5.), center);
+        assert_eq!(zoom, 3.);
+        assert_eq!(canvas_center * zoom + pan, center + egui::vec2(5., -5.));
+    }
+
+    #[test]
+    fn separates_wheel_input() {
+        let wheel = |delta, modifiers| egui::Event::MouseWheel {
+            unit: egui::MouseWheelUnit::Line,
+            delta,
+            modifiers,
+        };
+        let options = egui::InputOptions::default();
+        let input = wheel_input(
+            &[
+                wheel(egui::vec2(0., 1.), egui::Modifiers::NONE),
+                wheel(egui::vec2(0., 1.), egui::Modifiers::SHIFT),
+                wheel(egui::vec2(0., -2.), egui::Modifiers::COMMAND),
+                egui::Event::Zoom(2.),
+            ],
+            &options,
+            500.,
+        );
+        let line = options.line_scroll_speed;
+        assert_eq!(
+            input,
+            WheelInput {
+                plain: egui::vec2(line, line),
+                ctrl: egui::vec2(0., -2. * line),
+                pinch: 2.,
+            }
+        );
+    }
+}
@@ -1,18 +1,133 @@
-//! Ugly override of DefaultNodeShape to get larger label text
+//! Ugly override of DefaultNodeShape to get larger label text and badges
 
-use eframe::egui::{FontFamily, FontId};
+use eframe::egui::text::{LayoutJob, TextFormat};
+use eframe::egui::{Color32, FontFamily, FontId, TextureId};
 use egui_graphs::{DefaultNodeShape, DisplayNode, NodeProps};
 use petgraph::{EdgeType, csr::IndexType};
+use revset_explorer_core::color_vision::{self, Deficiency};
+use serde::{Deserialize, Serialize};
+
+use crate::change_sizes::ChangeSize;
+use crate::label_placement::LabelPlacement;
+
+/// What the size of the nodes shows
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NodeSize {
+    #[default]
+    Uniform,
+    /// Number of files changed by the commit
+    Files,
+    /// Number of lines inserted and deleted by the commit
+    Lines,
+}
+
+impl NodeSize {
+    pub const ALL: [Self; 3] = [Self::Uniform, Self::Files, Self::Lines];
+
+    /// Radius relative to the default one. Grows with the logarithm of the
+    /// count, so that large commits stand out without hiding their neighbors.
+    pub fn scale(self, size: ChangeSize) -> f32 {
+        let (count, per_doubling) = match self {
+            NodeSize::Uniform => return 1.,
+            NodeSize::Files => (size.files, 0.25),
+            NodeSize::Lines => (size.lines, 0.15),
+        };
+        (1. + (count as f32).ln_1p() / std::f32::consts::LN_2 * per_doubling).min(2.5)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct NodeShape {
     default_node: DefaultNodeShape,
+    /// Small colored circle drawn at the top right of the node
+    pub badge: Option<Color32>,
+    /// Small colored circle drawn at the top left of the node
+    pub signature_badge: Option<Color32>,
+    /// Small colored circle drawn at the bottom right of the node
+    pub annotation_badge: Option<Color32>,
+    /// Text drawn to the left of the node, like the generation distance
+    pub distance_label: Option<String>,
+    /// Shortened description drawn after the label
+    pub description: Option<String>,
+    /// Text drawn above the node, like the position in the Select results
+    pub order_label: Option<String>,
+    /// Commit ID drawn below the node, and the length of its shortest unique
+    /// prefix, which is underlined
+    pub commit_id_label: Option<(String, usize)>,
+    /// Short author, like initials, drawn below the node and its commit ID
+    pub author_label: Option<String>,
+    /// Drawn with a pin at the bottom left of the node
+    pub pinned: bool,
+    /// Image drawn inside the node
+    pub avatar: Option<TextureId>,
+    /// Colored ring drawn around the node
+    pub ring: Option<Color32>,
+    /// Thin colored circles drawn around the node and its ring, like for the
+    /// heads and roots of the view
+    pub outlines: Vec<Color32>,
+    /// Thick border from a color rule, replacing the normal one
+    pub border: Option<Color32>,
+    /// Label drawn in bold, from a color rule
+    pub bold: bool,
+    /// Moves or hides the label to avoid overlaps. Hovered and selected nodes
+    /// always show their labels.
+    pub label_placement: LabelPlacement,
+    /// Screen area of the label at its normal position, when last drawn
+    pub label_rect: Option<eframe::egui::Rect>,
+    /// Has keyboard focus. Drawn as a ring in the selection color.
+    pub focused: bool,
+    /// Matches the diff search. Drawn as a glow behind the node.
+    pub highlighted: bool,
+    /// Outside the date range. Drawn faded.
+    pub dimmed: bool,
+    /// Size of the label text, relative to the default size
+    pub label_scale: f32,
+    /// Draw the colors as they appear with the color vision deficiency
+    pub simulation: Option<Deficiency>,
+    /// Radius before scaling. The label size follows it.
+    base_radius: f32,
+}
+
+impl NodeShape {
+    /// In canvas coordinates
+    pub fn radius(&self) -> f32 {
+        self.default_node.radius
+    }
+
+    /// Scales the node, but not its label
+    pub fn set_size_scale(&mut self, scale: f32) {
+        self.default_node.radius = self.base_radius * scale;
+    }
 }
 
 impl<N: Clone> From<NodeProps<N>> for NodeShape {
     fn from(node_props: NodeProps<N>) -> Self {
+        let default_node: DefaultNodeShape = node_props.into();
         Self {
-            default_node: node_props.into(),
+            base_radius: default_node.radius,
+            default_node,
+            badge: None,
+            signature_badge: None,
+            annotation_badge: None,
+            distance_label: None,
+            description: None,
+            order_label: None,
+            commit_id_label: None,
+            author_label: None,
+            pinned: false,
+            avatar: None,
+            ring: None,
+            outlines: vec![],
+            border: None,
+            bold: false,
+            label_placement: LabelPlacement::Normal,
+            label_rect: None,
+            focused: false,
+            highlighted: false,
+            dimmed: false,
+            label_scale: 1.,
+            simulation: None,
         }
     }
 }
@@ -29,22 +144,268 @@ impl<N: Clone, E: Clone, Ty: EdgeType, Ix: IndexType> DisplayNode<N, E, Ty, Ix>
         let mut r =
             <DefaultNodeShape as DisplayNode<N, E, Ty, Ix>>::shapes(&mut self.default_node, ctx);
 
+        let mut bold_copy = None;
+        self.label_rect = None;
+        let shown = self.default_node.hovered || self.default_node.selected;
+        let placement = if shown {
+            LabelPlacement::Normal
+        } else {
+            self.label_placement
+        };
         for shape in r.iter_mut() {
             if let eframe::egui::Shape::Text(shape) = shape {
                 let size = ctx
                     .meta
-                    .canvas_to_screen_size(self.default_node.radius * 2.5);
+                    .canvas_to_screen_size(self.base_radius * 2.5 * self.label_scale);
+                let text = match &self.description {
+                    Some(description) => format!("{} {description}", shape.galley.text()),
+                    None => shape.galley.text().to_owned(),
+                };
                 shape.galley = ctx.ctx.fonts_mut(|f| {
                     f.layout_no_wrap(
-                        shape.galley.text().to_owned(),
+                        text,
                         FontId::new(size, FontFamily::Monospace),
                         self.default_node.color.unwrap_or_default(),
                     )
                 });
                 shape.pos.x += size;
+                self.label_rect = Some(eframe::egui::Rect::from_min_size(
+                    shape.pos,
+                    shape.galley.size(),
+                ));
+                if let LabelPlacement::Shifted(shift) = placement {
+                    shape.pos.y += shift * shape.galley.size().y;
+                }
+                if self.bold {
+                    // No bold monospace font, so draw the text twice
+                    let mut copy = shape.clone();
+                    copy.pos.x += (size / 12.).max(1.);
+                    bold_copy = Some(eframe::egui::Shape::Text(copy));
+                }
                 break;
             }
         }
+        r.extend(bold_copy);
+        if placement == LabelPlacement::Hidden {
+            r.retain(|shape| !matches!(shape, eframe::egui::Shape::Text(_)));
+        }
+
+        if let Some(border) = self.border {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            if let Some(eframe::egui::Shape::Circle(circle)) =
+                (r.iter_mut()).find(|shape| matches!(shape, eframe::egui::Shape::Circle(_)))
+            {
+                circle.stroke = eframe::egui::Stroke::new(radius * 0.3, border);
+            }
+        }
+
+        if self.highlighted {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos);
+            let color = ctx.ctx.style().visuals.warn_fg_color.gamma_multiply(0.5);
+            r.insert(
+                0,
+                eframe::egui::Shape::circle_filled(center, radius * 2.5, color),
+            );
+        }
+
+        if let Some(avatar) = self.avatar {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos);
+            r.push(eframe::egui::Shape::image(
+                avatar,
+                eframe::egui::Rect::from_center_size(
+                    center,
+                    eframe::egui::Vec2::splat(radius * 1.4),
+                ),
+                eframe::egui::Rect::from_min_max(
+                    eframe::egui::pos2(0., 0.),
+                    eframe::egui::pos2(1., 1.),
+                ),
+                Color32::WHITE,
+            ));
+        }
+
+        if let Some(ring) = self.ring {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos);
+            r.push(eframe::egui::Shape::circle_stroke(
+                center,
+                radius * 1.6,
+                eframe::egui::Stroke::new(radius * 0.4, ring),
+            ));
+        }
+
+        for (i, outline) in self.outlines.iter().enumerate() {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos);
+            r.push(eframe::egui::Shape::circle_stroke(
+                center,
+                radius * (1.9 + 0.3 * i as f32),
+                eframe::egui::Stroke::new(radius * 0.15, *outline),
+            ));
+        }
+
+        if self.focused {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos);
+            r.push(eframe::egui::Shape::circle_stroke(
+                center,
+                radius * 2.2,
+                ctx.ctx.style().visuals.selection.stroke,
+            ));
+        }
+
+        if let Some(badge) = self.badge {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos)
+                + eframe::egui::vec2(radius, -radius);
+            r.push(eframe::egui::Shape::circle_filled(
+                center,
+                radius * 0.5,
+                badge,
+            ));
+        }
+
+        if let Some(badge) = self.signature_badge {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos)
+                + eframe::egui::vec2(-radius, -radius);
+            r.push(eframe::egui::Shape::circle_filled(
+                center,
+                radius * 0.5,
+                badge,
+            ));
+        }
+
+        if let Some(badge) = self.annotation_badge {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos)
+                + eframe::egui::vec2(radius, radius);
+            r.push(eframe::egui::Shape::circle_filled(
+                center,
+                radius * 0.5,
+                badge,
+            ));
+        }
+
+        if self.pinned {
+            let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+            let head = ctx.meta.canvas_to_screen_pos(self.default_node.pos)
+                + eframe::egui::vec2(-radius, radius * 0.7);
+            r.push(eframe::egui::Shape::line_segment(
+                [head, head + eframe::egui::vec2(-radius * 0.4, radius * 0.8)],
+                eframe::egui::Stroke::new(radius * 0.15, ctx.ctx.style().visuals.text_color()),
+            ));
+            r.push(eframe::egui::Shape::circle_filled(
+                head,
+                radius * 0.4,
+                Color32::from_rgb(0xff, 0x41, 0x36),
+            ));
+        }
+
+        if self.dimmed {
+            for shape in r.iter_mut() {
+                match shape {
+                    eframe::egui::Shape::Circle(circle) => {
+                        circle.fill = circle.fill.gamma_multiply(0.2);
+                        circle.stroke.color = circle.stroke.color.gamma_multiply(0.2);
+                    }
+                    eframe::egui::Shape::Text(text) => {
+                        text.opacity_factor = 0.2;
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(deficiency) = self.simulation {
+            let simulate = |color: Color32| {
+                let [red, green, blue, alpha] = color.to_srgba_unmultiplied();
+                let [red, green, blue] = color_vision::simulate([red, green, blue], deficiency);
+                Color32::from_rgba_unmultiplied(red, green, blue, alpha)
+            };
+            for shape in r.iter_mut() {
+                match shape {
+                    eframe::egui::Shape::Circle(circle) => {
+                        circle.fill = simulate(circle.fill);
+                        circle.stroke.color = simulate(circle.stroke.color);
+                    }
+                    eframe::egui::Shape::Text(text) => {
+                        text.override_text_color =
+                            Some(simulate(self.default_node.color.unwrap_or_default()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let radius = ctx.meta.canvas_to_screen_size(self.default_node.radius);
+        let center = ctx.meta.canvas_to_screen_pos(self.default_node.pos);
+        let size = ctx
+            .meta
+            .canvas_to_screen_size(self.base_radius * 2. * self.label_scale);
+        // Gray, so not affected by the simulation. Not inside fonts_mut(),
+        // which locks the context.
+        let color = ctx.ctx.style().visuals.text_color();
+        let layout = |text: &String| {
+            ctx.ctx.fonts_mut(|f| {
+                f.layout_no_wrap(
+                    text.clone(),
+                    FontId::new(size, FontFamily::Monospace),
+                    color,
+                )
+            })
+        };
+        if let Some(distance_label) = &self.distance_label {
+            let galley = layout(distance_label);
+            let pos =
+                center - eframe::egui::vec2(radius * 1.5 + galley.size().x, galley.size().y / 2.);
+            r.push(eframe::egui::Shape::galley(pos, galley, color));
+        }
+        if let Some(order_label) = &self.order_label {
+            let galley = layout(order_label);
+            let pos =
+                center - eframe::egui::vec2(galley.size().x / 2., radius * 1.2 + galley.size().y);
+            r.push(eframe::egui::Shape::galley(pos, galley, color));
+        }
+        let mut below = radius * 1.2;
+        if let Some((commit_id, unique_len)) = &self.commit_id_label {
+            // Like jj log, the unique prefix stands out from the rest
+            let visuals = &ctx.ctx.style().visuals;
+            let strong = visuals.strong_text_color();
+            let font_id = FontId::new(size, FontFamily::Monospace);
+            let (unique, rest) = commit_id.split_at((*unique_len).min(commit_id.len()));
+            let mut job = LayoutJob::default();
+            job.append(
+                unique,
+                0.,
+                TextFormat {
+                    font_id: font_id.clone(),
+                    color: strong,
+                    underline: eframe::egui::Stroke::new((size / 12.).max(1.), strong),
+                    ..Default::default()
+                },
+            );
+            job.append(
+                rest,
+                0.,
+                TextFormat {
+                    font_id,
+                    color: visuals.weak_text_color(),
+                    ..Default::default()
+                },
+            );
+            let galley = ctx.ctx.fonts_mut(|f| f.layout_job(job));
+            let pos = center + eframe::egui::vec2(-galley.size().x / 2., below);
+            below += galley.size().y;
+            r.push(eframe::egui::Shape::galley(pos, galley, color));
+        }
+        if let Some(author_label) = &self.author_label {
+            let galley = layout(author_label);
+            let pos = center + eframe::egui::vec2(-galley.size().x / 2., below);
+            r.push(eframe::egui::Shape::galley(pos, galley, color));
+        }
 
         r
     }
@@ -0,0 +1,212 @@
+//! Tab with the operation log as a graph, see [`revset_explorer_core::oplog`].
+//! The repository can be shown as it was at a selected operation, to see how
+//! the revsets evaluated before e.g. a rebase or an abandon.
+
+use eframe::egui::{self, ecolor::Color32};
+use jj_lib::object_id::ObjectId;
+use jj_lib::op_store::OperationId;
+use revset_explorer_core::JjGraph;
+use revset_explorer_core::oplog::{OpNode, op_log_graph};
+use std::collections::HashMap;
+
+use crate::edge_shape::EdgeShape;
+use crate::i18n::tr;
+use crate::navigation;
+use crate::node_shape::NodeShape;
+use crate::settings::NavigationSettings;
+
+/// Operations shown, as the log of an old repository is long
+const MAX_OPS: usize = 500;
+const OPERATION_COLOR: Color32 = Color32::from_rgb(0x00, 0x74, 0xd9);
+const CURRENT_OPERATION_COLOR: Color32 = Color32::from_rgb(0x2e, 0xcc, 0x40);
+
+type OpGraphType = egui_graphs::Graph<
+    OperationId,
+    (),
+    petgraph::Directed,
+    petgraph::csr::DefaultIx,
+    NodeShape,
+    EdgeShape,
+>;
+
+#[derive(Default)]
+pub struct OpLogView {
+    // Loaded when the tab is shown
+    graph: Option<OpGraphType>,
+    ops: HashMap<OperationId, OpNode>,
+    error: Option<String>,
+}
+
+impl OpLogView {
+    /// Loads the operations again the next time the tab is shown, e.g. after
+    /// the repository has been reloaded
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Shows the log. Returns the operation to show the repository at, when
+    /// its button is clicked.
+    pub fn ui(
+        &mut self,
+        ui: &mut egui::Ui,
+        jj_graph: &JjGraph,
+        style: &egui_graphs::SettingsStyle,
+        navigation_settings: &NavigationSettings,
+        graph_id: Option<String>,
+    ) -> Option<OperationId> {
+        if self.graph.is_none() && self.error.is_none() {
+            self.load(ui, jj_graph, &graph_id);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(ui.visuals().error_fg_color, error);
+            return None;
+        }
+        let graph = self.graph.as_mut()?;
+        let mut show = None;
+
+        let selected = (graph.selected_nodes().first())
+            .and_then(|node_idx| graph.node(*node_idx))
+            .and_then(|node| self.ops.get(node.payload()));
+        match selected {
+            Some(op) => {
+                egui::Grid::new("operation").num_columns(2).show(ui, |ui| {
+                    ui.label(tr!("operation-id"));
+                    ui.monospace(op.id.hex());
+                    ui.end_row();
+                    ui.label(tr!("operation-description"));
+                    ui.label(&op.description);
+                    ui.end_row();
+                    ui.label(tr!("operation-user"));
+                    ui.label(&op.user);
+                    ui.end_row();
+                    ui.label(tr!("operation-time"));
+                    ui.label(crate::format_timestamp(&op.time));
+                    ui.end_row();
+                });
+                if &op.id == jj_graph.get_repo().op_id() {
+                    ui.label(tr!("operation-shown"));
+                } else if (ui.button(tr!("operation-show")))
+                    .on_hover_text(tr!("operation-show-hover"))
+                    .clicked()
+                {
+                    show = Some(op.id.clone());
+                }
+            }
+            None => {
+                ui.label(tr!("operation-count", count = self.ops.len()));
+            }
+        }
+
+        let graph_navigation = egui_graphs::SettingsNavigation::default()
+            .with_fit_to_screen_enabled(false)
+            // Done by navigation::navigate()
+            .with_zoom_and_pan_enabled(false);
+        let graph_interaction = egui_graphs::SettingsInteraction::default()
+            .with_dragging_enabled(false)
+            .with_hover_enabled(true)
+            .with_node_clicking_enabled(true)
+            .with_node_selection_enabled(true);
+        let mut s = egui_graphs::get_layout_state::<egui_graphs::LayoutStateHierarchical>(
+            ui,
+            graph_id.clone(),
+        );
+        s.center_parent = true;
+        egui_graphs::set_layout_state(ui, s, graph_id.clone());
+        let mut graph_view = egui_graphs::GraphView::<
+            _,
+            _,
+            _,
+            _,
+            NodeShape,
+            EdgeShape,
+            egui_graphs::LayoutStateHierarchical,
+            egui_graphs::LayoutHierarchical,
+        >::new(graph)
+        .with_navigations(&graph_navigation)
+        .with_interactions(&graph_interaction)
+        .with_styles(style)
+        .with_id(graph_id.clone());
+        let response = ui.add(&mut graph_view);
+        response.widget_info(|| {
+            egui::WidgetInfo::labeled(egui::WidgetType::Other, true, tr!("operation-graph"))
+        });
+        navigation::navigate(ui, &response, navigation_settings, &graph_id);
+        show
+    }
+
+    fn load(&mut self, ui: &mut egui::Ui, jj_graph: &JjGraph, graph_id: &Option<String>) {
+        let op_graph = match op_log_graph(jj_graph, MAX_OPS) {
+            Ok(op_graph) => op_graph,
+            Err(e) => {
+                self.error = Some(tr!("operation-log-failed", error = format!("{e:#}")));
+                return;
+            }
+        };
+        let mut graph: OpGraphType =
+            egui_graphs::Graph::new(petgraph::stable_graph::StableGraph::default());
+        let mut node_map = HashMap::new();
+        // The walk starts at the current operation
+        for (i, node_idx) in op_graph.node_indices().enumerate() {
+            let op = &op_graph[node_idx];
+            let summary = op.description.lines().next().unwrap_or_default();
+            let label = format!("{} {summary}", &op.id.hex()[..12]);
+            let view_idx = graph.add_node_with_label(op.id.clone(), label);
+            let node = graph.node_mut(view_idx).unwrap();
+            node.set_color(if i == 0 {
+                CURRENT_OPERATION_COLOR
+            } else {
+                OPERATION_COLOR
+            });
+            // Snapshots are many and seldom interesting
+            node.display_mut().dimmed = op.is_snapshot;
+            node_map.insert(node_idx, view_idx);
+            self.ops.insert(op.id.clone(), op.clone());
+        }
+        for edge_idx in op_graph.edge_indices() {
+            let (start, end) = op_graph.edge_endpoints(edge_idx).unwrap();
+            graph.add_edge_with_label(node_map[&start], node_map[&end], (), String::new());
+        }
+        self.graph = Some(graph);
+        egui_graphs::reset_layout::<egui_graphs::LayoutStateHierarchical>(ui, graph_id.clone());
+        egui_graphs::reset_metadata(ui, graph_id.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui_kittest::Harness;
+    use egui_kittest::kittest::Queryable;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn shows_details_of_selected_operation() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        repo.set_bookmark("main", &a);
+        let jj_graph = repo.jj_graph();
+        let style = egui_graphs::SettingsStyle::default();
+        let navigation_settings = NavigationSettings::default();
+        let mut harness = Harness::new_ui_state(
+            |ui, view: &mut OpLogView| {
+                view.ui(ui, &jj_graph, &style, &navigation_settings, None);
+            },
+            OpLogView::default(),
+        );
+        harness.run_steps(2);
+        harness.get_by_label_contains("4 operations");
+
+        let graph = harness.state_mut().graph.as_mut().unwrap();
+        let latest = graph.g().node_indices().next().unwrap();
+        graph.node_mut(latest).unwrap().set_selected(true);
+        harness.run_steps(2);
+        harness.get_by_label("test bookmark");
+        let latest_id = jj_graph.get_repo().operation().id().hex();
+        harness.get_by_label(&latest_id);
+        harness.get_by_label("The repository is shown at this operation");
+
+        harness.state_mut().clear();
+        harness.run_steps(2);
+        harness.get_by_label_contains("4 operations");
+    }
+}
@@ -0,0 +1,151 @@
+//! Input for a path that commits should change. Applying it adds a `files()`
+//! term to the Select revset, or selects the commits in the view that last
+//! changed the path.
+
+use eframe::egui;
+use revset_explorer_core::jjgraph::JjGraph;
+use revset_explorer_core::resolve::string_literal;
+
+use crate::i18n::tr;
+
+const MAX_COMPLETIONS: usize = 10;
+
+/// What to do with the entered path
+#[derive(Debug, PartialEq, Eq)]
+pub enum PathAction {
+    /// Only select commits that change the path
+    AddToSelect(String),
+    /// Select the latest commits in the view that changed the path
+    LastTouched(String),
+}
+
+#[derive(Default)]
+pub struct PathFilter {
+    path: String,
+    // Loaded when the input first gets focus
+    tracked_paths: Option<Vec<String>>,
+    // Keeps the completions open while clicking them, which takes the focus
+    // from the input
+    completions_hovered: bool,
+}
+
+impl PathFilter {
+    /// Forgets the tracked paths, so that they are loaded again when needed
+    pub fn reload(&mut self) {
+        self.tracked_paths = None;
+    }
+
+    /// Shows the input. Returns the path when the user applies it.
+    pub fn ui(&mut self, ui: &mut egui::Ui, jj_graph: &JjGraph) -> Option<PathAction> {
+        let label = ui.label(tr!("path-filter"));
+        let response = ui
+            .add(
+                egui::TextEdit::singleline(&mut self.path)
+                    .desired_width(200.)
+                    .hint_text(tr!("path-filter-hint")),
+            )
+            .labelled_by(label.id);
+        if response.has_focus() && self.tracked_paths.is_none() {
+            // Without completions, the path can still be typed
+            self.tracked_paths = Some(jj_graph.tracked_paths().unwrap_or_default());
+        }
+        let completions = completions(
+            self.tracked_paths.as_deref().unwrap_or_default(),
+            &self.path,
+        );
+        let open = (response.has_focus() || self.completions_hovered)
+            && !self.path.is_empty()
+            && completions.first().is_some_and(|first| *first != self.path);
+        let mut chosen = None;
+        let popup = egui::Popup::from_response(&response).open(open).show(|ui| {
+            for completion in completions {
+                if ui.selectable_label(false, completion).clicked() {
+                    chosen = Some(completion.to_owned());
+                }
+            }
+        });
+        self.completions_hovered = popup.is_some_and(|popup| popup.response.contains_pointer());
+        if let Some(chosen) = chosen {
+            self.path = chosen;
+            self.completions_hovered = false;
+            response.request_focus();
+        }
+        let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let path = self.path.trim();
+        let apply = ui.add_enabled(
+            !path.is_empty(),
+            egui::Button::new(tr!("path-filter-apply")),
+        );
+        let last_touched = ui
+            .add_enabled(
+                !path.is_empty(),
+                egui::Button::new(tr!("path-filter-last-touched")),
+            )
+            .on_hover_text(tr!("path-filter-last-touched-hover"));
+        if path.is_empty() {
+            None
+        } else if entered || apply.clicked() {
+            Some(PathAction::AddToSelect(path.to_owned()))
+        } else if last_touched.clicked() {
+            Some(PathAction::LastTouched(path.to_owned()))
+        } else {
+            None
+        }
+    }
+}
+
+/// Paths starting with the input, then paths containing it
+fn completions<'a>(paths: &'a [String], input: &str) -> Vec<&'a str> {
+    let (mut starting, containing): (Vec<_>, Vec<_>) = (paths.iter())
+        .filter(|path| path.contains(input))
+        .map(|path| path.as_str())
+        .partition(|path| path.starts_with(input));
+    starting.extend(containing);
+    starting.truncate(MAX_COMPLETIONS);
+    starting
+}
+
+/// Restricts the revset to commits that change files at or under the path,
+/// which is relative to the repository root
+pub fn with_files(revset: &str, path: &str) -> String {
+    let files = format!("files(root:{})", string_literal(path));
+    match revset.trim() {
+        "" => files,
+        revset => format!("({revset}) & {files}"),
+    }
+}
+
+/// The latest commits in the view that change files at or under the path,
+/// like the commits that `jj file annotate` would show most for it
+pub fn last_touched(view: &str, path: &str) -> String {
+    format!("heads({})", with_files(view, path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn completes_prefixes_first() {
+        let paths = ["README", "docs/src/", "src/", "src/main.rs"].map(String::from);
+        assert_eq!(
+            completions(&paths, "src"),
+            ["src/", "src/main.rs", "docs/src/"]
+        );
+        assert_eq!(completions(&paths, "x"), [] as [&str; 0]);
+    }
+
+    #[test]
+    fn composes_files_term() {
+        assert_eq!(with_files("", "src/"), r#"files(root:"src/")"#);
+        assert_eq!(
+            with_files(" a | b ", "src/"),
+            r#"(a | b) & files(root:"src/")"#
+        );
+        assert_eq!(with_files("a", r#"x"\y"#), r#"(a) & files(root:"x\"\\y")"#);
+        assert_eq!(
+            last_touched("::@", "a.txt"),
+            r#"heads((::@) & files(root:"a.txt"))"#
+        );
+    }
+}
@@ -0,0 +1,70 @@
+//! The lineage of a node within the view, i.e. its ancestors and descendants,
+//! for highlighting the paths through the hovered node in a tangled graph
+
+use petgraph::graph::NodeIndex;
+use petgraph::stable_graph::StableGraph;
+use petgraph::visit::{Dfs, IntoNeighbors, Reversed, Visitable};
+use std::collections::HashSet;
+
+pub struct Lineage {
+    /// Including the node itself
+    ancestors: HashSet<NodeIndex>,
+    /// Including the node itself
+    descendants: HashSet<NodeIndex>,
+}
+
+impl Lineage {
+    /// Edges go from child to parent, like in the view graph
+    pub fn of<N, E>(graph: &StableGraph<N, E>, node_idx: NodeIndex) -> Self {
+        Self {
+            ancestors: reachable(graph, node_idx),
+            descendants: reachable(Reversed(graph), node_idx),
+        }
+    }
+
+    pub fn contains(&self, node_idx: NodeIndex) -> bool {
+        self.ancestors.contains(&node_idx) || self.descendants.contains(&node_idx)
+    }
+
+    /// Whether the edge is on a path through the node. Not true for an edge
+    /// from a descendant to an ancestor that bypasses the node.
+    pub fn contains_edge(&self, child: NodeIndex, parent: NodeIndex) -> bool {
+        (self.ancestors.contains(&child) && self.ancestors.contains(&parent))
+            || (self.descendants.contains(&child) && self.descendants.contains(&parent))
+    }
+}
+
+fn reachable<G>(graph: G, node_idx: NodeIndex) -> HashSet<NodeIndex>
+where
+    G: IntoNeighbors<NodeId = NodeIndex> + Visitable,
+{
+    let mut dfs = Dfs::new(graph, node_idx);
+    let mut nodes = HashSet::new();
+    while let Some(node_idx) = dfs.next(graph) {
+        nodes.insert(node_idx);
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_ancestors_and_descendants() {
+        // d -> c -> b -> a, d -> a and the unrelated side -> a
+        let mut graph = StableGraph::<(), ()>::new();
+        let [a, b, c, d, side] = [(); 5].map(|_| graph.add_node(()));
+        for (child, parent) in [(b, a), (c, b), (d, c), (d, a), (side, a)] {
+            graph.add_edge(child, parent, ());
+        }
+        let lineage = Lineage::of(&graph, c);
+        assert!([a, b, c, d].iter().all(|n| lineage.contains(*n)));
+        assert!(!lineage.contains(side));
+        assert!(lineage.contains_edge(c, b));
+        assert!(lineage.contains_edge(d, c));
+        // Around c
+        assert!(!lineage.contains_edge(d, a));
+        assert!(!lineage.contains_edge(side, a));
+    }
+}
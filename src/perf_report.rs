@@ -0,0 +1,155 @@
+//! Timings of the latest revset evaluations and graph layouts, exported as
+//! JSON from the File menu for attaching to performance bug reports.
+
+use revset_explorer_core::NodeCount;
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Number of evaluations and layouts kept of each kind
+pub const KEPT: usize = 50;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Kind {
+    View,
+    Select,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    kind: Kind,
+    revset: String,
+    milliseconds: f64,
+    /// Commits in the revset, at least this many if not `exact`
+    commits: usize,
+    exact: bool,
+    /// Size of the built graph, for the view
+    #[serde(skip_serializing_if = "Option::is_none")]
+    graph: Option<GraphSize>,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GraphSize {
+    pub nodes: usize,
+    pub edges: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Layout {
+    graph: GraphSize,
+    milliseconds: f64,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct PerfReport {
+    /// Oldest first
+    evaluations: VecDeque<Evaluation>,
+    /// Oldest first
+    layouts: VecDeque<Layout>,
+}
+
+/// What is written to the file, with what the numbers depend on
+#[derive(Serialize)]
+struct Export<'a> {
+    version: &'a str,
+    os: &'a str,
+    cores: Option<usize>,
+    node_limit: usize,
+    #[serde(flatten)]
+    report: &'a PerfReport,
+}
+
+impl Evaluation {
+    pub fn new(
+        kind: Kind,
+        revset: &str,
+        duration: Duration,
+        node_count: NodeCount,
+        graph: Option<GraphSize>,
+    ) -> Self {
+        let (commits, exact) = match node_count {
+            NodeCount::Exact(count) => (count, true),
+            NodeCount::AtLeast(count) => (count, false),
+        };
+        Self {
+            kind,
+            revset: revset.to_owned(),
+            milliseconds: milliseconds(duration),
+            commits,
+            exact,
+            graph,
+        }
+    }
+}
+
+impl PerfReport {
+    pub fn record_evaluation(&mut self, evaluation: Evaluation) {
+        push(&mut self.evaluations, evaluation);
+    }
+
+    pub fn record_layout(&mut self, graph: GraphSize, duration: Duration) {
+        let milliseconds = milliseconds(duration);
+        push(
+            &mut self.layouts,
+            Layout {
+                graph,
+                milliseconds,
+            },
+        );
+    }
+
+    pub fn to_json(&self, node_limit: usize) -> String {
+        let export = Export {
+            version: env!("CARGO_PKG_VERSION"),
+            os: std::env::consts::OS,
+            cores: std::thread::available_parallelism().map(|n| n.get()).ok(),
+            node_limit,
+            report: self,
+        };
+        serde_json::to_string_pretty(&export).unwrap()
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_micros() as f64 / 1000.0
+}
+
+fn push<T>(kept: &mut VecDeque<T>, item: T) {
+    if kept.len() == KEPT {
+        kept.pop_front();
+    }
+    kept.push_back(item);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_latest_timings() {
+        let mut report = PerfReport::default();
+        for i in 0..KEPT + 1 {
+            report.record_evaluation(Evaluation::new(
+                Kind::Select,
+                &i.to_string(),
+                Duration::from_micros(1500),
+                NodeCount::AtLeast(3),
+                None,
+            ));
+        }
+        let graph = GraphSize { nodes: 3, edges: 2 };
+        report.record_layout(graph, Duration::from_millis(2));
+        let json: serde_json::Value = serde_json::from_str(&report.to_json(100)).unwrap();
+        let evaluations = json["evaluations"].as_array().unwrap();
+        assert_eq!(evaluations.len(), KEPT);
+        assert_eq!(evaluations[0]["revset"], "1");
+        assert_eq!(evaluations[0]["kind"], "select");
+        assert_eq!(evaluations[0]["milliseconds"], 1.5);
+        assert_eq!(evaluations[0]["exact"], false);
+        assert!(evaluations[0].get("graph").is_none());
+        assert_eq!(json["layouts"][0]["graph"]["nodes"], 3);
+        assert_eq!(json["layouts"][0]["milliseconds"], 2.0);
+        assert_eq!(json["node_limit"], 100);
+    }
+}
@@ -0,0 +1,104 @@
+//! Commits pinned to the view, like a release commit that should stay visible
+//! as a reference point whatever the View revset. Stored per repository in
+//! `.jj/revset-explorer/pins.toml`, as full commit IDs:
+//!
+//! ```toml
+//! commits = ["5b8a33c0c2c5d2b2b858a5a04548ec4e5de32a74"]
+//! ```
+
+use anyhow::Context;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Pins {
+    /// Hex commit IDs, in the order they were pinned
+    commits: Vec<String>,
+}
+
+impl Pins {
+    /// Loads the pins of the repository. None are pinned if the file is
+    /// missing.
+    pub fn load(repository_path: &Path) -> anyhow::Result<Self> {
+        let path = path(repository_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let pins_str = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read the pins from {}", path.display()))?;
+        toml::from_str(&pins_str).with_context(|| format!("Invalid pins in {}", path.display()))
+    }
+
+    pub fn save(&self, repository_path: &Path) -> anyhow::Result<()> {
+        let path = path(repository_path);
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(&path, toml::to_string(self)?)
+            .with_context(|| format!("Cannot write the pins to {}", path.display()))
+    }
+
+    pub fn contains(&self, commit_id: &CommitId) -> bool {
+        self.commits.contains(&commit_id.hex())
+    }
+
+    pub fn toggle(&mut self, commit_id: &CommitId) {
+        let hex = commit_id.hex();
+        if let Some(pos) = self.commits.iter().position(|pinned| *pinned == hex) {
+            self.commits.remove(pos);
+        } else {
+            self.commits.push(hex);
+        }
+    }
+
+    /// The view with the pinned commits added, `(<view>) | <id> | <id>`.
+    /// `exists` filters out commits that are gone from the repository, which
+    /// would make the revset fail.
+    pub fn add_to_view(&self, view: &str, exists: impl Fn(&str) -> bool) -> String {
+        let mut pinned = self.commits.iter().filter(|hex| exists(hex)).peekable();
+        if pinned.peek().is_none() {
+            return view.to_owned();
+        }
+        let mut revset = format!("({})", view.trim());
+        for hex in pinned {
+            revset += &format!(" | {hex}");
+        }
+        revset
+    }
+}
+
+fn path(repository_path: &Path) -> PathBuf {
+    repository_path.join(".jj/revset-explorer/pins.toml")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_pinned_commits_to_view() {
+        let dir = std::env::temp_dir().join(format!("pins-test-{}", std::process::id()));
+        let mut pins = Pins::load(&dir).unwrap();
+        assert_eq!(pins.add_to_view("::@", |_| true), "::@");
+
+        let [a, b] = [[0xaa; 20], [0xbb; 20]].map(|bytes| CommitId::new(bytes.to_vec()));
+        pins.toggle(&a);
+        pins.toggle(&b);
+        assert!(pins.contains(&a));
+        pins.save(&dir).unwrap();
+        let pins = Pins::load(&dir).unwrap();
+        assert_eq!(
+            pins.add_to_view("::@ ", |_| true),
+            format!("(::@) | {} | {}", a.hex(), b.hex())
+        );
+        // Only existing commits
+        assert_eq!(
+            pins.add_to_view("::@", |hex| hex == b.hex()),
+            format!("(::@) | {}", b.hex())
+        );
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,96 @@
+//! Commits just outside the view, its parents and children, read on a
+//! [`crate::workers`] thread after the graph has been built. Views that are widened or
+//! moved a little, which is the usual next step, are then built without
+//! waiting for the store.
+
+use jj_lib::backend::CommitId;
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use revset_explorer_core::jjgraph::JjGraph;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc;
+
+use crate::workers::{self, Subsystem};
+
+#[derive(Default)]
+pub struct Prefetch {
+    // Commits never change, so they are kept
+    commits: HashMap<CommitId, Commit>,
+    // Dropping it stops the prefetch
+    receiver: Option<mpsc::Receiver<Commit>>,
+}
+
+impl Prefetch {
+    /// Starts reading up to `limit` commits bordering on the view, stopping
+    /// the previous prefetch
+    pub fn start(&mut self, jj_graph: &JjGraph, view: &str, limit: usize) {
+        let (sender, receiver) = mpsc::channel();
+        self.receiver = Some(receiver);
+        let jj_graph = jj_graph.clone();
+        let revset = boundary(view);
+        let prefetched: HashSet<_> = self.commits.keys().cloned().collect();
+        workers::spawn(Subsystem::Prefetch, move || {
+            // The view has already been resolved, so this only fails if the
+            // repository is broken, which the view will show
+            let Ok(revset) = jj_graph.get_revset(&revset) else {
+                return;
+            };
+            let store = jj_graph.get_repo().store().clone();
+            let commit_ids = (revset.iter().take(limit)).map_while(Result::ok);
+            for commit_id in commit_ids.filter(|id| !prefetched.contains(id)) {
+                let Ok(commit) = store.get_commit(&commit_id) else {
+                    continue;
+                };
+                if sender.send(commit).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Receives the commits read so far
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+        while let Ok(commit) = receiver.try_recv() {
+            self.commits.insert(commit.id().clone(), commit);
+        }
+    }
+
+    pub fn commits(&self) -> &HashMap<CommitId, Commit> {
+        &self.commits
+    }
+}
+
+/// `((<view>)- | (<view>)+) ~ (<view>)`
+fn boundary(view: &str) -> String {
+    let view = view.trim();
+    format!("(({view})- | ({view})+) ~ ({view})")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jj_lib::object_id::ObjectId;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn reads_parents_and_children_of_view() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let c = repo.commit("c", &[&b]);
+        let d = repo.commit("d", &[&c]);
+        let mut prefetch = Prefetch::default();
+        prefetch.start(&repo.jj_graph(), &b.hex(), 10);
+        let started = std::time::Instant::now();
+        while prefetch.commits().len() < 2 && started.elapsed().as_secs() < 10 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            prefetch.poll();
+        }
+        let commits = prefetch.commits();
+        assert!(commits.contains_key(&a) && commits.contains_key(&c));
+        assert!(!commits.contains_key(&b) && !commits.contains_key(&d));
+    }
+}
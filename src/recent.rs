@@ -0,0 +1,75 @@
+//! The recently opened repositories, most recent first, for File > Open
+//! recent. Stored in the data directory, in `revset-explorer/recent.toml`:
+//!
+//! ```toml
+//! repositories = ["/home/me/src/jj", "/home/me/src/revset-explorer"]
+//! ```
+
+use etcetera::BaseStrategy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Older repositories are forgotten
+const MAX_RECENT: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Recent {
+    /// Canonical paths
+    pub repositories: Vec<PathBuf>,
+    /// Where the list is saved. Not saved without one, like in tests.
+    #[serde(skip)]
+    path: Option<PathBuf>,
+}
+
+impl Recent {
+    /// Loads the list from the data directory. A missing or unreadable file
+    /// gives an empty list, as the list is only a convenience.
+    pub fn load() -> Self {
+        let path = etcetera::choose_base_strategy()
+            .ok()
+            .map(|strategy| strategy.data_dir().join("revset-explorer/recent.toml"));
+        let recent = (path.as_ref())
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|recent| toml::from_str(&recent).ok())
+            .unwrap_or_default();
+        Self { path, ..recent }
+    }
+
+    /// Moves the repository first in the list and saves it
+    pub fn add(&mut self, repository: &Path) -> anyhow::Result<()> {
+        self.repositories.retain(|path| path != repository);
+        self.repositories.insert(0, repository.to_owned());
+        self.repositories.truncate(MAX_RECENT);
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, toml::to_string(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_first() {
+        let mut recent = Recent::default();
+        for i in 0..=MAX_RECENT {
+            recent.add(Path::new(&format!("/repo{i}"))).unwrap();
+        }
+        recent.add(Path::new("/repo5")).unwrap();
+        assert_eq!(recent.repositories.len(), MAX_RECENT);
+        assert_eq!(recent.repositories[0], Path::new("/repo5"));
+        assert_eq!(
+            recent.repositories[1],
+            Path::new(&format!("/repo{MAX_RECENT}"))
+        );
+        // The oldest is forgotten
+        assert!(!recent.repositories.contains(&PathBuf::from("/repo0")));
+    }
+}
@@ -0,0 +1,283 @@
+//! Reloading of the repository when jj commands elsewhere change it. Checking
+//! for new operations is cheap, but reloading evaluates the revsets again,
+//! which can take long in huge repositories. So the checks can be made less
+//! often or paused, and the repository reloaded from the File menu instead.
+//! Instant watching waits for jj to write operations on a thread instead,
+//! with inotify where available and otherwise by listing the operation heads
+//! often, and only wakes the window when they change.
+
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, mpsc};
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Watch {
+    /// Only reloaded by hand
+    #[default]
+    Paused,
+    /// Checked every `watch_interval` seconds
+    Poll,
+    /// Watched on a thread, checked as soon as jj writes an operation
+    Instant,
+}
+
+pub const DEFAULT_POLL_SECONDS: u64 = 5;
+/// Between the listings of the operation heads with [`Watch::Instant`], when
+/// the directory can't be watched
+const NOTIFIER_INTERVAL: Duration = Duration::from_millis(250);
+/// How long the notifier waits for changes before checking if it has been
+/// stopped
+const NOTIFIER_STOP_CHECK: Duration = Duration::from_secs(1);
+/// Holds a file named by the ID of each current operation of jj, in the
+/// repositories that the app can load
+const OP_HEADS_DIR: &str = ".jj/repo/op_heads/heads";
+
+pub struct RepoWatch {
+    pub watch: Watch,
+    pub poll_seconds: u64,
+    /// `egui::InputState::time` of the last check
+    last_check: Option<f64>,
+    /// Running while watching instantly
+    notifier: Option<Notifier>,
+}
+
+impl RepoWatch {
+    pub fn new(watch: Watch, poll_seconds: u64) -> Self {
+        Self {
+            watch,
+            poll_seconds,
+            last_check: None,
+            notifier: None,
+        }
+    }
+
+    /// Whether it is time to check the repository at `repository_path`.
+    /// Requests a repaint for the next check, as nothing else may happen
+    /// until then.
+    pub fn due(&mut self, ctx: &egui::Context, repository_path: &Path) -> bool {
+        let interval = match self.watch {
+            Watch::Paused => None,
+            Watch::Poll => Some(self.poll_seconds.max(1) as f64),
+            Watch::Instant => {
+                let notifier =
+                    (self.notifier).get_or_insert_with(|| Notifier::start(ctx, repository_path));
+                return notifier.changed.swap(false, Ordering::Relaxed);
+            }
+        };
+        self.notifier = None;
+        let Some(interval) = interval else {
+            return false;
+        };
+        let now = ctx.input(|i| i.time);
+        let due = self.last_check.is_none_or(|last| now - last >= interval);
+        if due {
+            self.last_check = Some(now);
+        }
+        let since_check = now - self.last_check.unwrap_or(now);
+        ctx.request_repaint_after_secs((interval - since_check) as f32);
+        due
+    }
+
+    /// Why the operation heads could not be read while watching instantly,
+    /// once for each time they start failing
+    pub fn take_error(&mut self) -> Option<String> {
+        let notifier = self.notifier.as_ref()?;
+        notifier.errors.try_recv().ok()
+    }
+}
+
+/// Waits for changes of the operation heads on a thread and wakes the window
+/// when they change. Stopped when dropped.
+struct Notifier {
+    changed: Arc<AtomicBool>,
+    stopped: Arc<AtomicBool>,
+    errors: mpsc::Receiver<String>,
+}
+
+impl Notifier {
+    fn start(ctx: &egui::Context, repository_path: &Path) -> Self {
+        // Operations written before it started are checked for at once
+        let changed = Arc::new(AtomicBool::new(true));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (sender, errors) = mpsc::channel();
+        let dir = repository_path.join(OP_HEADS_DIR);
+        let ctx = ctx.clone();
+        std::thread::spawn({
+            let changed = changed.clone();
+            let stopped = stopped.clone();
+            move || {
+                // Polled when the directory can't be watched
+                let watcher = DirWatcher::new(&dir).ok();
+                let wait = || match &watcher {
+                    Some(watcher) => (watcher.wait(NOTIFIER_STOP_CHECK)).unwrap_or_else(|_| {
+                        std::thread::sleep(NOTIFIER_INTERVAL);
+                        true
+                    }),
+                    None => {
+                        std::thread::sleep(NOTIFIER_INTERVAL);
+                        true
+                    }
+                };
+                // The last listing that could be read
+                let mut op_heads = None;
+                let mut failing = false;
+                let mut first = true;
+                while !stopped.load(Ordering::Relaxed) {
+                    if !std::mem::take(&mut first) && !wait() {
+                        continue;
+                    }
+                    // Only a different listing counts, as the events also
+                    // come for the temporary files of jj
+                    match list(&dir) {
+                        Ok(current) => {
+                            failing = false;
+                            if op_heads
+                                .as_ref()
+                                .is_some_and(|op_heads| *op_heads != current)
+                            {
+                                changed.store(true, Ordering::Relaxed);
+                                ctx.request_repaint();
+                            }
+                            op_heads = Some(current);
+                        }
+                        Err(e) => {
+                            if !failing && sender.send(e.to_string()).is_ok() {
+                                ctx.request_repaint();
+                            }
+                            failing = true;
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            changed,
+            stopped,
+            errors,
+        }
+    }
+}
+
+impl Drop for Notifier {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// The sorted names in the directory
+fn list(dir: &Path) -> std::io::Result<Vec<OsString>> {
+    let mut names = (std::fs::read_dir(dir)?)
+        .map(|entry| entry.map(|e| e.file_name()))
+        .collect::<Result<Vec<_>, _>>()?;
+    names.sort();
+    Ok(names)
+}
+
+/// Wakes when files are added to or removed from a directory
+#[cfg(target_os = "linux")]
+struct DirWatcher(std::os::fd::OwnedFd);
+
+#[cfg(target_os = "linux")]
+impl DirWatcher {
+    fn new(dir: &Path) -> std::io::Result<Self> {
+        use rustix::fs::inotify::{self, CreateFlags, WatchFlags};
+        let fd = inotify::init(CreateFlags::CLOEXEC | CreateFlags::NONBLOCK)?;
+        let flags =
+            WatchFlags::CREATE | WatchFlags::DELETE | WatchFlags::MOVED_FROM | WatchFlags::MOVED_TO;
+        inotify::add_watch(&fd, dir, flags)?;
+        Ok(Self(fd))
+    }
+
+    /// Waits at most `timeout` for a change. Returns whether there was one.
+    fn wait(&self, timeout: Duration) -> std::io::Result<bool> {
+        use rustix::event::{PollFd, PollFlags, Timespec};
+        let timeout = Timespec::try_from(timeout).map_err(std::io::Error::other)?;
+        let mut fds = [PollFd::new(&self.0, PollFlags::IN)];
+        if rustix::event::poll(&mut fds, Some(&timeout))? == 0 {
+            return Ok(false);
+        }
+        // Only whether something changed matters, not what
+        let mut events = [0; 4096];
+        loop {
+            match rustix::io::read(&self.0, &mut events) {
+                Ok(_) => {}
+                Err(rustix::io::Errno::AGAIN) => return Ok(true),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// Nothing to watch with, so the directory is listed often instead
+#[cfg(not(target_os = "linux"))]
+struct DirWatcher(std::convert::Infallible);
+
+#[cfg(not(target_os = "linux"))]
+impl DirWatcher {
+    fn new(_dir: &Path) -> std::io::Result<Self> {
+        Err(std::io::ErrorKind::Unsupported.into())
+    }
+
+    fn wait(&self, _timeout: Duration) -> std::io::Result<bool> {
+        match self.0 {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn notices_new_operations() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let ctx = egui::Context::default();
+        let mut watch = RepoWatch::new(Watch::Instant, DEFAULT_POLL_SECONDS);
+        assert!(watch.due(&ctx, repo.path()));
+        assert!(!watch.due(&ctx, repo.path()));
+        // The notifier has listed the operation heads by now
+        std::thread::sleep(NOTIFIER_INTERVAL * 2);
+        repo.commit("b", &[&a]);
+        let started = std::time::Instant::now();
+        while !watch.due(&ctx, repo.path()) {
+            assert!(started.elapsed().as_secs() < 10);
+            std::thread::sleep(NOTIFIER_INTERVAL);
+        }
+        watch.watch = Watch::Paused;
+        assert!(!watch.due(&ctx, repo.path()));
+        assert!(watch.notifier.is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn waits_for_new_operations() {
+        let mut repo = TestRepo::init();
+        let watcher = DirWatcher::new(&repo.path().join(OP_HEADS_DIR)).unwrap();
+        assert!(!watcher.wait(Duration::ZERO).unwrap());
+        repo.commit("a", &[]);
+        assert!(watcher.wait(NOTIFIER_STOP_CHECK).unwrap());
+    }
+
+    #[test]
+    fn reports_read_errors() {
+        let ctx = egui::Context::default();
+        let mut watch = RepoWatch::new(Watch::Instant, DEFAULT_POLL_SECONDS);
+        let path = Path::new("no-such-repository");
+        watch.due(&ctx, path);
+        let started = std::time::Instant::now();
+        while watch.take_error().is_none() {
+            assert!(started.elapsed().as_secs() < 10);
+            std::thread::sleep(NOTIFIER_INTERVAL);
+        }
+        // Not again while it keeps failing
+        std::thread::sleep(NOTIFIER_INTERVAL * 2);
+        assert!(watch.take_error().is_none());
+        assert!(!watch.due(&ctx, path));
+    }
+}
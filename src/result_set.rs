@@ -0,0 +1,103 @@
+//! The commits a revset evaluated to, saved to a file together with the
+//! operation they were evaluated at, for auditing a history rewrite. Loading
+//! the file later overlays it on the view, showing which commits have joined
+//! the result since and which have left it.
+
+use anyhow::Context;
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use revset_explorer_core::jjgraph::JjGraph;
+use revset_explorer_core::resolve::{ResolveError, resolve_revset};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ResultSet {
+    pub revset: String,
+    /// Full hex ID of the operation the revset was evaluated at
+    pub operation_id: String,
+    /// Full hex commit IDs, in the order the revset yields them
+    pub commits: Vec<String>,
+}
+
+/// Differences between a saved result and the current one
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Comparison {
+    /// In the current result only
+    pub joined: HashSet<CommitId>,
+    /// In the saved result only, like commits that have been rewritten
+    pub left: HashSet<CommitId>,
+}
+
+impl ResultSet {
+    /// Evaluates up to `limit` commits of the revset
+    pub fn evaluate(jj_graph: &JjGraph, revset: &str, limit: usize) -> Result<Self, ResolveError> {
+        let commit_ids = evaluate(jj_graph, revset, limit)?;
+        Ok(Self {
+            revset: revset.to_owned(),
+            operation_id: jj_graph.get_repo().operation().id().hex(),
+            commits: commit_ids.iter().map(|id| id.hex()).collect(),
+        })
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid result set in {}", path.display()))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn compare(&self, current: &[CommitId]) -> Comparison {
+        let saved: HashSet<_> = (self.commits.iter())
+            .filter_map(CommitId::try_from_hex)
+            .collect();
+        let current: HashSet<_> = current.iter().cloned().collect();
+        Comparison {
+            joined: current.difference(&saved).cloned().collect(),
+            left: saved.difference(&current).cloned().collect(),
+        }
+    }
+}
+
+/// Up to `limit` commits of the revset, in the order it yields them
+pub fn evaluate(
+    jj_graph: &JjGraph,
+    revset: &str,
+    limit: usize,
+) -> Result<Vec<CommitId>, ResolveError> {
+    let (revset, ..) = resolve_revset(jj_graph, revset)?;
+    (revset.iter().take(limit))
+        .collect::<Result<_, _>>()
+        .map_err(ResolveError::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn compares_saved_and_current_results() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let saved = ResultSet::evaluate(&jj_graph, &b.hex(), 10).unwrap();
+        assert_eq!(saved.commits, [b.hex()]);
+        assert_eq!(
+            saved.operation_id,
+            jj_graph.get_repo().operation().id().hex()
+        );
+        let saved = serde_json::from_str::<ResultSet>(&saved.to_json()).unwrap();
+
+        let comparison = saved.compare(std::slice::from_ref(&a));
+        assert_eq!(comparison.joined, HashSet::from([a.clone()]));
+        assert_eq!(comparison.left, HashSet::from([b.clone()]));
+        assert_eq!(saved.compare(&[b]), Comparison::default());
+    }
+}
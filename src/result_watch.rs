@@ -0,0 +1,118 @@
+//! Notifications about the commits that join or leave the Select result when
+//! the watched repository is reloaded, e.g. for keeping an eye on a policy
+//! revset like `mutable() & ~signed()`. A shell command from the `[hooks]`
+//! settings can be run with the changes too.
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+
+use crate::i18n::tr;
+
+/// Results larger than this are only compared up to it
+pub const LIMIT: usize = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultChange {
+    pub revset: String,
+    /// In the new result only, in its order
+    pub joined: Vec<CommitId>,
+    /// In the old result only, in its order
+    pub left: Vec<CommitId>,
+}
+
+impl ResultChange {
+    /// The change from the old to the new result, unless they have the same
+    /// commits
+    pub fn between(revset: &str, before: &[CommitId], after: &[CommitId]) -> Option<Self> {
+        let before_set: HashSet<_> = before.iter().collect();
+        let after_set: HashSet<_> = after.iter().collect();
+        let joined: Vec<_> = (after.iter())
+            .filter(|id| !before_set.contains(id))
+            .cloned()
+            .collect();
+        let left: Vec<_> = (before.iter())
+            .filter(|id| !after_set.contains(id))
+            .cloned()
+            .collect();
+        (!joined.is_empty() || !left.is_empty()).then(|| Self {
+            revset: revset.to_owned(),
+            joined,
+            left,
+        })
+    }
+
+    /// The environment variables the hook gets
+    fn hook_env(&self) -> [(&'static str, String); 3] {
+        let hex = |ids: &[CommitId]| {
+            let ids: Vec<_> = ids.iter().map(|id| id.hex()).collect();
+            ids.join(" ")
+        };
+        [
+            ("REVSET_EXPLORER_REVSET", self.revset.clone()),
+            ("REVSET_EXPLORER_JOINED", hex(&self.joined)),
+            ("REVSET_EXPLORER_LEFT", hex(&self.left)),
+        ]
+    }
+
+    /// Runs the shell command in the repository in the background, with the
+    /// changes in environment variables. Failures are reported through
+    /// `error_sender`.
+    pub fn run_hook(&self, command: &str, repo_path: &Path, error_sender: mpsc::Sender<String>) {
+        let mut shell = if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.arg("/C");
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.arg("-c");
+            shell
+        };
+        shell
+            .arg(command)
+            .current_dir(PathBuf::from(repo_path))
+            .envs(self.hook_env())
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped());
+        let command = command.to_owned();
+        std::thread::spawn(move || {
+            let error = match shell.output() {
+                Ok(output) if output.status.success() => return,
+                Ok(output) => String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+                Err(e) => e.to_string(),
+            };
+            let _ = error_sender.send(tr!(
+                "watch-hook-failed",
+                command = command.as_str(),
+                error = error
+            ));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_joined_and_left_commits() {
+        let [a, b, c] = ["aa", "bb", "cc"].map(CommitId::from_hex);
+        let same = std::slice::from_ref(&a);
+        assert_eq!(ResultChange::between("x", same, same), None);
+        let change = ResultChange::between("x", &[a.clone(), b.clone()], &[c.clone(), a]).unwrap();
+        assert_eq!(change.joined, [c]);
+        assert_eq!(change.left, [b]);
+        assert_eq!(
+            change.hook_env(),
+            [
+                ("REVSET_EXPLORER_REVSET", "x".to_owned()),
+                ("REVSET_EXPLORER_JOINED", "cc".to_owned()),
+                ("REVSET_EXPLORER_LEFT", "bb".to_owned()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,339 @@
+//! Completions for the revset inputs. Typing a name suggests the revset
+//! functions, the aliases from the jj config, the bookmarks and the tags
+//! starting with it. After `(`, `::` and the other operators, all of them are
+//! suggested, and after a finished expression and a space, the operators.
+
+use eframe::egui::text::{CCursor, CCursorRange};
+use eframe::egui::{self, EventFilter};
+use revset_explorer_core::jjgraph::JjGraph;
+use revset_explorer_core::resolve::string_literal;
+use std::ops::Range;
+
+use crate::i18n::tr;
+use crate::keymap;
+
+const MAX_COMPLETIONS: usize = 10;
+
+/// The built-in functions of jj that take no arguments
+const FUNCTIONS_WITHOUT_ARGUMENTS: &[&str] = &[
+    "all",
+    "conflicts",
+    "empty",
+    "git_head",
+    "git_refs",
+    "merges",
+    "mine",
+    "none",
+    "root",
+    "signed",
+    "visible_heads",
+    "working_copies",
+];
+
+/// The other built-in functions of jj
+const FUNCTIONS: &[&str] = &[
+    "ancestors",
+    "at_operation",
+    "author",
+    "author_date",
+    "author_email",
+    "author_name",
+    "bisect",
+    "bookmarks",
+    "change_id",
+    "children",
+    "coalesce",
+    "commit_id",
+    "committer",
+    "committer_date",
+    "committer_email",
+    "committer_name",
+    "connected",
+    "descendants",
+    "description",
+    "diff_contains",
+    "exactly",
+    "files",
+    "first_ancestors",
+    "first_parent",
+    "fork_point",
+    "heads",
+    "latest",
+    "parents",
+    "present",
+    "reachable",
+    "remote_bookmarks",
+    "roots",
+    "subject",
+    "tags",
+];
+
+/// Binary operators, suggested after a finished expression
+const OPERATORS: &[&str] = &["|", "&", "~", "::", ".."];
+
+/// Operators after which an expression follows
+const EXPRESSION_STARTS: &[&str] = &["(", ",", "|", "&", "~", "::", ".."];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Function,
+    Alias,
+    Bookmark,
+    Tag,
+    Operator,
+}
+
+impl Kind {
+    fn name(self) -> String {
+        match self {
+            Kind::Function => tr!("completion-function"),
+            Kind::Alias => tr!("completion-alias"),
+            Kind::Bookmark => tr!("completion-bookmark"),
+            Kind::Tag => tr!("completion-tag"),
+            Kind::Operator => tr!("completion-operator"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Inserted in place of the name before the cursor
+    pub text: String,
+    pub kind: Kind,
+}
+
+/// The names that can be completed in a repository
+#[derive(Debug, Default)]
+pub struct Vocabulary {
+    /// Sorted by kind, then name
+    symbols: Vec<Completion>,
+}
+
+impl Vocabulary {
+    pub fn load(jj_graph: &JjGraph) -> Self {
+        let aliases = jj_graph.aliases_map();
+        let alias_functions = (aliases.function_names()).map(|name| {
+            // Aliases can have several arities
+            let without_arguments = aliases.get_function(name, 0).is_some()
+                && (1..4).all(|arity| aliases.get_function(name, arity).is_none());
+            (name.to_owned(), without_arguments)
+        });
+        let alias_symbols = aliases.symbol_names().map(str::to_owned);
+        let repo = jj_graph.get_repo();
+        let bookmarks = (repo.view().local_bookmarks()).map(|(name, _)| name.as_str().to_owned());
+        let tags = (repo.view().local_tags()).map(|(name, _)| name.as_str().to_owned());
+        Self::new(
+            alias_symbols.collect(),
+            alias_functions.collect(),
+            bookmarks.collect(),
+            tags.collect(),
+        )
+    }
+
+    /// `alias_functions` tells whether each function alias takes no arguments
+    fn new(
+        alias_symbols: Vec<String>,
+        alias_functions: Vec<(String, bool)>,
+        bookmarks: Vec<String>,
+        tags: Vec<String>,
+    ) -> Self {
+        let call = |name: &str, without_arguments: bool| {
+            if without_arguments {
+                format!("{name}()")
+            } else {
+                format!("{name}(")
+            }
+        };
+        let mut aliases: Vec<_> = (alias_symbols.into_iter())
+            .chain(
+                alias_functions
+                    .iter()
+                    .map(|(name, without)| call(name, *without)),
+            )
+            .collect();
+        aliases.sort();
+        let mut functions: Vec<_> = (FUNCTIONS_WITHOUT_ARGUMENTS.iter())
+            .map(|name| call(name, true))
+            .chain(FUNCTIONS.iter().map(|name| call(name, false)))
+            // Aliases like `trunk()` take precedence
+            .filter(|function| !aliases.contains(function))
+            .collect();
+        functions.sort();
+        let mut symbols = vec![];
+        for (kind, mut names) in [
+            (Kind::Alias, aliases),
+            (Kind::Function, functions),
+            (Kind::Bookmark, bookmarks),
+            (Kind::Tag, tags),
+        ] {
+            names.sort();
+            symbols.extend(names.into_iter().map(|name| Completion {
+                text: match kind {
+                    Kind::Bookmark | Kind::Tag if !is_identifier(&name) => string_literal(&name),
+                    _ => name,
+                },
+                kind,
+            }));
+        }
+        Self { symbols }
+    }
+
+    /// The completions at the cursor, a byte offset into `revset`, with the
+    /// range of the name they replace
+    pub fn complete(&self, revset: &str, cursor: usize) -> (Range<usize>, Vec<Completion>) {
+        let before = &revset[..cursor];
+        let start = before
+            .rfind(|c: char| !is_name_char(c))
+            .map_or(0, |i| i + before[i..].chars().next().unwrap().len_utf8());
+        // `x..y` is a range, but `v1.2` a name
+        let start = before[start..].rfind("..").map_or(start, |i| start + i + 2);
+        let name = &before[start..];
+        let range = start..cursor;
+        let preceding = before[..start].trim_end();
+        // Inside a string, or the pattern of a string pattern like `exact:`
+        if before.matches('"').count() % 2 == 1
+            || preceding.ends_with(':') && !preceding.ends_with("::")
+        {
+            return (range, vec![]);
+        }
+        let completions = if !name.is_empty() {
+            (self.symbols.iter())
+                .filter(|symbol| {
+                    // Also quoted names, without typing the quote
+                    let text = symbol.text.trim_start_matches('"');
+                    text.starts_with(name) && text != name
+                })
+                .cloned()
+                .collect()
+        } else if EXPRESSION_STARTS.iter().any(|op| preceding.ends_with(op)) {
+            self.symbols.clone()
+        } else if !preceding.is_empty() && preceding.len() < before.len() {
+            (OPERATORS.iter())
+                .map(|op| Completion {
+                    text: format!("{op} "),
+                    kind: Kind::Operator,
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        (
+            range,
+            completions.into_iter().take(MAX_COMPLETIONS).collect(),
+        )
+    }
+
+    /// Shows the completions below the revset input while it has focus.
+    /// Clicking one, or pressing Tab for the first one, inserts it.
+    /// `hovered` keeps them open while clicking them, which takes the focus
+    /// from the input. Returns whether the revset was changed.
+    pub fn popup(
+        &self,
+        ui: &egui::Ui,
+        response: &egui::Response,
+        revset: &mut String,
+        hovered: &mut bool,
+    ) -> bool {
+        let Some(mut state) = egui::TextEdit::load_state(ui.ctx(), response.id) else {
+            return false;
+        };
+        let Some(cursor) = state.cursor.char_range().map(|range| range.primary.index) else {
+            return false;
+        };
+        let cursor = (revset.char_indices().nth(cursor)).map_or(revset.len(), |(i, _)| i);
+        let (range, completions) = self.complete(revset, cursor);
+        let open = (response.has_focus() || *hovered) && !completions.is_empty();
+        let mut chosen = None;
+        if open && response.has_focus() {
+            // Keeps the focus in the input when pressing Tab in the next frame
+            let filter = EventFilter {
+                tab: true,
+                horizontal_arrows: true,
+                vertical_arrows: true,
+                ..Default::default()
+            };
+            ui.memory_mut(|m| m.set_focus_lock_filter(response.id, filter));
+            if ui.input_mut(|i| i.consume_shortcut(&keymap::COMPLETE_REVSET)) {
+                chosen = completions.first().cloned();
+            }
+        }
+        let popup = egui::Popup::from_response(response).open(open).show(|ui| {
+            for completion in &completions {
+                ui.horizontal(|ui| {
+                    if ui.selectable_label(false, &completion.text).clicked() {
+                        chosen = Some(completion.clone());
+                    }
+                    ui.weak(completion.kind.name());
+                });
+            }
+        });
+        *hovered = popup.is_some_and(|popup| popup.response.contains_pointer());
+        let Some(chosen) = chosen else {
+            return false;
+        };
+        revset.replace_range(range.clone(), &chosen.text);
+        let end = revset[..range.start + chosen.text.len()].chars().count();
+        (state.cursor).set_char_range(Some(CCursorRange::one(CCursor::new(end))));
+        state.store(ui.ctx(), response.id);
+        *hovered = false;
+        response.request_focus();
+        true
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || "_/.-+@".contains(c)
+}
+
+/// Whether the name can be written without quotes, like `main` or
+/// `release/1.2`
+fn is_identifier(name: &str) -> bool {
+    name.split(['.', '-', '+']).all(|part| {
+        !part.is_empty() && (part.chars()).all(|c| c.is_alphanumeric() || "_/".contains(c))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(vocabulary: &Vocabulary, revset: &str) -> Vec<String> {
+        let (_, completions) = vocabulary.complete(revset, revset.len());
+        completions.into_iter().map(|c| c.text).collect()
+    }
+
+    #[test]
+    fn completes_by_context() {
+        let vocabulary = Vocabulary::new(
+            vec!["trunk".to_owned()],
+            vec![("trunk".to_owned(), true), ("wip".to_owned(), false)],
+            vec!["main".to_owned(), "my feature".to_owned()],
+            vec!["v1.2".to_owned()],
+        );
+        assert_eq!(texts(&vocabulary, "::tr"), ["trunk", "trunk()"]);
+        assert_eq!(texts(&vocabulary, "mi"), ["mine()"]);
+        assert_eq!(texts(&vocabulary, "author_n"), ["author_name("]);
+        assert_eq!(texts(&vocabulary, "main..v1"), ["v1.2"]);
+        assert_eq!(texts(&vocabulary, "my"), [r#""my feature""#]);
+        // Nothing more to complete
+        assert!(texts(&vocabulary, "main").is_empty());
+        let (range, _) = vocabulary.complete("heads(ma) & x", 8);
+        assert_eq!(range, 6..8);
+
+        assert_eq!(texts(&vocabulary, "heads(")[0], "trunk");
+        assert_eq!(texts(&vocabulary, "main:: ").len(), MAX_COMPLETIONS);
+        assert_eq!(texts(&vocabulary, "main::")[0], "trunk");
+        assert_eq!(
+            texts(&vocabulary, "main "),
+            ["| ", "& ", "~ ", ":: ", ".. "]
+        );
+        assert_eq!(
+            texts(&vocabulary, "heads(main) "),
+            ["| ", "& ", "~ ", ":: ", ".. "]
+        );
+
+        assert!(texts(&vocabulary, "").is_empty());
+        assert!(texts(&vocabulary, r#"description("ma"#).is_empty());
+        assert!(texts(&vocabulary, "author(exact:ma").is_empty());
+    }
+}
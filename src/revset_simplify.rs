@@ -0,0 +1,176 @@
+//! Tidying of the revsets that the app composes, like when a toggle wraps the
+//! View revset or a filter is added to the Select revset, so that they stay
+//! readable after a few rounds. Repeated union terms are dropped, and so are
+//! parentheses that don't group anything. Function arguments are left as
+//! they are.
+
+/// The revset with the same meaning, simplified
+pub fn simplify(revset: &str) -> String {
+    let mut revset = ungroup(revset.trim());
+    while let Some(inner) = enclosed(&revset) {
+        revset = inner.trim().to_owned();
+    }
+    let mut terms: Vec<&str> = vec![];
+    for term in split_unions(&revset) {
+        if !terms.contains(&term) {
+            terms.push(term);
+        }
+    }
+    terms.join(" | ")
+}
+
+/// Simplifies the parenthesized groups of the revset, removing the
+/// parentheses around single symbols and function calls
+fn ungroup(revset: &str) -> String {
+    let mut simplified = String::new();
+    let mut rest = revset;
+    while let Some((before, open)) = next_paren(rest) {
+        let Some(close) = closing(&rest[open..]).map(|close| open + close) else {
+            // Unbalanced, which jj will report
+            break;
+        };
+        simplified += &rest[..open];
+        let inner = &rest[open + 1..close];
+        if before.is_some_and(is_symbol_char) {
+            // Function arguments
+            simplified += &rest[open..=close];
+        } else {
+            let inner = simplify(inner);
+            if is_atom(&inner) {
+                simplified += &inner;
+            } else {
+                simplified += &format!("({inner})");
+            }
+        }
+        rest = &rest[close + 1..];
+    }
+    simplified + rest
+}
+
+/// The character before and the offset of the first opening parenthesis
+/// outside string literals
+fn next_paren(revset: &str) -> Option<(Option<char>, usize)> {
+    let mut before = None;
+    for (i, c, in_string) in chars(revset) {
+        if c == '(' && !in_string {
+            return Some((before, i));
+        }
+        before = Some(c);
+    }
+    None
+}
+
+/// Offset of the parenthesis that closes the one the revset starts with
+fn closing(revset: &str) -> Option<usize> {
+    let mut depth = 0;
+    for (i, c, in_string) in chars(revset) {
+        match c {
+            _ if in_string => {}
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The text inside the parentheses if they enclose all of the revset
+fn enclosed(revset: &str) -> Option<&str> {
+    (revset.starts_with('(') && closing(revset) == Some(revset.len() - 1))
+        .then(|| &revset[1..revset.len() - 1])
+}
+
+/// The terms of the top-level unions, trimmed
+fn split_unions(revset: &str) -> Vec<&str> {
+    let mut terms = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c, in_string) in chars(revset) {
+        match c {
+            _ if in_string => {}
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '|' if depth == 0 => {
+                terms.push(revset[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    terms.push(revset[start..].trim());
+    terms
+}
+
+/// A symbol, a string literal or a function call, which operators can't
+/// split
+fn is_atom(revset: &str) -> bool {
+    let symbol_end = revset.find(|c| !is_symbol_char(c)).unwrap_or(revset.len());
+    let rest = &revset[symbol_end..];
+    match rest.chars().next() {
+        None => symbol_end > 0,
+        Some('(') => symbol_end > 0 && enclosed(rest).is_some(),
+        Some('"' | '\'') => {
+            symbol_end == 0 && (chars(rest).skip(1)).all(|(_, _, in_string)| in_string)
+        }
+        _ => false,
+    }
+}
+
+fn is_symbol_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '@' | '/')
+}
+
+/// The characters with their byte offsets, and whether they are in a string
+/// literal, closing quotes included
+fn chars(revset: &str) -> impl Iterator<Item = (usize, char, bool)> + '_ {
+    let mut quote = None;
+    let mut escaped = false;
+    revset.char_indices().map(move |(i, c)| match quote {
+        Some(q) => {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' && q == '"' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            (i, c, true)
+        }
+        None => {
+            if c == '"' || c == '\'' {
+                quote = Some(c);
+            }
+            (i, c, quote.is_some())
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_repeated_terms_and_needless_parentheses() {
+        assert_eq!(simplify("(main) & mine()"), "main & mine()");
+        assert_eq!(simplify("((a | b)) & mine()"), "(a | b) & mine()");
+        assert_eq!(simplify("(a | b | a)"), "a | b");
+        assert_eq!(simplify("a | (b | a) | a"), "a | (b | a)");
+        assert_eq!(
+            simplify("((trunk()..@)) & files(root:\"src/\")"),
+            "(trunk()..@) & files(root:\"src/\")"
+        );
+        assert_eq!(simplify("(@)- | (x)::"), "@- | x::");
+        // Strings are not looked into
+        assert_eq!(
+            simplify(r#"(description("a | (b)")) | description("a | (b)")"#),
+            r#"description("a | (b)")"#
+        );
+        assert_eq!(simplify("(\"x\") & y"), "\"x\" & y");
+        assert_eq!(simplify("~(a ~ b)"), "~(a ~ b)");
+    }
+}
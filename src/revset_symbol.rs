@@ -0,0 +1,48 @@
+//! The shortest symbol that refers to a commit in revsets, for pasting into
+//! the revset fields or jj commands
+
+use jj_lib::backend::CommitId;
+use jj_lib::object_id::ObjectId;
+use jj_lib::repo::Repo;
+use revset_explorer_core::JjGraph;
+
+/// The shortest unique change ID prefix, like the labels show. When the
+/// change ID does not pick out only this commit, e.g. for divergent changes or
+/// hidden commits, `commit_id()` with the shortest unique commit ID prefix.
+pub fn revset_symbol(jj_graph: &JjGraph, commit_id: &CommitId) -> anyhow::Result<String> {
+    let repo = jj_graph.get_repo();
+    let commit = repo.store().get_commit(commit_id)?;
+    let change_id = commit.change_id();
+    if repo.resolve_change_id(change_id)?.as_deref() == Some(std::slice::from_ref(commit_id)) {
+        let prefix_len = repo.shortest_unique_change_id_prefix_len(change_id)?;
+        return Ok(change_id.to_string()[..prefix_len].to_owned());
+    }
+    let prefix_len = repo
+        .index()
+        .shortest_unique_commit_id_prefix_len(commit_id)?;
+    Ok(format!("commit_id({})", &commit_id.hex()[..prefix_len]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use revset_explorer_core::resolve::resolve_revset;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn symbols_resolve_to_their_commit() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.diverge(&a, "b");
+        let c = repo.commit("c", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let c_symbol = revset_symbol(&jj_graph, &c).unwrap();
+        assert!(!c_symbol.starts_with("commit_id("));
+        for commit_id in [a, b, c] {
+            let symbol = revset_symbol(&jj_graph, &commit_id).unwrap();
+            let (revset, ..) = resolve_revset(&jj_graph, &symbol).unwrap();
+            let resolved: Vec<_> = revset.iter().map(Result::unwrap).collect();
+            assert_eq!(resolved, [commit_id], "{symbol}");
+        }
+    }
+}
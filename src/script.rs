@@ -0,0 +1,138 @@
+//! Startup actions read from a TOML file given with `--script`, for making
+//! graph images in build pipelines with the same drawing as in the window:
+//!
+//! ```toml
+//! [[step]]
+//! view = "trunk()..@"
+//! select = "mine()"
+//! layout = "layout.json"
+//! export_image = "graph.png"
+//!
+//! [[step]]
+//! exit = true
+//! ```
+//!
+//! The fields of a step are done in the order above.
+
+use anyhow::Context;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+
+/// Frames drawn after changing the graph, so that it is laid out and fitted
+/// to the window before the next action
+const SETTLE_FRAMES: usize = 5;
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Step {
+    view: Option<String>,
+    select: Option<String>,
+    /// Node positions exported from the File menu
+    layout: Option<PathBuf>,
+    /// PNG file to save the graph area to
+    export_image: Option<PathBuf>,
+    /// Close the window
+    exit: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct ScriptFile {
+    step: Vec<Step>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    SetView(String),
+    SetSelect(String),
+    Layout(PathBuf),
+    ExportImage(PathBuf),
+    Exit,
+}
+
+#[derive(Debug)]
+pub struct Script {
+    actions: VecDeque<Action>,
+    // Frames left to draw before the next action
+    wait: usize,
+}
+
+impl Script {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content).with_context(|| format!("Failed to load {}", path.display()))
+    }
+
+    fn parse(content: &str) -> anyhow::Result<Self> {
+        let file: ScriptFile = toml::from_str(content)?;
+        let mut actions = VecDeque::new();
+        for step in file.step {
+            actions.extend(step.view.map(Action::SetView));
+            actions.extend(step.select.map(Action::SetSelect));
+            actions.extend(step.layout.map(Action::Layout));
+            actions.extend(step.export_image.map(Action::ExportImage));
+            if step.exit {
+                actions.push_back(Action::Exit);
+            }
+        }
+        Ok(Self {
+            actions,
+            wait: SETTLE_FRAMES,
+        })
+    }
+
+    /// The next action, once the graph has settled after the previous one.
+    /// Called once per frame.
+    pub fn next(&mut self) -> Option<Action> {
+        if self.wait > 0 {
+            self.wait -= 1;
+            return None;
+        }
+        let action = self.actions.pop_front()?;
+        if matches!(
+            action,
+            Action::SetView(_) | Action::SetSelect(_) | Action::Layout(_)
+        ) {
+            self.wait = SETTLE_FRAMES;
+        }
+        Some(action)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.actions.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_steps_after_settling() {
+        let mut script = Script::parse(
+            r#"
+            [[step]]
+            view = "::@"
+            export_image = "graph.png"
+
+            [[step]]
+            exit = true
+            "#,
+        )
+        .unwrap();
+        let mut actions = vec![];
+        while !script.is_done() {
+            actions.extend(script.next());
+        }
+        assert_eq!(
+            actions,
+            [
+                Action::SetView("::@".to_owned()),
+                Action::ExportImage("graph.png".into()),
+                Action::Exit
+            ]
+        );
+        assert!(Script::parse("[[step]]\nzoom = 2").is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Evaluation of the Select and Compared revsets for the commits of the graph,
+//! run on a background thread by [`crate::background_evaluation`]. The nodes
+//! are colored by the result when it arrives.
+
+use jj_lib::backend::CommitId;
+use jj_lib::repo::Repo;
+use revset_explorer_core::jjgraph::JjGraph;
+use revset_explorer_core::{Marking, NodeCount, ResolveError, resolve_revset_measure};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// What to mark the commits of the graph by
+pub struct Request {
+    pub select: String,
+    /// The Compared revset, when comparing
+    pub compare: Option<String>,
+    /// The commits of the graph
+    pub commit_ids: Vec<CommitId>,
+    /// Reads the authors of the commits, for coloring by author
+    pub authors: bool,
+}
+
+pub struct Marked {
+    pub select: String,
+    pub calc_time: Duration,
+    pub node_count: NodeCount,
+    pub markings: HashMap<CommitId, Marking>,
+    /// The commits in the Compared revset, or why it failed
+    pub compared: Option<Result<HashSet<CommitId>, ResolveError>>,
+    /// The author emails, when requested
+    pub authors: HashMap<CommitId, String>,
+}
+
+pub type Outcome = Result<Marked, ResolveError>;
+
+/// Nothing when cancelled
+pub fn evaluate(jj_graph: &JjGraph, request: Request, cancelled: &AtomicBool) -> Option<Outcome> {
+    let is_cancelled = || cancelled.load(Ordering::Relaxed);
+    let resolved = resolve_revset_measure(jj_graph, &request.select, cancelled);
+    if is_cancelled() {
+        return None;
+    }
+    let (select_revset, calc_time, node_count) = match resolved {
+        Ok(resolved) => resolved,
+        Err(error) => return Some(Err(error)),
+    };
+    let markings =
+        revset_explorer_core::mark_commits(jj_graph, &request.commit_ids, Some(select_revset));
+    let markings = match markings {
+        Ok(markings) => (request.commit_ids.iter().cloned()).zip(markings).collect(),
+        Err(error) => return Some(Err(error)),
+    };
+    if is_cancelled() {
+        return None;
+    }
+    let compared =
+        (request.compare).map(|compare| compared_commits(jj_graph, &compare, &request.commit_ids));
+    let mut authors = HashMap::new();
+    if request.authors {
+        let store = jj_graph.get_repo().store().clone();
+        for commit_id in &request.commit_ids {
+            if is_cancelled() {
+                return None;
+            }
+            match store.get_commit(commit_id) {
+                Ok(commit) => {
+                    authors.insert(commit_id.clone(), commit.author().email.clone());
+                }
+                Err(error) => return Some(Err(ResolveError::JjError(error.to_string()))),
+            }
+        }
+    }
+    Some(Ok(Marked {
+        select: request.select,
+        calc_time,
+        node_count,
+        markings,
+        compared,
+        authors,
+    }))
+}
+
+/// The commits that are in the Compared revset
+fn compared_commits(
+    jj_graph: &JjGraph,
+    compare: &str,
+    commit_ids: &[CommitId],
+) -> Result<HashSet<CommitId>, ResolveError> {
+    let revset = (jj_graph.get_revset(compare))
+        .map_err(|e| ResolveError::RevsetParseError(e.to_string()))?;
+    let in_compare = revset.containing_fn();
+    let mut compared = HashSet::new();
+    for commit_id in commit_ids {
+        if in_compare(commit_id)? {
+            compared.insert(commit_id.clone());
+        }
+    }
+    Ok(compared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jj_lib::object_id::ObjectId;
+    use revset_explorer_core::FilterMatch;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn marks_select_and_compared_commits() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let request = Request {
+            select: a.hex(),
+            compare: Some(format!("{a} | {b}")),
+            commit_ids: vec![a.clone(), b.clone()],
+            authors: true,
+        };
+        let marked = evaluate(&repo.jj_graph(), request, &AtomicBool::new(false))
+            .unwrap()
+            .unwrap();
+        assert_eq!(marked.node_count, NodeCount::Exact(1));
+        assert_eq!(marked.markings[&a].filter_match, FilterMatch::Match);
+        assert_eq!(marked.markings[&b].filter_match, FilterMatch::NoMatch);
+        assert_eq!(
+            marked.compared,
+            Some(Ok(HashSet::from([a.clone(), b.clone()])))
+        );
+        assert_eq!(marked.authors.len(), 2);
+    }
+
+    #[test]
+    fn cancelled() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let request = Request {
+            select: a.hex(),
+            compare: None,
+            commit_ids: vec![a],
+            authors: false,
+        };
+        assert!(evaluate(&repo.jj_graph(), request, &AtomicBool::new(true)).is_none());
+    }
+}
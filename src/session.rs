@@ -0,0 +1,183 @@
+//! Recording of the revsets entered during a session, and replaying them step
+//! by step, e.g. for revset demos or for reproducing bug reports. A recording
+//! is a JSON Lines file with one step per line:
+//!
+//! ```json
+//! {"elapsed_ms": 0, "view": "::@", "select": ""}
+//! {"elapsed_ms": 4200, "view": "::@", "select": "mine()"}
+//! ```
+
+use anyhow::Context;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+
+use crate::i18n::tr;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    /// Time since the recording started
+    pub elapsed_ms: u64,
+    pub view: String,
+    pub select: String,
+}
+
+pub struct Recorder {
+    file: File,
+    start: Instant,
+    last: Option<(String, String)>,
+}
+
+impl Recorder {
+    /// Starts a recording, replacing the file
+    pub fn create(path: &Path) -> anyhow::Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Cannot record the session to {}", path.display()))?;
+        Ok(Self {
+            file,
+            start: Instant::now(),
+            last: None,
+        })
+    }
+
+    /// Appends a step, unless the revsets are the same as in the last step.
+    /// Written to the file right away, so that the recording survives crashes.
+    pub fn record(&mut self, view: &str, select: &str) -> anyhow::Result<()> {
+        let revsets = (view.to_owned(), select.to_owned());
+        if self.last.as_ref() == Some(&revsets) {
+            return Ok(());
+        }
+        let step = Step {
+            elapsed_ms: self.start.elapsed().as_millis() as u64,
+            view: revsets.0.clone(),
+            select: revsets.1.clone(),
+        };
+        self.last = Some(revsets);
+        writeln!(self.file, "{}", serde_json::to_string(&step)?)?;
+        Ok(())
+    }
+}
+
+/// Rewrites the revsets of the steps in a recording. Returns the number of
+/// steps that changed.
+pub fn map_recording(path: &Path, f: impl Fn(&str) -> String) -> anyhow::Result<usize> {
+    let mut replay = Replay::load(path)?;
+    let mut changed = 0;
+    for step in &mut replay.steps {
+        let (view, select) = (f(&step.view), f(&step.select));
+        if (&view, &select) != (&step.view, &step.select) {
+            changed += 1;
+        }
+        (step.view, step.select) = (view, select);
+    }
+    let mut content = String::new();
+    for step in &replay.steps {
+        content += &serde_json::to_string(step)?;
+        content += "\n";
+    }
+    std::fs::write(path, content)
+        .with_context(|| format!("Cannot write the session to {}", path.display()))?;
+    Ok(changed)
+}
+
+pub struct Replay {
+    steps: Vec<Step>,
+    // Index of the step shown, None before the first one
+    pos: Option<usize>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let steps = (content.lines().enumerate())
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Invalid step on line {}", i + 1))
+            })
+            .collect::<anyhow::Result<_>>()
+            .with_context(|| format!("Failed to load {}", path.display()))?;
+        Ok(Self { steps, pos: None })
+    }
+
+    /// Shows the replay controls. Returns the step to show, when the user goes
+    /// to another one.
+    pub fn ui(&mut self, ui: &mut egui::Ui) -> Option<&Step> {
+        ui.label(match self.pos {
+            Some(pos) => tr!(
+                "replay-step",
+                step = pos + 1,
+                count = self.steps.len(),
+                seconds = format!("{:.1}", self.steps[pos].elapsed_ms as f64 / 1000.)
+            ),
+            None => tr!("replay-start", count = self.steps.len()),
+        });
+        let mut new_pos = self.pos;
+        if ui
+            .add_enabled(
+                self.pos.is_some_and(|pos| pos > 0),
+                egui::Button::new(tr!("replay-previous")),
+            )
+            .clicked()
+        {
+            new_pos = self.pos.map(|pos| pos - 1);
+        }
+        let next = self.pos.map_or(0, |pos| pos + 1);
+        if ui
+            .add_enabled(
+                next < self.steps.len(),
+                egui::Button::new(tr!("replay-next")),
+            )
+            .clicked()
+        {
+            new_pos = Some(next);
+        }
+        if new_pos == self.pos {
+            return None;
+        }
+        self.pos = new_pos;
+        self.pos.map(|pos| &self.steps[pos])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_and_loads_steps() {
+        let dir = std::env::temp_dir().join(format!("session-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("session.jsonl");
+        let mut recorder = Recorder::create(&path).unwrap();
+        recorder.record("::@", "").unwrap();
+        recorder.record("::@", "").unwrap();
+        recorder.record("::@", "mine()").unwrap();
+        let replay = Replay::load(&path).unwrap();
+        let revsets: Vec<_> = (replay.steps.iter())
+            .map(|step| (step.view.as_str(), step.select.as_str()))
+            .collect();
+        assert_eq!(revsets, [("::@", ""), ("::@", "mine()")]);
+        assert!(replay.steps[0].elapsed_ms <= replay.steps[1].elapsed_ms);
+
+        std::fs::write(&path, "{\"view\": \"@\"}\n").unwrap();
+        let error = Replay::load(&path).err().unwrap();
+        assert!(format!("{error:#}").contains("line 1"), "{error:#}");
+
+        std::fs::write(
+            &path,
+            "{\"elapsed_ms\": 0, \"view\": \"::main\", \"select\": \"\"}\n\
+             {\"elapsed_ms\": 5, \"view\": \"::@\", \"select\": \"\"}\n",
+        )
+        .unwrap();
+        let changed = map_recording(&path, |revset| revset.replace("main", "trunk")).unwrap();
+        assert_eq!(changed, 1);
+        let replay = Replay::load(&path).unwrap();
+        assert_eq!(replay.steps[0].view, "::trunk");
+        assert_eq!(replay.steps[1].elapsed_ms, 5);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
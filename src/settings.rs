@@ -0,0 +1,284 @@
+//! User settings for Revset Explorer, read from `revset-explorer/config.toml`
+//! in the user's configuration directory. The shareable part of them can be
+//! exported and imported as a [`Bundle`].
+
+use anyhow::Context;
+use etcetera::BaseStrategy;
+use revset_explorer_core::Palette;
+
+use crate::author_label::AuthorLabel;
+use crate::color_rules::ColorRule;
+use crate::description_label::Truncation;
+use crate::edge_shape::{ArrowDirection, Arrowheads, EdgeRouting};
+use crate::graph_guides::Guide;
+use crate::navigation::{MouseButton, ScrollAction};
+use crate::node_shape::NodeSize;
+use crate::repo_watch::Watch;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub forge: ForgeSettings,
+    pub diff: DiffSettings,
+    pub avatars: AvatarSettings,
+    pub background: BackgroundSettings,
+    pub hooks: HookSettings,
+    pub ui: UiSettings,
+    pub navigation: NavigationSettings,
+    /// Styling of the commits matching revsets, see [`crate::color_rules`]
+    pub color_rules: Vec<ColorRule>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ForgeSettings {
+    /// URL template for showing a commit in a web forge, e.g.
+    /// "https://github.com/org/repo/commit/{commit_id}". Auto-detected from the
+    /// git remote when not set.
+    pub commit_url: Option<String>,
+    /// Show CI status badges on nodes, fetched from the GitHub or GitLab API
+    pub ci_status: bool,
+    /// Access token for the forge API
+    pub token: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct DiffSettings {
+    /// Diff tool passed to `jj diff --tool`. Without a tool, `jj diff` uses
+    /// `ui.diff-formatter` from the jj config.
+    pub tool: Option<String>,
+    /// Diffs with more lines are not shown in the details panel, only in the
+    /// diff tool. Defaults to 2000.
+    pub max_lines: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct AvatarSettings {
+    /// Fetch author avatars from Gravatar. Identicons are used otherwise.
+    pub gravatar: bool,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackgroundSettings {
+    /// Threads shared by the background work, see [`crate::workers`].
+    /// Defaults to the number of cores.
+    pub threads: Option<usize>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct HookSettings {
+    /// Shell command run in the repository when reloading it while watching
+    /// changes the Select result, see [`crate::result_watch`]
+    pub select_changed: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct UiSettings {
+    /// Language of the user interface, like "sv". Defaults to the language of
+    /// the locale environment variables.
+    pub language: Option<String>,
+    /// Scale of the whole user interface, like 1.5. Can be changed with Ctrl+=
+    /// and Ctrl+-.
+    pub zoom: Option<f32>,
+    /// Size of the commit labels in the graph, relative to the default size
+    pub graph_label_scale: Option<f32>,
+    /// Characters of the description to show after the change ID in the
+    /// commit labels. 0, the default, shows only the change ID.
+    pub label_description_length: usize,
+    /// Where long descriptions are cut, "words" or "characters"
+    pub label_truncation: Truncation,
+    /// Appended to cut descriptions. Defaults to "…".
+    pub label_ellipsis: Option<String>,
+    /// Node colors, "standard", "color-blind" or "high-contrast"
+    pub palette: Palette,
+    /// What the node sizes show, "uniform", "files" or "lines" changed
+    pub node_size: NodeSize,
+    /// How the author is shown on the nodes, "none", "initials" or "email"
+    /// for the part before the @
+    pub author_label: AuthorLabel,
+    /// Drawn behind the graph: "generation-bands", "date-lines" and
+    /// "immutable-boundary"
+    pub guides: Vec<Guide>,
+    /// The View of the immutable boundary focus mode. Defaults to
+    /// `ancestors(immutable_heads(), 3) | immutable_heads()..`.
+    pub boundary_focus_view: Option<String>,
+    /// How the edges are drawn, "curved" or "straight"
+    pub edge_routing: EdgeRouting,
+    /// Size of the arrowheads on the edges, "none", "small", "normal" or
+    /// "large"
+    pub arrowheads: Arrowheads,
+    /// Where the arrows point, "parents" like the parent pointers in jj, or
+    /// "children" in the order the commits were made
+    pub arrow_direction: ArrowDirection,
+    /// Show badges with the signature status of the commits on the nodes
+    pub signature_badges: bool,
+    /// When to reload the repository after jj commands change it, "paused"
+    /// for only from the File menu, "poll" or "instant"
+    pub watch: Watch,
+    /// Seconds between the checks for changes with "poll". Defaults to 5.
+    pub watch_interval: Option<u64>,
+    /// Show which commits joined and left the Select result when reloading
+    /// while watching
+    pub watch_notify: bool,
+    /// Milliseconds after the last keystroke in the revset inputs before they
+    /// are evaluated. Defaults to 300. Leaving an input, like with Enter,
+    /// evaluates it at once.
+    pub revset_debounce: Option<u64>,
+    /// Show changes instantly instead of animating them, e.g. opening menus
+    /// and expanding sections
+    pub reduced_motion: bool,
+    /// Font files for characters missing from the built-in fonts, like CJK,
+    /// in order of preference. Defaults to a common system font with CJK.
+    pub fonts: Vec<PathBuf>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct NavigationSettings {
+    /// What scrolling does in the graph, including two-finger swipes on
+    /// touchpads
+    pub scroll: ScrollAction,
+    /// What scrolling with Ctrl (Cmd on macOS) held does. Touchpad pinches
+    /// often arrive as this.
+    pub ctrl_scroll: ScrollAction,
+    /// Mouse buttons that pan the graph when dragging
+    pub pan_buttons: Vec<MouseButton>,
+    /// Mouse button that selects nodes. Cannot be the secondary button, which
+    /// opens the context menu.
+    pub select_button: MouseButton,
+    /// Open the context menu by pressing and holding a node with a mouse or a
+    /// pen. Touch screens always support it.
+    pub long_press_menu: bool,
+}
+
+impl Default for NavigationSettings {
+    fn default() -> Self {
+        Self {
+            scroll: ScrollAction::Pan,
+            ctrl_scroll: ScrollAction::Zoom,
+            pan_buttons: vec![MouseButton::Primary, MouseButton::Middle],
+            select_button: MouseButton::Primary,
+            long_press_menu: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> anyhow::Result<Self> {
+        let path = config_path();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let config_str = std::fs::read_to_string(&path)
+            .with_context(|| format!("Cannot read settings from {}", path.display()))?;
+        let settings: Self = toml::from_str(&config_str)
+            .with_context(|| format!("Invalid settings in {}", path.display()))?;
+        check_navigation(&settings.navigation, &path)?;
+        Ok(settings)
+    }
+}
+
+/// The settings that make up the look and controls of the explorer, for
+/// sharing a standard setup within a team. Written in the same layout as the
+/// settings file, leaving out the personal sections like `forge` with its
+/// token.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Bundle {
+    pub ui: UiSettings,
+    pub navigation: NavigationSettings,
+    pub color_rules: Vec<ColorRule>,
+}
+
+impl Bundle {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let bundle_str = std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read settings from {}", path.display()))?;
+        let bundle: Self = toml::from_str(&bundle_str)
+            .with_context(|| format!("Invalid settings in {}", path.display()))?;
+        check_navigation(&bundle.navigation, path)?;
+        Ok(bundle)
+    }
+
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).unwrap()
+    }
+
+    /// Replaces the sections of the bundle in the settings file at
+    /// `config_path`, keeping the other sections
+    pub fn install(&self, config_path: &Path) -> anyhow::Result<()> {
+        replace_sections(
+            config_path,
+            &["ui", "navigation", "color_rules"],
+            toml::Table::try_from(self)?,
+        )
+    }
+}
+
+/// Replaces the color rules in the settings file at `config_path`
+pub fn save_color_rules(config_path: &Path, color_rules: &[ColorRule]) -> anyhow::Result<()> {
+    let mut sections = toml::Table::new();
+    sections.insert(
+        "color_rules".to_owned(),
+        toml::Value::try_from(color_rules)?,
+    );
+    replace_sections(config_path, &["color_rules"], sections)
+}
+
+/// Replaces the top-level `keys` in the settings file with `sections`.
+/// Comments are not kept.
+fn replace_sections(
+    config_path: &Path,
+    keys: &[&str],
+    sections: toml::Table,
+) -> anyhow::Result<()> {
+    let mut config = if config_path.exists() {
+        let config_str = std::fs::read_to_string(config_path)
+            .with_context(|| format!("Cannot read settings from {}", config_path.display()))?;
+        toml::from_str::<toml::Table>(&config_str)
+            .with_context(|| format!("Invalid settings in {}", config_path.display()))?
+    } else {
+        toml::Table::new()
+    };
+    for key in keys {
+        config.remove(*key);
+    }
+    config.extend(sections);
+    if let Some(dir) = config_path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    std::fs::write(config_path, toml::to_string(&config)?)
+        .with_context(|| format!("Cannot write settings to {}", config_path.display()))?;
+    Ok(())
+}
+
+fn check_navigation(navigation: &NavigationSettings, path: &Path) -> anyhow::Result<()> {
+    if navigation.select_button == MouseButton::Secondary {
+        anyhow::bail!(
+            "Invalid settings in {}: navigation.select_button cannot be \"secondary\"",
+            path.display()
+        );
+    }
+    Ok(())
+}
+
+pub fn config_path() -> PathBuf {
+    let mut path = etcetera::choose_base_strategy().unwrap().config_dir();
+    path.push("revset-explorer/config.toml");
+    path
+}
+
+/// Directory with symbol tables for custom revset functions, see
+/// [`revset_explorer_core::symbols`]
+pub fn symbols_dir() -> PathBuf {
+    let mut path = etcetera::choose_base_strategy().unwrap().config_dir();
+    path.push("revset-explorer/symbols");
+    path
+}
@@ -0,0 +1,78 @@
+//! Signatures of commits, for the signature badges and the details panel.
+//! Verifying runs the signing program, so it is done one commit at a time in
+//! a background thread.
+
+use eframe::egui;
+use jj_lib::backend::CommitId;
+use jj_lib::repo::{ReadonlyRepo, Repo};
+use revset_explorer_core::signatures::{Signature, SignatureStatus, verify};
+use std::collections::HashMap;
+use std::sync::{Arc, mpsc};
+
+type Request = (Arc<ReadonlyRepo>, CommitId);
+
+pub struct Signatures {
+    // None while being verified. Commits never change, so they are kept.
+    signatures: HashMap<CommitId, Option<Signature>>,
+    // Started on the first request
+    worker: Option<mpsc::Sender<Request>>,
+    sender: mpsc::Sender<(CommitId, Signature)>,
+    receiver: mpsc::Receiver<(CommitId, Signature)>,
+}
+
+impl Signatures {
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            signatures: HashMap::new(),
+            worker: None,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Returns the signature of the commit, or None until it has been verified
+    pub fn get(
+        &mut self,
+        ctx: &egui::Context,
+        repo: &Arc<ReadonlyRepo>,
+        commit_id: &CommitId,
+    ) -> Option<&Signature> {
+        if !self.signatures.contains_key(commit_id) {
+            self.signatures.insert(commit_id.clone(), None);
+            let worker = self.worker.get_or_insert_with(|| {
+                let (worker, requests) = mpsc::channel::<Request>();
+                let sender = self.sender.clone();
+                let ctx = ctx.clone();
+                std::thread::spawn(move || {
+                    for (repo, commit_id) in requests {
+                        let signature = match repo.store().get_commit(&commit_id) {
+                            Ok(commit) => verify(&commit),
+                            Err(_) => Signature {
+                                status: SignatureStatus::Unknown,
+                                signer: None,
+                            },
+                        };
+                        if sender.send((commit_id, signature)).is_err() {
+                            break;
+                        }
+                        ctx.request_repaint();
+                    }
+                });
+                worker
+            });
+            let _ = worker.send((repo.clone(), commit_id.clone()));
+        }
+        self.signatures[commit_id].as_ref()
+    }
+
+    /// Receives verified signatures. Returns true if any was verified.
+    pub fn poll(&mut self) -> bool {
+        let mut changed = false;
+        while let Ok((commit_id, signature)) = self.receiver.try_recv() {
+            self.signatures.insert(commit_id, Some(signature));
+            changed = true;
+        }
+        changed
+    }
+}
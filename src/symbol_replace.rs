@@ -0,0 +1,73 @@
+//! Replacing a symbol, like a renamed bookmark, in revsets. Only whole symbols
+//! are replaced, so `main` is found in `main@origin` and `"exact:main"` but
+//! not in `main-old` or the function call `main()`.
+
+/// Byte offsets of the occurrences of `symbol` in the revset
+pub fn occurrences(revset: &str, symbol: &str) -> Vec<usize> {
+    if symbol.is_empty() {
+        return vec![];
+    }
+    (revset.match_indices(symbol))
+        .map(|(start, _)| start)
+        .filter(|&start| {
+            let before = &revset[..start];
+            let after = &revset[start + symbol.len()..];
+            !continues(before.chars().rev()) && !continues(after.chars()) && !is_call(after)
+        })
+        .collect()
+}
+
+/// The revset with the whole-symbol occurrences of `from` replaced by `to`
+pub fn replace(revset: &str, from: &str, to: &str) -> String {
+    let mut replaced = String::new();
+    let mut end = 0;
+    for start in occurrences(revset, from) {
+        replaced += &revset[end..start];
+        replaced += to;
+        end = start + from.len();
+    }
+    replaced + &revset[end..]
+}
+
+/// Whether jj would read the symbol as part of a longer one, going outwards
+/// from it. `.`, `-` and `+` only join two parts, so `main..` ends at `main`.
+fn continues(mut outwards: impl Iterator<Item = char>) -> bool {
+    match outwards.next() {
+        Some(c) if is_part(c) => true,
+        Some('.' | '-' | '+') => outwards.next().is_some_and(is_part),
+        _ => false,
+    }
+}
+
+fn is_part(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '/'
+}
+
+fn is_call(after: &str) -> bool {
+    after.trim_start().starts_with('(')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replaces_whole_symbols() {
+        assert_eq!(replace("main", "main", "trunk"), "trunk");
+        assert_eq!(
+            replace("::main@origin | main..@", "main", "trunk"),
+            "::trunk@origin | trunk..@"
+        );
+        assert_eq!(
+            replace(r#"bookmarks(exact:"main") ~ main-old"#, "main", "trunk"),
+            r#"bookmarks(exact:"trunk") ~ main-old"#
+        );
+        assert_eq!(
+            replace("my_main | main.x", "main", "trunk"),
+            "my_main | main.x"
+        );
+        // A function, not the symbol
+        assert_eq!(replace("main() | main", "main", "trunk"), "main() | trunk");
+        assert_eq!(occurrences("main", ""), Vec::<usize>::new());
+    }
+}
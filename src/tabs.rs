@@ -0,0 +1,383 @@
+//! Tabs with one repository each, for comparing how the same revsets behave in
+//! different repositories. The tab bar is only shown with more than one tab.
+//! Repository folders dropped on the window, and those picked from File > Open
+//! recent, are opened in new tabs, or switched to when they are already open.
+//! The window title names the repository of the active tab.
+
+use eframe::egui;
+use jj_lib::object_id::ObjectId;
+use std::path::{Path, PathBuf};
+
+use crate::ExplorerApp;
+use crate::i18n::tr;
+use crate::recent::Recent;
+
+/// Opens the repository at the canonical path in a new tab
+pub type OpenRepository = Box<dyn Fn(&Path) -> anyhow::Result<ExplorerApp>>;
+
+struct Tab {
+    name: String,
+    path: PathBuf,
+    app: ExplorerApp,
+}
+
+struct OpenDialog {
+    path: String,
+    error: Option<String>,
+}
+
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+    open: OpenRepository,
+    open_dialog: Option<OpenDialog>,
+    // For the graph IDs of new tabs
+    opened_count: usize,
+    recent: Recent,
+    // Last set window title
+    title: String,
+}
+
+impl Tabs {
+    pub fn new(repo_path: &Path, app: ExplorerApp, open: OpenRepository) -> Self {
+        Self {
+            tabs: vec![Tab {
+                name: tab_name(repo_path),
+                path: repo_path.to_owned(),
+                app,
+            }],
+            active: 0,
+            open,
+            open_dialog: None,
+            opened_count: 1,
+            recent: Recent::default(),
+            title: String::new(),
+        }
+    }
+
+    /// Keeps the list of recent repositories up to date, starting with the
+    /// repository of the first tab
+    pub fn with_recent(mut self, recent: Recent) -> Self {
+        self.recent = recent;
+        self.add_recent(&self.tabs[0].path.clone());
+        self
+    }
+
+    fn add_recent(&mut self, path: &Path) {
+        if let Err(e) = self.recent.add(path) {
+            // Only a convenience, so opening the repository goes on
+            self.tabs[self.active].app.status_message =
+                Some(tr!("recent-save-failed", error = format!("{e:#}")));
+        }
+    }
+
+    /// Opens the repository in a new tab and switches to it
+    fn open_tab(&mut self, path: &str) -> anyhow::Result<()> {
+        let path = PathBuf::from(path.trim())
+            .canonicalize()
+            .map_err(|_| anyhow::anyhow!(tr!("repository-not-found")))?;
+        self.open_path(path)
+    }
+
+    /// Opens the repository at the canonical path in a new tab and switches
+    /// to it
+    fn open_path(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        let mut app = (self.open)(&path)?;
+        app.graph_id = Some(format!("tab-{}", self.opened_count));
+        self.opened_count += 1;
+        self.tabs.push(Tab {
+            name: tab_name(&path),
+            path: path.clone(),
+            app,
+        });
+        self.active = self.tabs.len() - 1;
+        self.add_recent(&path);
+        Ok(())
+    }
+
+    /// Switches to the tab of the repository, opening it if it is not open
+    fn switch_to(&mut self, path: &Path) -> anyhow::Result<()> {
+        if !path.join(".jj").is_dir() {
+            anyhow::bail!(tr!("not-a-repository", path = path.display().to_string()));
+        }
+        let path = path.canonicalize()?;
+        match self.tabs.iter().position(|tab| tab.path == path) {
+            Some(i) => {
+                self.active = i;
+                self.add_recent(&path);
+                Ok(())
+            }
+            None => self.open_path(path),
+        }
+    }
+
+    /// Opens the folders dropped on the window, and shows where they can be
+    /// dropped while they are dragged over it
+    fn handle_dropped(&mut self, ctx: &egui::Context) {
+        let (hovered, dropped) = ctx.input(|i| {
+            let dropped: Vec<_> = (i.raw.dropped_files.iter())
+                .filter_map(|file| file.path.clone())
+                .collect();
+            (!i.raw.hovered_files.is_empty(), dropped)
+        });
+        if hovered {
+            let layer = egui::LayerId::new(egui::Order::Foreground, egui::Id::new("drop_hint"));
+            let painter = ctx.layer_painter(layer);
+            let rect = ctx.content_rect();
+            painter.rect_filled(rect, 0., egui::Color32::from_black_alpha(160));
+            painter.text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                tr!("drop-repository"),
+                egui::TextStyle::Heading.resolve(&ctx.style()),
+                egui::Color32::WHITE,
+            );
+        }
+        for path in dropped {
+            if let Err(e) = self.switch_to(&path) {
+                self.tabs[self.active].app.status_message = Some(format!("{e:#}"));
+            }
+        }
+    }
+
+    fn tab_bar(&mut self, ui: &mut egui::Ui) {
+        let mut close = None;
+        ui.horizontal_wrapped(|ui| {
+            for (i, tab) in self.tabs.iter().enumerate() {
+                if ui
+                    .selectable_label(i == self.active, &tab.name)
+                    .on_hover_text(tab.path.display().to_string())
+                    .clicked()
+                {
+                    self.active = i;
+                }
+                if ui
+                    .small_button("×")
+                    .on_hover_text(tr!("tab-close", name = tab.name.as_str()))
+                    .clicked()
+                {
+                    close = Some(i);
+                }
+            }
+        });
+        if let Some(i) = close {
+            self.tabs.remove(i);
+            if self.active > i || self.active == self.tabs.len() {
+                self.active -= 1;
+            }
+        }
+    }
+
+    fn open_dialog(&mut self, ctx: &egui::Context) {
+        let Some(dialog) = &mut self.open_dialog else {
+            return;
+        };
+        let mut open = false;
+        let mut cancel = false;
+        let modal = egui::Modal::new(egui::Id::new("open_tab_dialog")).show(ctx, |ui| {
+            ui.heading(tr!("open-tab-title"));
+            ui.horizontal(|ui| {
+                let label = ui.label(tr!("open-tab-path"));
+                let path_edit = ui
+                    .text_edit_singleline(&mut dialog.path)
+                    .labelled_by(label.id);
+                open = path_edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+            });
+            if let Some(error) = &dialog.error {
+                ui.colored_label(ui.visuals().error_fg_color, error);
+            }
+            ui.horizontal(|ui| {
+                open |= ui.button(tr!("open-tab")).clicked();
+                cancel = ui.button(tr!("cancel")).clicked();
+            });
+        });
+        if open {
+            let path = dialog.path.clone();
+            match self.open_tab(&path) {
+                Ok(()) => self.open_dialog = None,
+                Err(e) => {
+                    if let Some(dialog) = &mut self.open_dialog {
+                        dialog.error = Some(format!("{e:#}"));
+                    }
+                }
+            }
+        } else if cancel || modal.should_close() {
+            self.open_dialog = None;
+        }
+    }
+
+    /// Names the operation too, when an earlier one is shown
+    fn update_title(&mut self, ctx: &egui::Context) {
+        let tab = &self.tabs[self.active];
+        let repository = tab.name.as_str();
+        let title = match &tab.app.past_operation {
+            Some(op_id) => {
+                let operation = op_id.hex();
+                tr!(
+                    "window-title-operation",
+                    repository = repository,
+                    operation = &operation[..12]
+                )
+            }
+            None => tr!("window-title", repository = repository),
+        };
+        if title != self.title {
+            ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+            self.title = title;
+        }
+    }
+}
+
+fn tab_name(repo_path: &Path) -> String {
+    repo_path
+        .file_name()
+        .unwrap_or(repo_path.as_os_str())
+        .display()
+        .to_string()
+}
+
+impl eframe::App for Tabs {
+    fn on_exit(&mut self, gl: Option<&eframe::glow::Context>) {
+        for tab in &mut self.tabs {
+            eframe::App::on_exit(&mut tab.app, gl);
+        }
+    }
+
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        if self.tabs.len() > 1 {
+            egui::TopBottomPanel::top("tabs").show(ctx, |ui| self.tab_bar(ui));
+        }
+        self.handle_dropped(ctx);
+        let app = &mut self.tabs[self.active].app;
+        (app.recent_repositories).clone_from(&self.recent.repositories);
+        eframe::App::update(app, ctx, frame);
+        if std::mem::take(&mut app.open_tab_requested) {
+            self.open_dialog = Some(OpenDialog {
+                path: String::new(),
+                error: None,
+            });
+        }
+        if let Some(path) = app.open_recent_request.take()
+            && let Err(e) = self.switch_to(&path)
+        {
+            self.tabs[self.active].app.status_message = Some(format!("{e:#}"));
+        }
+        self.open_dialog(ctx);
+        self.update_title(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AppOptions, settings};
+    use egui_kittest::Harness;
+    use egui_kittest::kittest::Queryable;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    #[test]
+    fn opens_repositories_in_tabs() {
+        let mut first = TestRepo::init();
+        first.commit("first", &[]);
+        let mut second = TestRepo::init();
+        second.commit("second", &[]);
+        let open = |path: &Path| {
+            let options = AppOptions {
+                wait_for_evaluations: true,
+                initial_view: Some("all()".to_owned()),
+                ..Default::default()
+            };
+            ExplorerApp::new(path, settings::Settings::default(), options)
+        };
+        let first_path = first.path().canonicalize().unwrap();
+        let tabs = Tabs::new(&first_path, open(&first_path).unwrap(), Box::new(open));
+        let mut harness = Harness::builder().build_eframe(|_cc| tabs);
+        harness.run_steps(2);
+        harness.get_by_label_contains(": first");
+
+        harness.get_by_label("File").click();
+        harness.run_steps(1);
+        harness.get_by_label("Open repository in new tab…").click();
+        harness.run_steps(1);
+        harness.get_by_label("Repository:").focus();
+        harness.run_steps(1);
+        harness
+            .get_by_label("Repository:")
+            .type_text("no-such-repository");
+        harness.run_steps(1);
+        harness.get_by_label("Open").click();
+        harness.run_steps(2);
+        harness.get_by_label("Cannot find the specified repository");
+
+        harness.state_mut().open_dialog.as_mut().unwrap().path =
+            second.path().display().to_string();
+        harness.get_by_label("Open").click();
+        harness.run_steps(2);
+        assert!(harness.state().open_dialog.is_none());
+        assert_eq!(harness.state().active, 1);
+        harness.get_by_label_contains(": second");
+        assert!(harness.query_by_label_contains(": first").is_none());
+        // Separate pan, zoom and layout
+        assert_eq!(
+            harness.state().tabs[1].app.graph_id.as_deref(),
+            Some("tab-1")
+        );
+
+        let first_name = tab_name(&first_path);
+        harness.get_by_label(&first_name).click();
+        harness.run_steps(2);
+        harness.get_by_label_contains(": first");
+        // Close the first tab
+        harness.get_all_by_label("×").next().unwrap().click();
+        harness.run_steps(2);
+        assert_eq!(harness.state().tabs.len(), 1);
+        harness.get_by_label_contains(": second");
+    }
+
+    #[test]
+    fn opens_dropped_repositories() {
+        let mut first = TestRepo::init();
+        first.commit("first", &[]);
+        let mut second = TestRepo::init();
+        second.commit("second", &[]);
+        let open = |path: &Path| {
+            let options = AppOptions {
+                wait_for_evaluations: true,
+                initial_view: Some("all()".to_owned()),
+                ..Default::default()
+            };
+            ExplorerApp::new(path, settings::Settings::default(), options)
+        };
+        let first_path = first.path().canonicalize().unwrap();
+        let tabs = Tabs::new(&first_path, open(&first_path).unwrap(), Box::new(open))
+            .with_recent(Recent::default());
+        let mut harness = Harness::builder().build_eframe(|_cc| tabs);
+        harness.run_steps(2);
+        let drop = |harness: &mut Harness<Tabs>, path: &Path| {
+            harness.input_mut().dropped_files.push(egui::DroppedFile {
+                path: Some(path.to_owned()),
+                ..Default::default()
+            });
+            harness.run_steps(2);
+        };
+
+        drop(&mut harness, &first_path.join(".jj"));
+        assert_eq!(harness.state().tabs.len(), 1);
+        harness.get_by_label_contains("is not a jj repository");
+
+        drop(&mut harness, second.path());
+        assert_eq!(harness.state().tabs.len(), 2);
+        harness.get_by_label_contains(": second");
+        let second_path = second.path().canonicalize().unwrap();
+        assert_eq!(
+            harness.state().recent.repositories,
+            [second_path, first_path.clone()]
+        );
+
+        // Already open
+        drop(&mut harness, &first_path);
+        assert_eq!(harness.state().tabs.len(), 2);
+        assert_eq!(harness.state().active, 0);
+        assert_eq!(harness.state().recent.repositories[0], first_path);
+    }
+}
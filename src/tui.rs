@@ -0,0 +1,307 @@
+//! Terminal user interface, for exploring revsets where no GUI is available,
+//! e.g. over SSH. The graph is drawn using the same characters as `jj log`.
+
+use crate::i18n::tr;
+use jj_lib::graph::GraphEdgeType;
+use petgraph::visit::EdgeRef;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Position, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use renderdag::{Ancestor, GraphRowRenderer, Renderer};
+use revset_explorer_core::mark::NodeType;
+use revset_explorer_core::{
+    CommitGraph, GraphBuilder, Marking, Palette, jjgraph, mark_commits, resolve_revset,
+};
+use std::path::Path;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+    Select,
+    View,
+}
+
+struct Tui {
+    jj_graph: jjgraph::JjGraph,
+    select: String,
+    select_error: Option<String>,
+    view: String,
+    view_error: Option<String>,
+    focus: Focus,
+    graph: CommitGraph,
+    max_nodes: usize,
+    limit_hit: Option<usize>,
+    lines: Vec<Line<'static>>,
+    scroll: u16,
+    palette: Palette,
+}
+
+pub fn run(
+    repository_path: &Path,
+    load_options: crate::LoadOptions,
+    palette: Palette,
+    initial_view: String,
+    initial_select: String,
+    max_nodes: usize,
+) -> anyhow::Result<()> {
+    let mut tui = Tui {
+        jj_graph: crate::load_jj_graph(repository_path, load_options)?,
+        select: initial_select,
+        select_error: None,
+        view: initial_view,
+        view_error: None,
+        focus: Focus::Select,
+        graph: CommitGraph::default(),
+        max_nodes,
+        limit_hit: None,
+        lines: vec![],
+        scroll: 0,
+        palette,
+    };
+    tui.update_view();
+    let mut terminal = ratatui::init();
+    let result = tui.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+impl Tui {
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> anyhow::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            let field = match self.focus {
+                Focus::Select => &mut self.select,
+                Focus::View => &mut self.view,
+            };
+            let mut changed = false;
+            match key.code {
+                KeyCode::Esc => return Ok(()),
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                    return Ok(());
+                }
+                KeyCode::Tab | KeyCode::BackTab => {
+                    self.focus = match self.focus {
+                        Focus::Select => Focus::View,
+                        Focus::View => Focus::Select,
+                    };
+                }
+                KeyCode::Char(c) => {
+                    field.push(c);
+                    changed = true;
+                }
+                KeyCode::Backspace => {
+                    changed = field.pop().is_some();
+                }
+                KeyCode::Up => self.scroll = self.scroll.saturating_sub(1),
+                KeyCode::Down => self.scroll = self.scroll.saturating_add(1),
+                KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(20),
+                KeyCode::PageDown => self.scroll = self.scroll.saturating_add(20),
+                _ => {}
+            }
+            if changed {
+                match self.focus {
+                    Focus::Select => self.update_select(),
+                    Focus::View => self.update_view(),
+                }
+            }
+        }
+    }
+
+    fn update_view(&mut self) {
+        let result = resolve_revset(&self.jj_graph, &self.view).and_then(|(revset, ..)| {
+            GraphBuilder::new(&self.jj_graph)
+                .max_nodes(self.max_nodes)
+                // The working copy is shown by the node glyph
+                .label_formatter(|ctx| {
+                    let description = ctx.commit.description().lines().next().unwrap_or("");
+                    format!("{} {description}", ctx.change_id_prefix)
+                })
+                .build(revset)
+        });
+        match result {
+            Ok(built) => {
+                self.graph = built.graph;
+                self.limit_hit = built.metadata.limit_hit;
+                self.view_error = None;
+                self.scroll = 0;
+            }
+            // Keep showing the previous graph
+            Err(e) => self.view_error = Some(e.to_string()),
+        }
+        self.update_select();
+    }
+
+    fn update_select(&mut self) {
+        let commit_ids: Vec<_> = self
+            .graph
+            .node_weights()
+            .map(|node| node.commit_id.clone())
+            .collect();
+        let markings = resolve_revset(&self.jj_graph, &self.select)
+            .and_then(|(revset, ..)| mark_commits(&self.jj_graph, &commit_ids, Some(revset)));
+        let markings = match markings {
+            Ok(markings) => {
+                self.select_error = None;
+                markings
+            }
+            Err(e) => {
+                self.select_error = Some(e.to_string());
+                mark_commits(&self.jj_graph, &commit_ids, None).unwrap_or_default()
+            }
+        };
+        self.lines = graph_lines(&self.graph, &markings, self.palette);
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [select_area, view_area, graph_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        self.draw_field(
+            frame,
+            select_area,
+            &tr!("revset-select"),
+            &self.select,
+            &self.select_error,
+            Focus::Select,
+        );
+        self.draw_field(
+            frame,
+            view_area,
+            &tr!("revset-view"),
+            &self.view,
+            &self.view_error,
+            Focus::View,
+        );
+        frame.render_widget(
+            Paragraph::new(self.lines.clone()).scroll((self.scroll, 0)),
+            graph_area,
+        );
+        let mut help = tr!("tui-help");
+        if let Some(limit) = self.limit_hit {
+            help += &format!("  {}", tr!("node-limit", limit = limit));
+        }
+        frame.render_widget(Line::from(help).dark_gray(), help_area);
+    }
+
+    fn draw_field(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        title: &str,
+        value: &str,
+        error: &Option<String>,
+        focus: Focus,
+    ) {
+        let mut block = Block::bordered().title(title);
+        if self.focus == focus {
+            block = block.border_style(Style::new().cyan());
+            frame.set_cursor_position(Position::new(
+                area.x + 1 + value.chars().count() as u16,
+                area.y + 1,
+            ));
+        }
+        if let Some(error) = error {
+            // Parse errors span multiple lines
+            let error = error.split_whitespace().collect::<Vec<_>>().join(" ");
+            block = block.title_bottom(Line::from(error).red());
+        }
+        frame.render_widget(Paragraph::new(value).block(block), area);
+    }
+}
+
+/// Draws the graph like `jj log`, with the nodes colored by their marking
+fn graph_lines(graph: &CommitGraph, markings: &[Marking], palette: Palette) -> Vec<Line<'static>> {
+    let mut renderer = GraphRowRenderer::new()
+        .output()
+        .with_min_row_height(1)
+        .build_box_drawing();
+    let mut lines = vec![];
+    for (node_idx, marking) in graph.node_indices().zip(markings) {
+        let parents = graph
+            .edges(node_idx)
+            .map(|edge| match edge.weight() {
+                GraphEdgeType::Direct => Ancestor::Parent(edge.target()),
+                _ => Ancestor::Ancestor(edge.target()),
+            })
+            .collect();
+        let glyph = match marking.node_type {
+            NodeType::WorkingCopy => "@",
+            NodeType::Immutable => "◆",
+            NodeType::Regular => "○",
+        };
+        let row = renderer.next_row(
+            node_idx,
+            parents,
+            glyph.to_owned(),
+            graph[node_idx].label.clone(),
+        );
+        let [red, green, blue] = palette.color(*marking);
+        let color = Color::Rgb(red, green, blue);
+        for (i, line) in row.lines().enumerate() {
+            match line.find(glyph) {
+                // The node line. Color the node and its label.
+                Some(pos) if i == 0 => lines.push(Line::from(vec![
+                    Span::raw(line[..pos].to_owned()),
+                    Span::styled(line[pos..].to_owned(), Style::new().fg(color)),
+                ])),
+                _ => lines.push(Line::raw(line.to_owned())),
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jj_lib::backend::CommitId;
+    use revset_explorer_core::CommitNode;
+    use revset_explorer_core::mark::FilterMatch;
+
+    #[test]
+    fn draws_branches() {
+        let mut graph = CommitGraph::default();
+        let mut add = |label: &str| {
+            graph.add_node(CommitNode {
+                commit_id: CommitId::from_hex("00"),
+                label: label.to_owned(),
+            })
+        };
+        let (a, b, c) = (add("a"), add("b"), add("c"));
+        graph.add_edge(a, c, GraphEdgeType::Direct);
+        graph.add_edge(b, c, GraphEdgeType::Indirect);
+        let marking = |node_type| Marking {
+            node_type,
+            filter_match: FilterMatch::NoMatch,
+        };
+        let lines = graph_lines(
+            &graph,
+            &[
+                marking(NodeType::WorkingCopy),
+                marking(NodeType::Regular),
+                marking(NodeType::Immutable),
+            ],
+            Palette::Standard,
+        );
+        let text: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        assert_eq!(text, ["@  a", "│ ○  b", "├─╯", "◆  c"]);
+        // The node and label are colored, the edges are not
+        assert_eq!(lines[1].spans[0].content, "│ ");
+        assert_eq!(
+            lines[1].spans[1].style.fg,
+            Some(Color::Rgb(0x63, 0x62, 0x22))
+        );
+    }
+}
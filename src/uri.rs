@@ -0,0 +1,114 @@
+//! Handling of `revset-explorer://open?repo=...&view=...&select=...` links
+
+use anyhow::Context;
+use std::path::PathBuf;
+
+pub const SCHEME: &str = "revset-explorer";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct OpenRequest {
+    pub repo: Option<PathBuf>,
+    pub view: Option<String>,
+    pub select: Option<String>,
+}
+
+pub fn parse(uri: &str) -> anyhow::Result<OpenRequest> {
+    let url = url::Url::parse(uri).with_context(|| format!("Invalid URI: {uri}"))?;
+    if url.scheme() != SCHEME {
+        anyhow::bail!("Unsupported URI scheme \"{}\"", url.scheme());
+    }
+    // "open" is parsed as the host of the URI
+    let action = url.host_str().unwrap_or(url.path());
+    if action != "open" {
+        anyhow::bail!("Unsupported URI action \"{action}\"");
+    }
+    let mut request = OpenRequest::default();
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "repo" => request.repo = Some(PathBuf::from(value.as_ref())),
+            "view" => request.view = Some(value.into_owned()),
+            "select" => request.select = Some(value.into_owned()),
+            _ => anyhow::bail!("Unknown URI parameter \"{key}\""),
+        }
+    }
+    Ok(request)
+}
+
+/// Registers Revset Explorer as the handler of revset-explorer:// links for
+/// the current user
+#[cfg(target_os = "linux")]
+pub fn register_handler() -> anyhow::Result<()> {
+    use etcetera::BaseStrategy;
+
+    let exe = std::env::current_exe()?;
+    let desktop_file_name = "revset-explorer-url-handler.desktop";
+    let mut path = etcetera::choose_base_strategy()?.data_dir();
+    path.push("applications");
+    std::fs::create_dir_all(&path)?;
+    path.push(desktop_file_name);
+    std::fs::write(
+        &path,
+        format!(
+            "[Desktop Entry]\n\
+             Type=Application\n\
+             Name=Revset Explorer\n\
+             Exec=\"{}\" %u\n\
+             NoDisplay=true\n\
+             MimeType=x-scheme-handler/{SCHEME};\n",
+            exe.display()
+        ),
+    )?;
+    let status = std::process::Command::new("xdg-mime")
+        .args([
+            "default",
+            desktop_file_name,
+            &format!("x-scheme-handler/{SCHEME}"),
+        ])
+        .status()
+        .context("Failed to run xdg-mime")?;
+    if !status.success() {
+        anyhow::bail!("xdg-mime failed to register the URI handler");
+    }
+    println!(
+        "Registered {} as handler for {SCHEME}:// links",
+        path.display()
+    );
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn register_handler() -> anyhow::Result<()> {
+    anyhow::bail!("Registering the URI handler is only supported on Linux")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_open() {
+        assert_eq!(
+            parse("revset-explorer://open?repo=/home/me/repo&view=::%40&select=mine()").unwrap(),
+            OpenRequest {
+                repo: Some(PathBuf::from("/home/me/repo")),
+                view: Some("::@".to_owned()),
+                select: Some("mine()".to_owned()),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_without_parameters() {
+        assert_eq!(
+            parse("revset-explorer://open").unwrap(),
+            OpenRequest::default()
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse("https://open?repo=x").is_err());
+        assert!(parse("revset-explorer://close").is_err());
+        assert!(parse("revset-explorer://open?colour=red").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Evaluation of the View revset and loading of its commits, run on a
+//! background thread by [`crate::background_evaluation`]. The graph shows a
+//! spinner meanwhile, also for small views, as the window never waits for the
+//! evaluation.
+
+use jj_lib::backend::{ChangeId, CommitId};
+use jj_lib::commit::Commit;
+use jj_lib::repo::Repo;
+use petgraph::graph::NodeIndex;
+use revset_explorer_core::graph::BuiltGraph;
+use revset_explorer_core::jjgraph::JjGraph;
+use revset_explorer_core::{GraphBuilder, NodeCount, ResolveError, resolve_revset};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::change_nodes;
+use crate::i18n::tr;
+use crate::result_set;
+
+/// What to build the graph of
+pub struct Request {
+    pub view: String,
+    /// The Select revset, whose matches are included first
+    pub select: String,
+    pub node_limit: usize,
+    pub prefetched: HashMap<CommitId, Commit>,
+    /// Collapses the commits of each change into one node, except these
+    pub change_nodes: Option<HashSet<ChangeId>>,
+    /// A graph of the view cut off by the node limit, to add commits to up
+    /// to the limit, instead of building a new one
+    pub extend: Option<BuiltGraph>,
+}
+
+pub struct Evaluated {
+    pub view: String,
+    pub built: BuiltGraph,
+    pub collapsed_changes: HashMap<CommitId, (ChangeId, usize)>,
+    pub node_count: NodeCount,
+    pub duration: Duration,
+    /// Messages about the commits left out by the node limit
+    pub truncated: Vec<String>,
+    /// The nodes added, when extending a graph
+    pub added: Option<Vec<NodeIndex>>,
+}
+
+pub type Outcome = Result<Evaluated, ResolveError>;
+
+/// Nothing when cancelled
+pub fn evaluate(
+    jj_graph: &JjGraph,
+    mut request: Request,
+    cancelled: &AtomicBool,
+) -> Option<Outcome> {
+    let is_cancelled = || cancelled.load(Ordering::Relaxed);
+    let started = Instant::now();
+    let resolved = resolve_revset(jj_graph, &request.view);
+    if is_cancelled() {
+        return None;
+    }
+    let (revset, _calc_time, node_count) = match resolved {
+        Ok(resolved) => resolved,
+        Err(error) => return Some(Err(error)),
+    };
+    let builder = GraphBuilder::new(jj_graph)
+        .prefetched(&request.prefetched)
+        .cancelled(cancelled);
+    let built = match request.extend.take() {
+        Some(mut built) => {
+            let count = request.node_limit.saturating_sub(built.graph.node_count());
+            (builder.max_nodes(count).extend(revset, &mut built)).map(|added| (built, Some(added)))
+        }
+        None => {
+            let prioritized = prioritized_commits(jj_graph, &request, cancelled);
+            (builder.max_nodes(request.node_limit))
+                .prioritize(prioritized)
+                .build(revset)
+                .map(|built| (built, None))
+        }
+    };
+    if is_cancelled() {
+        return None;
+    }
+    let (mut built, added) = match built {
+        Ok(built) => built,
+        Err(error) => return Some(Err(error)),
+    };
+    let truncated = if built.metadata.limit_hit.is_some() {
+        find_truncated(jj_graph, &request, &built, cancelled)
+    } else {
+        vec![]
+    };
+    if is_cancelled() {
+        return None;
+    }
+    let collapsed_changes = match &request.change_nodes {
+        Some(expanded_changes) => {
+            let store = jj_graph.get_repo().store().clone();
+            let change_of = |commit_id: &CommitId| {
+                let commit = store.get_commit(commit_id).ok()?;
+                Some(commit.change_id().clone())
+            };
+            change_nodes::collapse(&mut built.graph, change_of, expanded_changes)
+        }
+        None => HashMap::new(),
+    };
+    Some(Ok(Evaluated {
+        view: request.view,
+        built,
+        collapsed_changes,
+        node_count,
+        duration: started.elapsed(),
+        truncated,
+        added,
+    }))
+}
+
+/// Up to the node limit of commits of the view that are also in the
+/// revset.
+/// Revsets that fail, like trunk() in some repositories, have none.
+fn view_part(jj_graph: &JjGraph, request: &Request, revset: &str) -> Vec<CommitId> {
+    if revset.trim().is_empty() {
+        return vec![];
+    }
+    let revset = format!("({}) & ({revset})", request.view);
+    result_set::evaluate(jj_graph, &revset, request.node_limit).unwrap_or_default()
+}
+
+/// The commits of the view to include before the others when it has more
+/// than the node limit, most important first: the working copy, trunk(), the
+/// heads and the Select matches
+fn prioritized_commits(
+    jj_graph: &JjGraph,
+    request: &Request,
+    cancelled: &AtomicBool,
+) -> Vec<CommitId> {
+    let mut prioritized = vec![];
+    let mut seen = HashSet::new();
+    let revsets = [
+        "@",
+        "trunk()",
+        &format!("heads({})", request.view),
+        &request.select,
+    ];
+    for revset in revsets {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        for commit_id in view_part(jj_graph, request, revset) {
+            if prioritized.len() == request.node_limit {
+                return prioritized;
+            }
+            if seen.insert(commit_id.clone()) {
+                prioritized.push(commit_id);
+            }
+        }
+    }
+    prioritized
+}
+
+/// Messages about the working copy, trunk() and the Select matches that
+/// are in the view revset but were left out of the graph, as there were
+/// more of them than the node limit. None are found once cancelled.
+fn find_truncated(
+    jj_graph: &JjGraph,
+    request: &Request,
+    built: &BuiltGraph,
+    cancelled: &AtomicBool,
+) -> Vec<String> {
+    let shown: HashSet<_> = (built.graph.node_weights())
+        .map(|node| &node.commit_id)
+        .collect();
+    let missing = |revset: &str| {
+        if cancelled.load(Ordering::Relaxed) {
+            return 0;
+        }
+        (view_part(jj_graph, request, revset).iter())
+            .filter(|id| !shown.contains(id))
+            .count()
+    };
+    let mut truncated = vec![];
+    if missing("@") > 0 {
+        truncated.push(tr!("truncated-working-copy"));
+    }
+    if missing("trunk()") > 0 {
+        truncated.push(tr!("truncated-trunk"));
+    }
+    let select = missing(&request.select);
+    if select > 0 {
+        truncated.push(tr!("truncated-select", count = select));
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::background_evaluation::BackgroundEvaluation;
+    use eframe::egui;
+    use jj_lib::object_id::ObjectId;
+    use revset_explorer_core::test_repo::TestRepo;
+
+    fn request(view: &str) -> Request {
+        Request {
+            view: view.to_owned(),
+            select: String::new(),
+            node_limit: 10,
+            prefetched: HashMap::new(),
+            change_nodes: None,
+            extend: None,
+        }
+    }
+
+    /// Waits for the evaluation to be done
+    fn finish(evaluation: &mut BackgroundEvaluation<Request, Evaluated>) -> Outcome {
+        let started = Instant::now();
+        let mut outcome = evaluation.poll();
+        while outcome.is_none() && started.elapsed().as_secs() < 10 {
+            std::thread::sleep(Duration::from_millis(10));
+            outcome = evaluation.poll();
+        }
+        outcome.unwrap()
+    }
+
+    #[test]
+    fn evaluates_in_background() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let b = repo.commit("b", &[&a]);
+        let jj_graph = repo.jj_graph();
+        let ctx = egui::Context::default();
+        let mut evaluation =
+            BackgroundEvaluation::new(evaluate, "view-evaluation-failed", "view-evaluating", false);
+        evaluation.start(&ctx, &jj_graph, request(&format!("::{}", b.hex())));
+        assert!(evaluation.is_pending());
+        let evaluated = finish(&mut evaluation).unwrap();
+        // With the root commit
+        assert_eq!(evaluated.built.graph.node_count(), 3);
+        assert!(evaluated.truncated.is_empty());
+        assert!(!evaluation.is_pending());
+
+        evaluation.start(&ctx, &jj_graph, request("::("));
+        assert!(finish(&mut evaluation).is_err());
+    }
+
+    #[test]
+    fn cancelled() {
+        let mut repo = TestRepo::init();
+        let a = repo.commit("a", &[]);
+        let jj_graph = repo.jj_graph();
+        let cancelled = AtomicBool::new(true);
+        assert!(evaluate(&jj_graph, request(&a.hex()), &cancelled).is_none());
+    }
+}
@@ -0,0 +1,166 @@
+//! Toggles that restrict the View revset to common scopes, like `mutable()`,
+//! by adding and removing wrappers in the revset text. The wrapped form is
+//! `(<base>) ~ empty() & mutable() & mine()`, with the enabled scopes in that
+//! order, which is also recognized when typed.
+
+use crate::i18n::tr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scope {
+    NonEmpty,
+    Mutable,
+    Mine,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 3] = [Scope::NonEmpty, Scope::Mutable, Scope::Mine];
+
+    pub fn name(self) -> String {
+        match self {
+            Scope::NonEmpty => tr!("scope-non-empty"),
+            Scope::Mutable => tr!("scope-mutable"),
+            Scope::Mine => tr!("scope-mine"),
+        }
+    }
+
+    /// What is appended to the View revset
+    pub fn suffix(self) -> &'static str {
+        match self {
+            Scope::NonEmpty => " ~ empty()",
+            Scope::Mutable => " & mutable()",
+            Scope::Mine => " & mine()",
+        }
+    }
+}
+
+/// Splits the revset into the base revset and the scopes applied to it
+pub fn decompose(revset: &str) -> (String, Vec<Scope>) {
+    let mut base = revset.trim();
+    let mut scopes = vec![];
+    loop {
+        let stripped = Scope::ALL.iter().find_map(|scope| {
+            let rest = base.strip_suffix(scope.suffix().trim_start())?.trim_end();
+            // `a | b & mine()` is `a | (b & mine())`
+            let applies_to_all = !rest.is_empty() && !has_top_level_union(rest);
+            (applies_to_all && !scopes.contains(scope)).then_some((*scope, rest))
+        });
+        let Some((scope, rest)) = stripped else {
+            break;
+        };
+        scopes.push(scope);
+        base = rest;
+    }
+    if !scopes.is_empty()
+        && let Some(inner) = strip_outer_parentheses(base)
+    {
+        base = inner;
+    }
+    (base.to_owned(), scopes)
+}
+
+/// Applies the scopes to the base revset, see [`decompose`]
+pub fn compose(base: &str, scopes: &[Scope]) -> String {
+    let base = base.trim();
+    if scopes.is_empty() {
+        return base.to_owned();
+    }
+    let mut revset = format!("({base})");
+    for scope in Scope::ALL.iter().filter(|scope| scopes.contains(scope)) {
+        revset += scope.suffix();
+    }
+    revset
+}
+
+/// The revset with the scope turned on or off
+pub fn toggle(revset: &str, scope: Scope) -> String {
+    let (base, mut scopes) = decompose(revset);
+    if scopes.contains(&scope) {
+        scopes.retain(|s| *s != scope);
+    } else {
+        scopes.push(scope);
+    }
+    compose(&base, &scopes)
+}
+
+/// Depth in parentheses of each character, outside string literals. None
+/// inside string literals.
+fn depths(revset: &str) -> impl Iterator<Item = (char, Option<usize>)> + '_ {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    revset.chars().map(move |c| {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            return (c, None);
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        (c, Some(depth))
+    })
+}
+
+fn has_top_level_union(revset: &str) -> bool {
+    depths(revset).any(|(c, depth)| c == '|' && depth == Some(0))
+}
+
+/// The text inside the parentheses if they enclose all of it
+fn strip_outer_parentheses(revset: &str) -> Option<&str> {
+    let inner = revset.strip_prefix('(')?.strip_suffix(')')?;
+    // The opening parenthesis must not close before the end
+    let closes_early = depths(inner)
+        .scan(1usize, |depth, (c, d)| {
+            if d.is_some() {
+                match c {
+                    '(' => *depth += 1,
+                    ')' => *depth -= 1,
+                    _ => {}
+                }
+            }
+            Some(*depth)
+        })
+        .any(|depth| depth == 0);
+    (!closes_early).then_some(inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggles_scopes_in_and_out() {
+        let view = "ancestors(heads(mutable()), 7)";
+        let mutable = toggle(view, Scope::Mutable);
+        assert_eq!(mutable, "(ancestors(heads(mutable()), 7)) & mutable()");
+        let both = toggle(&mutable, Scope::NonEmpty);
+        assert_eq!(
+            both,
+            "(ancestors(heads(mutable()), 7)) ~ empty() & mutable()"
+        );
+        assert_eq!(toggle(&both, Scope::Mutable), toggle(view, Scope::NonEmpty));
+        assert_eq!(
+            toggle(&toggle(&both, Scope::Mutable), Scope::NonEmpty),
+            view
+        );
+
+        // Typed scopes are recognized
+        assert_eq!(decompose("x & mine()"), ("x".to_owned(), vec![Scope::Mine]));
+        assert_eq!(toggle("x & mine()", Scope::Mine), "x");
+        // But not when they only apply to a part
+        assert_eq!(decompose("a | b & mine()").1, vec![]);
+        assert_eq!(toggle("(a) | (b)", Scope::Mine), "((a) | (b)) & mine()");
+        assert_eq!(toggle("((a) | (b)) & mine()", Scope::Mine), "(a) | (b)");
+        assert_eq!(
+            decompose(r#"description(")") & mine()"#).0,
+            r#"description(")")"#
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Named places in the graph, the View revset with the zoom and pan, saved
+//! for the session from the View menu. The number keys jump back to them,
+//! for going back and forth between regions of a large graph.
+
+use eframe::egui;
+
+/// One for each number key
+pub const MAX_VIEWPOINTS: usize = 9;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Viewpoint {
+    pub name: String,
+    pub view: String,
+    pub zoom: f32,
+    pub pan: egui::Vec2,
+}
+
+#[derive(Debug, Default)]
+pub struct Viewpoints {
+    /// In the order of the number keys
+    viewpoints: Vec<Viewpoint>,
+    /// Name entered in the menu
+    pub name: String,
+}
+
+impl Viewpoints {
+    /// Saves the viewpoint, replacing the one with the same name. Returns
+    /// false if all number keys are taken.
+    pub fn save(&mut self, viewpoint: Viewpoint) -> bool {
+        if let Some(saved) = (self.viewpoints.iter_mut()).find(|saved| saved.name == viewpoint.name)
+        {
+            *saved = viewpoint;
+        } else if self.viewpoints.len() < MAX_VIEWPOINTS {
+            self.viewpoints.push(viewpoint);
+        } else {
+            return false;
+        }
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) {
+        self.viewpoints.remove(index);
+    }
+
+    /// The viewpoint of the number key `index + 1`
+    pub fn get(&self, index: usize) -> Option<&Viewpoint> {
+        self.viewpoints.get(index)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Viewpoint> {
+        self.viewpoints.iter()
+    }
+
+    /// A name that is not taken, like "3"
+    pub fn next_name(&self) -> String {
+        (1..)
+            .map(|n| n.to_string())
+            .find(|name| self.viewpoints.iter().all(|saved| saved.name != *name))
+            .unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn viewpoint(name: &str, zoom: f32) -> Viewpoint {
+        Viewpoint {
+            name: name.to_owned(),
+            view: "::@".to_owned(),
+            zoom,
+            pan: egui::Vec2::ZERO,
+        }
+    }
+
+    #[test]
+    fn saves_by_name_up_to_the_number_keys() {
+        let mut viewpoints = Viewpoints::default();
+        assert_eq!(viewpoints.next_name(), "1");
+        assert!(viewpoints.save(viewpoint("1", 1.)));
+        assert!(viewpoints.save(viewpoint("main", 1.)));
+        // Same name replaces, keeping the number key
+        assert!(viewpoints.save(viewpoint("1", 2.)));
+        assert_eq!(viewpoints.get(0).unwrap().zoom, 2.);
+        assert_eq!(viewpoints.next_name(), "2");
+
+        for n in 3..=MAX_VIEWPOINTS {
+            assert!(viewpoints.save(viewpoint(&n.to_string(), 1.)));
+        }
+        assert!(!viewpoints.save(viewpoint("extra", 1.)));
+        assert!(viewpoints.save(viewpoint("main", 3.)));
+
+        viewpoints.remove(0);
+        assert_eq!(viewpoints.get(0).unwrap().name, "main");
+        assert_eq!(viewpoints.next_name(), "1");
+    }
+}
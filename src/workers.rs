@@ -0,0 +1,156 @@
+//! Threads shared by the heavy background work: evaluating revsets for the
+//! diff search, computing diffs and change sizes, and prefetching commits.
+//! Their number is limited, by default to the number of cores, so that these
+//! together don't oversubscribe the machine. Work waits in a queue when all
+//! threads are busy. Network requests, like for avatars and CI statuses, the
+//! signature checks, which run gpg, and the evaluation of the View, which is
+//! waited for, have their own threads.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock, mpsc};
+
+use crate::i18n::tr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    Evaluation,
+    Diffs,
+    Prefetch,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 3] = [Subsystem::Evaluation, Subsystem::Diffs, Subsystem::Prefetch];
+
+    pub fn busy_text(self) -> String {
+        match self {
+            Subsystem::Evaluation => tr!("busy-evaluation"),
+            Subsystem::Diffs => tr!("busy-diffs"),
+            Subsystem::Prefetch => tr!("busy-prefetch"),
+        }
+    }
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+static POOL: OnceLock<Pool> = OnceLock::new();
+
+/// Starts the shared threads, `threads` of them or one per core. Does
+/// nothing if they have already been started.
+pub fn init(threads: Option<usize>) {
+    POOL.get_or_init(|| Pool::new(threads.unwrap_or_else(default_threads)));
+}
+
+/// Queues the work on the shared threads
+pub fn spawn(subsystem: Subsystem, job: impl FnOnce() + Send + 'static) {
+    pool().spawn(subsystem, job);
+}
+
+/// The subsystems with queued or running work
+pub fn busy() -> Vec<Subsystem> {
+    pool().busy()
+}
+
+fn pool() -> &'static Pool {
+    POOL.get_or_init(|| Pool::new(default_threads()))
+}
+
+fn default_threads() -> usize {
+    std::thread::available_parallelism().map_or(4, |threads| threads.get())
+}
+
+struct Pool {
+    sender: mpsc::Sender<Job>,
+    /// Number of queued and running jobs of each subsystem
+    busy: Arc<Mutex<HashMap<Subsystem, usize>>>,
+}
+
+impl Pool {
+    fn new(threads: usize) -> Self {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for _ in 0..threads.max(1) {
+            let receiver = receiver.clone();
+            std::thread::spawn(move || {
+                loop {
+                    // The lock is released before running the job
+                    let job = receiver.lock().unwrap().recv();
+                    let Ok(job) = job else {
+                        break;
+                    };
+                    // A failing job must not take the thread with it
+                    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(job));
+                }
+            });
+        }
+        Self {
+            sender,
+            busy: Arc::default(),
+        }
+    }
+
+    fn spawn(&self, subsystem: Subsystem, job: impl FnOnce() + Send + 'static) {
+        *self.busy.lock().unwrap().entry(subsystem).or_default() += 1;
+        let done = BusyGuard {
+            busy: self.busy.clone(),
+            subsystem,
+        };
+        let _ = self.sender.send(Box::new(move || {
+            let _done = done;
+            job();
+        }));
+    }
+
+    fn busy(&self) -> Vec<Subsystem> {
+        let busy = self.busy.lock().unwrap();
+        (Subsystem::ALL.into_iter())
+            .filter(|subsystem| busy.get(subsystem).is_some_and(|&jobs| jobs > 0))
+            .collect()
+    }
+}
+
+/// Counts the job as done when dropped, also if it panics
+struct BusyGuard {
+    busy: Arc<Mutex<HashMap<Subsystem, usize>>>,
+    subsystem: Subsystem,
+}
+
+impl Drop for BusyGuard {
+    fn drop(&mut self) {
+        if let Ok(mut busy) = self.busy.lock() {
+            *busy.entry(self.subsystem).or_default() -= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queues_jobs_on_limited_threads() {
+        let pool = Pool::new(1);
+        let (release, wait) = mpsc::channel::<()>();
+        let (done, finished) = mpsc::channel();
+        pool.spawn(Subsystem::Prefetch, move || {
+            let _ = wait.recv();
+        });
+        let done_diffs = done.clone();
+        pool.spawn(Subsystem::Diffs, move || done_diffs.send(()).unwrap());
+        assert_eq!(pool.busy(), [Subsystem::Diffs, Subsystem::Prefetch]);
+        // The only thread is taken by the prefetch
+        let timeout = std::time::Duration::from_millis(100);
+        assert!(finished.recv_timeout(timeout).is_err());
+
+        release.send(()).unwrap();
+        finished.recv().unwrap();
+        pool.spawn(Subsystem::Evaluation, || panic!("failed"));
+        pool.spawn(Subsystem::Evaluation, move || done.send(()).unwrap());
+        finished.recv().unwrap();
+        // The guard of the last job is dropped after it has sent
+        let started = std::time::Instant::now();
+        while !pool.busy().is_empty() && started.elapsed().as_secs() < 10 {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(pool.busy().is_empty());
+    }
+}